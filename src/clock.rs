@@ -0,0 +1,220 @@
+//! Clock abstraction
+//!
+//! Tools that need "now" or "today" take a `&dyn Clock` instead of reading the
+//! wall clock directly, so output can be generated as of a fixed date.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+
+/// Layout of stored reading and meal timestamps: local wall-clock time, no zone suffix
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    /// Current instant
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current wall-clock time in the user's (local) time zone
+    fn local_now(&self) -> NaiveDateTime {
+        self.now().with_timezone(&Local).naive_local()
+    }
+
+    /// Current local date (YYYY-MM-DD)
+    fn today(&self) -> String {
+        self.local_now().format("%Y-%m-%d").to_string()
+    }
+
+    /// Current local time as a stored timestamp (TIMESTAMP_FORMAT)
+    fn timestamp(&self) -> String {
+        self.local_now().format(TIMESTAMP_FORMAT).to_string()
+    }
+}
+
+/// Wall-clock time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock pinned to a single instant. Its local time is the pinned time
+/// itself, so output as of a fixed date doesn't depend on the host's zone.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl FixedClock {
+    /// Pin the clock to midnight UTC of a YYYY-MM-DD date
+    pub fn from_date(date: &str) -> Result<Self, String> {
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{}'. Use YYYY-MM-DD", date))?;
+        Ok(Self(day.and_hms_opt(0, 0, 0).unwrap().and_utc()))
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+
+    fn local_now(&self) -> NaiveDateTime {
+        self.0.naive_utc()
+    }
+}
+
+/// Resolve a date parameter: ISO "YYYY-MM-DD", "today", "yesterday", or "-N"
+/// (N days before today), relative to `clock`. Returns an ISO date.
+pub fn parse_flexible_date(input: &str, clock: &dyn Clock) -> Result<String, String> {
+    let value = input.trim();
+    let today = clock.local_now().date();
+    let days_ago = match value.to_lowercase().as_str() {
+        "today" => Some(0),
+        "yesterday" => Some(1),
+        v => v.strip_prefix('-').and_then(|n| n.parse::<u32>().ok()),
+    };
+    if let Some(n) = days_ago {
+        return today
+            .checked_sub_signed(chrono::Duration::days(n as i64))
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .ok_or_else(|| format!("'{}' is too far in the past", input));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .map_err(|_| format!(
+            "Invalid date '{}'. Use YYYY-MM-DD, \"today\", \"yesterday\", or \"-N\" for N days ago",
+            input
+        ))
+}
+
+/// Normalize a timestamp to local YYYY-MM-DDTHH:MM:SS.
+///
+/// Accepts a T or space separator, missing seconds, fractional seconds or a
+/// bare date (midnight). Values without zone information are already local
+/// time and are only reformatted, never shifted; only an explicit trailing Z
+/// or UTC offset is converted to local time.
+pub fn canonical_timestamp(ts: &str) -> Option<String> {
+    canonical_datetime(ts).map(|dt| dt.format(TIMESTAMP_FORMAT).to_string())
+}
+
+/// The local time a stored timestamp stands for, read as canonical_timestamp does
+/// (fractional seconds dropped)
+pub fn canonical_datetime(ts: &str) -> Option<NaiveDateTime> {
+    use chrono::Timelike;
+
+    let ts = ts.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return dt.with_timezone(&Local).naive_local().with_nanosecond(0);
+    }
+
+    let (body, utc) = match ts.strip_suffix('Z') {
+        Some(body) => (body, true),
+        None => (ts, false),
+    };
+    let body = body.replacen(' ', "T", 1);
+    let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(&body, fmt).ok())
+        .or_else(|| NaiveDate::parse_from_str(&body, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)))?;
+    let local = if utc { naive.and_utc().with_timezone(&Local).naive_local() } else { naive };
+
+    local.with_nanosecond(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_today() {
+        let clock = FixedClock::from_date("2024-03-15").unwrap();
+        assert_eq!(clock.today(), "2024-03-15");
+        assert_eq!(clock.now().format("%H:%M:%S").to_string(), "00:00:00");
+    }
+
+    #[test]
+    fn test_fixed_clock_rejects_bad_date() {
+        assert!(FixedClock::from_date("03/15/2024").is_err());
+    }
+
+    #[test]
+    fn test_parse_flexible_date_relative() {
+        let clock = FixedClock::from_date("2024-03-01").unwrap();
+        assert_eq!(parse_flexible_date("today", &clock).unwrap(), "2024-03-01");
+        assert_eq!(parse_flexible_date(" Yesterday ", &clock).unwrap(), "2024-02-29");
+        assert_eq!(parse_flexible_date("-7", &clock).unwrap(), "2024-02-23");
+        assert_eq!(parse_flexible_date("-0", &clock).unwrap(), "2024-03-01");
+    }
+
+    #[test]
+    fn test_parse_flexible_date_huge_offset_is_an_error() {
+        let clock = FixedClock::from_date("2024-03-01").unwrap();
+        assert!(parse_flexible_date("-4294967295", &clock).is_err());
+        assert!(parse_flexible_date("-99999999999999999999", &clock).is_err());
+    }
+
+    #[test]
+    fn test_system_clock_today_is_local() {
+        let local_today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+        assert_eq!(SystemClock.today(), local_today);
+    }
+
+    #[test]
+    fn test_parse_flexible_date_iso_and_invalid() {
+        let clock = FixedClock::from_date("2024-03-01").unwrap();
+        assert_eq!(parse_flexible_date("2023-12-25", &clock).unwrap(), "2023-12-25");
+        assert!(parse_flexible_date("2023-02-30", &clock).is_err());
+        assert!(parse_flexible_date("tomorrow", &clock).is_err());
+        assert!(parse_flexible_date("-x", &clock).is_err());
+        assert!(parse_flexible_date("+3", &clock).is_err());
+        assert!(parse_flexible_date("", &clock).is_err());
+    }
+
+    /// What an explicit UTC instant looks like stored as local time on this host
+    fn local(utc: &str) -> String {
+        chrono::DateTime::parse_from_rfc3339(utc)
+            .unwrap()
+            .with_timezone(&chrono::Local)
+            .naive_local()
+            .format(TIMESTAMP_FORMAT)
+            .to_string()
+    }
+
+    #[test]
+    fn test_canonical_timestamp_missing_seconds() {
+        assert_eq!(canonical_timestamp("2026-01-06T08:18").as_deref(), Some("2026-01-06T08:18:00"));
+        assert_eq!(canonical_timestamp("2026-01-06 08:18").as_deref(), Some("2026-01-06T08:18:00"));
+    }
+
+    #[test]
+    fn test_canonical_timestamp_already_canonical() {
+        assert_eq!(canonical_timestamp("2026-01-06T08:18:00").as_deref(), Some("2026-01-06T08:18:00"));
+        assert_eq!(canonical_timestamp("2026-01-06T08:18:00.250").as_deref(), Some("2026-01-06T08:18:00"));
+    }
+
+    #[test]
+    fn test_canonical_datetime_keeps_time_of_day() {
+        let at = |ts: &str| canonical_datetime(ts).map(|dt| dt.format("%H:%M:%S").to_string());
+        assert_eq!(at("2026-01-06T08:18").as_deref(), Some("08:18:00"));
+        assert_eq!(at("2026-01-06 08:18:42.5").as_deref(), Some("08:18:42"));
+        assert_eq!(canonical_datetime("2026-01-06T08:18:00Z").map(|dt| dt.format(TIMESTAMP_FORMAT).to_string()), Some(local("2026-01-06T08:18:00Z")));
+        assert_eq!(canonical_datetime("later"), None);
+    }
+
+    #[test]
+    fn test_canonical_timestamp_date_only() {
+        assert_eq!(canonical_timestamp("2026-01-06").as_deref(), Some("2026-01-06T00:00:00"));
+    }
+
+    #[test]
+    fn test_canonical_timestamp_only_shifts_explicit_zones() {
+        // Offset-less values are local readings and keep their wall-clock time
+        assert_eq!(canonical_timestamp("2026-07-06 23:30:00").as_deref(), Some("2026-07-06T23:30:00"));
+        assert_eq!(canonical_timestamp("2026-01-06T08:18:00Z"), Some(local("2026-01-06T08:18:00Z")));
+        assert_eq!(canonical_timestamp("2026-01-06T08:18:00.250Z"), Some(local("2026-01-06T08:18:00Z")));
+        assert_eq!(canonical_timestamp("2026-01-06T08:18Z"), Some(local("2026-01-06T08:18:00Z")));
+        assert_eq!(canonical_timestamp("2026-01-06T08:18:00-05:00"), Some(local("2026-01-06T13:18:00Z")));
+        assert_eq!(canonical_timestamp("yesterday"), None);
+    }
+}
@@ -3,7 +3,7 @@
 //! Provides SQLite connection pooling and management.
 
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
@@ -21,15 +21,20 @@ pub enum DbError {
 
     #[error("Database not initialized")]
     NotInitialized,
+
+    #[error("Database handle is unusable: a thread panicked while switching databases")]
+    Poisoned,
 }
 
 /// Result type for database operations
 pub type DbResult<T> = Result<T, DbError>;
 
 /// Database connection pool wrapper
+///
+/// Clones share one swappable pool, so `replace_with` repoints every clone at once.
 #[derive(Clone)]
 pub struct Database {
-    pool: Arc<Pool<SqliteConnectionManager>>,
+    pool: Arc<RwLock<Arc<Pool<SqliteConnectionManager>>>>,
 }
 
 impl Database {
@@ -58,13 +63,36 @@ impl Database {
             .build(manager)?;
 
         Ok(Self {
-            pool: Arc::new(pool),
+            pool: Arc::new(RwLock::new(Arc::new(pool))),
         })
     }
 
+    /// The pool currently in use
+    fn current_pool(&self) -> DbResult<Arc<Pool<SqliteConnectionManager>>> {
+        Ok(self.pool.read().map_err(|_| DbError::Poisoned)?.clone())
+    }
+
     /// Get a connection from the pool
     pub fn get_conn(&self) -> DbResult<PooledConnection<SqliteConnectionManager>> {
-        Ok(self.pool.get()?)
+        Ok(self.current_pool()?.get()?)
+    }
+
+    /// A handle fixed to the file open right now: a later `replace_with` on
+    /// this database doesn't affect it, so work done through it never spans
+    /// two files
+    pub fn pinned(&self) -> DbResult<Database> {
+        Ok(Self {
+            pool: Arc::new(RwLock::new(self.current_pool()?)),
+        })
+    }
+
+    /// Point this database (and every clone of it) at another database's pool.
+    /// Pinned handles and connections already checked out keep using the old
+    /// pool until dropped.
+    pub fn replace_with(&self, other: &Database) -> DbResult<()> {
+        let new_pool = other.current_pool()?;
+        *self.pool.write().map_err(|_| DbError::Poisoned)? = new_pool;
+        Ok(())
     }
 
     /// Execute a closure with a database connection
@@ -84,4 +112,95 @@ impl Database {
         let mut conn = self.get_conn()?;
         f(&mut conn)
     }
+
+    /// Run a closure inside a transaction: commit if it returns Ok, roll back
+    /// (on drop) if it returns Err, so multi-step operations never leave partial state
+    pub fn transaction<F, T, E>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&rusqlite::Transaction) -> Result<T, E>,
+        E: From<DbError>,
+    {
+        let mut conn = self.get_conn()?;
+        transaction_on(&mut conn, f)
+    }
+}
+
+/// `Database::transaction` on a connection that is already checked out, for
+/// work that depends on per-connection state such as an attached database
+pub fn transaction_on<F, T, E>(conn: &mut rusqlite::Connection, f: F) -> Result<T, E>
+where
+    F: FnOnce(&rusqlite::Transaction) -> Result<T, E>,
+    E: From<DbError>,
+{
+    let tx = conn.transaction().map_err(DbError::from)?;
+    let value = f(&tx)?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{remove_db_files, temp_db_path};
+    use crate::tools::error::ToolError;
+
+    /// Temp database with a single `items` table (no migrations)
+    fn temp_db(name: &str) -> (Database, std::path::PathBuf) {
+        let path = temp_db_path(name);
+        let db = Database::new(&path).unwrap();
+        db.with_conn(|conn| {
+            conn.execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")?;
+            Ok(())
+        }).unwrap();
+        (db, path)
+    }
+
+    fn count(db: &Database) -> i64 {
+        db.with_conn(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))?)).unwrap()
+    }
+
+    #[test]
+    fn test_transaction_commits_on_ok() {
+        let (db, path) = temp_db("tx-commit");
+
+        let result: Result<(), ToolError> = db.transaction(|tx| {
+            tx.execute("INSERT INTO items (name) VALUES ('a')", []).map_err(DbError::from)?;
+            tx.execute("INSERT INTO items (name) VALUES ('b')", []).map_err(DbError::from)?;
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(count(&db), 2);
+
+        drop(db);
+        remove_db_files(&path);
+    }
+
+    #[test]
+    fn test_transaction_rolls_back_on_mid_batch_failure() {
+        let (db, path) = temp_db("tx-rollback");
+
+        let result: Result<(), ToolError> = db.transaction(|tx| {
+            for (i, name) in ["a", "b", "c"].iter().enumerate() {
+                if i == 2 {
+                    return Err(ToolError::Validation("injected failure".to_string()));
+                }
+                tx.execute("INSERT INTO items (name) VALUES (?1)", [name]).map_err(DbError::from)?;
+            }
+            Ok(())
+        });
+        assert!(matches!(result, Err(ToolError::Validation(ref m)) if m == "injected failure"));
+        assert_eq!(count(&db), 0);
+
+        // A failing statement aborts the batch the same way
+        let result: Result<(), ToolError> = db.transaction(|tx| {
+            tx.execute("INSERT INTO items (name) VALUES ('a')", []).map_err(DbError::from)?;
+            tx.execute("INSERT INTO items (name) VALUES (NULL)", []).map_err(DbError::from)?;
+            Ok(())
+        });
+        assert!(matches!(result, Err(ToolError::Db(ref m)) if m.starts_with("Database error")));
+        assert_eq!(count(&db), 0);
+
+        drop(db);
+        remove_db_files(&path);
+    }
 }
@@ -7,7 +7,7 @@ use rusqlite::Connection;
 use super::connection::DbResult;
 
 /// Current schema version
-const SCHEMA_VERSION: i32 = 5;
+const SCHEMA_VERSION: i32 = 13;
 
 /// Run all migrations to bring the database up to the current schema version
 pub fn run_migrations(conn: &Connection) -> DbResult<()> {
@@ -55,6 +55,46 @@ pub fn run_migrations(conn: &Connection) -> DbResult<()> {
         conn.execute("INSERT INTO schema_migrations (version) VALUES (5)", [])?;
     }
 
+    if current_version < 6 {
+        migrate_v6(conn)?;
+        conn.execute("INSERT INTO schema_migrations (version) VALUES (6)", [])?;
+    }
+
+    if current_version < 7 {
+        migrate_v7(conn)?;
+        conn.execute("INSERT INTO schema_migrations (version) VALUES (7)", [])?;
+    }
+
+    if current_version < 8 {
+        migrate_v8(conn)?;
+        conn.execute("INSERT INTO schema_migrations (version) VALUES (8)", [])?;
+    }
+
+    if current_version < 9 {
+        migrate_v9(conn)?;
+        conn.execute("INSERT INTO schema_migrations (version) VALUES (9)", [])?;
+    }
+
+    if current_version < 10 {
+        migrate_v10(conn)?;
+        conn.execute("INSERT INTO schema_migrations (version) VALUES (10)", [])?;
+    }
+
+    if current_version < 11 {
+        migrate_v11(conn)?;
+        conn.execute("INSERT INTO schema_migrations (version) VALUES (11)", [])?;
+    }
+
+    if current_version < 12 {
+        migrate_v12(conn)?;
+        conn.execute("INSERT INTO schema_migrations (version) VALUES (12)", [])?;
+    }
+
+    if current_version < 13 {
+        migrate_v13(conn)?;
+        conn.execute("INSERT INTO schema_migrations (version) VALUES (13)", [])?;
+    }
+
     Ok(())
 }
 
@@ -466,6 +506,266 @@ fn migrate_existing_food_items(conn: &Connection) -> DbResult<()> {
     Ok(())
 }
 
+/// Migration v6: Meal entry eaten_at timestamp
+fn migrate_v6(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        -- ============================================
+        -- MEAL TIMES
+        -- When a meal was actually eaten (local YYYY-MM-DDTHH:MM:SS)
+        -- ============================================
+        ALTER TABLE meal_entries ADD COLUMN eaten_at TEXT;
+
+        -- Existing entries: best guess is when they were logged
+        -- (created_at is UTC, so convert it to local time)
+        UPDATE meal_entries SET eaten_at = strftime('%Y-%m-%dT%H:%M:%S', created_at, 'localtime')
+            WHERE eaten_at IS NULL;
+
+        CREATE INDEX idx_meal_entries_eaten_at ON meal_entries(eaten_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// Migration v7: Medication dose schedules (tapers)
+fn migrate_v7(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        -- ============================================
+        -- MEDICATION SCHEDULES
+        -- JSON array of dose steps for tapering, e.g.
+        -- [{"start_date": "2026-01-01", "dosage_amount": 40}, ...]
+        -- ============================================
+        ALTER TABLE medications ADD COLUMN schedule TEXT;
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// Migration v8: Water intake vital type
+fn migrate_v8(conn: &Connection) -> DbResult<()> {
+    // SQLite can't alter a CHECK constraint, so rebuild the vitals table
+    conn.execute_batch(
+        r#"
+        -- ============================================
+        -- WATER INTAKE
+        -- Allow vital_type 'water' (value1 = ml)
+        -- ============================================
+        CREATE TABLE vitals_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            vital_type TEXT NOT NULL CHECK(vital_type IN ('weight', 'blood_pressure', 'heart_rate', 'oxygen_saturation', 'glucose', 'water')),
+            timestamp TEXT NOT NULL DEFAULT (datetime('now')),
+            value1 REAL NOT NULL,
+            value2 REAL,
+            unit TEXT NOT NULL,
+            notes TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            group_id INTEGER REFERENCES vital_groups(id)
+        );
+
+        INSERT INTO vitals_new (id, vital_type, timestamp, value1, value2, unit, notes, created_at, updated_at, group_id)
+            SELECT id, vital_type, timestamp, value1, value2, unit, notes, created_at, updated_at, group_id
+            FROM vitals;
+
+        DROP TABLE vitals;
+        ALTER TABLE vitals_new RENAME TO vitals;
+
+        CREATE INDEX idx_vitals_type ON vitals(vital_type);
+        CREATE INDEX idx_vitals_timestamp ON vitals(timestamp);
+        CREATE INDEX idx_vitals_group ON vitals(group_id);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// Migration v9: Prepared batches for meal prep
+fn migrate_v9(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        -- ============================================
+        -- PREPARED BATCHES
+        -- A recipe cooked in bulk and eaten over several meals
+        -- ============================================
+        CREATE TABLE prepared_batches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            recipe_id INTEGER NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+            servings_prepared REAL NOT NULL CHECK(servings_prepared > 0),
+            remaining_servings REAL NOT NULL CHECK(remaining_servings >= 0),
+            prepared_date TEXT NOT NULL,          -- ISO date: YYYY-MM-DD
+            notes TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX idx_prepared_batches_recipe ON prepared_batches(recipe_id);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// Migration v10: Ad-hoc meal entries with inline nutrition
+fn migrate_v10(conn: &Connection) -> DbResult<()> {
+    // SQLite can't alter a CHECK constraint, so rebuild the meal_entries table
+    conn.execute_batch(
+        r#"
+        -- ============================================
+        -- AD-HOC MEAL ENTRIES
+        -- One-off foods logged by description with their own nutrition,
+        -- without creating a food item or recipe
+        -- ============================================
+        CREATE TABLE meal_entries_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            day_id INTEGER NOT NULL REFERENCES days(id) ON DELETE CASCADE,
+            meal_type TEXT NOT NULL CHECK(meal_type IN ('breakfast', 'lunch', 'dinner', 'snack', 'unspecified')),
+
+            -- Source: a recipe, a direct food item, or inline (ad-hoc) nutrition
+            source_type TEXT NOT NULL CHECK(source_type IN ('recipe', 'food_item', 'adhoc')),
+            recipe_id INTEGER REFERENCES recipes(id) ON DELETE RESTRICT,
+            food_item_id INTEGER REFERENCES food_items(id) ON DELETE RESTRICT,
+            description TEXT,                -- ad-hoc only: what was eaten
+            inline_nutrition TEXT,           -- ad-hoc only: JSON nutrition per serving
+
+            servings REAL NOT NULL DEFAULT 1.0,
+            percent_eaten REAL NOT NULL DEFAULT 100.0,
+
+            cached_calories REAL DEFAULT 0,
+            cached_protein REAL DEFAULT 0,
+            cached_carbs REAL DEFAULT 0,
+            cached_fat REAL DEFAULT 0,
+            cached_fiber REAL DEFAULT 0,
+            cached_sodium REAL DEFAULT 0,
+            cached_sugar REAL DEFAULT 0,
+            cached_saturated_fat REAL DEFAULT 0,
+            cached_cholesterol REAL DEFAULT 0,
+
+            notes TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            eaten_at TEXT,
+
+            -- Constraint: the columns set must match source_type
+            CHECK ((source_type = 'recipe' AND recipe_id IS NOT NULL AND food_item_id IS NULL AND inline_nutrition IS NULL) OR
+                   (source_type = 'food_item' AND recipe_id IS NULL AND food_item_id IS NOT NULL AND inline_nutrition IS NULL) OR
+                   (source_type = 'adhoc' AND recipe_id IS NULL AND food_item_id IS NULL AND inline_nutrition IS NOT NULL))
+        );
+
+        INSERT INTO meal_entries_new (
+            id, day_id, meal_type, source_type, recipe_id, food_item_id, servings, percent_eaten,
+            cached_calories, cached_protein, cached_carbs, cached_fat, cached_fiber,
+            cached_sodium, cached_sugar, cached_saturated_fat, cached_cholesterol,
+            notes, created_at, updated_at, eaten_at
+        )
+            SELECT id, day_id, meal_type,
+                   CASE WHEN recipe_id IS NOT NULL THEN 'recipe' ELSE 'food_item' END,
+                   recipe_id, food_item_id, servings, percent_eaten,
+                   cached_calories, cached_protein, cached_carbs, cached_fat, cached_fiber,
+                   cached_sodium, cached_sugar, cached_saturated_fat, cached_cholesterol,
+                   notes, created_at, updated_at, eaten_at
+            FROM meal_entries;
+
+        DROP TABLE meal_entries;
+        ALTER TABLE meal_entries_new RENAME TO meal_entries;
+
+        CREATE INDEX idx_meal_entries_day ON meal_entries(day_id);
+        CREATE INDEX idx_meal_entries_type ON meal_entries(meal_type);
+        CREATE INDEX idx_meal_entries_recipe ON meal_entries(recipe_id);
+        CREATE INDEX idx_meal_entries_food ON meal_entries(food_item_id);
+        CREATE INDEX idx_meal_entries_eaten_at ON meal_entries(eaten_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// Migration v11: Recipe cooked yield in grams
+fn migrate_v11(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        -- ============================================
+        -- RECIPE YIELD
+        -- Total cooked weight of one batch; differs from the raw
+        -- ingredient sum because of water lost (or absorbed) in cooking
+        -- ============================================
+        ALTER TABLE recipes ADD COLUMN yield_grams REAL CHECK(yield_grams IS NULL OR yield_grams > 0);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// Migration v12: Planned meal entries
+fn migrate_v12(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        -- ============================================
+        -- PLANNED MEALS
+        -- Entries logged ahead of time as a meal plan; they are kept
+        -- out of the day's cached totals, which reflect what was eaten
+        -- ============================================
+        ALTER TABLE meal_entries ADD COLUMN planned INTEGER NOT NULL DEFAULT 0 CHECK(planned IN (0, 1));
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// Migration v13: Custom vital types registered at runtime
+fn migrate_v13(conn: &Connection) -> DbResult<()> {
+    // SQLite can't alter a CHECK constraint, so rebuild the vitals table
+    conn.execute_batch(
+        r#"
+        -- ============================================
+        -- CUSTOM VITAL TYPES
+        -- User-defined measurements (mood 1-10, peak flow, ...);
+        -- readings are stored with vital_type 'custom' and a reference here
+        -- ============================================
+        CREATE TABLE custom_vital_types (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            unit TEXT NOT NULL,
+            value2_label TEXT,               -- set when readings carry a second value
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE vitals_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            vital_type TEXT NOT NULL CHECK(vital_type IN ('weight', 'blood_pressure', 'heart_rate', 'oxygen_saturation', 'glucose', 'water', 'custom')),
+            timestamp TEXT NOT NULL DEFAULT (datetime('now')),
+            value1 REAL NOT NULL,
+            value2 REAL,
+            unit TEXT NOT NULL,
+            notes TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            group_id INTEGER REFERENCES vital_groups(id),
+            custom_type_id INTEGER REFERENCES custom_vital_types(id) ON DELETE RESTRICT,
+
+            -- Custom readings, and only those, reference a custom type
+            CHECK ((vital_type = 'custom') = (custom_type_id IS NOT NULL))
+        );
+
+        INSERT INTO vitals_new (id, vital_type, timestamp, value1, value2, unit, notes, created_at, updated_at, group_id)
+            SELECT id, vital_type, timestamp, value1, value2, unit, notes, created_at, updated_at, group_id
+            FROM vitals;
+
+        DROP TABLE vitals;
+        ALTER TABLE vitals_new RENAME TO vitals;
+
+        CREATE INDEX idx_vitals_type ON vitals(vital_type);
+        CREATE INDEX idx_vitals_timestamp ON vitals(timestamp);
+        CREATE INDEX idx_vitals_group ON vitals(group_id);
+        CREATE INDEX idx_vitals_custom_type ON vitals(custom_type_id);
+        "#,
+    )?;
+
+    Ok(())
+}
+
 /// Get the current schema version
 pub fn get_schema_version(conn: &Connection) -> DbResult<i32> {
     let version: i32 = conn
@@ -5,4 +5,4 @@
 pub mod connection;
 pub mod migrations;
 
-pub use connection::{Database, DbError, DbResult};
+pub use connection::{transaction_on, Database, DbError, DbResult};
@@ -8,12 +8,16 @@ use tokio::io::{stdin, stdout};
 use tracing_subscriber::EnvFilter;
 
 mod build_info;
+mod clock;
 mod db;
 mod mcp;
 mod models;
 mod nutrition;
 mod tools;
 
+#[cfg(test)]
+mod test_support;
+
 use mcp::UhmService;
 
 /// Get the database path from environment or use default
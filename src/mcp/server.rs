@@ -2,8 +2,8 @@
 //!
 //! Implements the MCP server with all UHM tools.
 
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use rmcp::handler::server::router::tool::ToolRouter;
@@ -15,17 +15,23 @@ use rmcp::{schemars, tool, tool_handler, tool_router, ErrorData as McpError, Ser
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
+use crate::clock::{parse_flexible_date, Clock, FixedClock, SystemClock};
 use crate::db::Database;
 use crate::models::{
-    FoodItemCreate, FoodItemUpdate, Preference,
+    FoodItemCreate, FoodItemUpdate, Nutrition, Preference,
     RecipeCreate, RecipeUpdate, RecipeIngredientCreate, RecipeIngredientUpdate,
     RecipeComponentCreate, RecipeComponentUpdate,
-    MedicationCreate, MedicationUpdate, MedType, DosageUnit,
+    MedicationCreate, MedicationUpdate, MedType, DosageUnit, DoseStep,
+    DEFAULT_SODIUM_DENSITY_LIMIT,
 };
+use crate::tools::backup;
 use crate::tools::days;
+use crate::tools::error::ToolError;
 use crate::tools::food_items;
+use crate::tools::maintenance;
 use crate::tools::medications;
 use crate::tools::recipes;
+use crate::tools::snapshot;
 use crate::tools::status::StatusTracker;
 use crate::tools::vitals;
 
@@ -43,18 +49,52 @@ struct BatchUpdateState {
 pub struct UhmService {
     status_tracker: Arc<Mutex<StatusTracker>>,
     database: Database,
+    /// Path to the SQLite file (for maintenance tools that inspect file size).
+    /// Shared across clones so open_database can repoint it.
+    database_path: Arc<std::sync::Mutex<PathBuf>>,
     tool_router: ToolRouter<UhmService>,
     /// Batch update state for efficient bulk operations
     batch_state: Arc<std::sync::Mutex<BatchUpdateState>>,
+    /// Source of "now" for tools that default to the current date
+    clock: Arc<dyn Clock>,
 }
 
 impl UhmService {
     pub fn new(database_path: PathBuf, database: Database) -> Self {
+        Self::with_clock(database_path, database, Arc::new(SystemClock))
+    }
+
+    /// Create a service whose date-dependent tools read from the given clock
+    pub fn with_clock(database_path: PathBuf, database: Database, clock: Arc<dyn Clock>) -> Self {
         Self {
-            status_tracker: Arc::new(Mutex::new(StatusTracker::new(database_path))),
+            status_tracker: Arc::new(Mutex::new(StatusTracker::new(database_path.clone()))),
+            database_path: Arc::new(std::sync::Mutex::new(database_path)),
             database,
             tool_router: Self::tool_router(),
             batch_state: Arc::new(std::sync::Mutex::new(BatchUpdateState::default())),
+            clock,
+        }
+    }
+
+    /// The database for one tool call, pinned to the file open when the call
+    /// started so an open_database meanwhile can't split it across two files
+    fn db(&self) -> Result<Database, McpError> {
+        self.database.pinned().map_err(|e| ToolError::from(e).into())
+    }
+
+    /// Resolve a date parameter ("today", "yesterday", "-N" or YYYY-MM-DD) against the server clock
+    fn date_arg(&self, date: &str) -> Result<String, McpError> {
+        parse_flexible_date(date, self.clock.as_ref()).map_err(|e| McpError::invalid_params(e, None))
+    }
+}
+
+/// Unknown ids and invalid arguments are the caller's to fix (invalid_params);
+/// database and file failures are internal errors
+impl From<ToolError> for McpError {
+    fn from(e: ToolError) -> Self {
+        match e {
+            ToolError::NotFound(_) | ToolError::Validation(_) => McpError::invalid_params(e.to_string(), None),
+            ToolError::Db(_) | ToolError::Io(_) => McpError::internal_error(e.to_string(), None),
         }
     }
 }
@@ -79,6 +119,26 @@ struct FinishBatchUpdateResponse {
     days_recalculated: i64,
 }
 
+// ============================================================================
+// Tool Catalog Response Structs
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+struct ToolDescription {
+    name: String,
+    description: Option<String>,
+    /// JSON Schema of the tool's parameters
+    input_schema: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct DescribeToolsResponse {
+    version: &'static str,
+    build_number: u64,
+    tool_count: usize,
+    tools: Vec<ToolDescription>,
+}
+
 // ============================================================================
 // Food Item Parameter Structs
 // ============================================================================
@@ -106,6 +166,9 @@ pub struct AddFoodItemParams {
     #[serde(default)]
     pub preference: Option<String>,
     pub notes: Option<String>,
+    /// Reject servings other than 100 g, 100 ml or 1 count (default true). Set false for edge cases; the item is stored with a warning.
+    #[serde(default = "default_true")]
+    pub strict: bool,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -120,11 +183,17 @@ fn default_search_limit() -> i64 { 20 }
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct GetFoodItemParams {
     pub id: i64,
+    /// Sodium mg per 1000 kcal above which the item is flagged sodium_dense (default: 1000)
+    #[serde(default = "default_sodium_density_limit")]
+    pub sodium_density_limit: f64,
 }
 
+fn default_sodium_density_limit() -> f64 { DEFAULT_SODIUM_DENSITY_LIMIT }
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ListFoodItemsParams {
     pub preference: Option<String>,
+    /// name (default), created_at, calories, protein, or protein_per_calorie
     #[serde(default = "default_sort_by")]
     pub sort_by: String,
     #[serde(default = "default_sort_order")]
@@ -139,6 +208,40 @@ fn default_sort_by() -> String { "name".to_string() }
 fn default_sort_order() -> String { "asc".to_string() }
 fn default_list_limit() -> i64 { 50 }
 
+fn default_audit_sort() -> String { "severity".to_string() }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LintFoodItemsParams {
+    /// Sort by: severity (worst first) or id (default severity)
+    #[serde(default = "default_audit_sort")]
+    pub sort_by: String,
+    /// Maximum issues to return (default 50, max 200)
+    #[serde(default = "default_list_limit")]
+    pub limit: i64,
+    /// Offset for pagination (default 0)
+    #[serde(default)]
+    pub offset: i64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RescaleFoodItemParams {
+    /// Food item ID
+    pub id: i64,
+    /// Gram amount the stored nutrition actually describes, e.g. 30 if label values for 30 g were entered
+    pub from_basis_grams: f64,
+    /// Gram amount the nutrition should describe, e.g. 100
+    pub to_basis_grams: f64,
+    /// Also set the serving to to_basis_grams g (default false: only nutrition is rescaled)
+    #[serde(default)]
+    pub update_serving: bool,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PreviewFoodItemCascadeParams {
+    /// Food item ID
+    pub id: i64,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct UpdateFoodItemParams {
     pub id: i64,
@@ -165,6 +268,29 @@ pub struct DeleteFoodItemParams {
     pub id: i64,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ExportFoodItemsCsvParams {
+    /// Full path of the CSV file to write
+    pub output_path: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ImportFoodItemsCsvParams {
+    /// Full path to a CSV file in the export_food_items_csv layout
+    pub file_path: String,
+    /// "upsert" (update items matching name+brand, create the rest) or "insert" (always create)
+    #[serde(default = "default_food_import_mode")]
+    pub mode: String,
+}
+
+fn default_food_import_mode() -> String { "upsert".to_string() }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ImportUsdaFoodParams {
+    /// Full path to a FoodData Central JSON file (a single food, or a Foundation/SR Legacy download)
+    pub file_path: String,
+}
+
 // ============================================================================
 // Recipe Parameter Structs
 // ============================================================================
@@ -176,6 +302,9 @@ pub struct CreateRecipeParams {
     /// Number of servings this recipe produces (default 1.0)
     #[serde(default = "default_servings")]
     pub servings_produced: f64,
+    /// Cooked weight of the whole batch in grams (optional). Weigh after cooking: it accounts for
+    /// water lost or absorbed, so per-100g values are more accurate than the raw ingredient sum.
+    pub yield_grams: Option<f64>,
     /// Mark as favorite (default false)
     #[serde(default)]
     pub is_favorite: bool,
@@ -189,6 +318,12 @@ fn default_servings() -> f64 { 1.0 }
 pub struct GetRecipeParams {
     /// Recipe ID
     pub id: i64,
+    /// Sodium mg per 1000 kcal above which the recipe is flagged sodium_dense (default: 1000)
+    #[serde(default = "default_sodium_density_limit")]
+    pub sodium_density_limit: f64,
+    /// Add a friendly_amount to gram-measured ingredients showing an approximate volume ("320 g ≈ 2.75 cups") when a conversion is known (default false)
+    #[serde(default)]
+    pub friendly_units: bool,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -198,7 +333,7 @@ pub struct ListRecipesParams {
     /// Only show favorites (default false)
     #[serde(default)]
     pub favorites_only: bool,
-    /// Sort by: name, created_at, or times_logged (default name)
+    /// Sort by: name, created_at, times_logged, or last_logged (default name). Use desc with last_logged for most recent first.
     #[serde(default = "default_sort_by")]
     pub sort_by: String,
     /// Sort order: asc or desc (default asc)
@@ -220,12 +355,142 @@ pub struct UpdateRecipeParams {
     pub name: Option<String>,
     /// New servings produced (optional)
     pub servings_produced: Option<f64>,
+    /// New cooked batch weight in grams (optional)
+    pub yield_grams: Option<f64>,
     /// New favorite status (optional)
     pub is_favorite: Option<bool>,
     /// New notes (optional)
     pub notes: Option<String>,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ToggleRecipeFavoriteParams {
+    /// Recipe ID
+    pub id: i64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PrepareBatchParams {
+    /// Recipe ID
+    pub recipe_id: i64,
+    /// Servings in the batch (defaults to the recipe's servings_produced)
+    pub servings: Option<f64>,
+    /// Date cooked: YYYY-MM-DD (defaults to today)
+    pub prepared_date: Option<String>,
+    /// Optional notes
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ListPreparedBatchesParams {
+    /// Also list batches with no servings left (default false)
+    #[serde(default)]
+    pub include_finished: bool,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LogRecipePortionParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
+    /// Meal type: breakfast, lunch, dinner, snack, or unspecified
+    #[serde(default = "default_meal_type")]
+    pub meal_type: String,
+    /// Recipe ID
+    pub recipe_id: i64,
+    /// Servings eaten (default 1.0)
+    #[serde(default = "default_servings")]
+    pub servings_eaten: f64,
+    /// Batch to take the servings from (defaults to the recipe's oldest batch with servings left)
+    pub batch_id: Option<i64>,
+    /// When the meal was eaten: time of day (HH:MM) or timestamp (YYYY-MM-DDTHH:MM:SS). Defaults to now.
+    pub eaten_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LintRecipeParams {
+    /// Recipe ID
+    pub recipe_id: i64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RecipeToFoodItemParams {
+    /// Recipe ID to convert
+    pub recipe_id: i64,
+    /// "serving" (1 count = one recipe serving) or "100g" (needs a known batch weight)
+    #[serde(default = "default_serving_basis")]
+    pub serving_basis: String,
+}
+
+fn default_serving_basis() -> String { "serving".to_string() }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct FoodItemToRecipeParams {
+    /// Food item ID to wrap in a one-ingredient recipe
+    pub food_item_id: i64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct NutritionCalculatorItemParam {
+    /// Food item ID (provide this OR recipe_id)
+    pub food_item_id: Option<i64>,
+    /// Recipe ID (provide this OR food_item_id)
+    pub recipe_id: Option<i64>,
+    /// Amount, e.g. 100
+    pub quantity: f64,
+    /// Unit for quantity, e.g. "g", "cup", "servings" (default "servings"). Recipes accept servings or a weight unit.
+    #[serde(default = "default_calculator_unit")]
+    pub unit: String,
+}
+
+fn default_calculator_unit() -> String { "servings".to_string() }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct NutritionCalculatorParams {
+    /// Food items and recipes to combine
+    pub items: Vec<NutritionCalculatorItemParam>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ExportRecipeParams {
+    /// Recipe ID
+    pub recipe_id: i64,
+    /// Output format: "markdown" (readable) or "json" (re-importable with import_recipe). Default: markdown
+    #[serde(default = "default_recipe_export_format")]
+    pub format: String,
+    /// Full path of the file to write
+    pub output_path: String,
+}
+
+fn default_recipe_export_format() -> String { "markdown".to_string() }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ImportRecipeParams {
+    /// Full path to a JSON file written by export_recipe
+    pub file_path: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ListRecentRecipesParams {
+    /// Maximum results (default 10, max 200)
+    #[serde(default = "default_recent_limit")]
+    pub limit: i64,
+}
+
+fn default_recent_limit() -> i64 { 10 }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SuggestMealsParams {
+    /// Calories left in today's budget
+    pub remaining_calories: f64,
+    /// Protein (g) you'd like this meal to provide (optional)
+    pub min_protein: Option<f64>,
+    /// Maximum sodium (mg) for this meal (optional)
+    pub max_sodium: Option<f64>,
+    /// Maximum suggestions (default 10, max 50)
+    #[serde(default = "default_recent_limit")]
+    pub limit: i64,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct DeleteRecipeParams {
     /// Recipe ID to delete
@@ -318,6 +583,18 @@ pub struct UpdateRecipeComponentParams {
     pub notes: Option<String>,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ConvertRecipeIngredientUnitsParams {
+    /// Recipe ID
+    pub recipe_id: i64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RecipeNestingDepthParams {
+    /// Recipe ID
+    pub recipe_id: i64,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct RemoveRecipeComponentParams {
     /// Recipe component ID to remove
@@ -330,21 +607,21 @@ pub struct RemoveRecipeComponentParams {
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct GetOrCreateDayParams {
-    /// Date in ISO format: YYYY-MM-DD
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
     pub date: String,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct GetDayParams {
-    /// Date in ISO format: YYYY-MM-DD
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
     pub date: String,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ListDaysParams {
-    /// Start date (inclusive) - optional
+    /// Start date (inclusive) - optional; YYYY-MM-DD, "today", "yesterday", or "-N"
     pub start_date: Option<String>,
-    /// End date (inclusive) - optional
+    /// End date (inclusive) - optional; YYYY-MM-DD, "today", "yesterday", or "-N"
     pub end_date: Option<String>,
     /// Maximum results (default 50, max 200)
     #[serde(default = "default_list_limit")]
@@ -352,37 +629,205 @@ pub struct ListDaysParams {
     /// Offset for pagination
     #[serde(default)]
     pub offset: i64,
+    /// Also total each day's calories and protein by meal type (breakfast, lunch, dinner, snack), with each type's share of the day (default false)
+    #[serde(default)]
+    pub by_meal_type: bool,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ListDaysStatsParams {
-    /// Start date (inclusive) - optional, defaults to all time
+    /// Start date (inclusive) - optional, defaults to all time; accepts "today", "yesterday", "-N"
     pub start_date: Option<String>,
-    /// End date (inclusive) - optional, defaults to all time
+    /// End date (inclusive) - optional, defaults to all time; accepts "today", "yesterday", "-N"
     pub end_date: Option<String>,
+    /// Flag days more than this many standard deviations from the mean as outliers (default 2.0)
+    #[serde(default = "default_outlier_sd")]
+    pub outlier_sd: f64,
+    /// Also break calories and protein down by meal type (breakfast, lunch, dinner, snack), with each type's share of total calories (default false)
+    #[serde(default)]
+    pub by_meal_type: bool,
+}
+
+fn default_outlier_sd() -> f64 { 2.0 }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ProteinDistributionParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
+    /// Minimum grams of protein per main meal (default 30)
+    #[serde(default = "default_min_protein_per_meal")]
+    pub min_per_meal: f64,
+}
+
+fn default_min_protein_per_meal() -> f64 { 30.0 }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DaySodiumBreakdownParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
+    /// Return only the top N contributors (default: all)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DayBudgetStatusParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
+    /// Daily calorie goal (default 2000)
+    #[serde(default = "default_calorie_goal")]
+    pub calorie_goal: f64,
+    /// Daily protein goal in grams (default 100)
+    #[serde(default = "default_protein_goal")]
+    pub protein_goal: f64,
+    /// Daily sodium limit in mg (default 2300)
+    #[serde(default = "default_sodium_limit")]
+    pub sodium_limit: f64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct HealthSnapshotParams {
+    /// Daily calorie goal (default 2000)
+    #[serde(default = "default_calorie_goal")]
+    pub calorie_goal: f64,
+    /// Daily protein goal in grams (default 100)
+    #[serde(default = "default_protein_goal")]
+    pub protein_goal: f64,
+    /// Daily sodium limit in mg (default 2300)
+    #[serde(default = "default_sodium_limit")]
+    pub sodium_limit: f64,
+}
+
+fn default_calorie_goal() -> f64 { 2000.0 }
+fn default_protein_goal() -> f64 { 100.0 }
+fn default_sodium_limit() -> f64 { 2300.0 }
+fn default_fiber_goal() -> f64 { 25.0 }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CalorieBalanceParams {
+    /// Start date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub start_date: String,
+    /// End date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub end_date: String,
+    /// Daily energy expenditure (TDEE) in kcal. Needed for net balance and predicted weight change.
+    pub tdee: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct QualityWeightsParam {
+    /// Weight for staying under the calorie goal
+    pub calories: f64,
+    /// Weight for reaching the protein goal
+    pub protein: f64,
+    /// Weight for staying under the sodium limit
+    pub sodium: f64,
+    /// Weight for reaching the fiber goal
+    pub fiber: f64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ComputeDayQualityParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
+    /// Daily calorie goal (default 2000)
+    #[serde(default = "default_calorie_goal")]
+    pub calorie_goal: f64,
+    /// Daily protein goal in grams (default 100)
+    #[serde(default = "default_protein_goal")]
+    pub protein_goal: f64,
+    /// Daily sodium limit in mg (default 2300)
+    #[serde(default = "default_sodium_limit")]
+    pub sodium_limit: f64,
+    /// Daily fiber goal in grams (default 25)
+    #[serde(default = "default_fiber_goal")]
+    pub fiber_goal: f64,
+    /// Component weights, normalized to 100 (default calories 35, protein 30, sodium 20, fiber 15)
+    pub weights: Option<QualityWeightsParam>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CompareDaysParams {
+    /// First date (baseline): YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub date_a: String,
+    /// Second date: YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub date_b: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ComparePlannedVsActualParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetDayTimelineParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct UpdateDayParams {
-    /// Date in ISO format: YYYY-MM-DD
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
     pub date: String,
-    /// Notes for the day
+    /// Notes for the day (free text; #hashtags like #sick or #travel can be searched with list_days_with_tag)
     pub notes: Option<String>,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ListDaysWithTagParams {
+    /// Tag to find, with or without the leading # (e.g. "sick" or "#travel")
+    pub tag: String,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct DeleteDayParams {
-    /// Date in ISO format: YYYY-MM-DD
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
     pub date: String,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ArchiveOldDataParams {
+    /// Archive everything dated strictly before this date (YYYY-MM-DD)
+    pub before_date: String,
+    /// Full path of the new SQLite archive file (must not exist yet)
+    pub archive_path: String,
+    /// Also delete the archived rows from the main database. Default: false (copy only)
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct OpenDatabaseParams {
+    /// Full path of the SQLite file to switch to (migrated if its schema is older)
+    pub path: String,
+    /// Create the UHM schema if the file is missing or has none (default false: such a path is refused)
+    #[serde(default)]
+    pub create: bool,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ExportAllJsonParams {
+    /// Full path of the JSON file to write
+    pub output_path: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ImportAllJsonParams {
+    /// Full path to a file written by export_all_json
+    pub file_path: String,
+    /// "restore" (default): database must be empty, backup reproduced exactly. "merge": add to existing data, reusing food items (name + brand) and recipes (name) that already exist and skipping meals, vitals and medications already present
+    #[serde(default = "default_import_all_mode")]
+    pub mode: String,
+}
+
+fn default_import_all_mode() -> String { "restore".to_string() }
+
 // ============================================================================
 // Meal Entry Parameter Structs
 // ============================================================================
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct LogMealParams {
-    /// Date in ISO format: YYYY-MM-DD
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
     pub date: String,
     /// Meal type: breakfast, lunch, dinner, snack, or unspecified
     #[serde(default = "default_meal_type")]
@@ -398,10 +843,94 @@ pub struct LogMealParams {
     pub percent_eaten: Option<f64>,
     /// Optional notes
     pub notes: Option<String>,
+    /// When the meal was eaten: time of day (HH:MM) or timestamp (YYYY-MM-DDTHH:MM:SS). Defaults to now.
+    pub eaten_at: Option<String>,
+    /// Log as a planned meal rather than one eaten (default false). Planned entries are listed separately by get_day and excluded from day totals.
+    #[serde(default)]
+    pub planned: bool,
 }
 
 fn default_meal_type() -> String { "unspecified".to_string() }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ImportCronometerCsvParams {
+    /// Full path to a Cronometer "Servings" or "Daily Summary" CSV export
+    pub file_path: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LogAdhocMealParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
+    /// Meal type: breakfast, lunch, dinner, snack, or unspecified (default)
+    #[serde(default = "default_meal_type")]
+    pub meal_type: String,
+    /// What was eaten, e.g. "Pad thai at Thai Palace"
+    pub description: String,
+    /// Estimated nutrition for the whole portion eaten
+    pub calories: f64,
+    pub protein: f64,
+    pub carbs: f64,
+    pub fat: f64,
+    #[serde(default)]
+    pub fiber: f64,
+    #[serde(default)]
+    pub sodium: f64,
+    #[serde(default)]
+    pub sugar: f64,
+    #[serde(default)]
+    pub saturated_fat: f64,
+    #[serde(default)]
+    pub cholesterol: f64,
+    /// Optional notes
+    pub notes: Option<String>,
+    /// When the meal was eaten: time of day (HH:MM) or timestamp (YYYY-MM-DDTHH:MM:SS). Defaults to now.
+    pub eaten_at: Option<String>,
+}
+
+/// Food item to create when quick_log finds no item with the given name
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct QuickLogNewItemParam {
+    /// Optional brand name
+    pub brand: Option<String>,
+    /// Canonical serving: 100 (g or ml) or 1 (count)
+    pub serving_size: f64,
+    /// Canonical unit: "g", "ml", or "count"
+    pub serving_unit: String,
+    /// Nutrition per serving
+    pub calories: f64,
+    pub protein: f64,
+    pub carbs: f64,
+    pub fat: f64,
+    #[serde(default)]
+    pub fiber: f64,
+    #[serde(default)]
+    pub sodium: f64,
+    #[serde(default)]
+    pub sugar: f64,
+    #[serde(default)]
+    pub saturated_fat: f64,
+    #[serde(default)]
+    pub cholesterol: f64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct QuickLogParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago). Defaults to today
+    pub date: Option<String>,
+    /// Meal type: breakfast, lunch, dinner, snack, or unspecified (default)
+    #[serde(default = "default_meal_type")]
+    pub meal_type: String,
+    /// Food item name, matched case-insensitively against existing items
+    pub food_name: String,
+    /// Amount eaten, e.g. 2
+    pub quantity: f64,
+    /// Unit of the amount: g/oz/lb, ml/cup/tbsp, count/each, or servings
+    pub unit: String,
+    /// Nutrition for a new food item, used only if no item with food_name exists
+    pub nutrition_if_new: Option<QuickLogNewItemParam>,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct GetMealEntryParams {
     /// Meal entry ID
@@ -420,6 +949,8 @@ pub struct UpdateMealEntryParams {
     pub percent_eaten: Option<f64>,
     /// New notes (optional)
     pub notes: Option<String>,
+    /// New eaten_at: time of day (HH:MM) or timestamp (optional)
+    pub eaten_at: Option<String>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -428,12 +959,58 @@ pub struct DeleteMealEntryParams {
     pub id: i64,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SplitPartParam {
+    /// Recipe ID (provide this OR food_item_id)
+    pub recipe_id: Option<i64>,
+    /// Food item ID (provide this OR recipe_id)
+    pub food_item_id: Option<i64>,
+    /// Number of servings
+    pub servings: f64,
+    /// Percentage eaten, 0-100 (default 100)
+    pub percent_eaten: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SplitMealEntryParams {
+    /// Meal entry ID to replace
+    pub meal_entry_id: i64,
+    /// Itemized entries that together make up the original
+    pub parts: Vec<SplitPartParam>,
+    /// Allowed calorie difference between the parts and the original, in percent (default 10)
+    #[serde(default = "default_split_tolerance_percent")]
+    pub tolerance_percent: f64,
+}
+
+fn default_split_tolerance_percent() -> f64 { 10.0 }
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct RecalculateDayNutritionParams {
-    /// Date in ISO format: YYYY-MM-DD
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
     pub date: String,
 }
 
+/// Single meal entry reassignment
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MealTypeAssignmentParam {
+    /// Meal entry ID
+    pub meal_entry_id: i64,
+    /// New meal type: breakfast, lunch, dinner, snack, or unspecified
+    pub meal_type: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ReclassifyMealsParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
+    /// Map of old meal type to new meal type, e.g. {"unspecified": "snack"}
+    #[serde(default)]
+    pub type_mapping: HashMap<String, String>,
+    /// Specific meal entries to reassign (takes precedence over type_mapping)
+    #[serde(default)]
+    pub assignments: Vec<MealTypeAssignmentParam>,
+}
+
 // ============================================================================
 // Medication Parameter Structs
 // ============================================================================
@@ -466,6 +1043,23 @@ pub struct AddMedicationParams {
     pub start_date: Option<String>,
     /// Notes
     pub notes: Option<String>,
+    /// Dose schedule for tapering (dates must increase). The current dose is resolved from this.
+    pub schedule: Option<Vec<DoseStepParam>>,
+}
+
+/// Single step of a medication dose schedule
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DoseStepParam {
+    /// Date this dose takes effect (ISO format: YYYY-MM-DD)
+    pub start_date: String,
+    /// Dosage amount from this date (same unit as the medication)
+    pub dosage_amount: f64,
+}
+
+impl From<DoseStepParam> for DoseStep {
+    fn from(p: DoseStepParam) -> Self {
+        DoseStep { start_date: p.start_date, dosage_amount: p.dosage_amount }
+    }
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -481,6 +1075,8 @@ pub struct ListMedicationsParams {
     pub active_only: bool,
     /// Filter by type: prescription, supplement, otc, natural, compound, medical_device, other
     pub med_type: Option<String>,
+    /// Evaluate "active" on this date (YYYY-MM-DD, default today): is_active, started on or before it, not ended before it
+    pub as_of_date: Option<String>,
 }
 
 fn default_true() -> bool { true }
@@ -527,6 +1123,8 @@ pub struct UpdateMedicationParams {
     pub start_date: Option<String>,
     /// New notes
     pub notes: Option<String>,
+    /// Replace the dose schedule (empty list clears it)
+    pub schedule: Option<Vec<DoseStepParam>>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -539,6 +1137,20 @@ pub struct DeprecateMedicationParams {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DeprecateMedicationsBulkParams {
+    /// Deprecate active medications from this prescribing doctor (case-insensitive)
+    pub prescribing_doctor: Option<String>,
+    /// Deprecate active medications filled at this pharmacy (case-insensitive)
+    pub pharmacy: Option<String>,
+    /// Deprecate active medications of this type: prescription, supplement, otc, natural, compound, medical_device, other
+    pub med_type: Option<String>,
+    /// End date for all of them (defaults to today if not provided)
+    pub end_date: Option<String>,
+    /// Reason for discontinuing, recorded on each
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ReactivateMedicationParams {
     /// Medication ID
@@ -556,8 +1168,25 @@ pub struct DeleteMedicationParams {
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ExportMedicationsParams {
-    /// Patient name to display on the document
-    pub patient_name: String,
+    /// Patient name to display on the document (required unless allow_missing_patient is true)
+    pub patient_name: Option<String>,
+    /// Label the document "Unknown Patient" instead of failing when patient_name is missing (default false)
+    #[serde(default)]
+    pub allow_missing_patient: bool,
+    /// Generate the list as of this date (YYYY-MM-DD) instead of today; scheduled doses resolve to that date
+    pub as_of: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MedicationTimelineParams {
+    /// Full path for the markdown file to write
+    pub output_path: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct TotalDailyDoseParams {
+    /// Ingredient to total, matched case-insensitively against medication names (e.g. "acetaminophen")
+    pub ingredient: String,
 }
 
 // ============================================================================
@@ -602,15 +1231,29 @@ pub struct DeleteVitalGroupParams {
     pub id: i64,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct FindDuplicateVitalGroupsParams {
+    /// Maximum minutes between two group timestamps to count as duplicates (default: 5)
+    pub window_minutes: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MergeVitalGroupsParams {
+    /// Vital group ID to keep
+    pub keep_id: i64,
+    /// Vital group ID whose vitals move to keep_id; it is deleted afterwards
+    pub remove_id: i64,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct AddVitalParams {
-    /// Vital type: weight, blood_pressure (bp), heart_rate (hr/pulse), oxygen_saturation (o2/spo2), glucose
+    /// Vital type: weight, blood_pressure (bp), heart_rate (hr/pulse), oxygen_saturation (o2/spo2), glucose, water (hydration), or a name added with register_vital_type
     pub vital_type: String,
-    /// Primary value (weight, systolic BP, heart rate, O2%, glucose)
+    /// Primary value (weight, systolic BP, heart rate, O2%, glucose, water ml)
     pub value1: f64,
     /// Secondary value (diastolic BP - required for blood_pressure)
     pub value2: Option<f64>,
-    /// Unit (defaults to standard for vital type: lbs, mmHg, bpm, %, mg/dL)
+    /// Unit (defaults to standard for vital type: lbs, mmHg, bpm, %, mg/dL, ml; a custom type's registered unit)
     pub unit: Option<String>,
     /// Timestamp (defaults to now if not provided)
     pub timestamp: Option<String>,
@@ -620,6 +1263,41 @@ pub struct AddVitalParams {
     pub notes: Option<String>,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RegisterVitalTypeParams {
+    /// Name used as vital_type in add_vital and list_vitals_stats (e.g. "mood", "peak_flow"); unique, case-insensitive
+    pub name: String,
+    /// Unit readings are recorded in (e.g. "L/min", "points"); may be empty
+    pub unit: String,
+    /// Label for a second value (e.g. "Evening") if readings have one; omit for single-value types
+    pub value2_label: Option<String>,
+}
+
+/// Single reading for bulk add
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BulkVitalParam {
+    /// Vital type: weight, blood_pressure (bp), heart_rate (hr/pulse), oxygen_saturation (o2/spo2), glucose, water (hydration)
+    pub vital_type: String,
+    /// Primary value (weight, systolic BP, heart rate, O2%, glucose, water ml)
+    pub value1: f64,
+    /// Secondary value (diastolic BP - required for blood_pressure)
+    pub value2: Option<f64>,
+    /// Unit (defaults to standard for vital type)
+    pub unit: Option<String>,
+    /// Timestamp (defaults to now if not provided)
+    pub timestamp: Option<String>,
+    /// Group ID to associate with related readings
+    pub group_id: Option<i64>,
+    /// Notes
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AddVitalsBulkParams {
+    /// Array of readings to add
+    pub readings: Vec<BulkVitalParam>,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct GetVitalParams {
     /// Vital ID
@@ -628,7 +1306,7 @@ pub struct GetVitalParams {
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ListVitalsByTypeParams {
-    /// Vital type: weight, blood_pressure, heart_rate, oxygen_saturation, glucose
+    /// Vital type: weight, blood_pressure, heart_rate, oxygen_saturation, glucose, water
     pub vital_type: String,
     /// Maximum results
     pub limit: Option<i64>,
@@ -643,9 +1321,9 @@ pub struct ListRecentVitalsParams {
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ListVitalsByDateRangeParams {
-    /// Start date (ISO format: YYYY-MM-DD or full timestamp)
+    /// Start date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
     pub start_date: String,
-    /// End date (ISO format: YYYY-MM-DD or full timestamp)
+    /// End date (inclusive: every reading on this date is returned): YYYY-MM-DD, "today", "yesterday", or "-N"
     pub end_date: String,
     /// Filter by vital type (optional)
     pub vital_type: Option<String>,
@@ -673,6 +1351,22 @@ pub struct AssignVitalToGroupParams {
     pub group_id: Option<i64>,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SetVitalTimestampParams {
+    /// Vital ID
+    pub id: i64,
+    /// Corrected timestamp (local YYYY-MM-DDTHH:MM:SS; missing seconds or a space separator are normalized, a Z or UTC offset is converted to local time)
+    pub timestamp: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SetVitalGroupTimestampParams {
+    /// Vital group ID
+    pub id: i64,
+    /// Corrected timestamp (local YYYY-MM-DDTHH:MM:SS; missing seconds or a space separator are normalized, a Z or UTC offset is converted to local time)
+    pub timestamp: String,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct DeleteVitalParams {
     /// Vital ID
@@ -685,14 +1379,179 @@ pub struct ImportOmronBpCsvParams {
     pub file_path: String,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ImportWithingsCsvParams {
+    /// Full path to the Withings CSV file (weight.csv or bp.csv from the Withings data export)
+    pub file_path: String,
+    /// Which export this is: "weight" or "bp"
+    pub kind: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ImportVitalsCsvMappedParams {
+    /// Full path to the CSV file
+    pub file_path: String,
+    /// Timestamp column: header name (case-insensitive) or 0-based index
+    pub timestamp_column: String,
+    /// chrono format of the timestamp column, e.g. "%m/%d/%Y %H:%M" or "%d.%m.%Y" (date-only gives midnight). Omit for ISO 8601.
+    pub timestamp_format: Option<String>,
+    /// Column holding each row's vital type (weight, bp, hr, glucose, ... or a registered custom type)
+    pub vital_type_column: Option<String>,
+    /// Vital type for every row, instead of vital_type_column
+    pub vital_type: Option<String>,
+    /// Primary value column
+    pub value1_column: String,
+    /// Secondary value column (diastolic for blood pressure)
+    pub value2_column: Option<String>,
+    /// Column holding each row's unit
+    pub unit_column: Option<String>,
+    /// Unit for every row, instead of unit_column (defaults to the type's standard unit). kg, mmol/L and volume units are converted to lbs, mg/dL and ml.
+    pub unit: Option<String>,
+    /// Whether the first line is a header row (default true)
+    #[serde(default = "default_true")]
+    pub has_header: bool,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct ListVitalsStatsParams {
-    /// Vital type: weight, blood_pressure (bp), heart_rate (hr), oxygen_saturation (o2/spo2), glucose
+    /// Vital type: weight, blood_pressure (bp), heart_rate (hr), oxygen_saturation (o2/spo2), glucose, water, or a registered custom type
+    pub vital_type: String,
+    /// Start date (inclusive) - optional, defaults to all time; YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub start_date: Option<String>,
+    /// End date (inclusive) - optional, defaults to all time; YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub end_date: Option<String>,
+    /// Flag readings more than this many standard deviations from the mean as outliers (default 2.0)
+    #[serde(default = "default_outlier_sd")]
+    pub outlier_sd: f64,
+    /// Blood pressure display unit: "mmHg" (default) or "kPa". Storage stays in mmHg.
+    pub display_unit: Option<String>,
+    /// Decimal places for stats, 0-6 (default: 1; 2 for BP in kPa; 0 for water). coefficient_of_variation gets one extra place.
+    pub precision: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct VitalHistogramParams {
+    /// Vital type: weight, blood_pressure (bp), heart_rate (hr), oxygen_saturation (o2/spo2), glucose, water, or a registered custom type
     pub vital_type: String,
-    /// Start date (inclusive) - optional, defaults to all time
+    /// Start date (inclusive) - optional, defaults to all time; YYYY-MM-DD, "today", "yesterday", or "-N"
     pub start_date: Option<String>,
-    /// End date (inclusive) - optional, defaults to all time
+    /// End date (inclusive) - optional, defaults to all time; YYYY-MM-DD, "today", "yesterday", or "-N"
     pub end_date: Option<String>,
+    /// Width of each bin in the vital's unit, e.g. 5 for BP mmHg or 1 for weight lbs
+    pub bin_width: f64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LogWaterParams {
+    /// Amount of water in ml
+    pub amount_ml: f64,
+    /// Timestamp (defaults to now if not provided)
+    pub timestamp: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DailyWaterTotalParams {
+    /// Date: YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub date: String,
+    /// Daily water goal in ml (default 2000)
+    #[serde(default = "default_water_goal_ml")]
+    pub goal_ml: f64,
+}
+
+fn default_water_goal_ml() -> f64 { 2000.0 }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DetectWeightPlateauParams {
+    /// Number of days to look back from the latest weight reading (default 14)
+    #[serde(default = "default_plateau_window_days")]
+    pub window_days: i64,
+    /// Maximum weight range in lbs that still counts as a plateau (default 2.0)
+    #[serde(default = "default_plateau_threshold_lbs")]
+    pub threshold_lbs: f64,
+}
+
+fn default_plateau_window_days() -> i64 { 14 }
+fn default_plateau_threshold_lbs() -> f64 { 2.0 }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ExerciseRecoveryParams {
+    /// Vital group ID holding the post-exercise heart rate readings
+    pub group_id: i64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct HrZoneBreakdownParams {
+    /// Vital group ID holding the heart rate readings taken during the session
+    pub group_id: i64,
+    /// Maximum heart rate in bpm (commonly 220 minus age); required, must be greater than 0
+    pub max_hr: f64,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct VitalsCalendarParams {
+    /// Calendar year, e.g. 2026
+    pub year: i32,
+    /// Vital type filter (optional, built-in or a registered custom type). When given, each day also includes avg_value.
+    pub vital_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct VitalTrackingGapsParams {
+    /// Vital type: weight, blood_pressure (bp), heart_rate (hr), oxygen_saturation (o2/spo2), glucose, water, or a registered custom type
+    pub vital_type: String,
+    /// First date to check (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub start_date: String,
+    /// Last date to check (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub end_date: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DaysMissingWeightParams {
+    /// First date to check (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub start_date: String,
+    /// Last date to check (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub end_date: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct WeightChangeSeriesParams {
+    /// First date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub start_date: String,
+    /// Last date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub end_date: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct VitalCorrelationParams {
+    /// First vital type (weight, blood_pressure, heart_rate, ... or a registered custom type)
+    pub type_a: String,
+    /// Second vital type
+    pub type_b: String,
+    /// First date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub start_date: String,
+    /// Last date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub end_date: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct WeeklyBpAveragesParams {
+    /// First date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago)
+    pub start_date: String,
+    /// Last date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub end_date: String,
+    /// First day of the week: "mon" (ISO, default) or "sun"
+    #[serde(default = "default_week_start")]
+    pub week_start: String,
+}
+
+fn default_week_start() -> String { "mon".to_string() }
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct EstimateA1cParams {
+    /// First date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N" (N days ago). A1c reflects roughly the last 90 days.
+    pub start_date: String,
+    /// Last date (inclusive): YYYY-MM-DD, "today", "yesterday", or "-N"
+    pub end_date: String,
 }
 
 // ============================================================================
@@ -703,10 +1562,11 @@ pub struct ListVitalsStatsParams {
 impl UhmService {
     // --- Status ---
 
-    #[tool(description = "Get the current status of the UHM service including build info, database status, and process information")]
+    #[tool(description = "Get the current status of the UHM service including build info, database status, process information, and data_freshness: the date of the latest weight, BP and glucose readings and days since each (e.g. to remind 'your last weight was 8 days ago')")]
     async fn uhm_status(&self) -> Result<CallToolResult, McpError> {
         let tracker = self.status_tracker.lock().await;
-        let status = tracker.get_status();
+        let mut status = tracker.get_status();
+        status.data_freshness = vitals::data_freshness(&self.db()?, self.clock.as_ref()).ok();
         let json = serde_json::to_string_pretty(&status)
             .map_err(|e| McpError::internal_error(format!("Serialization error: {}", e), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
@@ -724,9 +1584,40 @@ impl UhmService {
         Ok(CallToolResult::success(vec![Content::text(MEDICATION_INSTRUCTIONS)]))
     }
 
+    #[tool(description = "List every tool with its description and parameter JSON schema, tagged with the server version. Use for building UIs or checking which toolset is running.")]
+    fn describe_tools(&self) -> Result<CallToolResult, McpError> {
+        let mut tools: Vec<ToolDescription> = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| ToolDescription {
+                name: tool.name.to_string(),
+                description: tool.description.map(|d| d.to_string()),
+                input_schema: serde_json::Value::Object((*tool.input_schema).clone()),
+            })
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let result = DescribeToolsResponse {
+            version: crate::build_info::VERSION,
+            build_number: crate::build_info::BUILD_NUMBER,
+            tool_count: tools.len(),
+            tools,
+        };
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "One-call dashboard overview: latest reading of each vital, today's calories/protein/sodium vs goals, active medication count, and weight trend direction over the last 14 days. Sections that fail are listed in errors; the rest is still returned.")]
+    fn health_snapshot(&self, Parameters(p): Parameters<HealthSnapshotParams>) -> Result<CallToolResult, McpError> {
+        let result = snapshot::health_snapshot(&self.db()?, self.clock.as_ref(), p.calorie_goal, p.protein_goal, p.sodium_limit)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
     // --- Food Items ---
 
-    #[tool(description = "Create a new food item with nutritional information")]
+    #[tool(description = "Create a new food item with nutritional information. serving_size/serving_unit must be 100 g (solids), 100 ml (liquids) or 1 count; other formats are rejected unless strict is false.")]
     fn add_food_item(&self, Parameters(p): Parameters<AddFoodItemParams>) -> Result<CallToolResult, McpError> {
         let data = FoodItemCreate {
             name: p.name, brand: p.brand, serving_size: p.serving_size, serving_unit: p.serving_unit,
@@ -736,21 +1627,21 @@ impl UhmService {
             notes: p.notes,
             base_unit_type: None, grams_per_serving: None, ml_per_serving: None,
         };
-        let result = food_items::add_food_item(&self.database, data).map_err(|e| McpError::internal_error(e, None))?;
+        let result = food_items::add_food_item(&self.db()?, data, p.strict)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Search for food items by name or brand")]
     fn search_food_items(&self, Parameters(p): Parameters<SearchFoodItemsParams>) -> Result<CallToolResult, McpError> {
-        let result = food_items::search_food_items(&self.database, &p.query, p.limit).map_err(|e| McpError::internal_error(e, None))?;
+        let result = food_items::search_food_items(&self.db()?, &p.query, p.limit)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Get full details for a food item including nutritional data and recipe usage")]
     fn get_food_item(&self, Parameters(p): Parameters<GetFoodItemParams>) -> Result<CallToolResult, McpError> {
-        let result = food_items::get_food_item(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let result = food_items::get_food_item(&self.db()?, p.id, p.sodium_density_limit)?;
         let json = match result {
             Some(item) => serde_json::to_string_pretty(&item),
             None => Ok(format!(r#"{{"error": "Food item not found", "id": {}}}"#, p.id)),
@@ -758,10 +1649,23 @@ impl UhmService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "List food items with optional filtering by preference, sorting, and pagination")]
+    #[tool(description = "List food items with optional filtering by preference, sorting (name, created_at, calories, protein, protein_per_calorie), and pagination. Use sort_by protein_per_calorie with sort_order desc to find the leanest protein sources.")]
     fn list_food_items(&self, Parameters(p): Parameters<ListFoodItemsParams>) -> Result<CallToolResult, McpError> {
-        let result = food_items::list_food_items(&self.database, p.preference.as_deref(), &p.sort_by, &p.sort_order, p.limit, p.offset)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = food_items::list_food_items(&self.db()?, p.preference.as_deref(), &p.sort_by, &p.sort_order, p.limit, p.offset)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Rescale a food item's nutrition to a different gram basis, e.g. values entered per 30 g but stored as per 100 g (from_basis_grams: 30, to_basis_grams: 100). Multiplies every nutrient by to/from, optionally sets the serving to to_basis_grams g, and recalculates affected recipes and days. Returns before/after nutrition.")]
+    fn rescale_food_item(&self, Parameters(p): Parameters<RescaleFoodItemParams>) -> Result<CallToolResult, McpError> {
+        let result = food_items::rescale_food_item(&self.db()?, p.id, p.from_basis_grams, p.to_basis_grams, p.update_serving)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Preview the cascade of a food item change without modifying anything. Lists the recipes (direct users and those reached via components, in recalculation order) and days that update_food_item would recalculate.")]
+    fn preview_food_item_cascade(&self, Parameters(p): Parameters<PreviewFoodItemCascadeParams>) -> Result<CallToolResult, McpError> {
+        let result = food_items::preview_food_item_cascade(&self.db()?, p.id)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
@@ -784,8 +1688,7 @@ impl UhmService {
 
         if batch_active {
             // Batch mode: update without cascade, record the ID
-            let result = food_items::update_food_item_no_cascade(&self.database, p.id, data)
-                .map_err(|e| McpError::internal_error(e, None))?;
+            let result = food_items::update_food_item_no_cascade(&self.db()?, p.id, data)?;
 
             // Record this food item ID for later cascade
             {
@@ -798,8 +1701,7 @@ impl UhmService {
             Ok(CallToolResult::success(vec![Content::text(json)]))
         } else {
             // Normal mode: update with immediate cascade
-            let result = food_items::update_food_item(&self.database, p.id, data)
-                .map_err(|e| McpError::internal_error(e, None))?;
+            let result = food_items::update_food_item(&self.db()?, p.id, data)?;
             let json = serde_json::to_string_pretty(&result)
                 .map_err(|e| McpError::internal_error(e.to_string(), None))?;
             Ok(CallToolResult::success(vec![Content::text(json)]))
@@ -808,7 +1710,7 @@ impl UhmService {
 
     #[tool(description = "Delete a food item (only allowed if not used in any recipes)")]
     fn delete_food_item(&self, Parameters(p): Parameters<DeleteFoodItemParams>) -> Result<CallToolResult, McpError> {
-        let result = food_items::delete_food_item(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let result = food_items::delete_food_item(&self.db()?, p.id)?;
         let json = match result {
             Ok(success) => serde_json::to_string_pretty(&success),
             Err(blocked) => serde_json::to_string_pretty(&blocked),
@@ -816,6 +1718,27 @@ impl UhmService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
+    #[tool(description = "Export all food items (serving info and every nutrition field) to a CSV file for backup or spreadsheet editing")]
+    fn export_food_items_csv(&self, Parameters(p): Parameters<ExportFoodItemsCsvParams>) -> Result<CallToolResult, McpError> {
+        let result = food_items::export_food_items_csv(&self.db()?, &p.output_path)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Import food items from a CSV in the export_food_items_csv layout. mode 'upsert' (default) updates items matching name+brand and creates the rest; 'insert' always creates. Updated items are recalculated once at the end across affected recipes and days. Returns counts and per-row validation errors.")]
+    fn import_food_items_csv(&self, Parameters(p): Parameters<ImportFoodItemsCsvParams>) -> Result<CallToolResult, McpError> {
+        let result = food_items::import_food_items_csv(&self.db()?, &p.file_path, &p.mode)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Import food items from a USDA FoodData Central JSON file (single food or Foundation Foods / SR Legacy download). Maps FDC nutrients (energy, protein, fat, carbs, fiber, sodium, sugar, saturated fat, cholesterol) to a per-100g food item. Skips foods whose name+brand already exist.")]
+    fn import_usda_food(&self, Parameters(p): Parameters<ImportUsdaFoodParams>) -> Result<CallToolResult, McpError> {
+        let result = food_items::import_usda_food(&self.db()?, &p.file_path)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
     // --- Batch Update Tools ---
 
     #[tool(description = "Start batch update mode. While active, update_food_item will skip cascade recalculation. Call finish_batch_update when done to perform one combined cascade for all changed items. Use this when updating many food items to avoid performance issues.")]
@@ -873,8 +1796,7 @@ impl UhmService {
         };
 
         // Perform the combined cascade
-        let result = food_items::batch_cascade_recalculate(&self.database, &changed_ids)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = food_items::batch_cascade_recalculate(&self.db()?, &changed_ids)?;
 
         let response = FinishBatchUpdateResponse {
             success: true,
@@ -888,38 +1810,143 @@ impl UhmService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    // --- Recipes ---
+    // --- Recipes ---
+
+    #[tool(description = "Create a new recipe (ingredients added separately)")]
+    fn create_recipe(&self, Parameters(p): Parameters<CreateRecipeParams>) -> Result<CallToolResult, McpError> {
+        let data = RecipeCreate { name: p.name, servings_produced: p.servings_produced, yield_grams: p.yield_grams, is_favorite: p.is_favorite, notes: p.notes };
+        let result = recipes::create_recipe(&self.db()?, data)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Get full recipe details with ingredients and calculated nutrition, including nutrition_per_100g (based on yield_grams when set, otherwise the raw ingredient weight). Set friendly_units to show approximate volumes beside gram amounts, using a conversion in the ingredient notes (e.g. \"1 cup = 120 g\") or the food item's grams and ml per serving.")]
+    fn get_recipe(&self, Parameters(p): Parameters<GetRecipeParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::get_recipe(&self.db()?, p.id, p.sodium_density_limit, p.friendly_units)?;
+        let json = match result {
+            Some(recipe) => serde_json::to_string_pretty(&recipe),
+            None => Ok(format!(r#"{{"error": "Recipe not found", "id": {}}}"#, p.id)),
+        }.map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List recipes with optional search, favorites filter, sorting, and pagination")]
+    fn list_recipes(&self, Parameters(p): Parameters<ListRecipesParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::list_recipes(&self.db()?, p.query.as_deref(), p.favorites_only, &p.sort_by, &p.sort_order, p.limit, p.offset)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Toggle a recipe's favorite flag. Works even if the recipe has been logged, since favorites don't affect nutrition.")]
+    fn toggle_recipe_favorite(&self, Parameters(p): Parameters<ToggleRecipeFavoriteParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::toggle_recipe_favorite(&self.db()?, p.id)?;
+        let json = match result {
+            Some(recipe) => serde_json::to_string_pretty(&recipe),
+            None => Ok(format!(r#"{{"error": "Recipe not found", "id": {}}}"#, p.id)),
+        }.map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Record a batch of a recipe cooked for meal prep. Servings default to the recipe's servings_produced. Use log_recipe_portion to eat from it.")]
+    fn prepare_batch(&self, Parameters(p): Parameters<PrepareBatchParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::prepare_batch(&self.db()?, p.recipe_id, p.servings, p.prepared_date.as_deref(), p.notes, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List prepared batches with servings remaining per recipe (finished batches only with include_finished)")]
+    fn list_prepared_batches(&self, Parameters(p): Parameters<ListPreparedBatchesParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::list_prepared_batches(&self.db()?, p.include_finished)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Log servings of a recipe as a meal and take them out of a prepared batch (the given batch_id, or the recipe's oldest batch with servings left). The meal is logged even if there is no open batch.")]
+    fn log_recipe_portion(&self, Parameters(p): Parameters<LogRecipePortionParams>) -> Result<CallToolResult, McpError> {
+        let date = self.date_arg(&p.date)?;
+        let meal = days::LogMealRequest {
+            date,
+            meal_type: p.meal_type,
+            recipe_id: Some(p.recipe_id),
+            servings: p.servings_eaten,
+            eaten_at: p.eaten_at,
+            ..Default::default()
+        };
+        let result = recipes::log_recipe_portion(&self.db()?, meal, p.batch_id, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Check a recipe's ingredients for likely unit mistakes: volume units on solid foods, units the food can't convert (silently treated as servings), and tiny gram quantities such as quantity:1 unit:g on a per-100g item. Returns warnings with suggested corrections.")]
+    fn lint_recipe(&self, Parameters(p): Parameters<LintRecipeParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::validate_recipe_units(&self.db()?, p.recipe_id)?;
+        let json = match result {
+            Some(lint) => serde_json::to_string_pretty(&lint),
+            None => Ok(format!(r#"{{"error": "Recipe not found", "id": {}}}"#, p.recipe_id)),
+        }.map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Create a food item from a recipe's nutrition (e.g. a sauce) so it can be used as a plain ingredient without nesting components. serving_basis \"serving\" stores 1 count = one recipe serving; \"100g\" stores per 100 g using the batch weight. The recipe is unchanged and the food item's notes record where it came from.")]
+    fn recipe_to_food_item(&self, Parameters(p): Parameters<RecipeToFoodItemParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::recipe_to_food_item(&self.db()?, p.recipe_id, &p.serving_basis)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Create a one-serving recipe containing a single food item (one food serving, e.g. 100 g). The recipe keeps the food's name and its notes record where it came from.")]
+    fn food_item_to_recipe(&self, Parameters(p): Parameters<FoodItemToRecipeParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::food_item_to_recipe(&self.db()?, p.food_item_id)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
 
-    #[tool(description = "Create a new recipe (ingredients added separately)")]
-    fn create_recipe(&self, Parameters(p): Parameters<CreateRecipeParams>) -> Result<CallToolResult, McpError> {
-        let data = RecipeCreate { name: p.name, servings_produced: p.servings_produced, is_favorite: p.is_favorite, notes: p.notes };
-        let result = recipes::create_recipe(&self.database, data).map_err(|e| McpError::internal_error(e, None))?;
+    #[tool(description = "What-if nutrition calculator: sum the nutrition of any combination of food items and recipes (each with quantity and unit) without logging or saving anything. Returns per-item scaled nutrition and the total.")]
+    fn nutrition_calculator(&self, Parameters(p): Parameters<NutritionCalculatorParams>) -> Result<CallToolResult, McpError> {
+        use crate::tools::recipes::CombineItem;
+        let items: Vec<CombineItem> = p.items.into_iter().map(|item| CombineItem {
+            food_item_id: item.food_item_id,
+            recipe_id: item.recipe_id,
+            quantity: item.quantity,
+            unit: item.unit,
+        }).collect();
+        let result = recipes::combine_nutrition(&self.db()?, &items)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "Get full recipe details with ingredients and calculated nutrition")]
-    fn get_recipe(&self, Parameters(p): Parameters<GetRecipeParams>) -> Result<CallToolResult, McpError> {
-        let result = recipes::get_recipe(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
-        let json = match result {
-            Some(recipe) => serde_json::to_string_pretty(&recipe),
-            None => Ok(format!(r#"{{"error": "Recipe not found", "id": {}}}"#, p.id)),
-        }.map_err(|e| McpError::internal_error(e.to_string(), None))?;
+    #[tool(description = "Export a recipe to a file for sharing: markdown (ingredients, components, per-serving nutrition table) or self-contained JSON including each food item's nutrition. Returns the file path.")]
+    fn export_recipe(&self, Parameters(p): Parameters<ExportRecipeParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::export_recipe(&self.db()?, p.recipe_id, &p.format, &p.output_path)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "List recipes with optional search, favorites filter, sorting, and pagination")]
-    fn list_recipes(&self, Parameters(p): Parameters<ListRecipesParams>) -> Result<CallToolResult, McpError> {
-        let result = recipes::list_recipes(&self.database, p.query.as_deref(), p.favorites_only, &p.sort_by, &p.sort_order, p.limit, p.offset)
-            .map_err(|e| McpError::internal_error(e, None))?;
+    #[tool(description = "Import a recipe from an export_recipe JSON file. Food items are matched by name + brand and created if missing; component recipes are matched by name. Fails if a recipe with the same name exists.")]
+    fn import_recipe(&self, Parameters(p): Parameters<ImportRecipeParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::import_recipe(&self.db()?, &p.file_path)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List recently logged recipes, most recent first, for quick re-logging")]
+    fn list_recent_recipes(&self, Parameters(p): Parameters<ListRecentRecipesParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::list_recent_recipes(&self.db()?, p.limit)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Suggest recipes that fit a remaining macro budget, e.g. \"600 calories and 40g protein left\". Scores recipes by per-serving calories (penalizing overage), protein toward min_protein, and sodium over max_sodium.")]
+    fn suggest_meals(&self, Parameters(p): Parameters<SuggestMealsParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::recommend_recipes(&self.db()?, p.remaining_calories, p.min_protein, p.max_sodium, p.limit)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Update recipe metadata (only allowed if not used in meal entries)")]
     fn update_recipe(&self, Parameters(p): Parameters<UpdateRecipeParams>) -> Result<CallToolResult, McpError> {
-        let data = RecipeUpdate { name: p.name, servings_produced: p.servings_produced, is_favorite: p.is_favorite, notes: p.notes };
-        let result = recipes::update_recipe(&self.database, p.id, data).map_err(|e| McpError::internal_error(e, None))?;
+        let data = RecipeUpdate { name: p.name, servings_produced: p.servings_produced, yield_grams: p.yield_grams, is_favorite: p.is_favorite, notes: p.notes };
+        let result = recipes::update_recipe(&self.db()?, p.id, data)?;
         let json = match result {
             Ok(success) => serde_json::to_string_pretty(&success),
             Err(blocked) => serde_json::to_string_pretty(&blocked),
@@ -929,7 +1956,7 @@ impl UhmService {
 
     #[tool(description = "Delete a recipe (only allowed if not logged in meals and not used as a component in other recipes)")]
     fn delete_recipe(&self, Parameters(p): Parameters<DeleteRecipeParams>) -> Result<CallToolResult, McpError> {
-        let result = recipes::delete_recipe(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let result = recipes::delete_recipe(&self.db()?, p.id)?;
         let json = match result {
             Ok(success) => serde_json::to_string_pretty(&success),
             Err(blocked) => serde_json::to_string_pretty(&blocked),
@@ -942,7 +1969,7 @@ impl UhmService {
     #[tool(description = "Add a food item to a recipe as an ingredient")]
     fn add_recipe_ingredient(&self, Parameters(p): Parameters<AddRecipeIngredientParams>) -> Result<CallToolResult, McpError> {
         let data = RecipeIngredientCreate { recipe_id: p.recipe_id, food_item_id: p.food_item_id, quantity: p.quantity, unit: p.unit, notes: p.notes };
-        let result = recipes::add_recipe_ingredient(&self.database, data).map_err(|e| McpError::internal_error(e, None))?;
+        let result = recipes::add_recipe_ingredient(&self.db()?, data)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
@@ -956,8 +1983,7 @@ impl UhmService {
             unit: i.unit,
             notes: i.notes,
         }).collect();
-        let result = recipes::add_recipe_ingredients_batch(&self.database, p.recipe_id, ingredients)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = recipes::add_recipe_ingredients_batch(&self.db()?, p.recipe_id, ingredients)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
@@ -965,7 +1991,7 @@ impl UhmService {
     #[tool(description = "Update a recipe ingredient's quantity or unit")]
     fn update_recipe_ingredient(&self, Parameters(p): Parameters<UpdateRecipeIngredientParams>) -> Result<CallToolResult, McpError> {
         let data = RecipeIngredientUpdate { quantity: p.quantity, unit: p.unit, notes: p.notes };
-        let result = recipes::update_recipe_ingredient(&self.database, p.id, data).map_err(|e| McpError::internal_error(e, None))?;
+        let result = recipes::update_recipe_ingredient(&self.db()?, p.id, data)?;
         let json = match result {
             Some(ing) => serde_json::to_string_pretty(&ing),
             None => Ok(format!(r#"{{"error": "Recipe ingredient not found", "id": {}}}"#, p.id)),
@@ -975,24 +2001,38 @@ impl UhmService {
 
     #[tool(description = "Remove an ingredient from a recipe")]
     fn remove_recipe_ingredient(&self, Parameters(p): Parameters<RemoveRecipeIngredientParams>) -> Result<CallToolResult, McpError> {
-        let deleted = recipes::remove_recipe_ingredient(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let deleted = recipes::remove_recipe_ingredient(&self.db()?, p.id)?;
         let json = serde_json::json!({"success": deleted, "id": p.id}).to_string();
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
+    #[tool(description = "Convert a recipe's volume-measured ingredients (cups, tbsp, ...) to grams using a density from the unit annotation, the ingredient notes (\"1 cup = 120 g\") or the food item's grams/ml per serving; liquids stored per ml become ml. The original measure is kept in the notes, the recipe is recalculated once, and ingredients without a density are reported.")]
+    fn convert_recipe_ingredient_units(&self, Parameters(p): Parameters<ConvertRecipeIngredientUnitsParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::convert_recipe_ingredient_units(&self.db()?, p.recipe_id)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
     #[tool(description = "Force recalculate cached nutrition values for a recipe")]
     fn recalculate_recipe_nutrition(&self, Parameters(p): Parameters<RecalculateNutritionParams>) -> Result<CallToolResult, McpError> {
-        let result = recipes::recalculate_nutrition(&self.database, p.recipe_id).map_err(|e| McpError::internal_error(e, None))?;
+        let result = recipes::recalculate_nutrition(&self.db()?, p.recipe_id)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     // --- Recipe Components ---
 
-    #[tool(description = "Add another recipe as a component of a recipe (recipe within a recipe). Automatically calculates combined nutrition.")]
+    #[tool(description = "Add another recipe as a component of a recipe (recipe within a recipe). Automatically calculates combined nutrition. Rejected if it would nest recipes more than 5 levels deep (UHM_MAX_RECIPE_DEPTH overrides).")]
     fn add_recipe_component(&self, Parameters(p): Parameters<AddRecipeComponentParams>) -> Result<CallToolResult, McpError> {
         let data = RecipeComponentCreate { recipe_id: p.recipe_id, component_recipe_id: p.component_recipe_id, servings: p.servings, notes: p.notes };
-        let result = recipes::add_recipe_component(&self.database, data).map_err(|e| McpError::internal_error(e, None))?;
+        let result = recipes::add_recipe_component(&self.db()?, data)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "How many levels of component recipes a recipe contains (0 = none), with the deepest chain of recipe names and the allowed maximum")]
+    fn recipe_nesting_depth(&self, Parameters(p): Parameters<RecipeNestingDepthParams>) -> Result<CallToolResult, McpError> {
+        let result = recipes::recipe_nesting_depth(&self.db()?, p.recipe_id)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
@@ -1000,7 +2040,7 @@ impl UhmService {
     #[tool(description = "Update a recipe component's servings")]
     fn update_recipe_component(&self, Parameters(p): Parameters<UpdateRecipeComponentParams>) -> Result<CallToolResult, McpError> {
         let data = RecipeComponentUpdate { servings: p.servings, notes: p.notes };
-        let result = recipes::update_recipe_component(&self.database, p.id, data).map_err(|e| McpError::internal_error(e, None))?;
+        let result = recipes::update_recipe_component(&self.db()?, p.id, data)?;
         let json = match result {
             Some(comp) => serde_json::to_string_pretty(&comp),
             None => Ok(format!(r#"{{"error": "Recipe component not found", "id": {}}}"#, p.id)),
@@ -1010,7 +2050,7 @@ impl UhmService {
 
     #[tool(description = "Remove a component recipe from a recipe")]
     fn remove_recipe_component(&self, Parameters(p): Parameters<RemoveRecipeComponentParams>) -> Result<CallToolResult, McpError> {
-        let deleted = recipes::remove_recipe_component(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let deleted = recipes::remove_recipe_component(&self.db()?, p.id)?;
         let json = serde_json::json!({"success": deleted, "id": p.id}).to_string();
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
@@ -1019,60 +2059,226 @@ impl UhmService {
 
     #[tool(description = "Get or create a day by date. Creates a new day if it doesn't exist.")]
     fn get_or_create_day(&self, Parameters(p): Parameters<GetOrCreateDayParams>) -> Result<CallToolResult, McpError> {
-        let result = days::get_or_create_day(&self.database, &p.date).map_err(|e| McpError::internal_error(e, None))?;
+        let date = self.date_arg(&p.date)?;
+        let result = days::get_or_create_day(&self.db()?, &date)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Get full day details including all meals organized by type and nutrition totals")]
     fn get_day(&self, Parameters(p): Parameters<GetDayParams>) -> Result<CallToolResult, McpError> {
-        let result = days::get_day(&self.database, &p.date).map_err(|e| McpError::internal_error(e, None))?;
+        let date = self.date_arg(&p.date)?;
+        let result = days::get_day(&self.db()?, &date)?;
         let json = match result {
             Some(day) => serde_json::to_string_pretty(&day),
-            None => Ok(format!(r#"{{"error": "Day not found", "date": "{}"}}"#, p.date)),
+            None => Ok(format!(r#"{{"error": "Day not found", "date": "{}"}}"#, date)),
         }.map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "List days with optional date range filter and pagination")]
+    #[tool(description = "List days with optional date range filter and pagination. Set by_meal_type for each day's calories and protein per breakfast/lunch/dinner/snack.")]
     fn list_days(&self, Parameters(p): Parameters<ListDaysParams>) -> Result<CallToolResult, McpError> {
-        let result = days::list_days(&self.database, p.start_date.as_deref(), p.end_date.as_deref(), p.limit, p.offset)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let start_date = p.start_date.as_deref().map(|d| self.date_arg(d)).transpose()?;
+        let end_date = p.end_date.as_deref().map(|d| self.date_arg(d)).transpose()?;
+        let result = days::list_days(&self.db()?, start_date.as_deref(), end_date.as_deref(), p.limit, p.offset, p.by_meal_type)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "Get comprehensive statistics for days' nutrition data. Returns mean, median, mode, standard deviation, min, max, percentiles, and outliers for each nutrient. Much faster than processing raw data externally.")]
+    #[tool(description = "Get comprehensive statistics for days' nutrition data. Returns mean, median, mode, standard deviation, min, max, percentiles, and outliers (beyond outlier_sd standard deviations, default 2) for each nutrient. Much faster than processing raw data externally. Set by_meal_type for per-meal-type (breakfast/lunch/dinner/snack) calorie and protein stats.")]
     fn list_days_stats(&self, Parameters(p): Parameters<ListDaysStatsParams>) -> Result<CallToolResult, McpError> {
-        let result = days::list_days_stats(&self.database, p.start_date.as_deref(), p.end_date.as_deref())
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let start_date = p.start_date.as_deref().map(|d| self.date_arg(d)).transpose()?;
+        let end_date = p.end_date.as_deref().map(|d| self.date_arg(d)).transpose()?;
+        let result = days::list_days_stats(&self.db()?, start_date.as_deref(), end_date.as_deref(), p.outlier_sd, p.by_meal_type)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Check how protein is spread across breakfast, lunch and dinner for a day. Flags which main meals reach the per-meal minimum (default 30g) and reports the largest gap between meals.")]
+    fn protein_distribution(&self, Parameters(p): Parameters<ProteinDistributionParams>) -> Result<CallToolResult, McpError> {
+        let date = self.date_arg(&p.date)?;
+        let result = days::protein_distribution(&self.db()?, &date, p.min_per_meal)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Rank the food items behind a day's sodium. Recipes (and nested component recipes) are expanded into their ingredients; returns each food item's mg and percent of the day's total, highest first. Use to answer 'what made my sodium so high today?'")]
+    fn day_sodium_breakdown(&self, Parameters(p): Parameters<DaySodiumBreakdownParams>) -> Result<CallToolResult, McpError> {
+        let date = self.date_arg(&p.date)?;
+        let result = days::day_sodium_breakdown(&self.db()?, &date, p.limit)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Get consumed vs remaining calories, protein and sodium for a day against daily goals (defaults: 2000 kcal, 100g protein, 2300mg sodium). Use to answer 'how many calories do I have left today?'")]
+    fn day_budget_status(&self, Parameters(p): Parameters<DayBudgetStatusParams>) -> Result<CallToolResult, McpError> {
+        let date = self.date_arg(&p.date)?;
+        let result = days::day_budget_status(&self.db()?, &date, p.calorie_goal, p.protein_goal, p.sodium_limit)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Score a logged day 0-100 for diet quality: calories under goal, protein reaching goal, sodium under limit and fiber reaching goal, with configurable weights. Returns each component's fraction met and points so the score is explainable.")]
+    fn compute_day_quality(&self, Parameters(p): Parameters<ComputeDayQualityParams>) -> Result<CallToolResult, McpError> {
+        let date = self.date_arg(&p.date)?;
+        let goals = days::DayGoals { calories: p.calorie_goal, protein: p.protein_goal, sodium: p.sodium_limit };
+        let weights = p.weights
+            .map(|w| days::QualityWeights { calories: w.calories, protein: w.protein, sodium: w.sodium, fiber: w.fiber })
+            .unwrap_or_default();
+        let result = days::compute_day_quality(&self.db()?, &date, goals, p.fiber_goal, weights)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Calorie balance over a date range for checking weight change: summed intake on logged days, expenditure (tdee x logged days) and net balance when tdee is given, the predicted weight change (balance / 3500 lbs), and the actual change between the first and last weight readings. Also returns implied_tdee, the daily expenditure that explains the actual change.")]
+    fn calorie_balance(&self, Parameters(p): Parameters<CalorieBalanceParams>) -> Result<CallToolResult, McpError> {
+        let (start, end) = (self.date_arg(&p.start_date)?, self.date_arg(&p.end_date)?);
+        let result = days::calorie_balance(&self.db()?, &start, &end, p.tdee)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Compare nutrition totals of two days. Returns both days' totals and per-nutrient deltas (date_b minus date_a) with percent change.")]
+    fn compare_days(&self, Parameters(p): Parameters<CompareDaysParams>) -> Result<CallToolResult, McpError> {
+        let (date_a, date_b) = (self.date_arg(&p.date_a)?, self.date_arg(&p.date_b)?);
+        let result = days::compare_days(&self.db()?, &date_a, &date_b)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Compare a day's planned meal entries (logged with planned: true) against what was actually eaten. Returns planned and actual totals and per-nutrient adherence deltas (actual minus planned).")]
+    fn compare_planned_vs_actual(&self, Parameters(p): Parameters<ComparePlannedVsActualParams>) -> Result<CallToolResult, McpError> {
+        let date = self.date_arg(&p.date)?;
+        let result = days::compare_planned_vs_actual(&self.db()?, &date)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Get a chronological timeline of a day: meals (by eaten_at) and vital readings/groups, sorted by time")]
+    fn get_day_timeline(&self, Parameters(p): Parameters<GetDayTimelineParams>) -> Result<CallToolResult, McpError> {
+        let date = self.date_arg(&p.date)?;
+        let result = days::get_day_timeline(&self.db()?, &date)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Update day notes")]
     fn update_day(&self, Parameters(p): Parameters<UpdateDayParams>) -> Result<CallToolResult, McpError> {
-        let result = days::update_day(&self.database, &p.date, p.notes).map_err(|e| McpError::internal_error(e, None))?;
+        let date = self.date_arg(&p.date)?;
+        let result = days::update_day(&self.db()?, &date, p.notes)?;
         let json = match result {
             Some(day) => serde_json::to_string_pretty(&day),
-            None => Ok(format!(r#"{{"error": "Day not found", "date": "{}"}}"#, p.date)),
+            None => Ok(format!(r#"{{"error": "Day not found", "date": "{}"}}"#, date)),
         }.map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
+    #[tool(description = "List days whose notes contain a #hashtag (e.g. #sick, #travel), newest first, with each day's nutrition totals and all of its tags")]
+    fn list_days_with_tag(&self, Parameters(p): Parameters<ListDaysWithTagParams>) -> Result<CallToolResult, McpError> {
+        let result = days::list_days_with_tag(&self.db()?, &p.tag)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List every #hashtag used in day notes with how many days use it and when it was last used")]
+    fn list_all_tags(&self) -> Result<CallToolResult, McpError> {
+        let result = days::list_all_tags(&self.db()?)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
     // --- Meal Entries ---
 
-    #[tool(description = "Log a meal entry. Provide either recipe_id OR food_item_id (not both). Automatically creates the day if needed.")]
+    #[tool(description = "Log a meal entry. Provide either recipe_id OR food_item_id (not both). Automatically creates the day if needed. Set planned to record a meal plan; compare it with what was eaten using compare_planned_vs_actual.")]
     fn log_meal(&self, Parameters(p): Parameters<LogMealParams>) -> Result<CallToolResult, McpError> {
-        let result = days::log_meal(&self.database, &p.date, &p.meal_type, p.recipe_id, p.food_item_id, p.servings, p.percent_eaten, p.notes)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let date = self.date_arg(&p.date)?;
+        let result = days::log_meal(&self.db()?, days::LogMealRequest {
+            date,
+            meal_type: p.meal_type,
+            recipe_id: p.recipe_id,
+            food_item_id: p.food_item_id,
+            servings: p.servings,
+            percent_eaten: p.percent_eaten,
+            notes: p.notes,
+            eaten_at: p.eaten_at,
+            planned: p.planned,
+        }, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Log a one-off food (e.g. a restaurant meal) by description with estimated nutrition, without adding it to the food item catalog. Shows up in get_day like any other entry, with source_type 'adhoc'.")]
+    fn log_adhoc_meal(&self, Parameters(p): Parameters<LogAdhocMealParams>) -> Result<CallToolResult, McpError> {
+        let nutrition = Nutrition {
+            calories: p.calories,
+            protein: p.protein,
+            carbs: p.carbs,
+            fat: p.fat,
+            fiber: p.fiber,
+            sodium: p.sodium,
+            sugar: p.sugar,
+            saturated_fat: p.saturated_fat,
+            cholesterol: p.cholesterol,
+        };
+        let date = self.date_arg(&p.date)?;
+        let request = days::LogAdhocMealRequest {
+            date,
+            meal_type: p.meal_type,
+            description: p.description,
+            nutrition,
+            notes: p.notes,
+            eaten_at: p.eaten_at,
+        };
+        let result = days::log_adhoc_meal(&self.db()?, request, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Import a Cronometer CSV export. A Servings export finds food items by name (creating missing ones on a 100 g / 100 ml / 1 count serving scaled from the row; rows in other units such as \"large egg\" are skipped) and logs each row under its meal group (Breakfast/Lunch/Dinner/Snacks). A Daily Summary export logs each day's totals as one ad-hoc entry. Entries logged on a day before the import are skipped as duplicates, so re-importing is safe; identical rows within one export are each logged.")]
+    fn import_cronometer_csv(&self, Parameters(p): Parameters<ImportCronometerCsvParams>) -> Result<CallToolResult, McpError> {
+        let result = days::import_cronometer_csv(&self.db()?, &p.file_path, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Log a food in one call: finds the food item by name (case-insensitive), creates it from nutrition_if_new (canonical 100 g / 100 ml / 1 count serving) if it doesn't exist, and logs quantity+unit of it. Example: food_name \"Egg\", quantity 2, unit \"count\", meal_type \"breakfast\". Returns whether the item was created and the meal entry.")]
+    fn quick_log(&self, Parameters(p): Parameters<QuickLogParams>) -> Result<CallToolResult, McpError> {
+        let date = match p.date {
+            Some(d) => self.date_arg(&d)?,
+            None => self.clock.today(),
+        };
+        let new_item = p.nutrition_if_new.map(|n| days::QuickLogNewItem {
+            brand: n.brand,
+            serving_size: n.serving_size,
+            serving_unit: n.serving_unit,
+            nutrition: Nutrition {
+                calories: n.calories,
+                protein: n.protein,
+                carbs: n.carbs,
+                fat: n.fat,
+                fiber: n.fiber,
+                sodium: n.sodium,
+                sugar: n.sugar,
+                saturated_fat: n.saturated_fat,
+                cholesterol: n.cholesterol,
+            },
+        });
+        let request = days::QuickLogRequest {
+            date,
+            meal_type: p.meal_type,
+            food_name: p.food_name,
+            quantity: p.quantity,
+            unit: p.unit,
+            new_item,
+        };
+        let result = days::quick_log(&self.db()?, request, self.clock.as_ref())?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Get a meal entry by ID with full details")]
     fn get_meal_entry(&self, Parameters(p): Parameters<GetMealEntryParams>) -> Result<CallToolResult, McpError> {
-        let result = days::get_meal_entry(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let result = days::get_meal_entry(&self.db()?, p.id)?;
         let json = match result {
             Some(entry) => serde_json::to_string_pretty(&entry),
             None => Ok(format!(r#"{{"error": "Meal entry not found", "id": {}}}"#, p.id)),
@@ -1080,10 +2286,9 @@ impl UhmService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "Update a meal entry (servings, percent eaten, meal type, or notes)")]
+    #[tool(description = "Update a meal entry (servings, percent eaten, meal type, notes, or eaten_at time)")]
     fn update_meal_entry(&self, Parameters(p): Parameters<UpdateMealEntryParams>) -> Result<CallToolResult, McpError> {
-        let result = days::update_meal_entry(&self.database, p.id, p.meal_type.as_deref(), p.servings, p.percent_eaten, p.notes)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = days::update_meal_entry(&self.db()?, p.id, p.meal_type.as_deref(), p.servings, p.percent_eaten, p.notes, p.eaten_at.as_deref())?;
         let json = match result {
             Some(entry) => serde_json::to_string_pretty(&entry),
             None => Ok(format!(r#"{{"error": "Meal entry not found", "id": {}}}"#, p.id)),
@@ -1093,14 +2298,45 @@ impl UhmService {
 
     #[tool(description = "Delete a meal entry")]
     fn delete_meal_entry(&self, Parameters(p): Parameters<DeleteMealEntryParams>) -> Result<CallToolResult, McpError> {
-        let deleted = days::delete_meal_entry(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let deleted = days::delete_meal_entry(&self.db()?, p.id)?;
         let json = serde_json::json!({"success": deleted, "id": p.id}).to_string();
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "Force recalculate cached nutrition totals for a day")]
+    #[tool(description = "Split a combined meal entry into itemized entries (recipe_id or food_item_id + servings each) on the same day and meal type. The original is deleted and the day recalculated once. Warns if the parts' calories differ from the original by more than tolerance_percent (default 10).")]
+    fn split_meal_entry(&self, Parameters(p): Parameters<SplitMealEntryParams>) -> Result<CallToolResult, McpError> {
+        use crate::tools::days::SplitPart;
+        let parts: Vec<SplitPart> = p.parts.into_iter().map(|part| SplitPart {
+            recipe_id: part.recipe_id,
+            food_item_id: part.food_item_id,
+            servings: part.servings,
+            percent_eaten: part.percent_eaten,
+        }).collect();
+        let result = days::split_meal_entry(&self.db()?, p.meal_entry_id, &parts, p.tolerance_percent, self.clock.as_ref())?;
+        let json = match result {
+            Some(split) => serde_json::to_string_pretty(&split),
+            None => Ok(format!(r#"{{"error": "Meal entry not found", "id": {}}}"#, p.meal_entry_id)),
+        }.map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Force recalculate cached nutrition totals for a day. Returns the totals before and after plus per-nutrient deltas, e.g. to see the effect of editing a food item.")]
     fn recalculate_day_nutrition(&self, Parameters(p): Parameters<RecalculateDayNutritionParams>) -> Result<CallToolResult, McpError> {
-        let result = days::recalculate_day_nutrition_tool(&self.database, &p.date).map_err(|e| McpError::internal_error(e, None))?;
+        let date = self.date_arg(&p.date)?;
+        let result = days::recalculate_day_nutrition_tool(&self.db()?, &date)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Reclassify meal entries on a date. Use type_mapping to move all entries of one meal type to another (e.g. unspecified -> snack) and/or assignments to set specific entries. Returns the number updated and the new meal type distribution. Nutrition is unchanged.")]
+    fn reclassify_meals(&self, Parameters(p): Parameters<ReclassifyMealsParams>) -> Result<CallToolResult, McpError> {
+        use crate::tools::days::MealTypeAssignment;
+        let assignments: Vec<MealTypeAssignment> = p.assignments.into_iter().map(|a| MealTypeAssignment {
+            meal_entry_id: a.meal_entry_id,
+            meal_type: a.meal_type,
+        }).collect();
+        let date = self.date_arg(&p.date)?;
+        let result = days::reclassify_meals(&self.db()?, &date, p.type_mapping, assignments)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
@@ -1123,15 +2359,16 @@ impl UhmService {
             refills_remaining: p.refills_remaining,
             start_date: p.start_date,
             notes: p.notes,
+            schedule: p.schedule.map(|steps| steps.into_iter().map(DoseStep::from).collect()),
         };
-        let result = medications::add_medication(&self.database, data).map_err(|e| McpError::internal_error(e, None))?;
+        let result = medications::add_medication(&self.db()?, data)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Get full details for a medication")]
     fn get_medication(&self, Parameters(p): Parameters<GetMedicationParams>) -> Result<CallToolResult, McpError> {
-        let result = medications::get_medication(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let result = medications::get_medication(&self.db()?, p.id, self.clock.as_ref())?;
         let json = match result {
             Some(med) => serde_json::to_string_pretty(&med),
             None => Ok(format!(r#"{{"error": "Medication not found", "id": {}}}"#, p.id)),
@@ -1139,18 +2376,24 @@ impl UhmService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "List medications with optional filtering by active status and type")]
+    #[tool(description = "List medications with optional filtering by active status and type. Pass as_of_date to see what was being taken on a past date (active = is_active, start_date <= as_of, end_date null or >= as_of).")]
     fn list_medications(&self, Parameters(p): Parameters<ListMedicationsParams>) -> Result<CallToolResult, McpError> {
-        let result = medications::list_medications(&self.database, p.active_only, p.med_type.as_deref())
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let fixed;
+        let clock: &dyn Clock = match p.as_of_date.as_deref() {
+            Some(date) => {
+                fixed = FixedClock::from_date(date).map_err(|e| McpError::invalid_params(e, None))?;
+                &fixed
+            }
+            None => self.clock.as_ref(),
+        };
+        let result = medications::list_medications(&self.db()?, p.active_only, p.med_type.as_deref(), clock)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Search medications by name")]
     fn search_medications(&self, Parameters(p): Parameters<SearchMedicationsParams>) -> Result<CallToolResult, McpError> {
-        let result = medications::search_medications(&self.database, &p.query, p.active_only)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = medications::search_medications(&self.db()?, &p.query, p.active_only)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
@@ -1171,9 +2414,9 @@ impl UhmService {
             refills_remaining: p.refills_remaining,
             start_date: p.start_date,
             notes: p.notes,
+            schedule: p.schedule.map(|steps| steps.into_iter().map(DoseStep::from).collect()),
         };
-        let result = medications::update_medication(&self.database, p.id, data, p.force)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = medications::update_medication(&self.db()?, p.id, data, p.force)?;
         let json = match result {
             Ok(success) => serde_json::to_string_pretty(&success),
             Err(blocked) => serde_json::to_string_pretty(&blocked),
@@ -1183,24 +2426,33 @@ impl UhmService {
 
     #[tool(description = "Deprecate a medication (mark as inactive). Preferred over deletion to preserve history.")]
     fn deprecate_medication(&self, Parameters(p): Parameters<DeprecateMedicationParams>) -> Result<CallToolResult, McpError> {
-        let result = medications::deprecate_medication(&self.database, p.id, p.end_date.as_deref(), p.reason.as_deref())
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = medications::deprecate_medication(&self.db()?, p.id, p.end_date.as_deref(), p.reason.as_deref(), self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Deprecate every active medication matching a filter in one transaction, e.g. all meds from a prescriber or pharmacy you are leaving. Give at least one of prescribing_doctor, pharmacy or med_type; all given must match. Returns the medications deprecated.")]
+    fn deprecate_medications_bulk(&self, Parameters(p): Parameters<DeprecateMedicationsBulkParams>) -> Result<CallToolResult, McpError> {
+        let filter = medications::MedicationFilter {
+            prescribing_doctor: p.prescribing_doctor,
+            pharmacy: p.pharmacy,
+            med_type: p.med_type,
+        };
+        let result = medications::deprecate_medications_bulk(&self.db()?, &filter, p.end_date.as_deref(), p.reason.as_deref(), self.clock.as_ref())?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Reactivate a previously deprecated medication")]
     fn reactivate_medication(&self, Parameters(p): Parameters<ReactivateMedicationParams>) -> Result<CallToolResult, McpError> {
-        let result = medications::reactivate_medication(&self.database, p.id)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = medications::reactivate_medication(&self.db()?, p.id, self.clock.as_ref())?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Delete a medication. Requires force=true. Consider deprecating instead to preserve history.")]
     fn delete_medication(&self, Parameters(p): Parameters<DeleteMedicationParams>) -> Result<CallToolResult, McpError> {
-        let result = medications::delete_medication(&self.database, p.id, p.force)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = medications::delete_medication(&self.db()?, p.id, p.force)?;
         let json = match result {
             Ok(success) => serde_json::to_string_pretty(&success),
             Err(blocked) => serde_json::to_string_pretty(&blocked),
@@ -1208,40 +2460,148 @@ impl UhmService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
+    #[tool(description = "Write a markdown medication timeline for a provider: each medication (active and stopped) as a bar from start to end date, grouped by type, with dose changes annotated when a deprecated medication is followed by a new entry with the same name. Returns the file path.")]
+    fn generate_medication_timeline(&self, Parameters(p): Parameters<MedicationTimelineParams>) -> Result<CallToolResult, McpError> {
+        let result = medications::generate_medication_timeline(&self.db()?, &p.output_path, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Total today's daily dose of an ingredient across active medications whose name contains it: dose x doses-per-day (parsed from frequency; PRN counted at its maximum), with mg/g/mcg normalized to mg. Medications in other units or with an unreadable frequency are listed as excluded, not summed. Warns when acetaminophen exceeds 4000 mg/day.")]
+    fn total_daily_dose(&self, Parameters(p): Parameters<TotalDailyDoseParams>) -> Result<CallToolResult, McpError> {
+        let result = medications::total_daily_dose(&self.db()?, &p.ingredient, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
     #[tool(description = "Export active medications to a formatted markdown document")]
     fn export_medications_markdown(&self, Parameters(p): Parameters<ExportMedicationsParams>) -> Result<CallToolResult, McpError> {
-        let result = medications::export_medications_markdown(&self.database, &p.patient_name)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let fixed;
+        let clock: &dyn Clock = match p.as_of.as_deref() {
+            Some(date) => {
+                fixed = FixedClock::from_date(date).map_err(|e| McpError::invalid_params(e, None))?;
+                &fixed
+            }
+            None => self.clock.as_ref(),
+        };
+        let result = medications::export_medications_markdown(&self.db()?, p.patient_name.as_deref(), p.allow_missing_patient, clock)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     // --- Cleanup/Maintenance ---
 
-    #[tool(description = "List all food items with zero uses (not used in any recipe). These are safe to delete with delete_food_item.")]
+    #[tool(description = "Find food items with implausible nutrition from data-entry errors: negative values, more than ~9 kcal per gram, or protein + carbs + fat heavier than the serving (gram-based items). Returns each offending item with the rule it violates and a severity; sorted worst first by default, paginated with limit/offset, and issue_count gives the total found.")]
+    fn lint_food_items(&self, Parameters(p): Parameters<LintFoodItemsParams>) -> Result<CallToolResult, McpError> {
+        let result = food_items::audit_food_items(&self.db()?, &p.sort_by, p.limit, p.offset)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List all food items with zero uses: not an ingredient of any recipe and not logged directly in any meal entry. These are safe to delete with delete_food_item.")]
     fn list_unused_food_items(&self) -> Result<CallToolResult, McpError> {
-        let result = food_items::list_unused_food_items(&self.database).map_err(|e| McpError::internal_error(e, None))?;
+        let result = food_items::list_unused_food_items(&self.db()?)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "List all recipes with zero uses (not logged in meals, not used as component in other recipes). These are safe to delete with delete_recipe.")]
     fn list_unused_recipes(&self) -> Result<CallToolResult, McpError> {
-        let result = recipes::list_unused_recipes(&self.database).map_err(|e| McpError::internal_error(e, None))?;
+        let result = recipes::list_unused_recipes(&self.db()?)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "List all days with no meal entries (orphaned days). These are safe to delete with delete_day.")]
     fn list_orphaned_days(&self) -> Result<CallToolResult, McpError> {
-        let result = days::list_orphaned_days(&self.database).map_err(|e| McpError::internal_error(e, None))?;
+        let result = days::list_orphaned_days(&self.db()?)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Delete a day by date. Only succeeds if the day has no meal entries. Use list_orphaned_days to find days safe to delete.")]
     fn delete_day(&self, Parameters(p): Parameters<DeleteDayParams>) -> Result<CallToolResult, McpError> {
-        let result = days::delete_day(&self.database, &p.date).map_err(|e| McpError::internal_error(e, None))?;
+        let date = self.date_arg(&p.date)?;
+        let result = days::delete_day(&self.db()?, &date)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Reclaim disk space after bulk deletes or imports. Checkpoints the WAL, runs PRAGMA optimize and VACUUM, and returns the database size before and after. Refuses to run while other connections are busy.")]
+    fn optimize_database(&self) -> Result<CallToolResult, McpError> {
+        // Pin the handle under the path lock, which open_database holds while
+        // swapping, so the path and the file being optimized can't disagree
+        let (db, database_path) = {
+            let database_path = self.database_path.lock()
+                .map_err(|_| ToolError::Db("Database path lock poisoned".to_string()))?;
+            (self.db()?, database_path.clone())
+        };
+        let result = maintenance::optimize_database(&db, &database_path)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Find meal entries whose food item or recipe no longer exists (they show as 'Unknown'), with date, meal type and the missing source id, so they can be fixed or deleted. Also reports whether foreign key enforcement is on.")]
+    fn find_dangling_meal_entries(&self) -> Result<CallToolResult, McpError> {
+        let result = maintenance::find_dangling_meal_entries(&self.db()?)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Scan vitals, vital groups and meal entries for timestamps not in canonical local YYYY-MM-DDTHH:MM:SS form (missing seconds, space separator, bare dates, Z or UTC offsets). Date-range queries can miss such rows. Reports each offender and its canonical replacement.")]
+    fn check_timestamp_formats(&self) -> Result<CallToolResult, McpError> {
+        let result = maintenance::check_timestamp_formats(&self.db()?)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Rewrite every non-canonical timestamp found by check_timestamp_formats into local YYYY-MM-DDTHH:MM:SS in one transaction. Values without a zone are local readings and keep their time; only an explicit Z or UTC offset is converted to local time. Unparseable values are left alone and listed. This is the way to move readings stored in UTC by older versions to local time; migrations never rewrite them.")]
+    fn fix_timestamp_formats(&self) -> Result<CallToolResult, McpError> {
+        let result = maintenance::fix_timestamp_formats(&self.db()?)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Copy days (with their meal entries), vitals and vital groups dated before before_date into a new SQLite archive file, along with the recipes those meals reference (with their ingredients and nested components), the food items they use and the custom vital types of the archived vitals. With force=true the archived days, meals and vitals are then deleted from the main database; food items, recipes, custom vital types and vital groups still in use are kept. Returns archived and deleted counts.")]
+    fn archive_old_data(&self, Parameters(p): Parameters<ArchiveOldDataParams>) -> Result<CallToolResult, McpError> {
+        let result = maintenance::archive_old_data(&self.db()?, &p.before_date, Path::new(&p.archive_path), p.force)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Switch the running server to another UHM database file (e.g. a test copy) without restarting. Runs migrations, then repoints every tool at it; a missing file or one without the UHM schema is refused unless create=true. Refused while a batch update is active. Returns the new path, previous path and schema version.")]
+    async fn open_database(&self, Parameters(p): Parameters<OpenDatabaseParams>) -> Result<CallToolResult, McpError> {
+        let new_path = PathBuf::from(&p.path);
+        let result = {
+            // Hold the batch lock so no batch can start mid-swap
+            let state = self.batch_state.lock()
+                .map_err(|_| ToolError::Db("Batch state lock poisoned".to_string()))?;
+            if state.active {
+                return Err(ToolError::Validation(
+                    "Cannot switch databases during a batch update; call finish_batch_update first".to_string(),
+                ).into());
+            }
+            let mut database_path = self.database_path.lock()
+                .map_err(|_| ToolError::Db("Database path lock poisoned".to_string()))?;
+            let result = maintenance::open_database(&self.database, &database_path, &new_path, p.create)?;
+            *database_path = new_path.clone();
+            result
+        };
+        self.status_tracker.lock().await.set_database_path(new_path);
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Export everything (food items, recipes with ingredients/components/prepared batches, days with meal entries, vital groups, vitals, medications) to one versioned JSON file for backup or migration. Returns record counts.")]
+    fn export_all_json(&self, Parameters(p): Parameters<ExportAllJsonParams>) -> Result<CallToolResult, McpError> {
+        let result = backup::export_all_json(&self.db()?, &p.output_path, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Import a file written by export_all_json, remapping ids so recipes, meals and vital groups stay linked. mode=\"restore\" (default) requires an empty database and keeps timestamps and cached nutrition as exported; mode=\"merge\" adds to existing data, reusing food items with the same name and brand and recipes with the same name, attaching meals to existing days with the same date, and skipping meals, vitals and medications already present, so merging a file twice changes nothing. Merged meals on a reused food item or recipe take its current nutrition, and every day receiving meals is recalculated. All-or-nothing. Returns inserted and skipped counts per record kind.")]
+    fn import_all_json(&self, Parameters(p): Parameters<ImportAllJsonParams>) -> Result<CallToolResult, McpError> {
+        let result = backup::import_all_json(&self.db()?, &p.file_path, &p.mode)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
@@ -1256,15 +2616,14 @@ impl UhmService {
 
     #[tool(description = "Create a vital group to link related readings together (e.g., BP + HR taken at the same time)")]
     fn create_vital_group(&self, Parameters(p): Parameters<CreateVitalGroupParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::create_vital_group(&self.database, p.description.as_deref(), p.timestamp.as_deref(), p.notes.as_deref())
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::create_vital_group(&self.db()?, p.description.as_deref(), p.timestamp.as_deref(), p.notes.as_deref(), self.clock.as_ref())?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Get a vital group with all its linked vital readings")]
     fn get_vital_group(&self, Parameters(p): Parameters<GetVitalGroupParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::get_vital_group(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::get_vital_group(&self.db()?, p.id)?;
         let json = match result {
             Some(group) => serde_json::to_string_pretty(&group),
             None => Ok(format!(r#"{{"error": "Vital group not found", "id": {}}}"#, p.id)),
@@ -1274,16 +2633,14 @@ impl UhmService {
 
     #[tool(description = "List vital groups with summary of linked vitals")]
     fn list_vital_groups(&self, Parameters(p): Parameters<ListVitalGroupsParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::list_vital_groups(&self.database, p.limit)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::list_vital_groups(&self.db()?, p.limit)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Update a vital group's description or notes")]
     fn update_vital_group(&self, Parameters(p): Parameters<UpdateVitalGroupParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::update_vital_group(&self.database, p.id, p.description.as_deref(), p.notes.as_deref())
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::update_vital_group(&self.db()?, p.id, p.description.as_deref(), p.notes.as_deref())?;
         let json = match result {
             Some(group) => serde_json::to_string_pretty(&group),
             None => Ok(format!(r#"{{"error": "Vital group not found", "id": {}}}"#, p.id)),
@@ -1293,31 +2650,61 @@ impl UhmService {
 
     #[tool(description = "Delete a vital group (vitals are unlinked but not deleted)")]
     fn delete_vital_group(&self, Parameters(p): Parameters<DeleteVitalGroupParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::delete_vital_group(&self.database, p.id)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::delete_vital_group(&self.db()?, p.id)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "Add a vital reading (weight, blood_pressure, heart_rate, oxygen_saturation, glucose)")]
+    #[tool(description = "Find vital groups recorded within window_minutes of each other (default 5) that contain the same vital type, e.g. an import and a manual entry of one BP reading. Returns candidate pairs for merge_vital_groups.")]
+    fn find_duplicate_vital_groups(&self, Parameters(p): Parameters<FindDuplicateVitalGroupsParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::find_duplicate_vital_groups(&self.db()?, p.window_minutes.unwrap_or(5))?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Merge two vital groups: move every vital from remove_id into keep_id, then delete the empty remove_id group. Returns the kept group with its vitals.")]
+    fn merge_vital_groups(&self, Parameters(p): Parameters<MergeVitalGroupsParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::merge_vital_groups(&self.db()?, p.keep_id, p.remove_id)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Add a vital reading (weight, blood_pressure, heart_rate, oxygen_saturation, glucose, water, or a type added with register_vital_type)")]
     fn add_vital(&self, Parameters(p): Parameters<AddVitalParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::add_vital(
-            &self.database,
-            &p.vital_type,
-            p.value1,
-            p.value2,
-            p.unit.as_deref(),
-            p.timestamp.as_deref(),
-            p.group_id,
-            p.notes.as_deref(),
-        ).map_err(|e| McpError::internal_error(e, None))?;
+        let reading = vitals::VitalReading {
+            vital_type: p.vital_type,
+            value1: p.value1,
+            value2: p.value2,
+            unit: p.unit,
+            timestamp: p.timestamp,
+            group_id: p.group_id,
+            notes: p.notes,
+        };
+        let result = vitals::add_vital(&self.db()?, reading, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Add several vital readings in one call, e.g. catching up on a week of manual logs. Each reading is validated like add_vital; invalid ones are reported per item with the rest still added. Returns the new id for each reading.")]
+    fn add_vitals_bulk(&self, Parameters(p): Parameters<AddVitalsBulkParams>) -> Result<CallToolResult, McpError> {
+        use crate::tools::vitals::VitalReading;
+        let readings: Vec<VitalReading> = p.readings.into_iter().map(|r| VitalReading {
+            vital_type: r.vital_type,
+            value1: r.value1,
+            value2: r.value2,
+            unit: r.unit,
+            timestamp: r.timestamp,
+            group_id: r.group_id,
+            notes: r.notes,
+        }).collect();
+        let result = vitals::add_vitals_bulk(&self.db()?, readings, self.clock.as_ref())?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Get a vital reading by ID")]
     fn get_vital(&self, Parameters(p): Parameters<GetVitalParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::get_vital(&self.database, p.id).map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::get_vital(&self.db()?, p.id)?;
         let json = match result {
             Some(vital) => serde_json::to_string_pretty(&vital),
             None => Ok(format!(r#"{{"error": "Vital not found", "id": {}}}"#, p.id)),
@@ -1327,40 +2714,36 @@ impl UhmService {
 
     #[tool(description = "List vitals by type (e.g., all weight readings or all blood pressure readings)")]
     fn list_vitals_by_type(&self, Parameters(p): Parameters<ListVitalsByTypeParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::list_vitals_by_type(&self.database, &p.vital_type, p.limit)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::list_vitals_by_type(&self.db()?, &p.vital_type, p.limit)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "List recent vitals across all types")]
     fn list_recent_vitals(&self, Parameters(p): Parameters<ListRecentVitalsParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::list_recent_vitals(&self.database, p.limit)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::list_recent_vitals(&self.db()?, p.limit)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "List vitals within a date range, optionally filtered by type")]
     fn list_vitals_by_date_range(&self, Parameters(p): Parameters<ListVitalsByDateRangeParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::list_vitals_by_date_range(&self.database, &p.start_date, &p.end_date, p.vital_type.as_deref())
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let (start, end) = (self.date_arg(&p.start_date)?, self.date_arg(&p.end_date)?);
+        let result = vitals::list_vitals_by_date_range(&self.db()?, &start, &end, p.vital_type.as_deref())?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Get the latest reading for each vital type")]
     fn get_latest_vitals(&self) -> Result<CallToolResult, McpError> {
-        let result = vitals::get_latest_vitals(&self.database)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::get_latest_vitals(&self.db()?, self.clock.as_ref())?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Update a vital reading's values or notes")]
     fn update_vital(&self, Parameters(p): Parameters<UpdateVitalParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::update_vital(&self.database, p.id, p.value1, p.value2, p.unit.as_deref(), p.notes.as_deref())
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::update_vital(&self.db()?, p.id, p.value1, p.value2, p.unit.as_deref(), p.notes.as_deref())?;
         let json = match result {
             Some(resp) => serde_json::to_string_pretty(&resp),
             None => Ok(format!(r#"{{"error": "Vital not found", "id": {}}}"#, p.id)),
@@ -1370,24 +2753,35 @@ impl UhmService {
 
     #[tool(description = "Assign a vital to a group (or remove from group by passing null)")]
     fn assign_vital_to_group(&self, Parameters(p): Parameters<AssignVitalToGroupParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::assign_vital_to_group(&self.database, p.vital_id, p.group_id)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::assign_vital_to_group(&self.db()?, p.vital_id, p.group_id)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Correct a vital's timestamp, e.g. after importing from a device with a wrong clock. The vital keeps its id, values and group link.")]
+    fn set_vital_timestamp(&self, Parameters(p): Parameters<SetVitalTimestampParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::set_vital_timestamp(&self.db()?, p.id, &p.timestamp)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Correct a vital group's timestamp. The group's vitals keep their own timestamps; use set_vital_timestamp for those.")]
+    fn set_vital_group_timestamp(&self, Parameters(p): Parameters<SetVitalGroupTimestampParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::set_vital_group_timestamp(&self.db()?, p.id, &p.timestamp)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Delete a vital reading")]
     fn delete_vital(&self, Parameters(p): Parameters<DeleteVitalParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::delete_vital(&self.database, p.id)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::delete_vital(&self.db()?, p.id)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
     #[tool(description = "Import blood pressure and heart rate data from an Omron CSV export file. Creates grouped BP/HR vitals for each reading. File format: Date,Time,Systolic,Diastolic,Pulse,...")]
     fn import_omron_bp_csv(&self, Parameters(p): Parameters<ImportOmronBpCsvParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::import_omron_bp_csv(&self.database, &p.file_path)
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let result = vitals::import_omron_bp_csv(&self.db()?, &p.file_path, self.clock.as_ref())?;
         // Only return summary, not all readings (can be huge)
         let summary = serde_json::json!({
             "success": result.success,
@@ -1405,10 +2799,158 @@ impl UhmService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "Get comprehensive statistics for vitals by type. Returns mean, median, mode, standard deviation, min, max, percentiles, and outliers. For blood pressure, includes systolic, diastolic, and pulse pressure stats. Much faster than processing raw data externally.")]
+    #[tool(description = "Import weight or blood pressure data from a Withings (Nokia Health) CSV export. kind=\"weight\" for weight.csv (kg converted to lbs), kind=\"bp\" for bp.csv (creates grouped BP/HR vitals). Duplicate readings (same timestamp and value) are skipped.")]
+    fn import_withings_csv(&self, Parameters(p): Parameters<ImportWithingsCsvParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::import_withings_csv(&self.db()?, &p.file_path, &p.kind, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Import vitals from any CSV by mapping its columns (header names or 0-based indexes) to timestamp, vital type (or one fixed type), value1, value2 and unit, with an optional chrono timestamp format. Rows are validated like add_vital, kg/mmol/L/volume units are converted, and identical existing readings are skipped as duplicates.")]
+    fn import_vitals_csv_mapped(&self, Parameters(p): Parameters<ImportVitalsCsvMappedParams>) -> Result<CallToolResult, McpError> {
+        let mapping = vitals::CsvColumnMapping {
+            timestamp: p.timestamp_column,
+            timestamp_format: p.timestamp_format,
+            vital_type: p.vital_type_column,
+            fixed_vital_type: p.vital_type,
+            value1: p.value1_column,
+            value2: p.value2_column,
+            unit: p.unit_column,
+            fixed_unit: p.unit,
+            has_header: p.has_header,
+        };
+        let result = vitals::import_vitals_csv_mapped(&self.db()?, &p.file_path, &mapping, self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Get comprehensive statistics for vitals by type. Returns mean, median, mode, standard deviation, min, max, percentiles, and outliers (beyond outlier_sd standard deviations, default 2). For blood pressure, includes systolic, diastolic, and pulse pressure stats (display_unit \"kPa\" converts from mmHg). For heart rate, also splits resting from active readings, where active means in a vital group whose description or notes mention \"exercise\" or \"workout\". Much faster than processing raw data externally.")]
     fn list_vitals_stats(&self, Parameters(p): Parameters<ListVitalsStatsParams>) -> Result<CallToolResult, McpError> {
-        let result = vitals::list_vitals_stats(&self.database, &p.vital_type, p.start_date.as_deref(), p.end_date.as_deref())
-            .map_err(|e| McpError::internal_error(e, None))?;
+        let start_date = p.start_date.as_deref().map(|d| self.date_arg(d)).transpose()?;
+        let end_date = p.end_date.as_deref().map(|d| self.date_arg(d)).transpose()?;
+        let result = vitals::list_vitals_stats(&self.db()?, &p.vital_type, start_date.as_deref(), end_date.as_deref(), p.outlier_sd, p.display_unit.as_deref(), p.precision)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Binned counts of a vital's readings for bar charts: contiguous [low, high) bins of bin_width from the lowest to highest reading. Blood pressure returns two histograms (systolic and diastolic).")]
+    fn vital_histogram(&self, Parameters(p): Parameters<VitalHistogramParams>) -> Result<CallToolResult, McpError> {
+        let start_date = p.start_date.as_deref().map(|d| self.date_arg(d)).transpose()?;
+        let end_date = p.end_date.as_deref().map(|d| self.date_arg(d)).transpose()?;
+        let result = vitals::vital_histogram(&self.db()?, &p.vital_type, start_date.as_deref(), end_date.as_deref(), p.bin_width)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Log water intake in ml (stored as a 'water' vital). Returns the running total for that day.")]
+    fn log_water(&self, Parameters(p): Parameters<LogWaterParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::log_water(&self.db()?, p.amount_ml, p.timestamp.as_deref(), self.clock.as_ref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Total a day's water intake and compare it to a goal (default 2000 ml)")]
+    fn daily_water_total(&self, Parameters(p): Parameters<DailyWaterTotalParams>) -> Result<CallToolResult, McpError> {
+        let date = self.date_arg(&p.date)?;
+        let result = vitals::daily_water_total(&self.db()?, &date, p.goal_ml)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Detect a weight plateau: over the last window_days (default 14) of weight readings, reports the average, range, and trend slope, and flags a plateau when the range stays within threshold_lbs (default 2) with a near-flat trend")]
+    fn detect_weight_plateau(&self, Parameters(p): Parameters<DetectWeightPlateauParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::detect_weight_plateau(&self.db()?, p.window_days, p.threshold_lbs)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Heart rate recovery after exercise. Exercises are not tracked, so pass the id of a vital group holding the post-exercise HR readings in place of an exercise. Orders those readings by time and reports peak HR minus HR at the 1-minute and 2-minute marks (when readings exist within 30 seconds of each mark)")]
+    fn exercise_recovery(&self, Parameters(p): Parameters<ExerciseRecoveryParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::exercise_recovery(&self.db()?, p.group_id)?;
+        let json = match result {
+            Some(recovery) => serde_json::to_string_pretty(&recovery),
+            None => Ok(format!(r#"{{"error": "Vital group not found", "id": {}}}"#, p.group_id)),
+        }.map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Time in heart rate zones for an exercise session. Exercises and segments are not tracked, so the session is a vital group: pass the id of a group holding the HR readings taken during it. max_hr is required (there is no age to default it from). Each interval between readings counts toward the zone of its starting reading: Z1 <60%, Z2 60-70%, Z3 70-80%, Z4 80-90%, Z5 >90% of max_hr. Returns minutes and percentage per zone.")]
+    fn hr_zone_breakdown(&self, Parameters(p): Parameters<HrZoneBreakdownParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::hr_zone_breakdown(&self.db()?, p.group_id, p.max_hr)?;
+        let json = match result {
+            Some(zones) => serde_json::to_string_pretty(&zones),
+            None => Ok(format!(r#"{{"error": "Vital group not found", "id": {}}}"#, p.group_id)),
+        }.map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Get per-day reading counts for a year (GitHub-style calendar heatmap data). Optionally filter by vital type to also get each day's average value. Only days with readings are returned, as a compact JSON array.")]
+    fn vitals_calendar(&self, Parameters(p): Parameters<VitalsCalendarParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::vitals_calendar(&self.db()?, p.year, p.vital_type.as_deref())?;
+        // Compact output: a year of days can be long
+        let json = serde_json::to_string(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Average blood pressure per week (Monday-Sunday by default, or Sunday-Saturday with week_start=\"sun\") for home BP logs: reading count, average systolic/diastolic, and the ACC/AHA category of the averages. Weeks without readings are omitted.")]
+    fn weekly_bp_averages(&self, Parameters(p): Parameters<WeeklyBpAveragesParams>) -> Result<CallToolResult, McpError> {
+        let week_start = vitals::parse_week_start(&p.week_start)?;
+        let (start, end) = (self.date_arg(&p.start_date)?, self.date_arg(&p.end_date)?);
+        let result = vitals::weekly_bp_averages(&self.db()?, &start, &end, week_start)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Estimate A1c (%) from mean glucose over a date range using the ADAG formula (avg mg/dL + 46.7) / 28.7. Returns the reading count and average; warns when there are too few readings for a meaningful estimate.")]
+    fn estimate_a1c(&self, Parameters(p): Parameters<EstimateA1cParams>) -> Result<CallToolResult, McpError> {
+        let (start, end) = (self.date_arg(&p.start_date)?, self.date_arg(&p.end_date)?);
+        let result = vitals::estimate_a1c(&self.db()?, &start, &end)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List the dates in a range with no readings of a vital type, plus the longest run of consecutive missed days. Use to nudge adherence, e.g. 'you haven't logged weight in 5 days'.")]
+    fn vital_tracking_gaps(&self, Parameters(p): Parameters<VitalTrackingGapsParams>) -> Result<CallToolResult, McpError> {
+        let (start, end) = (self.date_arg(&p.start_date)?, self.date_arg(&p.end_date)?);
+        let result = vitals::vital_tracking_gaps(&self.db()?, &p.vital_type, &start, &end)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List days in a range that have meals logged but no weight reading, for backfilling weigh-ins. Each day includes the nearest recorded weight before and after it as a hint.")]
+    fn days_missing_weight(&self, Parameters(p): Parameters<DaysMissingWeightParams>) -> Result<CallToolResult, McpError> {
+        let (start, end) = (self.date_arg(&p.start_date)?, self.date_arg(&p.end_date)?);
+        let result = vitals::days_missing_weight(&self.db()?, &start, &end)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Day-by-day weight series: each day with a weight reading (its last reading, in lbs), the change from the previous recorded weight, days since that reading, and the change over the last 7 days. Readings before start_date are used as the baseline.")]
+    fn weight_change_series(&self, Parameters(p): Parameters<WeightChangeSeriesParams>) -> Result<CallToolResult, McpError> {
+        let (start, end) = (self.date_arg(&p.start_date)?, self.date_arg(&p.end_date)?);
+        let result = vitals::weight_change_series(&self.db()?, &start, &end)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Correlation between two vitals (e.g. weight vs blood pressure): pairs the daily averages of each type on days that have both, and returns Pearson's r, n and a strength label. Blood pressure uses systolic.")]
+    fn vital_correlation(&self, Parameters(p): Parameters<VitalCorrelationParams>) -> Result<CallToolResult, McpError> {
+        let (start, end) = (self.date_arg(&p.start_date)?, self.date_arg(&p.end_date)?);
+        let result = vitals::vital_correlation(&self.db()?, &p.type_a, &p.type_b, &start, &end)?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Register a custom vital type (e.g. mood, peak flow, pain level) with its unit and an optional second value label. Its name then works as vital_type in add_vital, add_vitals_bulk, list_vitals_by_type, list_vitals_by_date_range and list_vitals_stats.")]
+    fn register_vital_type(&self, Parameters(p): Parameters<RegisterVitalTypeParams>) -> Result<CallToolResult, McpError> {
+        let result = vitals::register_vital_type(&self.db()?, &p.name, &p.unit, p.value2_label.as_deref())?;
+        let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List the built-in and registered custom vital types with their units, value labels and reading counts")]
+    fn list_vital_types(&self) -> Result<CallToolResult, McpError> {
+        let result = vitals::list_vital_types(&self.db()?)?;
         let json = serde_json::to_string_pretty(&result).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
@@ -1433,22 +2975,38 @@ impl ServerHandler for UhmService {
             },
             instructions: Some(
                 "Universal Health Manager (UHM) - Health, nutrition, and vital sign tracking. \
-                 IMPORTANT: Call meal_instructions for food logging, medication_instructions for meds, vital_instructions for vitals. \
-                 Food: add/search/get/list/update/delete_food_item. \
-                 Recipes: create/get/list/update/delete_recipe, add/update/remove_recipe_ingredient, \
-                 add/update/remove_recipe_component, recalculate_recipe_nutrition. \
-                 Days: get_or_create_day/get_day/list_days/update_day/list_days_stats. \
+                 IMPORTANT: Call meal_instructions for food logging, medication_instructions for meds, vital_instructions for vitals. describe_tools lists every tool with its parameter schema. health_snapshot gives a one-call overview. \
+                 Food: add/search/get/list/update/delete_food_item, rescale_food_item, preview_food_item_cascade, lint_food_items, export_food_items_csv, import_food_items_csv, import_usda_food. \
+                 Recipes: create/get/list/update/delete_recipe, toggle_recipe_favorite, list_recent_recipes, suggest_meals, lint_recipe, recipe_to_food_item, food_item_to_recipe, nutrition_calculator, export_recipe, import_recipe, prepare_batch, list_prepared_batches, log_recipe_portion, add/update/remove_recipe_ingredient, convert_recipe_ingredient_units, \
+                 add/update/remove_recipe_component, recipe_nesting_depth, recalculate_recipe_nutrition. \
+                 Days: get_or_create_day/get_day/list_days/update_day/list_days_stats, protein_distribution, day_sodium_breakdown, get_day_timeline, day_budget_status, compute_day_quality, calorie_balance, compare_days, compare_planned_vs_actual, list_days_with_tag, list_all_tags. \
                  list_days_stats: Get comprehensive nutrition statistics (mean, median, mode, SD, outliers, etc.) - much faster than processing raw data. \
-                 Meals: log_meal/get_meal_entry/update_meal_entry/delete_meal_entry, recalculate_day_nutrition. \
-                 Medications: add/get/list/search/update/deprecate/reactivate/delete_medication, export_medications_markdown. \
+                 Meals: log_meal/log_adhoc_meal/quick_log/get_meal_entry/update_meal_entry/delete_meal_entry, split_meal_entry, recalculate_day_nutrition, reclassify_meals. \
+                 Medications: add/get/list/search/update/deprecate/reactivate/delete_medication, deprecate_medications_bulk, export_medications_markdown, generate_medication_timeline, total_daily_dose. \
                  For medication dosage changes: deprecate old entry and add new one to preserve history. \
                  update/delete_medication require force=true. \
-                 Vitals: add/get/update/delete_vital, list_vitals_by_type, list_recent_vitals, list_vitals_by_date_range, get_latest_vitals, list_vitals_stats. \
+                 Vitals: add/get/update/delete_vital, set_vital_timestamp, add_vitals_bulk, list_vitals_by_type, list_recent_vitals, list_vitals_by_date_range, get_latest_vitals, list_vitals_stats, vital_histogram, vitals_calendar, vital_tracking_gaps, days_missing_weight, weight_change_series, vital_correlation, register_vital_type, list_vital_types, weekly_bp_averages, estimate_a1c, log_water, daily_water_total, detect_weight_plateau, exercise_recovery, hr_zone_breakdown. \
+                 Import: import_omron_bp_csv, import_withings_csv, import_vitals_csv_mapped, import_cronometer_csv. \
                  list_vitals_stats: Get comprehensive vital statistics by type (mean, median, mode, SD, outliers, etc.) - much faster than processing raw data. \
-                 Vital Groups: create/get/list/update/delete_vital_group, assign_vital_to_group (for linking BP+HR etc). \
-                 Cleanup: list_unused_food_items, list_unused_recipes, list_orphaned_days, delete_day."
+                 Vital Groups: create/get/list/update/delete_vital_group, assign_vital_to_group (for linking BP+HR etc), set_vital_group_timestamp, find_duplicate_vital_groups, merge_vital_groups. \
+                 Cleanup: list_unused_food_items, list_unused_recipes, list_orphaned_days, delete_day, optimize_database, open_database, check_timestamp_formats, fix_timestamp_formats, find_dangling_meal_entries, archive_old_data. \
+                 Backup: export_all_json, import_all_json."
                     .into(),
             ),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outlier_sd_defaults_to_two() {
+        let params: ListVitalsStatsParams = serde_json::from_value(serde_json::json!({ "vital_type": "weight" })).unwrap();
+        assert_eq!(params.outlier_sd, 2.0);
+        let params: ListVitalsStatsParams =
+            serde_json::from_value(serde_json::json!({ "vital_type": "weight", "outlier_sd": 3.5 })).unwrap();
+        assert_eq!(params.outlier_sd, 3.5);
+    }
+}
@@ -71,6 +71,17 @@ impl Day {
         })
     }
 
+    /// List every day ordered by id
+    pub fn list_all(conn: &Connection) -> DbResult<Vec<Self>> {
+        let mut stmt = conn.prepare("SELECT * FROM days ORDER BY id")?;
+
+        let rows = stmt
+            .query_map([], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
     /// Get a day by ID
     pub fn get_by_id(conn: &Connection, id: i64) -> DbResult<Option<Self>> {
         let mut stmt = conn.prepare("SELECT * FROM days WHERE id = ?1")?;
@@ -146,6 +157,19 @@ impl Day {
         Ok(days)
     }
 
+    /// List days whose notes contain a substring (case-sensitive), newest first
+    pub fn list_notes_containing(conn: &Connection, needle: &str) -> DbResult<Vec<Self>> {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM days WHERE instr(notes, ?1) > 0 ORDER BY date DESC"
+        )?;
+
+        let days = stmt
+            .query_map([needle], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(days)
+    }
+
     /// Count days with optional date range
     pub fn count(conn: &Connection, start_date: Option<&str>, end_date: Option<&str>) -> DbResult<i64> {
         let mut sql = String::from("SELECT COUNT(*) FROM days WHERE 1=1");
@@ -90,6 +90,31 @@ pub struct FoodItemCreate {
     pub ml_per_serving: Option<f64>,
 }
 
+impl From<&FoodItem> for FoodItemCreate {
+    fn from(item: &FoodItem) -> Self {
+        Self {
+            name: item.name.clone(),
+            brand: item.brand.clone(),
+            serving_size: item.serving_size,
+            serving_unit: item.serving_unit.clone(),
+            calories: item.nutrition.calories,
+            protein: item.nutrition.protein,
+            carbs: item.nutrition.carbs,
+            fat: item.nutrition.fat,
+            fiber: item.nutrition.fiber,
+            sodium: item.nutrition.sodium,
+            sugar: item.nutrition.sugar,
+            saturated_fat: item.nutrition.saturated_fat,
+            cholesterol: item.nutrition.cholesterol,
+            preference: item.preference,
+            notes: item.notes.clone(),
+            base_unit_type: item.base_unit_type,
+            grams_per_serving: item.grams_per_serving,
+            ml_per_serving: item.ml_per_serving,
+        }
+    }
+}
+
 /// Data for updating a food item
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FoodItemUpdate {
@@ -237,6 +262,48 @@ impl FoodItem {
         Ok(items)
     }
 
+    /// Find food items named exactly `name`, ignoring case; unbranded items
+    /// come first, then the oldest
+    pub fn find_by_name(conn: &Connection, name: &str) -> DbResult<Vec<Self>> {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM food_items WHERE lower(name) = lower(?1) ORDER BY brand IS NOT NULL, id"
+        )?;
+
+        let items = stmt
+            .query_map([name], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// Find a food item by exact name and brand (a missing brand matches only NULL)
+    pub fn find_by_name_brand(conn: &Connection, name: &str, brand: Option<&str>) -> DbResult<Option<Self>> {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM food_items WHERE name = ?1 AND brand IS ?2 ORDER BY id LIMIT 1"
+        )?;
+
+        let result = stmt.query_row(params![name, brand], Self::from_row);
+        match result {
+            Ok(item) => Ok(Some(item)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// List every food item ordered by id
+    pub fn list_all(conn: &Connection) -> DbResult<Vec<Self>> {
+        let mut stmt = conn.prepare("SELECT * FROM food_items ORDER BY id")?;
+
+        let items = stmt
+            .query_map([], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// sort_by values accepted by list
+    pub const SORT_FIELDS: &'static [&'static str] = &["name", "created_at", "calories", "protein", "protein_per_calorie"];
+
     /// List food items with optional filtering and sorting
     pub fn list(
         conn: &Connection,
@@ -247,20 +314,23 @@ impl FoodItem {
         offset: i64,
     ) -> DbResult<Vec<Self>> {
         let order = if sort_order.to_lowercase() == "desc" { "DESC" } else { "ASC" };
+        // Only fixed expressions reach the SQL; zero-calorie items sort last by density
         let sort_col = match sort_by.to_lowercase().as_str() {
             "created_at" => "created_at",
             "calories" => "calories",
+            "protein" => "protein",
+            "protein_per_calorie" => "CASE WHEN calories > 0 THEN protein / calories END",
             _ => "name",
         };
 
         let sql = if preference.is_some() {
             format!(
-                "SELECT * FROM food_items WHERE preference = ?1 ORDER BY {} {} LIMIT ?2 OFFSET ?3",
+                "SELECT * FROM food_items WHERE preference = ?1 ORDER BY {} {} NULLS LAST, name ASC LIMIT ?2 OFFSET ?3",
                 sort_col, order
             )
         } else {
             format!(
-                "SELECT * FROM food_items ORDER BY {} {} LIMIT ?1 OFFSET ?2",
+                "SELECT * FROM food_items ORDER BY {} {} NULLS LAST, name ASC LIMIT ?1 OFFSET ?2",
                 sort_col, order
             )
         };
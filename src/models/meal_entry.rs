@@ -1,10 +1,12 @@
 //! Meal Entry model
 //!
-//! Represents food consumed, either from a recipe or direct food item.
+//! Represents food consumed, either from a recipe, a direct food item, or an
+//! ad-hoc description with inline nutrition.
 
 use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 
+use crate::clock::Clock;
 use crate::db::DbResult;
 use super::{Day, FoodItem, Nutrition, Recipe};
 
@@ -49,10 +51,18 @@ pub struct MealEntry {
     pub meal_type: MealType,
     pub recipe_id: Option<i64>,
     pub food_item_id: Option<i64>,
+    /// Ad-hoc entries only: what was eaten
+    pub description: Option<String>,
+    /// Ad-hoc entries only: nutrition per serving
+    pub inline_nutrition: Option<Nutrition>,
     pub servings: f64,
     pub percent_eaten: f64,
     pub cached_nutrition: Nutrition,
     pub notes: Option<String>,
+    pub eaten_at: Option<String>,
+    /// Logged as part of a meal plan rather than eaten; excluded from day totals
+    #[serde(default)]
+    pub planned: bool,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -64,13 +74,15 @@ pub struct MealEntryDetail {
     pub day_id: i64,
     pub date: String,
     pub meal_type: MealType,
-    pub source_type: String,  // "recipe" or "food_item"
-    pub source_id: i64,
+    pub source_type: String,  // "recipe", "food_item" or "adhoc"
+    pub source_id: Option<i64>,  // None for ad-hoc entries
     pub source_name: String,
     pub servings: f64,
     pub percent_eaten: f64,
     pub nutrition: Nutrition,
     pub notes: Option<String>,
+    pub eaten_at: Option<String>,
+    pub planned: bool,
     pub created_at: String,
 }
 
@@ -81,9 +93,13 @@ pub struct MealEntryCreate {
     pub meal_type: MealType,
     pub recipe_id: Option<i64>,
     pub food_item_id: Option<i64>,
+    pub description: Option<String>,
+    pub inline_nutrition: Option<Nutrition>,  // ad-hoc: nutrition per serving
     pub servings: f64,
     pub percent_eaten: Option<f64>,  // defaults to 100.0
     pub notes: Option<String>,
+    pub eaten_at: Option<String>,    // defaults to now
+    pub planned: bool,
 }
 
 /// Data for updating a meal entry
@@ -93,18 +109,26 @@ pub struct MealEntryUpdate {
     pub servings: Option<f64>,
     pub percent_eaten: Option<f64>,
     pub notes: Option<String>,
+    pub eaten_at: Option<String>,
 }
 
 impl MealEntry {
     /// Create from a database row
     fn from_row(row: &Row) -> rusqlite::Result<Self> {
         let meal_type_str: String = row.get("meal_type")?;
+        let inline_json: Option<String> = row.get("inline_nutrition")?;
+        let inline_nutrition = inline_json
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
         Ok(Self {
             id: row.get("id")?,
             day_id: row.get("day_id")?,
             meal_type: MealType::from_str(&meal_type_str),
             recipe_id: row.get("recipe_id")?,
             food_item_id: row.get("food_item_id")?,
+            description: row.get("description")?,
+            inline_nutrition,
             servings: row.get("servings")?,
             percent_eaten: row.get("percent_eaten")?,
             cached_nutrition: Nutrition {
@@ -119,52 +143,73 @@ impl MealEntry {
                 cholesterol: row.get("cached_cholesterol")?,
             },
             notes: row.get("notes")?,
+            eaten_at: row.get("eaten_at")?,
+            planned: row.get("planned")?,
             created_at: row.get("created_at")?,
             updated_at: row.get("updated_at")?,
         })
     }
 
     /// Create a new meal entry
-    pub fn create(conn: &Connection, data: &MealEntryCreate) -> DbResult<Self> {
+    pub fn create(conn: &Connection, data: &MealEntryCreate, clock: &dyn Clock) -> DbResult<Self> {
+        let entry = Self::insert(conn, data, clock)?;
+
+        // Recalculate day nutrition
+        recalculate_day_nutrition(conn, data.day_id)?;
+
+        Ok(entry)
+    }
+
+    /// Insert a meal entry without recalculating the day (callers batching
+    /// several changes recalculate once at the end)
+    pub fn insert(conn: &Connection, data: &MealEntryCreate, clock: &dyn Clock) -> DbResult<Self> {
         // Validate that exactly one source is provided
-        if data.recipe_id.is_none() && data.food_item_id.is_none() {
+        let sources = [data.recipe_id.is_some(), data.food_item_id.is_some(), data.inline_nutrition.is_some()]
+            .iter()
+            .filter(|set| **set)
+            .count();
+        if sources == 0 {
             return Err(crate::db::DbError::Sqlite(rusqlite::Error::InvalidParameterName(
-                "Either recipe_id or food_item_id must be provided".to_string()
+                "One of recipe_id, food_item_id or inline_nutrition must be provided".to_string()
             )));
         }
-        if data.recipe_id.is_some() && data.food_item_id.is_some() {
+        if sources > 1 {
             return Err(crate::db::DbError::Sqlite(rusqlite::Error::InvalidParameterName(
-                "Only one of recipe_id or food_item_id can be provided".to_string()
+                "Only one of recipe_id, food_item_id or inline_nutrition can be provided".to_string()
             )));
         }
 
         let percent_eaten = data.percent_eaten.unwrap_or(100.0);
 
         // Calculate nutrition based on source
-        let base_nutrition = if let Some(recipe_id) = data.recipe_id {
-            let recipe = Recipe::get_by_id(conn, recipe_id)?
-                .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
-            recipe.cached_nutrition
-        } else if let Some(food_item_id) = data.food_item_id {
-            let food_item = FoodItem::get_by_id(conn, food_item_id)?
-                .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
-            food_item.nutrition
+        let base_nutrition = source_nutrition(conn, data.recipe_id, data.food_item_id, data.inline_nutrition.as_ref())?;
+        let source_type = if data.recipe_id.is_some() {
+            "recipe"
+        } else if data.food_item_id.is_some() {
+            "food_item"
         } else {
-            Nutrition::zero()
+            "adhoc"
         };
+        let inline_json = data.inline_nutrition.as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| crate::db::DbError::Sqlite(rusqlite::Error::ToSqlConversionFailure(Box::new(e))))?;
 
         // Scale by servings and percent eaten
         let nutrition = base_nutrition.scale(data.servings * (percent_eaten / 100.0));
 
+        let eaten_at = data.eaten_at.clone().unwrap_or_else(|| clock.timestamp());
+
         conn.execute(
             r#"
             INSERT INTO meal_entries (
                 day_id, meal_type, recipe_id, food_item_id, servings, percent_eaten,
                 cached_calories, cached_protein, cached_carbs, cached_fat,
                 cached_fiber, cached_sodium, cached_sugar, cached_saturated_fat,
-                cached_cholesterol, notes
+                cached_cholesterol, notes, eaten_at,
+                source_type, description, inline_nutrition, planned
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)
             "#,
             params![
                 data.day_id,
@@ -183,18 +228,18 @@ impl MealEntry {
                 nutrition.saturated_fat,
                 nutrition.cholesterol,
                 data.notes,
+                eaten_at,
+                source_type,
+                data.description,
+                inline_json,
+                data.planned,
             ],
         )?;
 
         let id = conn.last_insert_rowid();
-        let entry = Self::get_by_id(conn, id)?.ok_or_else(|| {
+        Self::get_by_id(conn, id)?.ok_or_else(|| {
             crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows)
-        })?;
-
-        // Recalculate day nutrition
-        recalculate_day_nutrition(conn, data.day_id)?;
-
-        Ok(entry)
+        })
     }
 
     /// Get a meal entry by ID
@@ -218,15 +263,7 @@ impl MealEntry {
                 let day = Day::get_by_id(conn, entry.day_id)?
                     .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
 
-                let (source_type, source_id, source_name) = if let Some(recipe_id) = entry.recipe_id {
-                    let recipe = Recipe::get_by_id(conn, recipe_id)?
-                        .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
-                    ("recipe".to_string(), recipe_id, recipe.name)
-                } else if let Some(food_item_id) = entry.food_item_id {
-                    let food_item = FoodItem::get_by_id(conn, food_item_id)?
-                        .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
-                    ("food_item".to_string(), food_item_id, food_item.name)
-                } else {
+                let Some((source_type, source_id, source_name)) = entry.source(conn)? else {
                     return Ok(None);
                 };
 
@@ -242,6 +279,8 @@ impl MealEntry {
                     percent_eaten: entry.percent_eaten,
                     nutrition: entry.cached_nutrition,
                     notes: entry.notes,
+                    eaten_at: entry.eaten_at,
+                    planned: entry.planned,
                     created_at: entry.created_at,
                 }))
             }
@@ -249,6 +288,24 @@ impl MealEntry {
         }
     }
 
+    /// Source type, id and display name; ad-hoc entries have no id and use their description
+    fn source(&self, conn: &Connection) -> DbResult<Option<(String, Option<i64>, String)>> {
+        if let Some(recipe_id) = self.recipe_id {
+            let recipe = Recipe::get_by_id(conn, recipe_id)?
+                .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
+            Ok(Some(("recipe".to_string(), Some(recipe_id), recipe.name)))
+        } else if let Some(food_item_id) = self.food_item_id {
+            let food_item = FoodItem::get_by_id(conn, food_item_id)?
+                .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
+            Ok(Some(("food_item".to_string(), Some(food_item_id), food_item.name)))
+        } else if self.inline_nutrition.is_some() {
+            let name = self.description.clone().unwrap_or_else(|| "Ad-hoc entry".to_string());
+            Ok(Some(("adhoc".to_string(), None, name)))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Get all meal entries for a day
     pub fn get_for_day(conn: &Connection, day_id: i64) -> DbResult<Vec<Self>> {
         let mut stmt = conn.prepare(
@@ -270,15 +327,7 @@ impl MealEntry {
 
         let mut details = Vec::new();
         for entry in entries {
-            let (source_type, source_id, source_name) = if let Some(recipe_id) = entry.recipe_id {
-                let recipe = Recipe::get_by_id(conn, recipe_id)?
-                    .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
-                ("recipe".to_string(), recipe_id, recipe.name)
-            } else if let Some(food_item_id) = entry.food_item_id {
-                let food_item = FoodItem::get_by_id(conn, food_item_id)?
-                    .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
-                ("food_item".to_string(), food_item_id, food_item.name)
-            } else {
+            let Some((source_type, source_id, source_name)) = entry.source(conn)? else {
                 continue;
             };
 
@@ -294,6 +343,8 @@ impl MealEntry {
                 percent_eaten: entry.percent_eaten,
                 nutrition: entry.cached_nutrition,
                 notes: entry.notes,
+                eaten_at: entry.eaten_at,
+                planned: entry.planned,
                 created_at: entry.created_at,
             });
         }
@@ -329,6 +380,10 @@ impl MealEntry {
             updates.push(format!("notes = ?{}", params_vec.len() + 1));
             params_vec.push(Box::new(notes.clone()));
         }
+        if let Some(ref eaten_at) = data.eaten_at {
+            updates.push(format!("eaten_at = ?{}", params_vec.len() + 1));
+            params_vec.push(Box::new(eaten_at.clone()));
+        }
 
         if updates.is_empty() {
             return Ok(Some(entry));
@@ -341,17 +396,7 @@ impl MealEntry {
             let servings = data.servings.unwrap_or(entry.servings);
             let percent_eaten = data.percent_eaten.unwrap_or(entry.percent_eaten);
 
-            let base_nutrition = if let Some(recipe_id) = entry.recipe_id {
-                let recipe = Recipe::get_by_id(conn, recipe_id)?
-                    .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
-                recipe.cached_nutrition
-            } else if let Some(food_item_id) = entry.food_item_id {
-                let food_item = FoodItem::get_by_id(conn, food_item_id)?
-                    .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
-                food_item.nutrition
-            } else {
-                Nutrition::zero()
-            };
+            let base_nutrition = source_nutrition(conn, entry.recipe_id, entry.food_item_id, entry.inline_nutrition.as_ref())?;
 
             let nutrition = base_nutrition.scale(servings * (percent_eaten / 100.0));
 
@@ -394,6 +439,15 @@ impl MealEntry {
         Self::get_by_id(conn, id)
     }
 
+    /// Change only the meal type of an entry (nutrition is unaffected, so no recalculation)
+    pub fn set_meal_type(conn: &Connection, id: i64, meal_type: &MealType) -> DbResult<bool> {
+        let rows = conn.execute(
+            "UPDATE meal_entries SET meal_type = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![meal_type.as_str(), id],
+        )?;
+        Ok(rows > 0)
+    }
+
     /// Delete a meal entry
     pub fn delete(conn: &Connection, id: i64) -> DbResult<bool> {
         // Get day_id before delete for recalculation
@@ -410,14 +464,58 @@ impl MealEntry {
 
         Ok(rows > 0)
     }
+
+    /// Nutrition of this entry from its source's current values, scaled by
+    /// servings and percent eaten (ignores the cached columns)
+    pub fn source_nutrition(&self, conn: &Connection) -> DbResult<Nutrition> {
+        let base = source_nutrition(conn, self.recipe_id, self.food_item_id, self.inline_nutrition.as_ref())?;
+        Ok(base.scale(self.servings * (self.percent_eaten / 100.0)))
+    }
+
+    /// Food items eaten through this entry, each with the servings of that item,
+    /// scaled by servings and percent eaten. Recipes are expanded into their
+    /// ingredients; ad-hoc entries have no food items.
+    pub fn expand_food_items(&self, conn: &Connection) -> DbResult<Vec<(FoodItem, f64)>> {
+        let multiplier = self.servings * (self.percent_eaten / 100.0);
+        if let Some(recipe_id) = self.recipe_id {
+            super::recipe_ingredient::expand_recipe_food_items(conn, recipe_id, multiplier)
+        } else if let Some(food_item_id) = self.food_item_id {
+            let food_item = FoodItem::get_by_id(conn, food_item_id)?
+                .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
+            Ok(vec![(food_item, multiplier)])
+        } else {
+            Ok(Vec::new())
+        }
+    }
 }
 
-/// Calculate total nutrition for a day from meal entries
+/// Nutrition of one serving of an entry's source (recipe, food item or inline)
+fn source_nutrition(
+    conn: &Connection,
+    recipe_id: Option<i64>,
+    food_item_id: Option<i64>,
+    inline_nutrition: Option<&Nutrition>,
+) -> DbResult<Nutrition> {
+    if let Some(recipe_id) = recipe_id {
+        let recipe = Recipe::get_by_id(conn, recipe_id)?
+            .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
+        Ok(recipe.cached_nutrition)
+    } else if let Some(food_item_id) = food_item_id {
+        let food_item = FoodItem::get_by_id(conn, food_item_id)?
+            .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
+        Ok(food_item.nutrition)
+    } else {
+        Ok(inline_nutrition.cloned().unwrap_or_else(Nutrition::zero))
+    }
+}
+
+/// Calculate total nutrition for a day from meal entries (planned entries are excluded)
 pub fn calculate_day_nutrition(conn: &Connection, day_id: i64) -> DbResult<Nutrition> {
     let entries = MealEntry::get_for_day(conn, day_id)?;
 
     let total: Nutrition = entries
         .iter()
+        .filter(|e| !e.planned)
         .map(|e| e.cached_nutrition.clone())
         .sum();
 
@@ -430,3 +528,97 @@ pub fn recalculate_day_nutrition(conn: &Connection, day_id: i64) -> DbResult<Nut
     Day::update_cached_nutrition(conn, day_id, &nutrition)?;
     Ok(nutrition)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::migrations::run_migrations;
+    use crate::test_support::make_food;
+
+    /// In-memory database with one 200 kcal / 10 g protein food item
+    fn setup() -> (Connection, i64, i64) {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let food = make_food(&conn, "Test food", serde_json::json!({
+            "calories": 200.0,
+            "protein": 10.0,
+            "carbs": 20.0,
+            "fat": 8.0
+        }));
+        let day = Day::get_or_create(&conn, "2024-03-15").unwrap();
+        (conn, day.id, food.id)
+    }
+
+    fn log(conn: &Connection, day_id: i64, food_item_id: i64, percent_eaten: Option<f64>) -> MealEntry {
+        log_entry(conn, day_id, food_item_id, percent_eaten, false)
+    }
+
+    fn log_entry(conn: &Connection, day_id: i64, food_item_id: i64, percent_eaten: Option<f64>, planned: bool) -> MealEntry {
+        MealEntry::create(conn, &MealEntryCreate {
+            day_id,
+            meal_type: MealType::Lunch,
+            recipe_id: None,
+            food_item_id: Some(food_item_id),
+            description: None,
+            inline_nutrition: None,
+            servings: 1.0,
+            percent_eaten,
+            notes: None,
+            eaten_at: None,
+            planned,
+        }, &SystemClock).unwrap()
+    }
+
+    #[test]
+    fn test_percent_eaten_scales_day_total() {
+        let (conn, day_id, food_id) = setup();
+
+        let full = log(&conn, day_id, food_id, Some(100.0));
+        let half = log(&conn, day_id, food_id, Some(50.0));
+        assert_eq!(full.cached_nutrition.calories, 200.0);
+        assert_eq!(half.cached_nutrition.calories, 100.0);
+        assert_eq!(half.cached_nutrition.protein, 5.0);
+
+        let total = recalculate_day_nutrition(&conn, day_id).unwrap();
+        assert_eq!(total.calories, 300.0);
+        assert_eq!(total.protein, 15.0);
+
+        // The cached day totals used by day summaries match
+        let day = Day::get_by_id(&conn, day_id).unwrap().unwrap();
+        assert_eq!(day.cached_nutrition.calories, 300.0);
+    }
+
+    #[test]
+    fn test_updating_percent_eaten_rescales() {
+        let (conn, day_id, food_id) = setup();
+
+        let entry = log(&conn, day_id, food_id, None);
+        assert_eq!(entry.percent_eaten, 100.0);
+
+        let updated = MealEntry::update(&conn, entry.id, &MealEntryUpdate {
+            percent_eaten: Some(50.0),
+            ..Default::default()
+        }).unwrap().unwrap();
+        assert_eq!(updated.cached_nutrition.calories, 100.0);
+        assert_eq!(calculate_day_nutrition(&conn, day_id).unwrap().calories, 100.0);
+    }
+
+    #[test]
+    fn test_planned_entries_excluded_from_day_total() {
+        let (conn, day_id, food_id) = setup();
+
+        log_entry(&conn, day_id, food_id, None, true);
+        let planned = log_entry(&conn, day_id, food_id, None, true);
+        assert!(planned.planned);
+        assert_eq!(calculate_day_nutrition(&conn, day_id).unwrap().calories, 0.0);
+
+        log(&conn, day_id, food_id, Some(50.0));
+        let day = Day::get_by_id(&conn, day_id).unwrap().unwrap();
+        assert_eq!(day.cached_nutrition.calories, 100.0);
+
+        let details = MealEntry::get_details_for_day(&conn, day_id).unwrap();
+        assert_eq!(details.iter().filter(|e| e.planned).count(), 2);
+    }
+}
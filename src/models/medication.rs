@@ -5,6 +5,7 @@
 use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 
+use crate::clock::Clock;
 use crate::db::DbResult;
 
 /// Medication type enum
@@ -34,14 +35,20 @@ impl MedType {
     }
 
     pub fn from_str(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "prescription" | "rx" => MedType::Prescription,
-            "supplement" | "vitamin" => MedType::Supplement,
-            "otc" | "over_the_counter" => MedType::Otc,
-            "natural" | "herbal" => MedType::Natural,
-            "compound" | "compounded" => MedType::Compound,
-            "medical_device" | "device" => MedType::MedicalDevice,
-            _ => MedType::Other,
+        Self::parse(s).unwrap_or(MedType::Other)
+    }
+
+    /// Like from_str, but None for an unrecognized name instead of Other
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "prescription" | "rx" => Some(MedType::Prescription),
+            "supplement" | "vitamin" => Some(MedType::Supplement),
+            "otc" | "over_the_counter" => Some(MedType::Otc),
+            "natural" | "herbal" => Some(MedType::Natural),
+            "compound" | "compounded" => Some(MedType::Compound),
+            "medical_device" | "device" => Some(MedType::MedicalDevice),
+            "other" => Some(MedType::Other),
+            _ => None,
         }
     }
 
@@ -156,6 +163,16 @@ impl DosageUnit {
             DosageUnit::Other => "unit(s)",
         }
     }
+
+    /// Milligrams in one of this unit, for mass units only (mg, g, mcg)
+    pub fn mg_per_unit(&self) -> Option<f64> {
+        match self {
+            DosageUnit::Mg => Some(1.0),
+            DosageUnit::G => Some(1000.0),
+            DosageUnit::Mcg => Some(0.001),
+            _ => None,
+        }
+    }
 }
 
 /// A medication record
@@ -178,10 +195,18 @@ pub struct Medication {
     pub end_date: Option<String>,
     pub discontinue_reason: Option<String>,
     pub notes: Option<String>,
+    pub schedule: Option<Vec<DoseStep>>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// One step of a dose schedule: the dose in effect from start_date until the next step
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DoseStep {
+    pub start_date: String,
+    pub dosage_amount: f64,
+}
+
 /// Data for creating a new medication
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MedicationCreate {
@@ -198,6 +223,7 @@ pub struct MedicationCreate {
     pub refills_remaining: Option<i32>,
     pub start_date: Option<String>,
     pub notes: Option<String>,
+    pub schedule: Option<Vec<DoseStep>>,
 }
 
 /// Data for updating a medication (requires force flag)
@@ -216,6 +242,8 @@ pub struct MedicationUpdate {
     pub refills_remaining: Option<i32>,
     pub start_date: Option<String>,
     pub notes: Option<String>,
+    /// Replaces the schedule; an empty list clears it
+    pub schedule: Option<Vec<DoseStep>>,
 }
 
 /// Data for deprecating a medication
@@ -228,8 +256,18 @@ pub struct MedicationDeprecate {
 impl Medication {
     /// Create from a database row
     fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let id: i64 = row.get("id")?;
+        let schedule = row
+            .get::<_, Option<String>>("schedule")?
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
+                0,
+                rusqlite::types::Type::Text,
+                format!("schedule of medication {} is not valid JSON: {}", id, e).into(),
+            ))?;
         Ok(Self {
-            id: row.get("id")?,
+            id,
             name: row.get("name")?,
             med_type: MedType::from_str(&row.get::<_, String>("med_type")?),
             dosage_amount: row.get("dosage_amount")?,
@@ -246,11 +284,41 @@ impl Medication {
             end_date: row.get("end_date")?,
             discontinue_reason: row.get("discontinue_reason")?,
             notes: row.get("notes")?,
+            schedule,
             created_at: row.get("created_at")?,
             updated_at: row.get("updated_at")?,
         })
     }
 
+    /// Serialize a schedule for storage (empty schedules are stored as NULL)
+    pub(crate) fn schedule_json(schedule: &Option<Vec<DoseStep>>) -> Option<String> {
+        match schedule {
+            Some(steps) if !steps.is_empty() => serde_json::to_string(steps).ok(),
+            _ => None,
+        }
+    }
+
+    /// Resolve the dose in effect on a date (YYYY-MM-DD).
+    /// Without a schedule, or before its first step, this is the base dosage_amount.
+    pub fn current_dose(&self, date: &str) -> f64 {
+        self.schedule
+            .as_ref()
+            .and_then(|steps| steps.iter().rev().find(|s| s.start_date.as_str() <= date))
+            .map(|s| s.dosage_amount)
+            .unwrap_or(self.dosage_amount)
+    }
+
+    /// Whether the medication was being taken on a date (YYYY-MM-DD): is_active,
+    /// started on or before the date, and not ended before it. A missing
+    /// start_date or end_date leaves that side of the window open.
+    pub fn is_active_on(&self, date: &str) -> bool {
+        // Dates may have been stored as full timestamps; compare the date part only
+        let day = |d: &String| d.get(..10).unwrap_or(d).to_string();
+        self.is_active
+            && self.start_date.as_ref().is_none_or(|start| day(start).as_str() <= date)
+            && self.end_date.as_ref().is_none_or(|end| day(end).as_str() >= date)
+    }
+
     /// Create a new medication
     pub fn create(conn: &Connection, data: &MedicationCreate) -> DbResult<Self> {
         conn.execute(
@@ -258,9 +326,9 @@ impl Medication {
             INSERT INTO medications (
                 name, med_type, dosage_amount, dosage_unit,
                 instructions, frequency, prescribing_doctor, prescribed_date,
-                pharmacy, rx_number, refills_remaining, start_date, notes
+                pharmacy, rx_number, refills_remaining, start_date, notes, schedule
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
             "#,
             params![
                 data.name,
@@ -276,6 +344,7 @@ impl Medication {
                 data.refills_remaining,
                 data.start_date,
                 data.notes,
+                Self::schedule_json(&data.schedule),
             ],
         )?;
 
@@ -402,6 +471,10 @@ impl Medication {
             updates.push(format!("notes = ?{}", params_vec.len() + 1));
             params_vec.push(Box::new(notes.clone()));
         }
+        if data.schedule.is_some() {
+            updates.push(format!("schedule = ?{}", params_vec.len() + 1));
+            params_vec.push(Box::new(Self::schedule_json(&data.schedule)));
+        }
 
         if updates.is_empty() {
             return Self::get_by_id(conn, id);
@@ -424,10 +497,8 @@ impl Medication {
     }
 
     /// Deprecate a medication (mark as inactive)
-    pub fn deprecate(conn: &Connection, id: i64, data: &MedicationDeprecate) -> DbResult<Option<Self>> {
-        let end_date = data.end_date.clone().unwrap_or_else(|| {
-            chrono::Utc::now().format("%Y-%m-%d").to_string()
-        });
+    pub fn deprecate(conn: &Connection, id: i64, data: &MedicationDeprecate, clock: &dyn Clock) -> DbResult<Option<Self>> {
+        let end_date = data.end_date.clone().unwrap_or_else(|| clock.today());
 
         conn.execute(
             r#"
@@ -488,3 +559,55 @@ impl Medication {
         Ok(count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn med(is_active: bool, start_date: Option<&str>, end_date: Option<&str>) -> Medication {
+        Medication {
+            id: 1,
+            name: "Test".to_string(),
+            med_type: MedType::Prescription,
+            dosage_amount: 10.0,
+            dosage_unit: DosageUnit::Mg,
+            instructions: None,
+            frequency: None,
+            prescribing_doctor: None,
+            prescribed_date: None,
+            pharmacy: None,
+            rx_number: None,
+            refills_remaining: None,
+            is_active,
+            start_date: start_date.map(String::from),
+            end_date: end_date.map(String::from),
+            discontinue_reason: None,
+            notes: None,
+            schedule: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_is_active_on_start_boundary() {
+        let m = med(true, Some("2024-03-10"), None);
+        assert!(!m.is_active_on("2024-03-09"));
+        assert!(m.is_active_on("2024-03-10"));
+        assert!(m.is_active_on("2024-03-11"));
+    }
+
+    #[test]
+    fn test_is_active_on_end_boundary() {
+        let m = med(true, Some("2024-03-01"), Some("2024-03-31"));
+        assert!(m.is_active_on("2024-03-31"));
+        assert!(!m.is_active_on("2024-04-01"));
+    }
+
+    #[test]
+    fn test_is_active_on_open_and_inactive() {
+        assert!(med(true, None, None).is_active_on("1999-01-01"));
+        assert!(med(true, Some("2024-03-10T08:30:00Z"), None).is_active_on("2024-03-10"));
+        assert!(!med(false, Some("2024-03-01"), None).is_active_on("2024-03-15"));
+    }
+}
@@ -7,6 +7,7 @@ mod food_item;
 mod meal_entry;
 mod medication;
 mod nutrition;
+mod prepared_batch;
 mod recipe;
 mod recipe_component;
 mod recipe_ingredient;
@@ -20,19 +21,21 @@ pub use meal_entry::{
 };
 pub use medication::{
     Medication, MedicationCreate, MedicationUpdate, MedicationDeprecate,
-    MedType, DosageUnit,
+    MedType, DosageUnit, DoseStep,
 };
-pub use nutrition::Nutrition;
-pub use recipe::{Recipe, RecipeCreate, RecipeUpdate};
+pub use nutrition::{Nutrition, DEFAULT_SODIUM_DENSITY_LIMIT};
+pub use prepared_batch::{PreparedBatch, PreparedBatchCreate, PreparedBatchDetail};
+pub use recipe::{Recipe, RecipeCreate, RecipeUpdate, RECIPE_SORT_KEYS};
 pub use recipe_component::{
+    deepest_component_chain, nesting_depth_after_adding, would_create_cycle,
     RecipeComponent, RecipeComponentCreate, RecipeComponentDetail, RecipeComponentUpdate,
-    would_create_cycle,
+    DEFAULT_MAX_NESTING_DEPTH,
 };
 pub use recipe_ingredient::{
     RecipeIngredient, RecipeIngredientCreate, RecipeIngredientDetail,
     RecipeIngredientUpdate, recalculate_recipe_nutrition,
-    cascade_recalculate_from_food_item, CascadeRecalculateResult,
+    cascade_recalculate_from_food_item, find_cascade_targets,
 };
 pub use vital::{
-    Vital, VitalCreate, VitalGroup, VitalGroupCreate, VitalType, VitalUpdate,
+    CustomVitalType, CustomVitalTypeCreate, Vital, VitalCreate, VitalGroup, VitalGroupCreate, VitalType, VitalUpdate,
 };
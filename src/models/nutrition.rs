@@ -4,6 +4,9 @@
 
 use serde::{Deserialize, Serialize};
 
+/// Default sodium density (mg per 1000 kcal) above which food is flagged as sodium-dense
+pub const DEFAULT_SODIUM_DENSITY_LIMIT: f64 = 1000.0;
+
 /// Nutritional information
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Nutrition {
@@ -53,6 +56,23 @@ impl Nutrition {
             cholesterol: self.cholesterol + other.cholesterol,
         }
     }
+
+    /// Sodium in mg per 1000 kcal, or None when there are no calories to compare against
+    pub fn sodium_density(&self) -> Option<f64> {
+        if self.calories > 0.0 {
+            Some(self.sodium / self.calories * 1000.0)
+        } else {
+            None
+        }
+    }
+
+    /// True when sodium density exceeds the limit; zero-calorie items with any sodium always count
+    pub fn is_sodium_dense(&self, limit: f64) -> bool {
+        match self.sodium_density() {
+            Some(density) => density > limit,
+            None => self.sodium > 0.0,
+        }
+    }
 }
 
 impl std::ops::Add for Nutrition {
@@ -0,0 +1,165 @@
+//! Prepared Batch model
+//!
+//! Tracks how many servings are left of a recipe cooked in bulk (meal prep).
+
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::db::DbResult;
+
+/// A cooked batch of a recipe with a running count of servings left
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreparedBatch {
+    pub id: i64,
+    pub recipe_id: i64,
+    pub servings_prepared: f64,
+    pub remaining_servings: f64,
+    pub prepared_date: String,
+    pub notes: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Prepared batch with the recipe name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreparedBatchDetail {
+    pub id: i64,
+    pub recipe_id: i64,
+    pub recipe_name: String,
+    pub servings_prepared: f64,
+    pub remaining_servings: f64,
+    pub prepared_date: String,
+    pub notes: Option<String>,
+}
+
+/// Data for recording a new prepared batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreparedBatchCreate {
+    pub recipe_id: i64,
+    pub servings_prepared: f64,
+    pub prepared_date: String,
+    pub notes: Option<String>,
+}
+
+impl PreparedBatch {
+    /// Create from a database row
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            recipe_id: row.get("recipe_id")?,
+            servings_prepared: row.get("servings_prepared")?,
+            remaining_servings: row.get("remaining_servings")?,
+            prepared_date: row.get("prepared_date")?,
+            notes: row.get("notes")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+
+    /// Record a new batch with all servings remaining
+    pub fn create(conn: &Connection, data: &PreparedBatchCreate) -> DbResult<Self> {
+        conn.execute(
+            r#"
+            INSERT INTO prepared_batches (recipe_id, servings_prepared, remaining_servings, prepared_date, notes)
+            VALUES (?1, ?2, ?2, ?3, ?4)
+            "#,
+            params![
+                data.recipe_id,
+                data.servings_prepared,
+                data.prepared_date,
+                data.notes,
+            ],
+        )?;
+
+        let id = conn.last_insert_rowid();
+        Self::get_by_id(conn, id)?.ok_or_else(|| {
+            crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows)
+        })
+    }
+
+    /// List every prepared batch ordered by id
+    pub fn list_all(conn: &Connection) -> DbResult<Vec<Self>> {
+        let mut stmt = conn.prepare("SELECT * FROM prepared_batches ORDER BY id")?;
+
+        let rows = stmt
+            .query_map([], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Get a batch by ID
+    pub fn get_by_id(conn: &Connection, id: i64) -> DbResult<Option<Self>> {
+        let mut stmt = conn.prepare("SELECT * FROM prepared_batches WHERE id = ?1")?;
+
+        let result = stmt.query_row([id], Self::from_row);
+        match result {
+            Ok(batch) => Ok(Some(batch)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Oldest batch of a recipe that still has servings left
+    pub fn find_open_for_recipe(conn: &Connection, recipe_id: i64) -> DbResult<Option<Self>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT * FROM prepared_batches
+            WHERE recipe_id = ?1 AND remaining_servings > 0
+            ORDER BY prepared_date ASC, id ASC
+            LIMIT 1
+            "#
+        )?;
+
+        let result = stmt.query_row([recipe_id], Self::from_row);
+        match result {
+            Ok(batch) => Ok(Some(batch)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// List batches with recipe names, newest first
+    pub fn list_details(conn: &Connection, include_finished: bool) -> DbResult<Vec<PreparedBatchDetail>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT pb.id, pb.recipe_id, r.name AS recipe_name, pb.servings_prepared,
+                   pb.remaining_servings, pb.prepared_date, pb.notes
+            FROM prepared_batches pb
+            JOIN recipes r ON r.id = pb.recipe_id
+            WHERE ?1 OR pb.remaining_servings > 0
+            ORDER BY pb.prepared_date DESC, pb.id DESC
+            "#
+        )?;
+
+        let batches = stmt
+            .query_map([include_finished], |row| {
+                Ok(PreparedBatchDetail {
+                    id: row.get("id")?,
+                    recipe_id: row.get("recipe_id")?,
+                    recipe_name: row.get("recipe_name")?,
+                    servings_prepared: row.get("servings_prepared")?,
+                    remaining_servings: row.get("remaining_servings")?,
+                    prepared_date: row.get("prepared_date")?,
+                    notes: row.get("notes")?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(batches)
+    }
+
+    /// Take servings out of a batch (never below zero)
+    pub fn consume(conn: &Connection, id: i64, servings: f64) -> DbResult<Option<Self>> {
+        conn.execute(
+            r#"
+            UPDATE prepared_batches
+            SET remaining_servings = MAX(remaining_servings - ?1, 0), updated_at = datetime('now')
+            WHERE id = ?2
+            "#,
+            params![servings, id],
+        )?;
+
+        Self::get_by_id(conn, id)
+    }
+}
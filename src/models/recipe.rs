@@ -14,6 +14,8 @@ pub struct Recipe {
     pub id: i64,
     pub name: String,
     pub servings_produced: f64,
+    /// Cooked weight of the whole batch (accounts for cooking losses)
+    pub yield_grams: Option<f64>,
     pub is_favorite: bool,
     pub cached_nutrition: Nutrition,
     pub notes: Option<String>,
@@ -28,6 +30,8 @@ pub struct RecipeCreate {
     #[serde(default = "default_servings")]
     pub servings_produced: f64,
     #[serde(default)]
+    pub yield_grams: Option<f64>,
+    #[serde(default)]
     pub is_favorite: bool,
     pub notes: Option<String>,
 }
@@ -41,10 +45,19 @@ fn default_servings() -> f64 {
 pub struct RecipeUpdate {
     pub name: Option<String>,
     pub servings_produced: Option<f64>,
+    pub yield_grams: Option<f64>,
     pub is_favorite: Option<bool>,
     pub notes: Option<String>,
 }
 
+/// Sort keys accepted by Recipe::list
+pub const RECIPE_SORT_KEYS: &[&str] = &["name", "created_at", "times_logged", "last_logged"];
+
+/// Latest eaten_at among eaten (not planned) meal entries for `recipes.id`
+/// (entries from before eaten_at fall back to created_at)
+const LAST_LOGGED_AT_SQL: &str = "(SELECT MAX(COALESCE(me.eaten_at, replace(me.created_at, ' ', 'T') || 'Z')) \
+     FROM meal_entries me WHERE me.recipe_id = recipes.id AND me.planned = 0)";
+
 impl Recipe {
     /// Create a Recipe from a database row
     fn from_row(row: &Row) -> rusqlite::Result<Self> {
@@ -52,6 +65,7 @@ impl Recipe {
             id: row.get("id")?,
             name: row.get("name")?,
             servings_produced: row.get("servings_produced")?,
+            yield_grams: row.get("yield_grams")?,
             is_favorite: row.get::<_, i32>("is_favorite")? != 0,
             cached_nutrition: Nutrition {
                 calories: row.get("cached_calories")?,
@@ -74,14 +88,15 @@ impl Recipe {
     pub fn create(conn: &Connection, data: &RecipeCreate) -> DbResult<Self> {
         conn.execute(
             r#"
-            INSERT INTO recipes (name, servings_produced, is_favorite, notes)
-            VALUES (?1, ?2, ?3, ?4)
+            INSERT INTO recipes (name, servings_produced, is_favorite, notes, yield_grams)
+            VALUES (?1, ?2, ?3, ?4, ?5)
             "#,
             params![
                 data.name,
                 data.servings_produced,
                 data.is_favorite as i32,
                 data.notes,
+                data.yield_grams,
             ],
         )?;
 
@@ -91,6 +106,17 @@ impl Recipe {
         })
     }
 
+    /// List every recipe ordered by id
+    pub fn list_all(conn: &Connection) -> DbResult<Vec<Self>> {
+        let mut stmt = conn.prepare("SELECT * FROM recipes ORDER BY id")?;
+
+        let rows = stmt
+            .query_map([], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
     /// Get a recipe by ID
     pub fn get_by_id(conn: &Connection, id: i64) -> DbResult<Option<Self>> {
         let mut stmt = conn.prepare("SELECT * FROM recipes WHERE id = ?1")?;
@@ -103,7 +129,23 @@ impl Recipe {
         }
     }
 
-    /// List recipes with optional filtering
+    /// Find a recipe by exact name (case-insensitive)
+    pub fn find_by_name(conn: &Connection, name: &str) -> DbResult<Option<Self>> {
+        let mut stmt = conn.prepare(
+            "SELECT * FROM recipes WHERE name = ?1 COLLATE NOCASE ORDER BY id LIMIT 1"
+        )?;
+
+        let result = stmt.query_row([name], Self::from_row);
+        match result {
+            Ok(recipe) => Ok(Some(recipe)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// List recipes with optional filtering. sort_by is one of RECIPE_SORT_KEYS
+    /// (anything else sorts by name); never-logged recipes sort first ascending
+    /// and last descending by last_logged.
     pub fn list(
         conn: &Connection,
         query: Option<&str>,
@@ -116,7 +158,8 @@ impl Recipe {
         let order = if sort_order.to_lowercase() == "desc" { "DESC" } else { "ASC" };
         let sort_col = match sort_by.to_lowercase().as_str() {
             "created_at" => "created_at",
-            "times_logged" => "id", // TODO: implement actual times_logged
+            "times_logged" => "(SELECT COUNT(*) FROM meal_entries me WHERE me.recipe_id = recipes.id AND me.planned = 0)",
+            "last_logged" => LAST_LOGGED_AT_SQL,
             _ => "name",
         };
 
@@ -188,6 +231,10 @@ impl Recipe {
             updates.push(format!("servings_produced = ?{}", params_vec.len() + 1));
             params_vec.push(Box::new(servings));
         }
+        if let Some(yield_grams) = data.yield_grams {
+            updates.push(format!("yield_grams = ?{}", params_vec.len() + 1));
+            params_vec.push(Box::new(yield_grams));
+        }
         if let Some(is_fav) = data.is_favorite {
             updates.push(format!("is_favorite = ?{}", params_vec.len() + 1));
             params_vec.push(Box::new(is_fav as i32));
@@ -217,6 +264,43 @@ impl Recipe {
         Self::get_by_id(conn, id)
     }
 
+    /// Flip the favorite flag. Favorites are metadata, so this is allowed even for logged recipes.
+    pub fn toggle_favorite(conn: &Connection, id: i64) -> DbResult<Option<Self>> {
+        conn.execute(
+            "UPDATE recipes SET is_favorite = 1 - is_favorite, updated_at = datetime('now') WHERE id = ?1",
+            [id],
+        )?;
+        Self::get_by_id(conn, id)
+    }
+
+    /// List recipes by most recent use in a meal entry.
+    /// Returns (recipe, last logged date, times logged).
+    pub fn list_recent(conn: &Connection, limit: i64) -> DbResult<Vec<(Self, String, i64)>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT r.*, MAX(d.date) AS last_logged_date, COUNT(me.id) AS times_logged
+            FROM recipes r
+            JOIN meal_entries me ON me.recipe_id = r.id AND me.planned = 0
+            JOIN days d ON d.id = me.day_id
+            GROUP BY r.id
+            ORDER BY last_logged_date DESC, MAX(me.created_at) DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let recipes = stmt
+            .query_map([limit], |row| {
+                Ok((
+                    Self::from_row(row)?,
+                    row.get::<_, String>("last_logged_date")?,
+                    row.get::<_, i64>("times_logged")?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(recipes)
+    }
+
     /// Update cached nutrition for a recipe
     pub fn update_cached_nutrition(conn: &Connection, id: i64, nutrition: &Nutrition) -> DbResult<()> {
         conn.execute(
@@ -264,16 +348,23 @@ impl Recipe {
         Ok(count)
     }
 
-    /// Get meal entry count for a recipe
+    /// Get eaten (not planned) meal entry count for a recipe
     pub fn get_times_logged(conn: &Connection, id: i64) -> DbResult<i64> {
         let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM meal_entries WHERE recipe_id = ?1",
+            "SELECT COUNT(*) FROM meal_entries WHERE recipe_id = ?1 AND planned = 0",
             [id],
             |row| row.get(0),
         )?;
         Ok(count)
     }
 
+    /// Most recent eaten_at of meal entries logging this recipe
+    pub fn get_last_logged_at(conn: &Connection, id: i64) -> DbResult<Option<String>> {
+        let sql = format!("SELECT {} FROM recipes WHERE id = ?1", LAST_LOGGED_AT_SQL);
+        let last: Option<String> = conn.query_row(&sql, [id], |row| row.get(0))?;
+        Ok(last)
+    }
+
     /// Check if recipe is used as a component in other recipes
     pub fn get_component_usage_count(conn: &Connection, id: i64) -> DbResult<i64> {
         let count: i64 = conn.query_row(
@@ -300,3 +391,96 @@ impl Recipe {
         Ok(rows > 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::migrations::run_migrations;
+    use crate::models::{Day, MealEntry, MealEntryCreate, MealType};
+
+    fn recipe(conn: &Connection, name: &str) -> Recipe {
+        Recipe::create(conn, &RecipeCreate {
+            name: name.to_string(),
+            servings_produced: 1.0,
+            yield_grams: None,
+            is_favorite: false,
+            notes: None,
+        }).unwrap()
+    }
+
+    fn log(conn: &Connection, recipe_id: i64, date: &str, eaten_at: &str) {
+        log_entry(conn, recipe_id, date, eaten_at, false);
+    }
+
+    fn log_entry(conn: &Connection, recipe_id: i64, date: &str, eaten_at: &str, planned: bool) {
+        let day = Day::get_or_create(conn, date).unwrap();
+        MealEntry::create(conn, &MealEntryCreate {
+            day_id: day.id,
+            meal_type: MealType::Dinner,
+            recipe_id: Some(recipe_id),
+            food_item_id: None,
+            description: None,
+            inline_nutrition: None,
+            servings: 1.0,
+            percent_eaten: None,
+            notes: None,
+            eaten_at: Some(eaten_at.to_string()),
+            planned,
+        }, &SystemClock).unwrap();
+    }
+
+    #[test]
+    fn test_list_sorted_by_last_logged() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let chili = recipe(&conn, "Chili");
+        let oats = recipe(&conn, "Oats");
+        let never = recipe(&conn, "Never logged");
+        log(&conn, chili.id, "2024-03-01", "2024-03-01T18:00:00Z");
+        log(&conn, oats.id, "2024-03-02", "2024-03-02T08:00:00Z");
+        log(&conn, chili.id, "2024-03-05", "2024-03-05T19:00:00Z");
+
+        let names = |order: &str| -> Vec<String> {
+            Recipe::list(&conn, None, false, "last_logged", order, 10, 0)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.name)
+                .collect()
+        };
+        assert_eq!(names("desc"), vec!["Chili", "Oats", "Never logged"]);
+        assert_eq!(names("asc"), vec!["Never logged", "Oats", "Chili"]);
+
+        assert_eq!(Recipe::get_last_logged_at(&conn, chili.id).unwrap().as_deref(), Some("2024-03-05T19:00:00Z"));
+        assert_eq!(Recipe::get_last_logged_at(&conn, never.id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_planned_entries_are_not_logged() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let chili = recipe(&conn, "Chili");
+        let plan = recipe(&conn, "Only planned");
+        log(&conn, chili.id, "2024-03-01", "2024-03-01T18:00:00");
+        log_entry(&conn, chili.id, "2024-03-09", "2024-03-09T18:00:00", true);
+        log_entry(&conn, plan.id, "2024-03-10", "2024-03-10T18:00:00", true);
+
+        assert_eq!(Recipe::get_times_logged(&conn, chili.id).unwrap(), 1);
+        assert_eq!(Recipe::get_times_logged(&conn, plan.id).unwrap(), 0);
+        assert_eq!(Recipe::get_last_logged_at(&conn, chili.id).unwrap().as_deref(), Some("2024-03-01T18:00:00"));
+        assert_eq!(Recipe::get_last_logged_at(&conn, plan.id).unwrap(), None);
+
+        let recent = Recipe::list_recent(&conn, 10).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!((recent[0].0.name.as_str(), recent[0].1.as_str(), recent[0].2), ("Chili", "2024-03-01", 1));
+
+        let by_count: Vec<String> = Recipe::list(&conn, None, false, "times_logged", "desc", 10, 0)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(by_count, vec!["Chili", "Only planned"]);
+    }
+}
@@ -238,6 +238,56 @@ pub fn would_create_cycle(conn: &Connection, recipe_id: i64, component_recipe_id
     Ok(false)
 }
 
+/// Default limit on how many levels of component recipes a recipe may contain
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 5;
+
+/// Longest chain of component recipes below recipe_id, starting with recipe_id itself.
+/// A recipe with no components has a chain of just itself (depth 0).
+pub fn deepest_component_chain(conn: &Connection, recipe_id: i64) -> DbResult<Vec<i64>> {
+    fn walk(conn: &Connection, recipe_id: i64, visiting: &mut HashSet<i64>) -> DbResult<Vec<i64>> {
+        // Cycles are rejected on insert; the guard only stops a corrupt table looping forever
+        if !visiting.insert(recipe_id) {
+            return Ok(Vec::new());
+        }
+        let mut deepest = Vec::new();
+        for comp in RecipeComponent::get_for_recipe(conn, recipe_id)? {
+            let chain = walk(conn, comp.component_recipe_id, visiting)?;
+            if chain.len() > deepest.len() {
+                deepest = chain;
+            }
+        }
+        visiting.remove(&recipe_id);
+        deepest.insert(0, recipe_id);
+        Ok(deepest)
+    }
+
+    walk(conn, recipe_id, &mut HashSet::new())
+}
+
+/// Levels of component recipes below recipe_id (0 when it has no components)
+pub fn nesting_depth(conn: &Connection, recipe_id: i64) -> DbResult<usize> {
+    Ok(deepest_component_chain(conn, recipe_id)?.len().saturating_sub(1))
+}
+
+/// Levels of parent recipes above recipe_id (0 when no recipe uses it)
+fn parent_height(conn: &Connection, recipe_id: i64, visiting: &mut HashSet<i64>) -> DbResult<usize> {
+    if !visiting.insert(recipe_id) {
+        return Ok(0);
+    }
+    let mut height = 0;
+    for parent_id in RecipeComponent::get_parent_recipe_ids(conn, recipe_id)? {
+        height = height.max(parent_height(conn, parent_id, visiting)? + 1);
+    }
+    visiting.remove(&recipe_id);
+    Ok(height)
+}
+
+/// Nesting depth of the deepest recipe affected by adding component_recipe_id to
+/// recipe_id: recipe_id's topmost ancestor down through the new component's deepest chain
+pub fn nesting_depth_after_adding(conn: &Connection, recipe_id: i64, component_recipe_id: i64) -> DbResult<usize> {
+    Ok(parent_height(conn, recipe_id, &mut HashSet::new())? + 1 + nesting_depth(conn, component_recipe_id)?)
+}
+
 /// Get all component recipe IDs recursively (for nutrition calculation)
 pub fn get_all_component_ids(conn: &Connection, recipe_id: i64) -> DbResult<Vec<i64>> {
     let mut all_ids = Vec::new();
@@ -259,3 +309,49 @@ pub fn get_all_component_ids(conn: &Connection, recipe_id: i64) -> DbResult<Vec<
 
     Ok(all_ids)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use crate::models::{Recipe, RecipeCreate};
+
+    fn chain(conn: &Connection, len: usize) -> Vec<i64> {
+        let ids: Vec<i64> = (0..len)
+            .map(|i| {
+                Recipe::create(conn, &RecipeCreate {
+                    name: format!("Level {}", i),
+                    servings_produced: 1.0,
+                    yield_grams: None,
+                    is_favorite: false,
+                    notes: None,
+                }).unwrap().id
+            })
+            .collect();
+        for pair in ids.windows(2) {
+            RecipeComponent::create(conn, &RecipeComponentCreate {
+                recipe_id: pair[0],
+                component_recipe_id: pair[1],
+                servings: 1.0,
+                notes: None,
+            }).unwrap();
+        }
+        ids
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let ids = chain(&conn, 4);
+        assert_eq!(nesting_depth(&conn, ids[0]).unwrap(), 3);
+        assert_eq!(nesting_depth(&conn, ids[3]).unwrap(), 0);
+        assert_eq!(deepest_component_chain(&conn, ids[1]).unwrap(), ids[1..].to_vec());
+
+        // Hanging a 2-deep chain under the middle reaches 2 + 1 + 1 levels from the top
+        let other = chain(&conn, 2);
+        assert_eq!(nesting_depth_after_adding(&conn, ids[2], other[0]).unwrap(), 4);
+        assert_eq!(nesting_depth_after_adding(&conn, other[1], ids[0]).unwrap(), 5);
+    }
+}
@@ -30,6 +30,10 @@ pub struct RecipeIngredientDetail {
     pub quantity: f64,
     pub unit: String,
     pub notes: Option<String>,
+    /// Gram amount with an approximate volume ("320 g ≈ 2.75 cups"), only
+    /// filled in when a recipe is fetched with friendly_units
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub friendly_amount: Option<String>,
 }
 
 /// Data for adding an ingredient to a recipe
@@ -134,6 +138,7 @@ impl RecipeIngredient {
                     quantity: row.get("quantity")?,
                     unit: row.get("unit")?,
                     notes: row.get("notes")?,
+                    friendly_amount: None,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -185,6 +190,18 @@ impl RecipeIngredient {
         Ok(rows > 0)
     }
 
+    /// Servings of `food_item` this ingredient's quantity amounts to
+    pub fn food_item_servings(&self, food_item: &FoodItem) -> f64 {
+        crate::nutrition::calculate_nutrition_multiplier(
+            self.quantity,
+            &self.unit,
+            food_item.serving_size,
+            &food_item.serving_unit,
+            food_item.grams_per_serving,
+            food_item.ml_per_serving,
+        )
+    }
+
     /// Get the recipe_id for an ingredient
     pub fn get_recipe_id(conn: &Connection, id: i64) -> DbResult<Option<i64>> {
         let result: Result<i64, _> = conn.query_row(
@@ -202,8 +219,6 @@ impl RecipeIngredient {
 
 /// Calculate total nutrition for a recipe based on its ingredients and component recipes
 pub fn calculate_recipe_nutrition(conn: &Connection, recipe_id: i64) -> DbResult<Nutrition> {
-    use crate::nutrition::calculate_nutrition_multiplier;
-
     let recipe = Recipe::get_by_id(conn, recipe_id)?
         .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
 
@@ -215,17 +230,7 @@ pub fn calculate_recipe_nutrition(conn: &Connection, recipe_id: i64) -> DbResult
         let food_item = FoodItem::get_by_id(conn, ingredient.food_item_id)?
             .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
 
-        // Calculate multiplier using the new unit conversion system
-        let multiplier = calculate_nutrition_multiplier(
-            ingredient.quantity,
-            &ingredient.unit,
-            food_item.serving_size,
-            &food_item.serving_unit,
-            food_item.grams_per_serving,
-            food_item.ml_per_serving,
-        );
-
-        total = total + food_item.nutrition.scale(multiplier);
+        total = total + food_item.nutrition.scale(ingredient.food_item_servings(&food_item));
     }
 
     // Sum nutrition from component recipes
@@ -246,6 +251,32 @@ pub fn calculate_recipe_nutrition(conn: &Connection, recipe_id: i64) -> DbResult
     Ok(per_serving)
 }
 
+/// Expand `servings` of a recipe into the food items behind it, descending into
+/// component recipes. Each item is paired with the servings of that food item
+/// eaten, scaled the same way calculate_recipe_nutrition scales ingredients.
+pub fn expand_recipe_food_items(conn: &Connection, recipe_id: i64, servings: f64) -> DbResult<Vec<(FoodItem, f64)>> {
+    use super::recipe_component::RecipeComponent;
+
+    let recipe = Recipe::get_by_id(conn, recipe_id)?
+        .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
+    // Ingredient amounts make up servings_produced servings
+    let per_serving = servings / recipe.servings_produced;
+
+    let mut items = Vec::new();
+    for ingredient in RecipeIngredient::get_for_recipe(conn, recipe_id)? {
+        let food_item = FoodItem::get_by_id(conn, ingredient.food_item_id)?
+            .ok_or_else(|| crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))?;
+        let food_servings = ingredient.food_item_servings(&food_item) * per_serving;
+        items.push((food_item, food_servings));
+    }
+
+    for component in RecipeComponent::get_for_recipe(conn, recipe_id)? {
+        items.extend(expand_recipe_food_items(conn, component.component_recipe_id, component.servings * per_serving)?);
+    }
+
+    Ok(items)
+}
+
 /// Recalculate and update cached nutrition for a recipe
 pub fn recalculate_recipe_nutrition(conn: &Connection, recipe_id: i64) -> DbResult<Nutrition> {
     let nutrition = calculate_recipe_nutrition(conn, recipe_id)?;
@@ -260,15 +291,20 @@ pub struct CascadeRecalculateResult {
     pub days_recalculated: i64,
 }
 
-/// Cascading recalculation: when a food item changes, recalculate all affected recipes and days
-pub fn cascade_recalculate_from_food_item(
-    conn: &Connection,
-    food_item_id: i64,
-) -> DbResult<CascadeRecalculateResult> {
-    use std::collections::HashSet;
-    use super::recipe_component::RecipeComponent;
+/// Recipes and days a change to a food item reaches
+#[derive(Debug, Clone, Default)]
+pub struct CascadeTargets {
+    /// Recipes using the food item as an ingredient
+    pub direct_recipe_ids: Vec<i64>,
+    /// Direct recipes plus every recipe using one as a component, dependencies first
+    pub recipe_ids: Vec<i64>,
+    /// Days with meal entries logging an affected recipe or the food item itself
+    pub day_ids: Vec<i64>,
+}
 
-    let mut result = CascadeRecalculateResult::default();
+/// Find what cascade_recalculate_from_food_item would recalculate, without changing anything
+pub fn find_cascade_targets(conn: &Connection, food_item_id: i64) -> DbResult<CascadeTargets> {
+    use std::collections::HashSet;
 
     // Step 1: Find all recipes directly using this food item
     let direct_recipe_ids: Vec<i64> = {
@@ -280,12 +316,12 @@ pub fn cascade_recalculate_from_food_item(
     };
 
     if direct_recipe_ids.is_empty() {
-        return Ok(result);
+        return Ok(CascadeTargets::default());
     }
 
     // Step 2: Collect all recipes affected (including parent recipes that use affected recipes as components)
     let mut all_affected: HashSet<i64> = HashSet::new();
-    let mut to_process: Vec<i64> = direct_recipe_ids;
+    let mut to_process: Vec<i64> = direct_recipe_ids.clone();
 
     while let Some(recipe_id) = to_process.pop() {
         if all_affected.insert(recipe_id) {
@@ -305,13 +341,7 @@ pub fn cascade_recalculate_from_food_item(
     // We need to recalculate in order so parent recipes get updated child values
     let sorted_recipes = topological_sort_recipes(conn, &all_affected)?;
 
-    // Step 4: Recalculate all affected recipes
-    for recipe_id in &sorted_recipes {
-        recalculate_recipe_nutrition(conn, *recipe_id)?;
-        result.recipes_recalculated += 1;
-    }
-
-    // Step 5: Find all days with meal entries using affected recipes or the food item directly
+    // Step 4: Find all days with meal entries using affected recipes or the food item directly
     let affected_day_ids: Vec<i64> = {
         let recipe_ids_str = sorted_recipes
             .iter()
@@ -333,9 +363,29 @@ pub fn cascade_recalculate_from_food_item(
         rows.collect::<Result<Vec<_>, _>>()?
     };
 
-    // Step 6: Recalculate all affected days
+    Ok(CascadeTargets {
+        direct_recipe_ids,
+        recipe_ids: sorted_recipes,
+        day_ids: affected_day_ids,
+    })
+}
+
+/// Cascading recalculation: when a food item changes, recalculate all affected recipes and days
+pub fn cascade_recalculate_from_food_item(
+    conn: &Connection,
+    food_item_id: i64,
+) -> DbResult<CascadeRecalculateResult> {
     use super::meal_entry::recalculate_day_nutrition;
-    for day_id in affected_day_ids {
+
+    let targets = find_cascade_targets(conn, food_item_id)?;
+    let mut result = CascadeRecalculateResult::default();
+
+    for recipe_id in &targets.recipe_ids {
+        recalculate_recipe_nutrition(conn, *recipe_id)?;
+        result.recipes_recalculated += 1;
+    }
+
+    for day_id in targets.day_ids {
         recalculate_day_nutrition(conn, day_id)?;
         result.days_recalculated += 1;
     }
@@ -1,11 +1,15 @@
 //! Vital model
 //!
 //! Represents vital signs and health measurements including weight, blood pressure,
-//! heart rate, oxygen saturation, and glucose levels. Supports grouping related readings.
+//! heart rate, oxygen saturation, and glucose levels. Supports grouping related readings
+//! and custom vital types registered at runtime.
+
+use std::collections::HashSet;
 
 use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 
+use crate::clock::Clock;
 use crate::db::DbResult;
 
 /// Vital type enum
@@ -17,6 +21,9 @@ pub enum VitalType {
     HeartRate,
     OxygenSaturation,
     Glucose,
+    Water,
+    /// A type from the custom_vital_types registry (see Vital::custom_type_id)
+    Custom,
 }
 
 impl VitalType {
@@ -27,9 +34,13 @@ impl VitalType {
             VitalType::HeartRate => "heart_rate",
             VitalType::OxygenSaturation => "oxygen_saturation",
             VitalType::Glucose => "glucose",
+            VitalType::Water => "water",
+            VitalType::Custom => "custom",
         }
     }
 
+    /// Parse a built-in type name or alias. Custom types need a registry
+    /// lookup; use VitalType::lookup for those.
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().replace('-', "_").as_str() {
             "weight" => Some(VitalType::Weight),
@@ -37,6 +48,7 @@ impl VitalType {
             "heart_rate" | "hr" | "pulse" => Some(VitalType::HeartRate),
             "oxygen_saturation" | "o2" | "spo2" | "oxygen" => Some(VitalType::OxygenSaturation),
             "glucose" | "blood_sugar" | "sugar" => Some(VitalType::Glucose),
+            "water" | "hydration" => Some(VitalType::Water),
             _ => None,
         }
     }
@@ -48,6 +60,8 @@ impl VitalType {
             VitalType::HeartRate => "Heart Rate",
             VitalType::OxygenSaturation => "Oxygen Saturation",
             VitalType::Glucose => "Blood Glucose",
+            VitalType::Water => "Water Intake",
+            VitalType::Custom => "Custom",
         }
     }
 
@@ -59,6 +73,8 @@ impl VitalType {
             VitalType::HeartRate => "bpm",
             VitalType::OxygenSaturation => "%",
             VitalType::Glucose => "mg/dL",
+            VitalType::Water => "ml",
+            VitalType::Custom => "",
         }
     }
 
@@ -75,8 +91,99 @@ impl VitalType {
             VitalType::HeartRate => ("BPM", None),
             VitalType::OxygenSaturation => ("SpO2 %", None),
             VitalType::Glucose => ("mg/dL", None),
+            VitalType::Water => ("ml", None),
+            VitalType::Custom => ("Value", None),
         }
     }
+
+    /// Resolve a type name: built-in names and aliases first, then the custom
+    /// vital type registry for anything else
+    pub fn lookup(conn: &Connection, s: &str) -> DbResult<Option<(Self, Option<CustomVitalType>)>> {
+        if let Some(vt) = Self::from_str(s) {
+            return Ok(Some((vt, None)));
+        }
+        Ok(CustomVitalType::get_by_name(conn, s)?.map(|custom| (VitalType::Custom, Some(custom))))
+    }
+}
+
+/// A user-defined vital type, e.g. "mood" (1-10) or "peak flow" (L/min)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomVitalType {
+    pub id: i64,
+    pub name: String,
+    pub unit: String,
+    /// Label for value2 when readings carry a second value; None if they don't
+    pub value2_label: Option<String>,
+    pub created_at: String,
+}
+
+/// Data for registering a custom vital type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomVitalTypeCreate {
+    pub name: String,
+    pub unit: String,
+    pub value2_label: Option<String>,
+}
+
+impl CustomVitalType {
+    /// Create from a database row
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            unit: row.get("unit")?,
+            value2_label: row.get("value2_label")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    /// Register a custom vital type (names are unique, case-insensitively)
+    pub fn create(conn: &Connection, data: &CustomVitalTypeCreate) -> DbResult<Self> {
+        conn.execute(
+            "INSERT INTO custom_vital_types (name, unit, value2_label) VALUES (?1, ?2, ?3)",
+            params![data.name, data.unit, data.value2_label],
+        )?;
+
+        let id = conn.last_insert_rowid();
+        Self::get_by_id(conn, id)?.ok_or_else(|| {
+            crate::db::DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows)
+        })
+    }
+
+    /// Get a custom vital type by ID
+    pub fn get_by_id(conn: &Connection, id: i64) -> DbResult<Option<Self>> {
+        let mut stmt = conn.prepare("SELECT * FROM custom_vital_types WHERE id = ?1")?;
+
+        let result = stmt.query_row([id], Self::from_row);
+        match result {
+            Ok(custom) => Ok(Some(custom)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Get a custom vital type by name (case-insensitive, surrounding whitespace ignored)
+    pub fn get_by_name(conn: &Connection, name: &str) -> DbResult<Option<Self>> {
+        let mut stmt = conn.prepare("SELECT * FROM custom_vital_types WHERE name = ?1")?;
+
+        let result = stmt.query_row([name.trim()], Self::from_row);
+        match result {
+            Ok(custom) => Ok(Some(custom)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// List every custom vital type by name
+    pub fn list(conn: &Connection) -> DbResult<Vec<Self>> {
+        let mut stmt = conn.prepare("SELECT * FROM custom_vital_types ORDER BY name")?;
+
+        let types = stmt
+            .query_map([], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(types)
+    }
 }
 
 /// A group of related vital readings
@@ -110,10 +217,8 @@ impl VitalGroup {
     }
 
     /// Create a new vital group
-    pub fn create(conn: &Connection, data: &VitalGroupCreate) -> DbResult<Self> {
-        let timestamp = data.timestamp.clone().unwrap_or_else(|| {
-            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
-        });
+    pub fn create(conn: &Connection, data: &VitalGroupCreate, clock: &dyn Clock) -> DbResult<Self> {
+        let timestamp = data.timestamp.clone().unwrap_or_else(|| clock.timestamp());
 
         conn.execute(
             r#"
@@ -196,6 +301,16 @@ impl VitalGroup {
         Self::get_by_id(conn, id)
     }
 
+    /// Set a group's timestamp (the caller validates the format)
+    pub fn set_timestamp(conn: &Connection, id: i64, timestamp: &str) -> DbResult<Option<Self>> {
+        conn.execute(
+            "UPDATE vital_groups SET timestamp = ?1 WHERE id = ?2",
+            params![timestamp, id],
+        )?;
+
+        Self::get_by_id(conn, id)
+    }
+
     /// Delete a vital group (unlinks vitals but doesn't delete them)
     pub fn delete(conn: &Connection, id: i64) -> DbResult<bool> {
         // Unlink any vitals from this group
@@ -206,6 +321,23 @@ impl VitalGroup {
         Ok(rows > 0)
     }
 
+    /// IDs of groups recorded around exercise ("Post Exercise", "after workout", ...)
+    pub fn exercise_group_ids(conn: &Connection) -> DbResult<HashSet<i64>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id FROM vital_groups
+            WHERE description LIKE '%exercise%' OR description LIKE '%workout%'
+               OR notes LIKE '%exercise%' OR notes LIKE '%workout%'
+            "#
+        )?;
+
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        Ok(ids)
+    }
+
     /// Get all vitals in this group
     pub fn get_vitals(conn: &Connection, group_id: i64) -> DbResult<Vec<Vital>> {
         Vital::list_by_group(conn, group_id)
@@ -223,6 +355,12 @@ pub struct Vital {
     pub unit: String,
     pub group_id: Option<i64>,
     pub notes: Option<String>,
+    /// Registry entry of a Custom reading
+    #[serde(default)]
+    pub custom_type_id: Option<i64>,
+    /// Name of the custom type, for display
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_type: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -237,6 +375,8 @@ pub struct VitalCreate {
     pub unit: Option<String>,
     pub group_id: Option<i64>,
     pub notes: Option<String>,
+    /// Required for (and only allowed with) VitalType::Custom
+    pub custom_type_id: Option<i64>,
 }
 
 /// Data for updating a vital
@@ -249,12 +389,17 @@ pub struct VitalUpdate {
     pub notes: Option<String>,
 }
 
+/// Vitals joined with their custom type name; filter columns need the `v.` prefix
+const VITAL_SELECT: &str = "SELECT v.*, c.name AS custom_type_name FROM vitals v LEFT JOIN custom_vital_types c ON c.id = v.custom_type_id";
+
 impl Vital {
     /// Create from a database row
     fn from_row(row: &Row) -> rusqlite::Result<Self> {
         let vital_type_str: String = row.get("vital_type")?;
-        let vital_type = VitalType::from_str(&vital_type_str)
-            .unwrap_or(VitalType::Weight);
+        let vital_type = match vital_type_str.as_str() {
+            "custom" => VitalType::Custom,
+            other => VitalType::from_str(other).unwrap_or(VitalType::Weight),
+        };
 
         Ok(Self {
             id: row.get("id")?,
@@ -265,24 +410,34 @@ impl Vital {
             unit: row.get("unit")?,
             group_id: row.get("group_id")?,
             notes: row.get("notes")?,
+            custom_type_id: row.get("custom_type_id")?,
+            custom_type: row.get("custom_type_name")?,
             created_at: row.get("created_at")?,
             updated_at: row.get("updated_at")?,
         })
     }
 
+    /// Type name: the custom type's name for custom readings, else the built-in name
+    pub fn type_name(&self) -> &str {
+        self.custom_type.as_deref().unwrap_or(self.vital_type.as_str())
+    }
+
+    /// Display name: the custom type's name for custom readings
+    pub fn type_display_name(&self) -> &str {
+        self.custom_type.as_deref().unwrap_or(self.vital_type.display_name())
+    }
+
     /// Create a new vital reading
-    pub fn create(conn: &Connection, data: &VitalCreate) -> DbResult<Self> {
-        let timestamp = data.timestamp.clone().unwrap_or_else(|| {
-            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
-        });
+    pub fn create(conn: &Connection, data: &VitalCreate, clock: &dyn Clock) -> DbResult<Self> {
+        let timestamp = data.timestamp.clone().unwrap_or_else(|| clock.timestamp());
         let unit = data.unit.clone().unwrap_or_else(|| {
             data.vital_type.default_unit().to_string()
         });
 
         conn.execute(
             r#"
-            INSERT INTO vitals (vital_type, timestamp, value1, value2, unit, group_id, notes)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            INSERT INTO vitals (vital_type, timestamp, value1, value2, unit, group_id, notes, custom_type_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
             "#,
             params![
                 data.vital_type.as_str(),
@@ -292,6 +447,7 @@ impl Vital {
                 unit,
                 data.group_id,
                 data.notes,
+                data.custom_type_id,
             ],
         )?;
 
@@ -301,9 +457,20 @@ impl Vital {
         })
     }
 
+    /// List every vital ordered by id
+    pub fn list_all(conn: &Connection) -> DbResult<Vec<Self>> {
+        let mut stmt = conn.prepare(&format!("{} ORDER BY v.id", VITAL_SELECT))?;
+
+        let rows = stmt
+            .query_map([], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
     /// Get a vital by ID
     pub fn get_by_id(conn: &Connection, id: i64) -> DbResult<Option<Self>> {
-        let mut stmt = conn.prepare("SELECT * FROM vitals WHERE id = ?1")?;
+        let mut stmt = conn.prepare(&format!("{} WHERE v.id = ?1", VITAL_SELECT))?;
 
         let result = stmt.query_row([id], Self::from_row);
         match result {
@@ -321,10 +488,10 @@ impl Vital {
     ) -> DbResult<Vec<Self>> {
         let sql = match limit {
             Some(n) => format!(
-                "SELECT * FROM vitals WHERE vital_type = ?1 ORDER BY timestamp DESC LIMIT {}",
-                n
+                "{} WHERE v.vital_type = ?1 ORDER BY v.timestamp DESC LIMIT {}",
+                VITAL_SELECT, n
             ),
-            None => "SELECT * FROM vitals WHERE vital_type = ?1 ORDER BY timestamp DESC".to_string(),
+            None => format!("{} WHERE v.vital_type = ?1 ORDER BY v.timestamp DESC", VITAL_SELECT),
         };
 
         let mut stmt = conn.prepare(&sql)?;
@@ -335,11 +502,34 @@ impl Vital {
         Ok(vitals)
     }
 
+    /// List readings of one custom vital type
+    pub fn list_by_custom_type(
+        conn: &Connection,
+        custom_type_id: i64,
+        limit: Option<i64>,
+    ) -> DbResult<Vec<Self>> {
+        let sql = match limit {
+            Some(n) => format!(
+                "{} WHERE v.custom_type_id = ?1 ORDER BY v.timestamp DESC LIMIT {}",
+                VITAL_SELECT, n
+            ),
+            None => format!("{} WHERE v.custom_type_id = ?1 ORDER BY v.timestamp DESC", VITAL_SELECT),
+        };
+
+        let mut stmt = conn.prepare(&sql)?;
+        let vitals = stmt
+            .query_map([custom_type_id], Self::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(vitals)
+    }
+
     /// List vitals by group
     pub fn list_by_group(conn: &Connection, group_id: i64) -> DbResult<Vec<Self>> {
-        let mut stmt = conn.prepare(
-            "SELECT * FROM vitals WHERE group_id = ?1 ORDER BY vital_type, timestamp"
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE v.group_id = ?1 ORDER BY v.vital_type, v.timestamp",
+            VITAL_SELECT
+        ))?;
         let vitals = stmt
             .query_map([group_id], Self::from_row)?
             .collect::<Result<Vec<_>, _>>()?;
@@ -349,9 +539,10 @@ impl Vital {
 
     /// List recent vitals across all types
     pub fn list_recent(conn: &Connection, limit: i64) -> DbResult<Vec<Self>> {
-        let mut stmt = conn.prepare(
-            "SELECT * FROM vitals ORDER BY timestamp DESC LIMIT ?1"
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "{} ORDER BY v.timestamp DESC LIMIT ?1",
+            VITAL_SELECT
+        ))?;
         let vitals = stmt
             .query_map([limit], Self::from_row)?
             .collect::<Result<Vec<_>, _>>()?;
@@ -359,29 +550,43 @@ impl Vital {
         Ok(vitals)
     }
 
-    /// List vitals by date range
+    /// List vitals from start_date through end_date, both inclusive.
+    ///
+    /// Only the date portion (YYYY-MM-DD) of each bound is used, and the range is
+    /// half-open on it: `start <= timestamp < end + 1 day`. Comparing bare dates
+    /// against timestamps keeps every reading on the end date whatever its format
+    /// ("T...Z", space-separated, fractional seconds) without touching the next day.
+    /// Bounds that aren't dates match nothing; tools validate user input first.
     pub fn list_by_date_range(
         conn: &Connection,
         start_date: &str,
         end_date: &str,
         vital_type: Option<VitalType>,
     ) -> DbResult<Vec<Self>> {
+        let (Some(start), Some(end)) = (date_part(start_date), date_part(end_date)) else {
+            return Ok(Vec::new());
+        };
+        let start = start.to_string();
+        let end_exclusive = (end + chrono::Duration::days(1)).to_string();
+
         let sql = match vital_type {
-            Some(_) => {
-                "SELECT * FROM vitals WHERE timestamp >= ?1 AND timestamp <= ?2 AND vital_type = ?3 ORDER BY timestamp DESC"
-            }
-            None => {
-                "SELECT * FROM vitals WHERE timestamp >= ?1 AND timestamp <= ?2 ORDER BY timestamp DESC"
-            }
+            Some(_) => format!(
+                "{} WHERE v.timestamp >= ?1 AND v.timestamp < ?2 AND v.vital_type = ?3 ORDER BY v.timestamp DESC",
+                VITAL_SELECT
+            ),
+            None => format!(
+                "{} WHERE v.timestamp >= ?1 AND v.timestamp < ?2 ORDER BY v.timestamp DESC",
+                VITAL_SELECT
+            ),
         };
 
-        let mut stmt = conn.prepare(sql)?;
+        let mut stmt = conn.prepare(&sql)?;
         let vitals = match vital_type {
             Some(vt) => stmt
-                .query_map(params![start_date, end_date, vt.as_str()], Self::from_row)?
+                .query_map(params![start, end_exclusive, vt.as_str()], Self::from_row)?
                 .collect::<Result<Vec<_>, _>>()?,
             None => stmt
-                .query_map(params![start_date, end_date], Self::from_row)?
+                .query_map(params![start, end_exclusive], Self::from_row)?
                 .collect::<Result<Vec<_>, _>>()?,
         };
 
@@ -444,23 +649,36 @@ impl Vital {
         Self::get_by_id(conn, id)
     }
 
+    /// Set a vital's timestamp (the caller validates the format); group_id is untouched
+    pub fn set_timestamp(conn: &Connection, id: i64, timestamp: &str) -> DbResult<Option<Self>> {
+        conn.execute(
+            "UPDATE vitals SET timestamp = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![timestamp, id],
+        )?;
+
+        Self::get_by_id(conn, id)
+    }
+
     /// Delete a vital
     pub fn delete(conn: &Connection, id: i64) -> DbResult<bool> {
         let rows = conn.execute("DELETE FROM vitals WHERE id = ?1", [id])?;
         Ok(rows > 0)
     }
 
-    /// Get the latest reading for each vital type
+    /// Get the latest reading for each vital type (each custom type counts separately)
     pub fn get_latest_by_type(conn: &Connection) -> DbResult<Vec<Self>> {
         let mut stmt = conn.prepare(
             r#"
-            SELECT v.* FROM vitals v
+            SELECT v.*, c.name AS custom_type_name FROM vitals v
             INNER JOIN (
-                SELECT vital_type, MAX(timestamp) as max_ts
+                SELECT vital_type, custom_type_id, MAX(timestamp) as max_ts
                 FROM vitals
-                GROUP BY vital_type
-            ) latest ON v.vital_type = latest.vital_type AND v.timestamp = latest.max_ts
-            ORDER BY v.vital_type
+                GROUP BY vital_type, custom_type_id
+            ) latest ON v.vital_type = latest.vital_type
+                AND v.custom_type_id IS latest.custom_type_id
+                AND v.timestamp = latest.max_ts
+            LEFT JOIN custom_vital_types c ON c.id = v.custom_type_id
+            ORDER BY v.vital_type, c.name
             "#
         )?;
         let vitals = stmt
@@ -486,9 +704,147 @@ impl Vital {
             VitalType::OxygenSaturation => {
                 format!("{}%", self.value1 as i32)
             }
-            VitalType::Glucose => {
+            VitalType::Glucose | VitalType::Water => {
                 format!("{} {}", self.value1 as i32, self.unit)
             }
+            VitalType::Custom => match self.value2 {
+                Some(v2) => format!("{}/{} {}", self.value1, v2, self.unit).trim_end().to_string(),
+                None => format!("{} {}", self.value1, self.unit).trim_end().to_string(),
+            },
         }
     }
 }
+
+/// Parse the YYYY-MM-DD prefix of a date or timestamp
+fn date_part(value: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(value.get(..10).unwrap_or(value), "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::migrations::run_migrations;
+
+    fn add(conn: &Connection, timestamp: &str) {
+        Vital::create(conn, &VitalCreate {
+            vital_type: VitalType::Weight,
+            timestamp: Some(timestamp.to_string()),
+            value1: 180.0,
+            value2: None,
+            unit: None,
+            group_id: None,
+            notes: None,
+            custom_type_id: None,
+        }, &SystemClock).unwrap();
+    }
+
+    fn timestamps(conn: &Connection, start: &str, end: &str) -> Vec<String> {
+        let mut found: Vec<String> = Vital::list_by_date_range(conn, start, end, None)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.timestamp)
+            .collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn test_create_defaults_timestamp_from_clock() {
+        use crate::clock::FixedClock;
+
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let clock = FixedClock::from_date("2024-03-01").unwrap();
+
+        let group = VitalGroup::create(&conn, &VitalGroupCreate { description: None, timestamp: None, notes: None }, &clock).unwrap();
+        let vital = Vital::create(&conn, &VitalCreate {
+            vital_type: VitalType::Weight,
+            timestamp: None,
+            value1: 180.0,
+            value2: None,
+            unit: None,
+            group_id: Some(group.id),
+            notes: None,
+            custom_type_id: None,
+        }, &clock).unwrap();
+
+        assert_eq!(group.timestamp, "2024-03-01T00:00:00");
+        assert_eq!(vital.timestamp, "2024-03-01T00:00:00");
+    }
+
+    #[test]
+    fn test_date_range_boundaries() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        for ts in [
+            "2024-02-29T23:59:59Z",
+            "2024-03-01T00:00:00Z",
+            "2024-03-01 00:00:00",
+            "2024-03-31T23:59:59.999Z",
+            "2024-03-31 23:59:59.5",
+            "2024-04-01T00:00:00Z",
+        ] {
+            add(&conn, ts);
+        }
+
+        assert_eq!(
+            timestamps(&conn, "2024-03-01", "2024-03-31"),
+            vec!["2024-03-01 00:00:00", "2024-03-01T00:00:00Z", "2024-03-31 23:59:59.5", "2024-03-31T23:59:59.999Z"]
+        );
+        // Adjacent ranges share no readings and together miss none
+        assert_eq!(timestamps(&conn, "2024-04-01", "2024-04-30"), vec!["2024-04-01T00:00:00Z"]);
+        assert_eq!(timestamps(&conn, "2024-02-01", "2024-02-29"), vec!["2024-02-29T23:59:59Z"]);
+        // Full timestamps are reduced to their date
+        assert_eq!(timestamps(&conn, "2024-03-31T12:00:00Z", "2024-03-31T12:00:00Z").len(), 2);
+
+        assert!(Vital::list_by_date_range(&conn, "March 1", "2024-03-31", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_custom_vital_type_lookup_and_readings() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let mood = CustomVitalType::create(&conn, &CustomVitalTypeCreate {
+            name: "Mood".to_string(),
+            unit: "points".to_string(),
+            value2_label: None,
+        }).unwrap();
+
+        // Built-in names win; registered names are case-insensitive
+        assert!(matches!(VitalType::lookup(&conn, "bp").unwrap(), Some((VitalType::BloodPressure, None))));
+        let (vt, custom) = VitalType::lookup(&conn, " mood ").unwrap().unwrap();
+        assert_eq!(vt, VitalType::Custom);
+        assert_eq!(custom.unwrap().id, mood.id);
+        assert!(VitalType::lookup(&conn, "energy").unwrap().is_none());
+
+        let vital = Vital::create(&conn, &VitalCreate {
+            vital_type: VitalType::Custom,
+            timestamp: Some("2024-03-01T08:00:00Z".to_string()),
+            value1: 7.0,
+            value2: None,
+            unit: Some(mood.unit.clone()),
+            group_id: None,
+            notes: None,
+            custom_type_id: Some(mood.id),
+        }, &SystemClock).unwrap();
+        assert_eq!(vital.type_name(), "Mood");
+        assert_eq!(vital.format_value(), "7 points");
+        assert_eq!(Vital::list_by_custom_type(&conn, mood.id, None).unwrap().len(), 1);
+
+        // A custom reading needs its registry entry
+        let orphan = VitalCreate {
+            vital_type: VitalType::Custom,
+            timestamp: None,
+            value1: 1.0,
+            value2: None,
+            unit: None,
+            group_id: None,
+            notes: None,
+            custom_type_id: None,
+        };
+        assert!(Vital::create(&conn, &orphan, &SystemClock).is_err());
+    }
+}
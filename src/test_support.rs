@@ -0,0 +1,87 @@
+//! Shared fixtures for unit tests
+//!
+//! Temp-file databases (for code that goes through `Database`) and food items
+//! built from a few fields over sensible defaults.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+
+use crate::db::migrations::run_migrations;
+use crate::db::Database;
+use crate::models::{FoodItem, FoodItemCreate};
+
+/// A migrated database in its own temp file, removed (with its WAL files) on drop
+pub struct TempDb {
+    db: Database,
+    path: PathBuf,
+}
+
+impl TempDb {
+    /// Path of the database file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for TempDb {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        &self.db
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        remove_db_files(&self.path);
+    }
+}
+
+/// Path for a test database named `name`, unique to this test process
+pub fn temp_db_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("uhm-test-{}-{}.db", name, std::process::id()));
+    remove_db_files(&path);
+    path
+}
+
+/// Remove a database file along with its -wal and -shm companions
+pub fn remove_db_files(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    for suffix in ["-wal", "-shm"] {
+        let mut companion = path.as_os_str().to_owned();
+        companion.push(suffix);
+        let _ = std::fs::remove_file(companion);
+    }
+}
+
+/// Fresh, migrated database in a temp file named after `name`
+pub fn temp_db(name: &str) -> TempDb {
+    let path = temp_db_path(name);
+    let db = Database::new(&path).unwrap();
+    db.with_conn(run_migrations).unwrap();
+    TempDb { db, path }
+}
+
+/// FoodItemCreate for `name`: 100 g servings with zero nutrition, overridden by `fields`
+pub fn food_create(name: &str, fields: serde_json::Value) -> FoodItemCreate {
+    let mut data = serde_json::json!({
+        "name": name,
+        "serving_size": 100.0,
+        "serving_unit": "g",
+        "calories": 0.0,
+        "protein": 0.0,
+        "carbs": 0.0,
+        "fat": 0.0
+    });
+    if let (Some(base), serde_json::Value::Object(overrides)) = (data.as_object_mut(), fields) {
+        base.extend(overrides);
+    }
+    serde_json::from_value(data).unwrap()
+}
+
+/// Create a food item from `food_create(name, fields)`
+pub fn make_food(conn: &Connection, name: &str, fields: serde_json::Value) -> FoodItem {
+    FoodItem::create(conn, &food_create(name, fields)).unwrap()
+}
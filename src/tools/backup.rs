@@ -0,0 +1,809 @@
+//! Full Backup MCP Tools
+//!
+//! Export every record to a single versioned JSON document and restore it,
+//! remapping ids so relationships survive the trip into another database.
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::Clock;
+use crate::db::Database;
+use crate::models::{
+    recalculate_day_nutrition, CustomVitalType, Day, FoodItem, MealEntry, Medication, PreparedBatch, Recipe,
+    RecipeComponent, RecipeIngredient, Vital, VitalGroup,
+};
+use super::error::ToolError;
+
+/// Version of the export_all_json layout
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// A recipe with the rows that belong to it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupRecipe {
+    #[serde(flatten)]
+    pub recipe: Recipe,
+    pub ingredients: Vec<RecipeIngredient>,
+    pub components: Vec<RecipeComponent>,
+    #[serde(default)]
+    pub prepared_batches: Vec<PreparedBatch>,
+}
+
+/// A day with its meal entries
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupDay {
+    #[serde(flatten)]
+    pub day: Day,
+    pub meal_entries: Vec<MealEntry>,
+}
+
+/// The export_all_json document. Ids are the source database's and are only
+/// used to link records within the document.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupDocument {
+    pub format_version: u32,
+    pub exported_at: String,
+    pub food_items: Vec<FoodItem>,
+    pub recipes: Vec<BackupRecipe>,
+    pub days: Vec<BackupDay>,
+    pub vital_groups: Vec<VitalGroup>,
+    #[serde(default)]
+    pub custom_vital_types: Vec<CustomVitalType>,
+    pub vitals: Vec<Vital>,
+    pub medications: Vec<Medication>,
+}
+
+/// Number of records of each kind in a backup
+#[derive(Debug, Default, Serialize)]
+pub struct BackupCounts {
+    pub food_items: usize,
+    pub recipes: usize,
+    pub recipe_ingredients: usize,
+    pub recipe_components: usize,
+    pub prepared_batches: usize,
+    pub days: usize,
+    pub meal_entries: usize,
+    pub vital_groups: usize,
+    pub custom_vital_types: usize,
+    pub vitals: usize,
+    pub medications: usize,
+}
+
+impl BackupCounts {
+    fn of(doc: &BackupDocument) -> Self {
+        Self {
+            food_items: doc.food_items.len(),
+            recipes: doc.recipes.len(),
+            recipe_ingredients: doc.recipes.iter().map(|r| r.ingredients.len()).sum(),
+            recipe_components: doc.recipes.iter().map(|r| r.components.len()).sum(),
+            prepared_batches: doc.recipes.iter().map(|r| r.prepared_batches.len()).sum(),
+            days: doc.days.len(),
+            meal_entries: doc.days.iter().map(|d| d.meal_entries.len()).sum(),
+            vital_groups: doc.vital_groups.len(),
+            custom_vital_types: doc.custom_vital_types.len(),
+            vitals: doc.vitals.len(),
+            medications: doc.medications.len(),
+        }
+    }
+}
+
+/// Response for export_all_json
+#[derive(Debug, Serialize)]
+pub struct ExportAllJsonResponse {
+    pub output_path: String,
+    pub format_version: u32,
+    pub exported_at: String,
+    pub exported: BackupCounts,
+}
+
+/// Response for import_all_json
+#[derive(Debug, Serialize)]
+pub struct ImportAllJsonResponse {
+    pub file_path: String,
+    pub mode: String,
+    pub format_version: u32,
+    pub exported_at: String,
+    /// Rows actually inserted
+    pub imported: BackupCounts,
+    /// Rows in the file matched to existing data (merge mode) and not inserted:
+    /// food items by name and brand; recipes by name, along with their
+    /// ingredients, components and batches; days by date, whose entries went to
+    /// the existing day; meal entries by day, source, meal type, servings and
+    /// eaten_at; vital groups by timestamp and description; custom vital types
+    /// by name; vitals by type, timestamp and values; medications by name and
+    /// start date
+    pub skipped: BackupCounts,
+    /// Days that received meal entries in merge mode, recalculated afterwards
+    pub days_recalculated: usize,
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Write every food item, recipe, day, vital and medication to one JSON file
+pub fn export_all_json(db: &Database, output_path: &str, clock: &dyn Clock) -> Result<ExportAllJsonResponse, ToolError> {
+    let conn = db.get_conn()?;
+    let load = |what: &str, e: crate::db::DbError| ToolError::Db(format!("Failed to load {}: {}", what, e));
+
+    let food_items = FoodItem::list_all(&conn).map_err(|e| load("food items", e))?;
+
+    let mut batches_by_recipe: HashMap<i64, Vec<PreparedBatch>> = HashMap::new();
+    for batch in PreparedBatch::list_all(&conn).map_err(|e| load("prepared batches", e))? {
+        batches_by_recipe.entry(batch.recipe_id).or_default().push(batch);
+    }
+    let recipes = Recipe::list_all(&conn)
+        .map_err(|e| load("recipes", e))?
+        .into_iter()
+        .map(|recipe| {
+            Ok(BackupRecipe {
+                ingredients: RecipeIngredient::get_for_recipe(&conn, recipe.id)
+                    .map_err(|e| load("recipe ingredients", e))?,
+                components: RecipeComponent::get_for_recipe(&conn, recipe.id)
+                    .map_err(|e| load("recipe components", e))?,
+                prepared_batches: batches_by_recipe.remove(&recipe.id).unwrap_or_default(),
+                recipe,
+            })
+        })
+        .collect::<Result<Vec<_>, ToolError>>()?;
+
+    let days = Day::list_all(&conn)
+        .map_err(|e| load("days", e))?
+        .into_iter()
+        .map(|day| {
+            Ok(BackupDay {
+                meal_entries: MealEntry::get_for_day(&conn, day.id).map_err(|e| load("meal entries", e))?,
+                day,
+            })
+        })
+        .collect::<Result<Vec<_>, ToolError>>()?;
+
+    let mut vital_groups = VitalGroup::list(&conn, None).map_err(|e| load("vital groups", e))?;
+    vital_groups.sort_by_key(|g| g.id);
+
+    let doc = BackupDocument {
+        format_version: BACKUP_FORMAT_VERSION,
+        exported_at: clock.timestamp(),
+        food_items,
+        recipes,
+        days,
+        vital_groups,
+        custom_vital_types: CustomVitalType::list(&conn).map_err(|e| load("custom vital types", e))?,
+        vitals: Vital::list_all(&conn).map_err(|e| load("vitals", e))?,
+        medications: Medication::list(&conn, false, None).map_err(|e| load("medications", e))?,
+    };
+
+    let json = serde_json::to_string_pretty(&doc).map_err(|e| ToolError::Io(format!("Serialization error: {}", e)))?;
+    std::fs::write(output_path, json)
+        .map_err(|e| ToolError::Io(format!("Failed to write file '{}': {}", output_path, e)))?;
+
+    Ok(ExportAllJsonResponse {
+        output_path: output_path.to_string(),
+        format_version: doc.format_version,
+        exported_at: doc.exported_at.clone(),
+        exported: BackupCounts::of(&doc),
+    })
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+/// Translate a source id through an id map, naming the record that referenced it
+fn remap(ids: &HashMap<i64, i64>, id: i64, kind: &str, referrer: &str) -> Result<i64, ToolError> {
+    ids.get(&id)
+        .copied()
+        .ok_or_else(|| ToolError::Validation(format!("{} references {} {} which is not in the backup", referrer, kind, id)))
+}
+
+/// Total rows across the tables a restore writes to
+fn data_row_count(conn: &Connection) -> Result<i64, ToolError> {
+    conn.query_row(
+        r#"SELECT (SELECT COUNT(*) FROM food_items) + (SELECT COUNT(*) FROM recipes)
+                + (SELECT COUNT(*) FROM days) + (SELECT COUNT(*) FROM vitals)
+                + (SELECT COUNT(*) FROM vital_groups) + (SELECT COUNT(*) FROM custom_vital_types)
+                + (SELECT COUNT(*) FROM medications)"#,
+        [],
+        |row| row.get(0),
+    )
+    .map_err(ToolError::from)
+}
+
+fn insert_food_item(conn: &Connection, item: &FoodItem) -> rusqlite::Result<i64> {
+    let n = &item.nutrition;
+    conn.execute(
+        r#"INSERT INTO food_items (
+               name, brand, serving_size, serving_unit,
+               calories, protein, carbs, fat, fiber, sodium, sugar, saturated_fat, cholesterol,
+               preference, notes, base_unit_type, grams_per_serving, ml_per_serving,
+               created_at, updated_at
+           ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)"#,
+        params![
+            item.name, item.brand, item.serving_size, item.serving_unit,
+            n.calories, n.protein, n.carbs, n.fat, n.fiber, n.sodium, n.sugar, n.saturated_fat, n.cholesterol,
+            item.preference.as_str(), item.notes, item.base_unit_type.map(|b| b.to_db_str()),
+            item.grams_per_serving, item.ml_per_serving, item.created_at, item.updated_at,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn insert_recipe(conn: &Connection, recipe: &Recipe) -> rusqlite::Result<i64> {
+    let n = &recipe.cached_nutrition;
+    conn.execute(
+        r#"INSERT INTO recipes (
+               name, servings_produced, yield_grams, is_favorite,
+               cached_calories, cached_protein, cached_carbs, cached_fat, cached_fiber,
+               cached_sodium, cached_sugar, cached_saturated_fat, cached_cholesterol,
+               notes, created_at, updated_at
+           ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)"#,
+        params![
+            recipe.name, recipe.servings_produced, recipe.yield_grams, recipe.is_favorite,
+            n.calories, n.protein, n.carbs, n.fat, n.fiber, n.sodium, n.sugar, n.saturated_fat, n.cholesterol,
+            recipe.notes, recipe.created_at, recipe.updated_at,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn insert_day(conn: &Connection, day: &Day) -> rusqlite::Result<i64> {
+    let n = &day.cached_nutrition;
+    conn.execute(
+        r#"INSERT INTO days (
+               date, cached_calories, cached_protein, cached_carbs, cached_fat, cached_fiber,
+               cached_sodium, cached_sugar, cached_saturated_fat, cached_cholesterol,
+               notes, created_at, updated_at
+           ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"#,
+        params![
+            day.date,
+            n.calories, n.protein, n.carbs, n.fat, n.fiber, n.sodium, n.sugar, n.saturated_fat, n.cholesterol,
+            day.notes, day.created_at, day.updated_at,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Insert a meal entry whose day, recipe and food item ids are already remapped
+fn insert_meal_entry(conn: &Connection, entry: &MealEntry) -> Result<(), ToolError> {
+    let source_type = match (entry.recipe_id, entry.food_item_id) {
+        (Some(_), _) => "recipe",
+        (None, Some(_)) => "food_item",
+        (None, None) => "adhoc",
+    };
+    let inline_json = entry.inline_nutrition.as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| ToolError::Io(format!("Serialization error: {}", e)))?;
+    let n = &entry.cached_nutrition;
+    conn.execute(
+        r#"INSERT INTO meal_entries (
+               day_id, meal_type, source_type, recipe_id, food_item_id, description, inline_nutrition,
+               servings, percent_eaten,
+               cached_calories, cached_protein, cached_carbs, cached_fat, cached_fiber,
+               cached_sodium, cached_sugar, cached_saturated_fat, cached_cholesterol,
+               notes, eaten_at, planned, created_at, updated_at
+           ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)"#,
+        params![
+            entry.day_id, entry.meal_type.as_str(), source_type, entry.recipe_id, entry.food_item_id,
+            entry.description, inline_json, entry.servings, entry.percent_eaten,
+            n.calories, n.protein, n.carbs, n.fat, n.fiber, n.sodium, n.sugar, n.saturated_fat, n.cholesterol,
+            entry.notes, entry.eaten_at, entry.planned, entry.created_at, entry.updated_at,
+        ],
+    )
+    .map_err(|e| ToolError::Db(format!("Failed to insert meal entry {}: {}", entry.id, e)))?;
+    Ok(())
+}
+
+/// Whether a meal entry with the same day, source, meal type, servings and
+/// eaten_at exists; ids must already be remapped
+fn meal_entry_exists(conn: &Connection, entry: &MealEntry) -> rusqlite::Result<bool> {
+    conn.query_row(
+        r#"SELECT EXISTS(SELECT 1 FROM meal_entries
+               WHERE day_id = ?1 AND meal_type = ?2 AND recipe_id IS ?3 AND food_item_id IS ?4
+                 AND description IS ?5 AND servings = ?6 AND eaten_at IS ?7)"#,
+        params![
+            entry.day_id, entry.meal_type.as_str(), entry.recipe_id, entry.food_item_id,
+            entry.description, entry.servings, entry.eaten_at,
+        ],
+        |row| row.get(0),
+    )
+}
+
+/// Id of a vital group with the same timestamp and description
+fn find_vital_group(conn: &Connection, group: &VitalGroup) -> rusqlite::Result<Option<i64>> {
+    conn.query_row(
+        "SELECT id FROM vital_groups WHERE timestamp = ?1 AND description IS ?2 ORDER BY id LIMIT 1",
+        params![group.timestamp, group.description],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Whether a vital with the same type, timestamp and values exists; the custom
+/// type id must already be remapped
+fn vital_exists(conn: &Connection, vital: &Vital, custom_type_id: Option<i64>) -> rusqlite::Result<bool> {
+    conn.query_row(
+        r#"SELECT EXISTS(SELECT 1 FROM vitals
+               WHERE vital_type = ?1 AND custom_type_id IS ?2 AND timestamp = ?3
+                 AND value1 = ?4 AND value2 IS ?5)"#,
+        params![vital.vital_type.as_str(), custom_type_id, vital.timestamp, vital.value1, vital.value2],
+        |row| row.get(0),
+    )
+}
+
+/// Whether a medication with the same name and start date exists
+fn medication_exists(conn: &Connection, med: &Medication) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM medications WHERE name = ?1 AND start_date IS ?2)",
+        params![med.name, med.start_date],
+        |row| row.get(0),
+    )
+}
+
+fn insert_medication(conn: &Connection, med: &Medication) -> rusqlite::Result<()> {
+    conn.execute(
+        r#"INSERT INTO medications (
+               name, med_type, dosage_amount, dosage_unit, instructions, frequency,
+               prescribing_doctor, prescribed_date, pharmacy, rx_number, refills_remaining,
+               is_active, start_date, end_date, discontinue_reason, notes, schedule,
+               created_at, updated_at
+           ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)"#,
+        params![
+            med.name, med.med_type.as_str(), med.dosage_amount, med.dosage_unit.as_str(),
+            med.instructions, med.frequency, med.prescribing_doctor, med.prescribed_date,
+            med.pharmacy, med.rx_number, med.refills_remaining, med.is_active,
+            med.start_date, med.end_date, med.discontinue_reason, med.notes,
+            Medication::schedule_json(&med.schedule), med.created_at, med.updated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Restore an export_all_json file.
+///
+/// mode "restore" requires an empty database and reproduces the backup exactly,
+/// including timestamps and cached nutrition. mode "merge" adds everything
+/// alongside existing data, reusing food items with the same name and brand
+/// and recipes with the same name rather than duplicating them; a day whose
+/// date already exists receives the imported meal entries. Entries on a reused
+/// recipe or food item take their nutrition from the existing one, and every
+/// day that received entries is recalculated.
+/// Meal entries, vital groups, vitals and medications already present are
+/// skipped, so merging the same file twice adds nothing the second time.
+/// Either way every id is remapped, and the whole import is one transaction.
+pub fn import_all_json(db: &Database, file_path: &str, mode: &str) -> Result<ImportAllJsonResponse, ToolError> {
+    let merge = match mode.to_lowercase().as_str() {
+        "merge" => true,
+        "restore" => false,
+        _ => return Err(ToolError::Validation(format!("Invalid mode '{}'. Must be 'restore' or 'merge'", mode))),
+    };
+
+    let contents = std::fs::read_to_string(file_path)
+        .map_err(|e| ToolError::Io(format!("Failed to read file '{}': {}", file_path, e)))?;
+    let doc: BackupDocument = serde_json::from_str(&contents)
+        .map_err(|e| ToolError::Validation(format!("Invalid backup file: {}", e)))?;
+    if doc.format_version > BACKUP_FORMAT_VERSION {
+        return Err(ToolError::Validation(format!(
+            "Unsupported format_version {} (this server reads up to {})",
+            doc.format_version, BACKUP_FORMAT_VERSION
+        )));
+    }
+
+    let mut imported = BackupCounts::default();
+    let mut skipped = BackupCounts::default();
+    let mut days_recalculated = 0;
+
+    db.transaction(|conn| -> Result<(), ToolError> {
+        if !merge && data_row_count(conn)? > 0 {
+            return Err(ToolError::Validation("Database is not empty; restore needs an empty database (use mode 'merge' to add to existing data)".to_string()));
+        }
+        let failed = |what: &str, id: i64, e: rusqlite::Error| ToolError::Db(format!("Failed to insert {} {}: {}", what, id, e));
+
+        let mut food_ids = HashMap::new();
+        let mut reused_food_items = HashSet::new();
+        for item in &doc.food_items {
+            let existing = if merge {
+                FoodItem::find_by_name_brand(conn, &item.name, item.brand.as_deref())?
+            } else {
+                None
+            };
+            let id = match existing {
+                Some(existing) => {
+                    reused_food_items.insert(item.id);
+                    skipped.food_items += 1;
+                    existing.id
+                }
+                None => {
+                    imported.food_items += 1;
+                    insert_food_item(conn, item).map_err(|e| failed("food item", item.id, e))?
+                }
+            };
+            food_ids.insert(item.id, id);
+        }
+
+        // All recipes first, since components may point at any of them
+        let mut recipe_ids = HashMap::new();
+        let mut reused_recipes = HashSet::new();
+        for r in &doc.recipes {
+            let existing = if merge {
+                Recipe::find_by_name(conn, &r.recipe.name)?
+            } else {
+                None
+            };
+            let id = match existing {
+                Some(existing) => {
+                    reused_recipes.insert(r.recipe.id);
+                    skipped.recipes += 1;
+                    skipped.recipe_ingredients += r.ingredients.len();
+                    skipped.recipe_components += r.components.len();
+                    skipped.prepared_batches += r.prepared_batches.len();
+                    existing.id
+                }
+                None => {
+                    imported.recipes += 1;
+                    insert_recipe(conn, &r.recipe).map_err(|e| failed("recipe", r.recipe.id, e))?
+                }
+            };
+            recipe_ids.insert(r.recipe.id, id);
+        }
+        for r in doc.recipes.iter().filter(|r| !reused_recipes.contains(&r.recipe.id)) {
+            imported.recipe_ingredients += r.ingredients.len();
+            imported.recipe_components += r.components.len();
+            imported.prepared_batches += r.prepared_batches.len();
+            let recipe_id = remap(&recipe_ids, r.recipe.id, "recipe", "Recipe")?;
+            for ing in &r.ingredients {
+                let referrer = format!("Ingredient {} of recipe '{}'", ing.id, r.recipe.name);
+                conn.execute(
+                    r#"INSERT INTO recipe_ingredients (recipe_id, food_item_id, quantity, unit, notes, created_at, updated_at)
+                       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+                    params![
+                        recipe_id, remap(&food_ids, ing.food_item_id, "food item", &referrer)?,
+                        ing.quantity, ing.unit, ing.notes, ing.created_at, ing.updated_at,
+                    ],
+                )
+                .map_err(|e| failed("recipe ingredient", ing.id, e))?;
+            }
+            for comp in &r.components {
+                let referrer = format!("Component {} of recipe '{}'", comp.id, r.recipe.name);
+                conn.execute(
+                    r#"INSERT INTO recipe_components (recipe_id, component_recipe_id, servings, notes, created_at, updated_at)
+                       VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+                    params![
+                        recipe_id, remap(&recipe_ids, comp.component_recipe_id, "recipe", &referrer)?,
+                        comp.servings, comp.notes, comp.created_at, comp.updated_at,
+                    ],
+                )
+                .map_err(|e| failed("recipe component", comp.id, e))?;
+            }
+            for batch in &r.prepared_batches {
+                conn.execute(
+                    r#"INSERT INTO prepared_batches (recipe_id, servings_prepared, remaining_servings, prepared_date, notes, created_at, updated_at)
+                       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+                    params![
+                        recipe_id, batch.servings_prepared, batch.remaining_servings, batch.prepared_date,
+                        batch.notes, batch.created_at, batch.updated_at,
+                    ],
+                )
+                .map_err(|e| failed("prepared batch", batch.id, e))?;
+            }
+        }
+
+        for d in &doc.days {
+            let existing = if merge {
+                Day::get_by_date(conn, &d.day.date)?
+            } else {
+                None
+            };
+            let day_id = match &existing {
+                Some(day) => {
+                    skipped.days += 1;
+                    day.id
+                }
+                None => {
+                    imported.days += 1;
+                    insert_day(conn, &d.day).map_err(|e| failed("day", d.day.id, e))?
+                }
+            };
+            let mut entries_added = 0;
+            for exported in &d.meal_entries {
+                let referrer = format!("Meal entry {} on {}", exported.id, d.day.date);
+                let mut entry = exported.clone();
+                entry.day_id = day_id;
+                entry.recipe_id = entry.recipe_id.map(|id| remap(&recipe_ids, id, "recipe", &referrer)).transpose()?;
+                entry.food_item_id = entry.food_item_id.map(|id| remap(&food_ids, id, "food item", &referrer)).transpose()?;
+                if merge && meal_entry_exists(conn, &entry).map_err(|e| failed("meal entry", entry.id, e))? {
+                    skipped.meal_entries += 1;
+                    continue;
+                }
+                // A reused recipe or food item may not match the exported one,
+                // so the exported cached nutrition can't be trusted
+                let reused_source = exported.recipe_id.is_some_and(|id| reused_recipes.contains(&id))
+                    || exported.food_item_id.is_some_and(|id| reused_food_items.contains(&id));
+                if reused_source {
+                    entry.cached_nutrition = entry.source_nutrition(conn)
+                        .map_err(|e| ToolError::Db(format!("Failed to recalculate meal entry {}: {}", entry.id, e)))?;
+                }
+                insert_meal_entry(conn, &entry)?;
+                imported.meal_entries += 1;
+                entries_added += 1;
+            }
+            if merge && entries_added > 0 {
+                recalculate_day_nutrition(conn, day_id)
+                    .map_err(|e| ToolError::Db(format!("Failed to recalculate {}: {}", d.day.date, e)))?;
+                days_recalculated += 1;
+            }
+        }
+
+        let mut group_ids = HashMap::new();
+        for group in &doc.vital_groups {
+            let existing = if merge {
+                find_vital_group(conn, group).map_err(|e| failed("vital group", group.id, e))?
+            } else {
+                None
+            };
+            if let Some(id) = existing {
+                skipped.vital_groups += 1;
+                group_ids.insert(group.id, id);
+                continue;
+            }
+            conn.execute(
+                "INSERT INTO vital_groups (description, timestamp, notes, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![group.description, group.timestamp, group.notes, group.created_at],
+            )
+            .map_err(|e| failed("vital group", group.id, e))?;
+            group_ids.insert(group.id, conn.last_insert_rowid());
+            imported.vital_groups += 1;
+        }
+        // Custom types are matched by name in merge mode, since names are unique
+        let mut custom_type_ids = HashMap::new();
+        for custom in &doc.custom_vital_types {
+            let existing = if merge {
+                CustomVitalType::get_by_name(conn, &custom.name)?
+            } else {
+                None
+            };
+            let id = match existing {
+                Some(existing) => {
+                    skipped.custom_vital_types += 1;
+                    existing.id
+                }
+                None => {
+                    imported.custom_vital_types += 1;
+                    conn.execute(
+                        "INSERT INTO custom_vital_types (name, unit, value2_label, created_at) VALUES (?1, ?2, ?3, ?4)",
+                        params![custom.name, custom.unit, custom.value2_label, custom.created_at],
+                    )
+                    .map_err(|e| failed("custom vital type", custom.id, e))?;
+                    conn.last_insert_rowid()
+                }
+            };
+            custom_type_ids.insert(custom.id, id);
+        }
+        for vital in &doc.vitals {
+            let referrer = format!("Vital {}", vital.id);
+            let group_id = vital.group_id.map(|id| remap(&group_ids, id, "vital group", &referrer)).transpose()?;
+            let custom_type_id = vital.custom_type_id
+                .map(|id| remap(&custom_type_ids, id, "custom vital type", &referrer))
+                .transpose()?;
+            if merge && vital_exists(conn, vital, custom_type_id).map_err(|e| failed("vital", vital.id, e))? {
+                skipped.vitals += 1;
+                continue;
+            }
+            conn.execute(
+                r#"INSERT INTO vitals (vital_type, timestamp, value1, value2, unit, group_id, notes, custom_type_id, created_at, updated_at)
+                   VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"#,
+                params![
+                    vital.vital_type.as_str(), vital.timestamp, vital.value1, vital.value2, vital.unit,
+                    group_id, vital.notes, custom_type_id, vital.created_at, vital.updated_at,
+                ],
+            )
+            .map_err(|e| failed("vital", vital.id, e))?;
+            imported.vitals += 1;
+        }
+
+        for med in &doc.medications {
+            if merge && medication_exists(conn, med).map_err(|e| failed("medication", med.id, e))? {
+                skipped.medications += 1;
+                continue;
+            }
+            insert_medication(conn, med).map_err(|e| failed("medication", med.id, e))?;
+            imported.medications += 1;
+        }
+        Ok(())
+    })?;
+
+    Ok(ImportAllJsonResponse {
+        file_path: file_path.to_string(),
+        mode: if merge { "merge" } else { "restore" }.to_string(),
+        format_version: doc.format_version,
+        exported_at: doc.exported_at.clone(),
+        imported,
+        skipped,
+        days_recalculated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::models::{
+        recalculate_recipe_nutrition, MealEntryCreate, MealType,
+        RecipeComponentCreate, RecipeCreate, RecipeIngredientCreate, VitalCreate, VitalGroupCreate, VitalType,
+    };
+    use crate::test_support::{make_food, temp_db};
+
+    #[test]
+    fn test_export_restore_remaps_ids() {
+        let source = temp_db("backup-source");
+        let target = temp_db("backup-target");
+        let json_path = std::env::temp_dir().join(format!("uhm-test-backup-{}.json", std::process::id()));
+
+        // Delete the first rows so source ids don't line up with a fresh database
+        let source_calories = source.with_conn(|conn| {
+            let scratch = make_food(conn, "Scratch", serde_json::json!({}));
+            FoodItem::delete(conn, scratch.id)?;
+            let oats = make_food(conn, "Oats", serde_json::json!({ "calories": 380.0, "protein": 13.0 }));
+            let scratch = Recipe::create(conn, &RecipeCreate { name: "Scratch".into(), servings_produced: 1.0, yield_grams: None, is_favorite: false, notes: None })?;
+            Recipe::delete(conn, scratch.id)?;
+            let bowl = Recipe::create(conn, &RecipeCreate { name: "Bowl".into(), servings_produced: 2.0, yield_grams: None, is_favorite: false, notes: None })?;
+            RecipeIngredient::create(conn, &RecipeIngredientCreate { recipe_id: bowl.id, food_item_id: oats.id, quantity: 80.0, unit: "g".into(), notes: None })?;
+            let big = Recipe::create(conn, &RecipeCreate { name: "Big bowl".into(), servings_produced: 1.0, yield_grams: None, is_favorite: false, notes: None })?;
+            RecipeComponent::create(conn, &RecipeComponentCreate { recipe_id: big.id, component_recipe_id: bowl.id, servings: 2.0, notes: None })?;
+            recalculate_recipe_nutrition(conn, bowl.id)?;
+            recalculate_recipe_nutrition(conn, big.id)?;
+            let day = Day::get_or_create(conn, "2026-03-01")?;
+            MealEntry::create(conn, &MealEntryCreate {
+                day_id: day.id, meal_type: MealType::Breakfast, recipe_id: Some(big.id), food_item_id: None,
+                description: None, inline_nutrition: None, servings: 1.0, percent_eaten: None, notes: None,
+                eaten_at: Some("2026-03-01T08:00:00".into()), planned: false,
+            }, &SystemClock)?;
+            let group = VitalGroup::create(conn, &VitalGroupCreate { description: Some("Morning".into()), timestamp: None, notes: None }, &SystemClock)?;
+            Vital::create(conn, &VitalCreate {
+                vital_type: VitalType::HeartRate, timestamp: None, value1: 62.0, value2: None,
+                unit: None, group_id: Some(group.id), notes: None, custom_type_id: None,
+            }, &SystemClock)?;
+            Ok(Day::get_by_id(conn, day.id)?.unwrap().cached_nutrition.calories)
+        }).unwrap();
+        assert!(source_calories > 0.0);
+
+        let clock = FixedClock::from_date("2026-03-02").unwrap();
+        let exported = export_all_json(&source, json_path.to_str().unwrap(), &clock).unwrap();
+        assert_eq!(exported.exported.recipes, 2);
+        assert_eq!(exported.exported.meal_entries, 1);
+
+        let imported = import_all_json(&target, json_path.to_str().unwrap(), "restore").unwrap();
+        assert_eq!(imported.imported.recipe_components, 1);
+        assert!(import_all_json(&target, json_path.to_str().unwrap(), "restore").is_err());
+
+        target.with_conn(|conn| {
+            let oats = FoodItem::find_by_name_brand(conn, "Oats", None)?.unwrap();
+            let recipes = Recipe::list_all(conn)?;
+            let bowl = recipes.iter().find(|r| r.name == "Bowl").unwrap();
+            let big = recipes.iter().find(|r| r.name == "Big bowl").unwrap();
+            assert_eq!(RecipeIngredient::get_for_recipe(conn, bowl.id)?[0].food_item_id, oats.id);
+            assert_eq!(RecipeComponent::get_for_recipe(conn, big.id)?[0].component_recipe_id, bowl.id);
+
+            let day = Day::get_by_date(conn, "2026-03-01")?.unwrap();
+            let entries = MealEntry::get_for_day(conn, day.id)?;
+            assert_eq!(entries[0].recipe_id, Some(big.id));
+            assert_eq!(day.cached_nutrition.calories, source_calories);
+
+            let group = VitalGroup::list(conn, None)?.remove(0);
+            assert_eq!(Vital::list_by_group(conn, group.id)?.len(), 1);
+            Ok(())
+        }).unwrap();
+
+        let _ = std::fs::remove_file(&json_path);
+    }
+
+    #[test]
+    fn test_merge_reuses_food_items_and_recipes() {
+        let source = temp_db("backup-merge-source");
+        let target = temp_db("backup-merge-target");
+        let json_path = std::env::temp_dir().join(format!("uhm-test-backup-merge-{}.json", std::process::id()));
+
+        source.with_conn(|conn| {
+            let oats = make_food(conn, "Oats", serde_json::json!({ "calories": 380.0, "protein": 13.0 }));
+            let bowl = Recipe::create(conn, &RecipeCreate { name: "Bowl".into(), servings_produced: 2.0, yield_grams: None, is_favorite: false, notes: None })?;
+            RecipeIngredient::create(conn, &RecipeIngredientCreate { recipe_id: bowl.id, food_item_id: oats.id, quantity: 80.0, unit: "g".into(), notes: None })?;
+            recalculate_recipe_nutrition(conn, bowl.id)?;
+            let day = Day::get_or_create(conn, "2026-03-01")?;
+            MealEntry::create(conn, &MealEntryCreate {
+                day_id: day.id, meal_type: MealType::Breakfast, recipe_id: Some(bowl.id), food_item_id: None,
+                description: None, inline_nutrition: None, servings: 1.0, percent_eaten: None, notes: None,
+                eaten_at: Some("2026-03-01T08:00:00".into()), planned: false,
+            }, &SystemClock)?;
+            let group = VitalGroup::create(conn, &VitalGroupCreate { description: Some("Morning".into()), timestamp: Some("2026-03-01T07:00:00".into()), notes: None }, &SystemClock)?;
+            Vital::create(conn, &VitalCreate {
+                vital_type: VitalType::HeartRate, timestamp: Some("2026-03-01T07:00:00".into()), value1: 62.0, value2: None,
+                unit: None, group_id: Some(group.id), notes: None, custom_type_id: None,
+            }, &SystemClock)?;
+            conn.execute(
+                "INSERT INTO medications (name, med_type, dosage_amount, dosage_unit, start_date) VALUES ('Metformin', 'prescription', 500, 'mg', '2026-01-01')",
+                [],
+            )?;
+            Ok(())
+        }).unwrap();
+        let clock = FixedClock::from_date("2026-03-02").unwrap();
+        export_all_json(&source, json_path.to_str().unwrap(), &clock).unwrap();
+
+        let counts = || target.with_conn(|conn| {
+            let count = |table: &str| conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get::<_, i64>(0));
+            Ok((
+                (count("food_items")?, count("recipes")?, count("recipe_ingredients")?),
+                (count("meal_entries")?, count("vital_groups")?, count("vitals")?, count("medications")?),
+            ))
+        }).unwrap();
+        let day_calories = || target.with_conn(|conn| Ok(Day::get_by_date(conn, "2026-03-01")?.unwrap().cached_nutrition.calories)).unwrap();
+
+        let first = import_all_json(&target, json_path.to_str().unwrap(), "merge").unwrap();
+        assert_eq!((first.imported.food_items, first.imported.recipes, first.imported.meal_entries), (1, 1, 1));
+        assert_eq!((first.skipped.food_items, first.skipped.recipes), (0, 0));
+        assert_eq!(counts(), ((1, 1, 1), (1, 1, 1, 1)));
+        let calories = day_calories();
+        assert!(calories > 0.0);
+
+        let second = import_all_json(&target, json_path.to_str().unwrap(), "merge").unwrap();
+        assert_eq!((second.skipped.food_items, second.skipped.recipes, second.skipped.recipe_ingredients), (1, 1, 1));
+        assert_eq!(
+            (second.skipped.meal_entries, second.skipped.vital_groups, second.skipped.vitals, second.skipped.medications),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(second.imported.meal_entries + second.imported.vitals + second.imported.medications, 0);
+        assert_eq!(second.days_recalculated, 0);
+        assert_eq!(counts(), ((1, 1, 1), (1, 1, 1, 1)));
+        assert_eq!(day_calories(), calories);
+
+        let _ = std::fs::remove_file(&json_path);
+    }
+
+    #[test]
+    fn test_merge_into_overlapping_target() {
+        let source = temp_db("backup-overlap-source");
+        let target = temp_db("backup-overlap-target");
+        let json_path = std::env::temp_dir().join(format!("uhm-test-backup-overlap-{}.json", std::process::id()));
+
+        let log = |conn: &Connection, date: &str, food_item_id: i64, eaten_at: &str| -> crate::db::DbResult<()> {
+            let day = Day::get_or_create(conn, date)?;
+            MealEntry::create(conn, &MealEntryCreate {
+                day_id: day.id, meal_type: MealType::Breakfast, recipe_id: None, food_item_id: Some(food_item_id),
+                description: None, inline_nutrition: None, servings: 1.0, percent_eaten: None, notes: None,
+                eaten_at: Some(eaten_at.into()), planned: false,
+            }, &SystemClock)?;
+            Ok(())
+        };
+
+        // The source prices Oats at 380 kcal, the target at 100
+        source.with_conn(|conn| {
+            let oats = make_food(conn, "Oats", serde_json::json!({ "calories": 380.0 }));
+            let rice = make_food(conn, "Rice", serde_json::json!({ "calories": 130.0 }));
+            log(conn, "2026-03-01", oats.id, "2026-03-01T08:00:00")?;
+            log(conn, "2026-03-02", oats.id, "2026-03-02T08:00:00")?;
+            log(conn, "2026-03-02", rice.id, "2026-03-02T12:00:00")
+        }).unwrap();
+        export_all_json(&source, json_path.to_str().unwrap(), &FixedClock::from_date("2026-03-03").unwrap()).unwrap();
+
+        target.with_conn(|conn| {
+            let oats = make_food(conn, "Oats", serde_json::json!({ "calories": 100.0 }));
+            let eggs = make_food(conn, "Eggs", serde_json::json!({ "calories": 150.0 }));
+            log(conn, "2026-03-01", eggs.id, "2026-03-01T07:00:00")?;
+            log(conn, "2026-03-02", oats.id, "2026-03-02T08:00:00")
+        }).unwrap();
+
+        let result = import_all_json(&target, json_path.to_str().unwrap(), "merge").unwrap();
+        assert_eq!((result.imported.food_items, result.skipped.food_items), (1, 1));
+        assert_eq!((result.imported.days, result.skipped.days), (0, 2));
+        // The 03-02 oats entry matches the target's and is skipped
+        assert_eq!((result.imported.meal_entries, result.skipped.meal_entries), (2, 1));
+        assert_eq!(result.days_recalculated, 2);
+
+        let day_calories = |date: &str| target.with_conn(|conn| Ok(Day::get_by_date(conn, date)?.unwrap().cached_nutrition.calories)).unwrap();
+        // Eggs plus oats at the target's price; oats plus the imported rice
+        assert_eq!(day_calories("2026-03-01"), 250.0);
+        assert_eq!(day_calories("2026-03-02"), 230.0);
+
+        let _ = std::fs::remove_file(&json_path);
+    }
+}
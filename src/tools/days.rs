@@ -5,11 +5,14 @@
 use std::collections::HashMap;
 use serde::Serialize;
 
+use crate::clock::Clock;
 use crate::db::Database;
 use crate::models::{
     Day, DayUpdate, MealEntry, MealEntryCreate, MealEntryDetail, MealEntryUpdate,
     MealType, Nutrition, recalculate_day_nutrition,
 };
+use super::error::ToolError;
+use super::numeric::round_to;
 
 /// Response for get_or_create_day
 #[derive(Debug, Serialize)]
@@ -26,11 +29,14 @@ pub struct DayDetail {
     pub date: String,
     pub meals: DayMeals,
     pub nutrition_total: Nutrition,
+    /// Entries logged with planned = true; not counted in nutrition_total
+    pub planned_meals: DayMeals,
+    pub planned_nutrition_total: Nutrition,
     pub notes: Option<String>,
 }
 
 /// Meals organized by type
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 pub struct DayMeals {
     pub breakfast: Vec<MealEntryDetail>,
     pub lunch: Vec<MealEntryDetail>,
@@ -54,6 +60,20 @@ pub struct DaySummary {
     pub total_saturated_fat: f64,
     pub total_cholesterol: f64,
     pub meal_count: usize,
+    /// Per-meal-type totals, present when requested with by_meal_type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meal_types: Option<Vec<MealTypeTotal>>,
+}
+
+/// One meal type's share of a day
+#[derive(Debug, Serialize)]
+pub struct MealTypeTotal {
+    pub meal_type: String,
+    pub entry_count: usize,
+    pub calories: f64,
+    pub protein: f64,
+    /// Share of the day's calories eaten as this meal type
+    pub percent_of_calories: f64,
 }
 
 /// Response for list_days
@@ -77,6 +97,8 @@ pub struct LogMealResponse {
     pub servings: f64,
     pub percent_eaten: f64,
     pub nutrition: Nutrition,
+    pub eaten_at: Option<String>,
+    pub planned: bool,
 }
 
 /// Response for update_meal_entry
@@ -87,6 +109,7 @@ pub struct UpdateMealEntryResponse {
     pub servings: f64,
     pub percent_eaten: f64,
     pub nutrition: Nutrition,
+    pub eaten_at: Option<String>,
     pub updated_at: String,
 }
 
@@ -95,7 +118,14 @@ pub struct UpdateMealEntryResponse {
 pub struct RecalculateDayNutritionResponse {
     pub day_id: i64,
     pub date: String,
+    /// Totals after recalculation
     pub nutrition: Nutrition,
+    /// Cached totals before recalculation
+    pub previous_nutrition: Nutrition,
+    /// Whether any nutrient moved by at least 0.01
+    pub changed: bool,
+    /// Per-nutrient change (value_a = before, value_b = after)
+    pub deltas: Vec<NutrientDelta>,
 }
 
 /// Orphaned day summary (day with no meals)
@@ -118,12 +148,12 @@ pub struct ListOrphanedDaysResponse {
 // ============================================================================
 
 /// Get or create a day by date
-pub fn get_or_create_day(db: &Database, date: &str) -> Result<GetOrCreateDayResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn get_or_create_day(db: &Database, date: &str) -> Result<GetOrCreateDayResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     // Check if day already exists
     let existing = Day::get_by_date(&conn, date)
-        .map_err(|e| format!("Failed to check day: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to check day: {}", e)))?;
 
     match existing {
         Some(day) => Ok(GetOrCreateDayResponse {
@@ -133,7 +163,7 @@ pub fn get_or_create_day(db: &Database, date: &str) -> Result<GetOrCreateDayResp
         }),
         None => {
             let day = Day::get_or_create(&conn, date)
-                .map_err(|e| format!("Failed to create day: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to create day: {}", e)))?;
             Ok(GetOrCreateDayResponse {
                 id: day.id,
                 date: day.date,
@@ -144,32 +174,34 @@ pub fn get_or_create_day(db: &Database, date: &str) -> Result<GetOrCreateDayResp
 }
 
 /// Get a day with full details including meals
-pub fn get_day(db: &Database, date: &str) -> Result<Option<DayDetail>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn get_day(db: &Database, date: &str) -> Result<Option<DayDetail>, ToolError> {
+    let conn = db.get_conn()?;
 
     let day = Day::get_by_date(&conn, date)
-        .map_err(|e| format!("Failed to get day: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?;
 
     match day {
         Some(day) => {
             let entries = MealEntry::get_details_for_day(&conn, day.id)
-                .map_err(|e| format!("Failed to get meal entries: {}", e))?;
-
-            let mut meals = DayMeals {
-                breakfast: Vec::new(),
-                lunch: Vec::new(),
-                dinner: Vec::new(),
-                snack: Vec::new(),
-                unspecified: Vec::new(),
-            };
+                .map_err(|e| ToolError::Db(format!("Failed to get meal entries: {}", e)))?;
+
+            let mut meals = DayMeals::default();
+            let mut planned_meals = DayMeals::default();
+            let mut planned_nutrition_total = Nutrition::zero();
 
             for entry in entries {
+                let target = if entry.planned {
+                    planned_nutrition_total = planned_nutrition_total + entry.nutrition.clone();
+                    &mut planned_meals
+                } else {
+                    &mut meals
+                };
                 match entry.meal_type {
-                    MealType::Breakfast => meals.breakfast.push(entry),
-                    MealType::Lunch => meals.lunch.push(entry),
-                    MealType::Dinner => meals.dinner.push(entry),
-                    MealType::Snack => meals.snack.push(entry),
-                    MealType::Unspecified => meals.unspecified.push(entry),
+                    MealType::Breakfast => target.breakfast.push(entry),
+                    MealType::Lunch => target.lunch.push(entry),
+                    MealType::Dinner => target.dinner.push(entry),
+                    MealType::Snack => target.snack.push(entry),
+                    MealType::Unspecified => target.unspecified.push(entry),
                 }
             }
 
@@ -178,6 +210,8 @@ pub fn get_day(db: &Database, date: &str) -> Result<Option<DayDetail>, String> {
                 date: day.date,
                 meals,
                 nutrition_total: day.cached_nutrition,
+                planned_meals,
+                planned_nutrition_total,
                 notes: day.notes,
             }))
         }
@@ -185,44 +219,77 @@ pub fn get_day(db: &Database, date: &str) -> Result<Option<DayDetail>, String> {
     }
 }
 
-/// List days with optional date range
+/// Build a DaySummary from a day's cached totals, optionally with eaten
+/// (non-planned) entries totalled by meal type
+fn day_summary(conn: &rusqlite::Connection, day: &Day, by_meal_type: bool) -> Result<DaySummary, ToolError> {
+    let entries = MealEntry::get_for_day(conn, day.id)
+        .map_err(|e| ToolError::Db(format!("Failed to get meal entries: {}", e)))?;
+
+    let meal_types = by_meal_type.then(|| {
+        [MealType::Breakfast, MealType::Lunch, MealType::Dinner, MealType::Snack, MealType::Unspecified]
+            .into_iter()
+            .filter_map(|meal_type| {
+                let of_type: Vec<&MealEntry> = entries.iter().filter(|e| !e.planned && e.meal_type == meal_type).collect();
+                if of_type.is_empty() {
+                    return None;
+                }
+                let calories: f64 = of_type.iter().map(|e| e.cached_nutrition.calories).sum();
+                Some(MealTypeTotal {
+                    meal_type: meal_type.as_str().to_string(),
+                    entry_count: of_type.len(),
+                    calories: round_to(calories, 1),
+                    protein: round_to(of_type.iter().map(|e| e.cached_nutrition.protein).sum(), 1),
+                    percent_of_calories: if day.cached_nutrition.calories > 0.0 {
+                        round_to(calories / day.cached_nutrition.calories * 100.0, 2)
+                    } else {
+                        0.0
+                    },
+                })
+            })
+            .collect()
+    });
+
+    Ok(DaySummary {
+        id: day.id,
+        date: day.date.clone(),
+        total_calories: day.cached_nutrition.calories,
+        total_protein: day.cached_nutrition.protein,
+        total_carbs: day.cached_nutrition.carbs,
+        total_fat: day.cached_nutrition.fat,
+        total_fiber: day.cached_nutrition.fiber,
+        total_sugar: day.cached_nutrition.sugar,
+        total_sodium: day.cached_nutrition.sodium,
+        total_saturated_fat: day.cached_nutrition.saturated_fat,
+        total_cholesterol: day.cached_nutrition.cholesterol,
+        meal_count: entries.iter().filter(|e| !e.planned).count(),
+        meal_types,
+    })
+}
+
+/// List days with optional date range; `by_meal_type` adds each day's
+/// breakdown by breakfast/lunch/dinner/snack
 pub fn list_days(
     db: &Database,
     start_date: Option<&str>,
     end_date: Option<&str>,
     limit: i64,
     offset: i64,
-) -> Result<ListDaysResponse, String> {
+    by_meal_type: bool,
+) -> Result<ListDaysResponse, ToolError> {
     let limit = limit.min(200).max(1);
     let offset = offset.max(0);
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let conn = db.get_conn()?;
 
     let days = Day::list(&conn, start_date, end_date, limit, offset)
-        .map_err(|e| format!("Failed to list days: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to list days: {}", e)))?;
 
     let total = Day::count(&conn, start_date, end_date)
-        .map_err(|e| format!("Failed to count days: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to count days: {}", e)))?;
 
     let mut summaries = Vec::new();
-    for day in days {
-        let entries = MealEntry::get_for_day(&conn, day.id)
-            .map_err(|e| format!("Failed to get meal entries: {}", e))?;
-
-        summaries.push(DaySummary {
-            id: day.id,
-            date: day.date,
-            total_calories: day.cached_nutrition.calories,
-            total_protein: day.cached_nutrition.protein,
-            total_carbs: day.cached_nutrition.carbs,
-            total_fat: day.cached_nutrition.fat,
-            total_fiber: day.cached_nutrition.fiber,
-            total_sugar: day.cached_nutrition.sugar,
-            total_sodium: day.cached_nutrition.sodium,
-            total_saturated_fat: day.cached_nutrition.saturated_fat,
-            total_cholesterol: day.cached_nutrition.cholesterol,
-            meal_count: entries.len(),
-        });
+    for day in &days {
+        summaries.push(day_summary(&conn, day, by_meal_type)?);
     }
 
     Ok(ListDaysResponse {
@@ -234,16 +301,16 @@ pub fn list_days(
 }
 
 /// Update day notes
-pub fn update_day(db: &Database, date: &str, notes: Option<String>) -> Result<Option<DayDetail>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn update_day(db: &Database, date: &str, notes: Option<String>) -> Result<Option<DayDetail>, ToolError> {
+    let conn = db.get_conn()?;
 
     let day = Day::get_by_date(&conn, date)
-        .map_err(|e| format!("Failed to get day: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?;
 
     match day {
         Some(day) => {
             Day::update(&conn, day.id, &DayUpdate { notes })
-                .map_err(|e| format!("Failed to update day: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to update day: {}", e)))?;
 
             // Return full day detail after update
             drop(conn);
@@ -257,60 +324,119 @@ pub fn update_day(db: &Database, date: &str, notes: Option<String>) -> Result<Op
 // Meal Entry Tools
 // ============================================================================
 
-/// Log a meal entry (food item or recipe)
-pub fn log_meal(
-    db: &Database,
-    date: &str,
-    meal_type: &str,
-    recipe_id: Option<i64>,
-    food_item_id: Option<i64>,
-    servings: f64,
-    percent_eaten: Option<f64>,
-    notes: Option<String>,
-) -> Result<LogMealResponse, String> {
+/// Normalize an eaten_at value for a meal on `date` to a canonical local timestamp.
+/// Accepts a time of day ("12:30", "12:30:15") or a full timestamp
+/// ("2026-01-06T12:30:00", "2026-01-06 12:30"; a trailing Z or offset is
+/// converted to local time).
+fn normalize_eaten_at(date: &str, input: &str) -> Result<String, ToolError> {
+    use chrono::NaiveTime;
+
+    let input = input.trim();
+    for fmt in ["%H:%M:%S", "%H:%M"] {
+        if let Ok(t) = NaiveTime::parse_from_str(input, fmt) {
+            return Ok(format!("{}T{}", date, t.format("%H:%M:%S")));
+        }
+    }
+
+    // A bare date says nothing about when the meal was eaten
+    let has_time = input.contains(':');
+    crate::clock::canonical_timestamp(input)
+        .filter(|_| has_time)
+        .ok_or_else(|| ToolError::Validation(format!(
+            "Invalid eaten_at: '{}'. Use a time (HH:MM) or timestamp (YYYY-MM-DDTHH:MM:SS)",
+            input
+        )))
+}
+
+/// A meal to log with log_meal: exactly one of recipe_id or food_item_id
+#[derive(Debug, Clone, Default)]
+pub struct LogMealRequest {
+    pub date: String,
+    pub meal_type: String,
+    pub recipe_id: Option<i64>,
+    pub food_item_id: Option<i64>,
+    pub servings: f64,
+    pub percent_eaten: Option<f64>,  // defaults to 100.0
+    pub notes: Option<String>,
+    /// Time of day or full timestamp; defaults to now
+    pub eaten_at: Option<String>,
+    pub planned: bool,
+}
+
+/// Log a meal entry (food item or recipe).
+///
+/// A planned entry records a meal plan: it is shown separately by get_day and
+/// left out of the day's totals until the actual meal is logged.
+pub fn log_meal(db: &Database, request: LogMealRequest, clock: &dyn Clock) -> Result<LogMealResponse, ToolError> {
+    db.transaction(|conn| log_meal_in(conn, request, clock))
+}
+
+/// log_meal on an existing connection, so callers can log a meal as one step
+/// of a larger transaction
+pub(crate) fn log_meal_in(
+    conn: &rusqlite::Connection,
+    request: LogMealRequest,
+    clock: &dyn Clock,
+) -> Result<LogMealResponse, ToolError> {
+    let LogMealRequest {
+        date,
+        meal_type,
+        recipe_id,
+        food_item_id,
+        servings,
+        percent_eaten,
+        notes,
+        eaten_at,
+        planned,
+    } = request;
+    let (date, meal_type) = (date.as_str(), meal_type.as_str());
+
     // Validate exactly one source is provided
     if recipe_id.is_none() && food_item_id.is_none() {
-        return Err("Must provide either recipe_id or food_item_id".to_string());
+        return Err(ToolError::Validation("Must provide either recipe_id or food_item_id".to_string()));
     }
     if recipe_id.is_some() && food_item_id.is_some() {
-        return Err("Provide only one of recipe_id or food_item_id, not both".to_string());
+        return Err(ToolError::Validation("Provide only one of recipe_id or food_item_id, not both".to_string()));
     }
 
     // Validate servings
     if servings <= 0.0 {
-        return Err("Servings must be greater than 0".to_string());
+        return Err(ToolError::Validation("Servings must be greater than 0".to_string()));
     }
 
     // Validate percent_eaten if provided
     if let Some(pct) = percent_eaten {
         if pct < 0.0 || pct > 100.0 {
-            return Err("percent_eaten must be between 0 and 100".to_string());
+            return Err(ToolError::Validation("percent_eaten must be between 0 and 100".to_string()));
         }
     }
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let eaten_at = match eaten_at {
+        Some(t) => normalize_eaten_at(date, &t)?,
+        None => clock.timestamp(),
+    };
 
     // Validate recipe exists if provided
     if let Some(rid) = recipe_id {
-        let recipe = crate::models::Recipe::get_by_id(&conn, rid)
-            .map_err(|e| format!("Database error checking recipe: {}", e))?;
+        let recipe = crate::models::Recipe::get_by_id(conn, rid)
+            .map_err(|e| ToolError::Db(format!("Database error checking recipe: {}", e)))?;
         if recipe.is_none() {
-            return Err(format!("Recipe not found with id: {}", rid));
+            return Err(ToolError::NotFound(format!("Recipe not found with id: {}", rid)));
         }
     }
 
     // Validate food item exists if provided
     if let Some(fid) = food_item_id {
-        let food_item = crate::models::FoodItem::get_by_id(&conn, fid)
-            .map_err(|e| format!("Database error checking food item: {}", e))?;
+        let food_item = crate::models::FoodItem::get_by_id(conn, fid)
+            .map_err(|e| ToolError::Db(format!("Database error checking food item: {}", e)))?;
         if food_item.is_none() {
-            return Err(format!("Food item not found with id: {}", fid));
+            return Err(ToolError::NotFound(format!("Food item not found with id: {}", fid)));
         }
     }
 
     // Get or create the day
-    let day = Day::get_or_create(&conn, date)
-        .map_err(|e| format!("Failed to get/create day: {}", e))?;
+    let day = Day::get_or_create(conn, date)
+        .map_err(|e| ToolError::Db(format!("Failed to get/create day: {}", e)))?;
 
     let meal_type_enum = MealType::from_str(meal_type);
 
@@ -319,27 +445,31 @@ pub fn log_meal(
         meal_type: meal_type_enum,
         recipe_id,
         food_item_id,
+        description: None,
+        inline_nutrition: None,
         servings,
         percent_eaten,
         notes,
+        eaten_at: Some(eaten_at),
+        planned,
     };
 
-    let entry = MealEntry::create(&conn, &data)
-        .map_err(|e| format!("Failed to log meal: {}", e))?;
+    let entry = MealEntry::create(conn, &data, clock)
+        .map_err(|e| ToolError::Db(format!("Failed to log meal: {}", e)))?;
 
     // Get source details
     let (source_type, source_name) = if let Some(recipe_id) = entry.recipe_id {
-        let recipe = crate::models::Recipe::get_by_id(&conn, recipe_id)
-            .map_err(|e| format!("Failed to get recipe: {}", e))?
-            .ok_or_else(|| "Recipe not found".to_string())?;
+        let recipe = crate::models::Recipe::get_by_id(conn, recipe_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound("Recipe not found".to_string()))?;
         ("recipe".to_string(), recipe.name)
     } else if let Some(food_item_id) = entry.food_item_id {
-        let food_item = crate::models::FoodItem::get_by_id(&conn, food_item_id)
-            .map_err(|e| format!("Failed to get food item: {}", e))?
-            .ok_or_else(|| "Food item not found".to_string())?;
+        let food_item = crate::models::FoodItem::get_by_id(conn, food_item_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound("Food item not found".to_string()))?;
         ("food_item".to_string(), food_item.name)
     } else {
-        return Err("No source found".to_string());
+        return Err(ToolError::Validation("No source found".to_string()));
     };
 
     Ok(LogMealResponse {
@@ -352,15 +482,274 @@ pub fn log_meal(
         servings: entry.servings,
         percent_eaten: entry.percent_eaten,
         nutrition: entry.cached_nutrition,
+        eaten_at: entry.eaten_at,
+        planned: entry.planned,
+    })
+}
+
+/// A one-off food to log with log_adhoc_meal
+#[derive(Debug, Clone)]
+pub struct LogAdhocMealRequest {
+    pub date: String,
+    pub meal_type: String,
+    pub description: String,
+    /// What was eaten, stored as one serving
+    pub nutrition: Nutrition,
+    pub notes: Option<String>,
+    /// Time of day or full timestamp; defaults to now
+    pub eaten_at: Option<String>,
+}
+
+/// Log a one-off food by description with inline nutrition, without creating a food item.
+///
+/// `nutrition` is what was eaten; it is stored as one serving, so a later
+/// update_meal_entry can still scale it by servings or percent eaten.
+pub fn log_adhoc_meal(db: &Database, request: LogAdhocMealRequest, clock: &dyn Clock) -> Result<LogMealResponse, ToolError> {
+    let LogAdhocMealRequest { date, meal_type, description, nutrition, notes, eaten_at } = request;
+    let (date, meal_type) = (date.as_str(), meal_type.as_str());
+    let description = description.trim();
+    if description.is_empty() {
+        return Err(ToolError::Validation("description is required for an ad-hoc meal".to_string()));
+    }
+    let fields = [
+        ("calories", nutrition.calories),
+        ("protein", nutrition.protein),
+        ("carbs", nutrition.carbs),
+        ("fat", nutrition.fat),
+        ("fiber", nutrition.fiber),
+        ("sodium", nutrition.sodium),
+        ("sugar", nutrition.sugar),
+        ("saturated_fat", nutrition.saturated_fat),
+        ("cholesterol", nutrition.cholesterol),
+    ];
+    if let Some((name, _)) = fields.iter().find(|(_, v)| *v < 0.0 || !v.is_finite()) {
+        return Err(ToolError::Validation(format!("{} must be a non-negative number", name)));
+    }
+
+    let eaten_at = eaten_at.map(|t| normalize_eaten_at(date, &t)).transpose()?;
+
+    db.transaction(|conn| {
+        let day = Day::get_or_create(conn, date)
+            .map_err(|e| ToolError::Db(format!("Failed to get/create day: {}", e)))?;
+
+        let data = MealEntryCreate {
+            day_id: day.id,
+            meal_type: MealType::from_str(meal_type),
+            recipe_id: None,
+            food_item_id: None,
+            description: Some(description.to_string()),
+            inline_nutrition: Some(nutrition),
+            servings: 1.0,
+            percent_eaten: None,
+            notes,
+            eaten_at,
+            planned: false,
+        };
+
+        let entry = MealEntry::create(conn, &data, clock)
+            .map_err(|e| ToolError::Db(format!("Failed to log ad-hoc meal: {}", e)))?;
+
+        Ok(LogMealResponse {
+            id: entry.id,
+            day_id: day.id,
+            date: day.date,
+            meal_type: entry.meal_type.as_str().to_string(),
+            source_type: "adhoc".to_string(),
+            source_name: description.to_string(),
+            servings: entry.servings,
+            percent_eaten: entry.percent_eaten,
+            nutrition: entry.cached_nutrition,
+            eaten_at: entry.eaten_at,
+            planned: entry.planned,
+        })
+    })
+}
+
+/// Food item details used by quick_log when no item with the name exists yet
+#[derive(Debug, Clone)]
+pub struct QuickLogNewItem {
+    pub brand: Option<String>,
+    /// Canonical serving: 100 g, 100 ml, or 1 count
+    pub serving_size: f64,
+    pub serving_unit: String,
+    /// Nutrition per serving
+    pub nutrition: Nutrition,
+}
+
+/// A food to log by name with quick_log
+#[derive(Debug, Clone)]
+pub struct QuickLogRequest {
+    pub date: String,
+    pub meal_type: String,
+    pub food_name: String,
+    pub quantity: f64,
+    /// g, ml, count, servings, or any unit convertible to them
+    pub unit: String,
+    /// Used to create the food item if none has this name
+    pub new_item: Option<QuickLogNewItem>,
+}
+
+/// Response for quick_log
+#[derive(Debug, Serialize)]
+pub struct QuickLogResponse {
+    pub food_item_id: i64,
+    pub food_item_name: String,
+    /// True if the food item was created by this call
+    pub created: bool,
+    pub meal_entry: LogMealResponse,
+}
+
+/// Convert an amount in `unit` into servings of `item`, refusing units that
+/// cannot be related to the item's serving (rather than guessing)
+fn quick_log_servings(item: &crate::models::FoodItem, quantity: f64, unit: &str) -> Result<f64, ToolError> {
+    use crate::nutrition::{categorize_unit, to_grams, to_ml, UnitCategory};
+
+    let unit = unit.trim();
+    match categorize_unit(unit) {
+        _ if unit.eq_ignore_ascii_case("serving") || unit.eq_ignore_ascii_case("servings") => Ok(quantity),
+        UnitCategory::Weight => match (to_grams(quantity, unit), item.grams_per_serving) {
+            (Some(grams), Some(per_serving)) if per_serving > 0.0 => Ok(grams / per_serving),
+            _ => Err(ToolError::Validation(format!("'{}' has no gram weight; log it in {} or servings", item.name, item.serving_unit))),
+        },
+        UnitCategory::Volume => match (to_ml(quantity, unit), item.ml_per_serving) {
+            (Some(ml), Some(per_serving)) if per_serving > 0.0 => Ok(ml / per_serving),
+            _ => Err(ToolError::Validation(format!("'{}' has no volume; log it in {} or servings", item.name, item.serving_unit))),
+        },
+        UnitCategory::Count if categorize_unit(&item.serving_unit) == UnitCategory::Count => {
+            Ok(quantity / item.serving_size)
+        }
+        UnitCategory::Count => Err(ToolError::Validation(format!(
+            "'{}' is measured in {}, not counted; log it in {} or servings",
+            item.name, item.serving_unit, item.serving_unit
+        ))),
+        UnitCategory::Custom => Err(ToolError::Validation(format!(
+            "Unit '{}' is not recognized. Use g, ml, count, or servings",
+            unit
+        ))),
+    }
+}
+
+/// Find a food item by name (case-insensitive exact match), creating it from
+/// `new_item` if none exists, and log `quantity` `unit` of it in one transaction.
+pub fn quick_log(db: &Database, request: QuickLogRequest, clock: &dyn Clock) -> Result<QuickLogResponse, ToolError> {
+    use crate::models::{FoodItem, FoodItemCreate};
+    use super::food_items::{non_canonical_serving, validate_food_item};
+
+    let QuickLogRequest { date, meal_type, food_name, quantity, unit, new_item } = request;
+    let (date, meal_type, unit) = (date.as_str(), meal_type.as_str(), unit.as_str());
+
+    let food_name = food_name.trim();
+    if food_name.is_empty() {
+        return Err(ToolError::Validation("food_name is required".to_string()));
+    }
+    if quantity <= 0.0 || !quantity.is_finite() {
+        return Err(ToolError::Validation("quantity must be greater than 0".to_string()));
+    }
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid date '{}'. Use YYYY-MM-DD", date)))?;
+
+    db.transaction(|conn| {
+        // Several brands may share the name; the unbranded (then oldest) one wins
+        let existing = FoodItem::find_by_name(conn, food_name)
+            .map_err(|e| ToolError::Db(format!("Failed to look up food item: {}", e)))?
+            .into_iter()
+            .next();
+
+        let (item, created) = match (existing, new_item) {
+            (Some(item), _) => (item, false),
+            (None, Some(new_item)) => {
+                if let Some(problem) = non_canonical_serving(new_item.serving_size, &new_item.serving_unit) {
+                    return Err(ToolError::Validation(problem));
+                }
+                let n = new_item.nutrition;
+                let data = FoodItemCreate {
+                    name: food_name.to_string(),
+                    brand: new_item.brand,
+                    serving_size: new_item.serving_size,
+                    serving_unit: new_item.serving_unit.trim().to_lowercase(),
+                    calories: n.calories,
+                    protein: n.protein,
+                    carbs: n.carbs,
+                    fat: n.fat,
+                    fiber: n.fiber,
+                    sodium: n.sodium,
+                    sugar: n.sugar,
+                    saturated_fat: n.saturated_fat,
+                    cholesterol: n.cholesterol,
+                    preference: Default::default(),
+                    notes: None,
+                    base_unit_type: None,
+                    grams_per_serving: None,
+                    ml_per_serving: None,
+                };
+                validate_food_item(&data)?;
+                let item = FoodItem::create(conn, &data)
+                    .map_err(|e| ToolError::Db(format!("Failed to create food item: {}", e)))?;
+                (item, true)
+            }
+            (None, None) => {
+                let matches = FoodItem::search(conn, food_name, 5)
+                    .map_err(|e| ToolError::Db(format!("Search failed: {}", e)))?;
+                let similar: Vec<&str> = matches.iter().map(|i| i.name.as_str()).collect();
+                return Err(ToolError::NotFound(if similar.is_empty() {
+                    format!("No food item named '{}'. Provide nutrition to create it", food_name)
+                } else {
+                    format!(
+                        "No food item named '{}' (similar: {}). Provide nutrition to create it",
+                        food_name,
+                        similar.join(", ")
+                    )
+                }));
+            }
+        };
+
+        let servings = quick_log_servings(&item, quantity, unit)?;
+
+        let day = Day::get_or_create(conn, date)
+            .map_err(|e| ToolError::Db(format!("Failed to get/create day: {}", e)))?;
+        let data = MealEntryCreate {
+            day_id: day.id,
+            meal_type: MealType::from_str(meal_type),
+            recipe_id: None,
+            food_item_id: Some(item.id),
+            description: None,
+            inline_nutrition: None,
+            servings,
+            percent_eaten: None,
+            notes: Some(format!("{} {}", quantity, unit.trim())),
+            eaten_at: None,
+            planned: false,
+        };
+        let entry = MealEntry::create(conn, &data, clock)
+            .map_err(|e| ToolError::Db(format!("Failed to log meal: {}", e)))?;
+
+        Ok(QuickLogResponse {
+            food_item_id: item.id,
+            food_item_name: item.name.clone(),
+            created,
+            meal_entry: LogMealResponse {
+                id: entry.id,
+                day_id: day.id,
+                date: day.date,
+                meal_type: entry.meal_type.as_str().to_string(),
+                source_type: "food_item".to_string(),
+                source_name: item.name,
+                servings: entry.servings,
+                percent_eaten: entry.percent_eaten,
+                nutrition: entry.cached_nutrition,
+                eaten_at: entry.eaten_at,
+                planned: entry.planned,
+            },
+        })
     })
 }
 
 /// Get a meal entry by ID
-pub fn get_meal_entry(db: &Database, id: i64) -> Result<Option<MealEntryDetail>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn get_meal_entry(db: &Database, id: i64) -> Result<Option<MealEntryDetail>, ToolError> {
+    let conn = db.get_conn()?;
 
     MealEntry::get_detail(&conn, id)
-        .map_err(|e| format!("Failed to get meal entry: {}", e))
+        .map_err(|e| ToolError::Db(format!("Failed to get meal entry: {}", e)))
 }
 
 /// Update a meal entry
@@ -371,18 +760,34 @@ pub fn update_meal_entry(
     servings: Option<f64>,
     percent_eaten: Option<f64>,
     notes: Option<String>,
-) -> Result<Option<UpdateMealEntryResponse>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    eaten_at: Option<&str>,
+) -> Result<Option<UpdateMealEntryResponse>, ToolError> {
+    let conn = db.get_conn()?;
+
+    // A bare time of day is anchored to the entry's own date
+    let eaten_at = match eaten_at {
+        Some(t) => {
+            let detail = match MealEntry::get_detail(&conn, id)
+                .map_err(|e| ToolError::Db(format!("Failed to get meal entry: {}", e)))?
+            {
+                Some(d) => d,
+                None => return Ok(None),
+            };
+            Some(normalize_eaten_at(&detail.date, t)?)
+        }
+        None => None,
+    };
 
     let data = MealEntryUpdate {
         meal_type: meal_type.map(MealType::from_str),
         servings,
         percent_eaten,
         notes,
+        eaten_at,
     };
 
     let updated = MealEntry::update(&conn, id, &data)
-        .map_err(|e| format!("Failed to update meal entry: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to update meal entry: {}", e)))?;
 
     match updated {
         Some(entry) => Ok(Some(UpdateMealEntryResponse {
@@ -391,6 +796,7 @@ pub fn update_meal_entry(
             servings: entry.servings,
             percent_eaten: entry.percent_eaten,
             nutrition: entry.cached_nutrition,
+            eaten_at: entry.eaten_at,
             updated_at: entry.updated_at,
         })),
         None => Ok(None),
@@ -398,34 +804,299 @@ pub fn update_meal_entry(
 }
 
 /// Delete a meal entry
-pub fn delete_meal_entry(db: &Database, id: i64) -> Result<bool, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn delete_meal_entry(db: &Database, id: i64) -> Result<bool, ToolError> {
+    let conn = db.get_conn()?;
 
     MealEntry::delete(&conn, id)
-        .map_err(|e| format!("Failed to delete meal entry: {}", e))
+        .map_err(|e| ToolError::Db(format!("Failed to delete meal entry: {}", e)))
+}
+
+/// One replacement entry for split_meal_entry
+#[derive(Debug, Clone)]
+pub struct SplitPart {
+    pub recipe_id: Option<i64>,
+    pub food_item_id: Option<i64>,
+    pub servings: f64,
+    pub percent_eaten: Option<f64>,
+}
+
+/// Response for split_meal_entry
+#[derive(Debug, Serialize)]
+pub struct SplitMealEntryResponse {
+    pub original_id: i64,
+    pub date: String,
+    pub meal_type: String,
+    pub original_nutrition: Nutrition,
+    pub parts_nutrition: Nutrition,
+    pub new_entries: Vec<MealEntryDetail>,
+    /// Parts' calories minus the original's
+    pub calorie_difference: f64,
+    pub within_tolerance: bool,
+    pub warning: Option<String>,
+    pub day_nutrition: Nutrition,
+}
+
+/// Replace a combined meal entry with itemized entries on the same day and meal type.
+///
+/// The parts keep the original's eaten_at and notes. Runs in one transaction and
+/// recalculates the day once. If the parts' calories differ from the original by
+/// more than tolerance_percent, the split still happens and a warning is returned.
+pub fn split_meal_entry(
+    db: &Database,
+    meal_entry_id: i64,
+    parts: &[SplitPart],
+    tolerance_percent: f64,
+    clock: &dyn Clock,
+) -> Result<Option<SplitMealEntryResponse>, ToolError> {
+    if parts.is_empty() {
+        return Err(ToolError::Validation("Provide at least one part".to_string()));
+    }
+    if tolerance_percent < 0.0 {
+        return Err(ToolError::Validation("tolerance_percent cannot be negative".to_string()));
+    }
+    for (i, part) in parts.iter().enumerate() {
+        if part.recipe_id.is_some() == part.food_item_id.is_some() {
+            return Err(ToolError::Validation(format!("Part {}: provide exactly one of recipe_id or food_item_id", i + 1)));
+        }
+        if part.servings <= 0.0 {
+            return Err(ToolError::Validation(format!("Part {}: servings must be greater than 0", i + 1)));
+        }
+        if part.percent_eaten.is_some_and(|p| !(0.0..=100.0).contains(&p)) {
+            return Err(ToolError::Validation(format!("Part {}: percent_eaten must be between 0 and 100", i + 1)));
+        }
+    }
+
+    let split = db.transaction(|tx| -> Result<_, ToolError> {
+        let Some(original) = MealEntry::get_detail(tx, meal_entry_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get meal entry: {}", e)))?
+        else {
+            return Ok(None);
+        };
+
+        for (i, part) in parts.iter().enumerate() {
+            if let Some(rid) = part.recipe_id {
+                if crate::models::Recipe::get_by_id(tx, rid)
+                    .map_err(|e| ToolError::Db(format!("Database error checking recipe: {}", e)))?
+                    .is_none()
+                {
+                    return Err(ToolError::NotFound(format!("Part {}: Recipe not found with id: {}", i + 1, rid)));
+                }
+            }
+            if let Some(fid) = part.food_item_id {
+                if crate::models::FoodItem::get_by_id(tx, fid)
+                    .map_err(|e| ToolError::Db(format!("Database error checking food item: {}", e)))?
+                    .is_none()
+                {
+                    return Err(ToolError::NotFound(format!("Part {}: Food item not found with id: {}", i + 1, fid)));
+                }
+            }
+        }
+
+        tx.execute("DELETE FROM meal_entries WHERE id = ?1", [meal_entry_id])
+            .map_err(|e| ToolError::Db(format!("Failed to delete meal entry: {}", e)))?;
+
+        let mut new_entries = Vec::with_capacity(parts.len());
+        for part in parts {
+            let entry = MealEntry::insert(tx, &MealEntryCreate {
+                day_id: original.day_id,
+                meal_type: original.meal_type.clone(),
+                recipe_id: part.recipe_id,
+                food_item_id: part.food_item_id,
+                description: None,
+                inline_nutrition: None,
+                servings: part.servings,
+                percent_eaten: part.percent_eaten,
+                notes: original.notes.clone(),
+                eaten_at: original.eaten_at.clone(),
+                planned: original.planned,
+            }, clock).map_err(|e| ToolError::Db(format!("Failed to create meal entry: {}", e)))?;
+
+            let detail = MealEntry::get_detail(tx, entry.id)
+                .map_err(|e| ToolError::Db(format!("Failed to get meal entry: {}", e)))?
+                .ok_or_else(|| ToolError::Db(format!("Meal entry {} vanished after insert", entry.id)))?;
+            new_entries.push(detail);
+        }
+
+        let day_nutrition = recalculate_day_nutrition(tx, original.day_id)
+            .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
+
+        Ok(Some((original, new_entries, day_nutrition)))
+    })?;
+    let Some((original, new_entries, day_nutrition)) = split else {
+        return Ok(None);
+    };
+
+    let parts_nutrition: Nutrition = new_entries.iter().map(|e| e.nutrition.clone()).sum();
+    let calorie_difference = parts_nutrition.calories - original.nutrition.calories;
+    let allowed = original.nutrition.calories.abs() * tolerance_percent / 100.0;
+    let within_tolerance = calorie_difference.abs() <= allowed.max(1.0);
+    let warning = (!within_tolerance).then(|| format!(
+        "Parts total {:.0} kcal vs {:.0} kcal originally ({:+.0} kcal, over the {}% tolerance)",
+        parts_nutrition.calories, original.nutrition.calories, calorie_difference, tolerance_percent
+    ));
+
+    Ok(Some(SplitMealEntryResponse {
+        original_id: meal_entry_id,
+        date: original.date,
+        meal_type: original.meal_type.as_str().to_string(),
+        original_nutrition: original.nutrition,
+        parts_nutrition,
+        new_entries,
+        calorie_difference: round_to(calorie_difference, 2),
+        within_tolerance,
+        warning,
+        day_nutrition,
+    }))
 }
 
 /// Force recalculate day nutrition
-pub fn recalculate_day_nutrition_tool(db: &Database, date: &str) -> Result<RecalculateDayNutritionResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn recalculate_day_nutrition_tool(db: &Database, date: &str) -> Result<RecalculateDayNutritionResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     let day = Day::get_by_date(&conn, date)
-        .map_err(|e| format!("Failed to get day: {}", e))?
-        .ok_or_else(|| format!("Day not found: {}", date))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?
+        .ok_or_else(|| ToolError::NotFound(format!("Day not found: {}", date)))?;
 
     let nutrition = recalculate_day_nutrition(&conn, day.id)
-        .map_err(|e| format!("Failed to recalculate nutrition: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
+
+    let deltas = nutrient_deltas(&day.cached_nutrition, &nutrition);
+    let changed = deltas.iter().any(|d| d.delta != 0.0);
 
     Ok(RecalculateDayNutritionResponse {
         day_id: day.id,
         date: day.date,
         nutrition,
+        previous_nutrition: day.cached_nutrition,
+        changed,
+        deltas,
+    })
+}
+
+/// Single meal entry → meal type assignment for reclassify_meals
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct MealTypeAssignment {
+    pub meal_entry_id: i64,
+    pub meal_type: String,
+}
+
+/// Count of meal entries per meal type
+#[derive(Debug, Default, Serialize)]
+pub struct MealTypeDistribution {
+    pub breakfast: usize,
+    pub lunch: usize,
+    pub dinner: usize,
+    pub snack: usize,
+    pub unspecified: usize,
+}
+
+impl MealTypeDistribution {
+    fn add(&mut self, meal_type: &MealType) {
+        match meal_type {
+            MealType::Breakfast => self.breakfast += 1,
+            MealType::Lunch => self.lunch += 1,
+            MealType::Dinner => self.dinner += 1,
+            MealType::Snack => self.snack += 1,
+            MealType::Unspecified => self.unspecified += 1,
+        }
+    }
+}
+
+/// Response for reclassify_meals
+#[derive(Debug, Serialize)]
+pub struct ReclassifyMealsResponse {
+    pub date: String,
+    pub updated: usize,
+    pub distribution: MealTypeDistribution,
+}
+
+/// Strictly parse a meal type (MealType::from_str silently falls back to unspecified)
+fn parse_meal_type(s: &str) -> Result<MealType, ToolError> {
+    let meal_type = MealType::from_str(s);
+    if meal_type == MealType::Unspecified && !s.eq_ignore_ascii_case("unspecified") {
+        return Err(ToolError::Validation(format!(
+            "Invalid meal type: '{}'. Must be breakfast, lunch, dinner, snack, or unspecified",
+            s
+        )));
+    }
+    Ok(meal_type)
+}
+
+/// Reclassify meal entries on a date.
+///
+/// `type_mapping` rewrites every entry of one meal type to another (e.g. unspecified → snack).
+/// `assignments` sets the meal type of specific entries and takes precedence over the mapping.
+/// Nutrition is unaffected, so the day is not recalculated.
+pub fn reclassify_meals(
+    db: &Database,
+    date: &str,
+    type_mapping: HashMap<String, String>,
+    assignments: Vec<MealTypeAssignment>,
+) -> Result<ReclassifyMealsResponse, ToolError> {
+    if type_mapping.is_empty() && assignments.is_empty() {
+        return Err(ToolError::Validation("Provide a type_mapping and/or assignments".to_string()));
+    }
+
+    let mut mapping: Vec<(MealType, MealType)> = Vec::new();
+    for (from, to) in &type_mapping {
+        mapping.push((parse_meal_type(from)?, parse_meal_type(to)?));
+    }
+
+    let mut by_entry: HashMap<i64, MealType> = HashMap::new();
+    for a in &assignments {
+        by_entry.insert(a.meal_entry_id, parse_meal_type(&a.meal_type)?);
+    }
+
+    db.transaction(|conn| {
+        let day = Day::get_by_date(conn, date)
+            .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Day not found: {}", date)))?;
+
+        let entries = MealEntry::get_for_day(conn, day.id)
+            .map_err(|e| ToolError::Db(format!("Failed to get meal entries: {}", e)))?;
+
+        // Every explicitly assigned entry must belong to this day
+        for id in by_entry.keys() {
+            if !entries.iter().any(|e| e.id == *id) {
+                return Err(ToolError::NotFound(format!("Meal entry {} not found on {}", id, date)));
+            }
+        }
+
+        let mut updated = 0;
+        let mut distribution = MealTypeDistribution::default();
+
+        for entry in &entries {
+            let new_type = by_entry.get(&entry.id).cloned().or_else(|| {
+                mapping
+                    .iter()
+                    .find(|(from, _)| *from == entry.meal_type)
+                    .map(|(_, to)| to.clone())
+            });
+
+            let final_type = match new_type {
+                Some(t) if t != entry.meal_type => {
+                    MealEntry::set_meal_type(conn, entry.id, &t)
+                        .map_err(|e| ToolError::Db(format!("Failed to update meal entry {}: {}", entry.id, e)))?;
+                    updated += 1;
+                    t
+                }
+                _ => entry.meal_type.clone(),
+            };
+
+            distribution.add(&final_type);
+        }
+
+        Ok(ReclassifyMealsResponse {
+            date: day.date,
+            updated,
+            distribution,
+        })
     })
 }
 
 /// List days with no meal entries (orphaned days safe to delete)
-pub fn list_orphaned_days(db: &Database) -> Result<ListOrphanedDaysResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn list_orphaned_days(db: &Database) -> Result<ListOrphanedDaysResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     // Find days that have no meal_entries
     let mut stmt = conn.prepare(
@@ -437,7 +1108,7 @@ pub fn list_orphaned_days(db: &Database) -> Result<ListOrphanedDaysResponse, Str
         )
         ORDER BY d.date DESC
         "#
-    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    ).map_err(|e| ToolError::Db(format!("Failed to prepare query: {}", e)))?;
 
     let days: Vec<OrphanedDaySummary> = stmt
         .query_map([], |row| {
@@ -447,9 +1118,9 @@ pub fn list_orphaned_days(db: &Database) -> Result<ListOrphanedDaysResponse, Str
                 notes: row.get("notes")?,
             })
         })
-        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .map_err(|e| ToolError::Db(format!("Failed to execute query: {}", e)))?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect results: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to collect results: {}", e)))?;
 
     let count = days.len();
 
@@ -465,12 +1136,12 @@ pub struct DeleteDayResponse {
 }
 
 /// Delete a day by date (only if it has no meal entries)
-pub fn delete_day(db: &Database, date: &str) -> Result<DeleteDayResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn delete_day(db: &Database, date: &str) -> Result<DeleteDayResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     // First, find the day
     let day = Day::get_by_date(&conn, date)
-        .map_err(|e| format!("Failed to get day: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?;
 
     let day = match day {
         Some(d) => d,
@@ -490,7 +1161,7 @@ pub fn delete_day(db: &Database, date: &str) -> Result<DeleteDayResponse, String
             [day.id],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to count meal entries: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to count meal entries: {}", e)))?;
 
     if meal_count > 0 {
         return Ok(DeleteDayResponse {
@@ -505,7 +1176,7 @@ pub fn delete_day(db: &Database, date: &str) -> Result<DeleteDayResponse, String
 
     // Safe to delete
     Day::delete(&conn, day.id)
-        .map_err(|e| format!("Failed to delete day: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to delete day: {}", e)))?;
 
     Ok(DeleteDayResponse {
         deleted: true,
@@ -515,81 +1186,830 @@ pub fn delete_day(db: &Database, date: &str) -> Result<DeleteDayResponse, String
 }
 
 // ============================================================================
-// Day Statistics
+// Protein Distribution
 // ============================================================================
 
-/// An outlier reading (value outside standard deviation bounds)
+/// Protein for a single main meal
 #[derive(Debug, Serialize)]
-pub struct Outlier {
-    pub date: String,
-    pub value: f64,
-    /// How many standard deviations from mean (positive = above, negative = below)
-    pub z_score: f64,
+pub struct MealProtein {
+    pub meal_type: String,
+    pub protein: f64,
+    pub entry_count: usize,
+    pub meets_minimum: bool,
 }
 
-/// Statistics for a single nutritional value
+/// Response for protein_distribution
 #[derive(Debug, Serialize)]
-pub struct NutritionStats {
-    pub count: i64,
-    pub sum: f64,
-    pub average: f64,
-    pub median: f64,
-    pub mode: Option<f64>,
-    pub standard_deviation: f64,
-    pub variance: f64,
-    pub min: f64,
-    pub max: f64,
-    pub range: f64,
-    /// 25th percentile (Q1)
-    pub percentile_25: f64,
-    /// 75th percentile (Q3)
-    pub percentile_75: f64,
-    /// Interquartile range (Q3 - Q1)
-    pub iqr: f64,
-    /// Coefficient of variation (SD/mean * 100) - relative variability
-    pub coefficient_of_variation: f64,
-    /// Days with values outside 1 standard deviation
-    pub outliers: Vec<Outlier>,
+pub struct ProteinDistributionResponse {
+    pub date: String,
+    pub min_per_meal: f64,
+    pub total_protein: f64,
+    /// Breakfast, lunch and dinner
+    pub main_meals: Vec<MealProtein>,
+    pub snack_protein: f64,
+    pub unspecified_protein: f64,
+    pub meals_meeting_minimum: usize,
+    /// Difference between the highest- and lowest-protein main meals
+    pub largest_gap: f64,
 }
 
-/// Response for list_days_stats
-#[derive(Debug, Serialize)]
-pub struct ListDaysStatsResponse {
-    pub days_analyzed: i64,
-    pub date_range: Option<DateRange>,
-    pub calories: NutritionStats,
-    pub protein: NutritionStats,
-    pub carbs: NutritionStats,
-    pub fat: NutritionStats,
-    pub fiber: NutritionStats,
-    pub sugar: NutritionStats,
-    pub sodium: NutritionStats,
-    pub saturated_fat: NutritionStats,
-    pub cholesterol: NutritionStats,
+/// Check how protein is spread across meals for a day
+pub fn protein_distribution(
+    db: &Database,
+    date: &str,
+    min_per_meal: f64,
+) -> Result<ProteinDistributionResponse, ToolError> {
+    if min_per_meal < 0.0 {
+        return Err(ToolError::Validation("min_per_meal must not be negative".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let day = Day::get_by_date(&conn, date)
+        .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?
+        .ok_or_else(|| ToolError::NotFound(format!("Day not found: {}", date)))?;
+
+    let entries = MealEntry::get_details_for_day(&conn, day.id)
+        .map_err(|e| ToolError::Db(format!("Failed to get meal entries: {}", e)))?;
+
+    let protein_for = |meal_type: &MealType| -> (f64, usize) {
+        entries
+            .iter()
+            .filter(|e| !e.planned && e.meal_type == *meal_type)
+            .fold((0.0, 0), |(sum, n), e| (sum + e.nutrition.protein, n + 1))
+    };
+
+    let main_meals: Vec<MealProtein> = [MealType::Breakfast, MealType::Lunch, MealType::Dinner]
+        .iter()
+        .map(|mt| {
+            let (protein, entry_count) = protein_for(mt);
+            MealProtein {
+                meal_type: mt.as_str().to_string(),
+                protein: round_to(protein, 2),
+                entry_count,
+                meets_minimum: protein >= min_per_meal,
+            }
+        })
+        .collect();
+
+    let meals_meeting_minimum = main_meals.iter().filter(|m| m.meets_minimum).count();
+    let max = main_meals.iter().map(|m| m.protein).fold(f64::MIN, f64::max);
+    let min = main_meals.iter().map(|m| m.protein).fold(f64::MAX, f64::min);
+
+    let total_protein: f64 = entries.iter().filter(|e| !e.planned).map(|e| e.nutrition.protein).sum();
+
+    Ok(ProteinDistributionResponse {
+        date: day.date,
+        min_per_meal,
+        total_protein: round_to(total_protein, 2),
+        main_meals,
+        snack_protein: round_to(protein_for(&MealType::Snack).0, 2),
+        unspecified_protein: round_to(protein_for(&MealType::Unspecified).0, 2),
+        meals_meeting_minimum,
+        largest_gap: round_to(max - min, 2),
+    })
 }
 
-/// Date range for stats
+// ============================================================================
+// Sodium Breakdown
+// ============================================================================
+
+/// One food item's share of a day's sodium
 #[derive(Debug, Serialize)]
-pub struct DateRange {
-    pub start: String,
-    pub end: String,
+pub struct SodiumSource {
+    /// None for ad-hoc entries (grouped by description)
+    pub food_item_id: Option<i64>,
+    pub name: String,
+    pub sodium_mg: f64,
+    pub percent_of_day: f64,
+    /// Top-level meal entries this food reached the day through
+    pub entry_count: usize,
 }
 
-/// Internal: day value for stats calculation
-struct DayValue {
-    date: String,
-    value: f64,
+/// Response for day_sodium_breakdown
+#[derive(Debug, Serialize)]
+pub struct DaySodiumBreakdownResponse {
+    pub date: String,
+    /// Sum of all expanded sources (eaten entries only)
+    pub total_sodium_mg: f64,
+    /// Highest contributor first
+    pub sources: Vec<SodiumSource>,
 }
 
-/// Calculate statistics for a list of day values
-fn calculate_stats(values: &[DayValue]) -> NutritionStats {
-    if values.is_empty() {
-        return NutritionStats {
-            count: 0,
-            sum: 0.0,
-            average: 0.0,
-            median: 0.0,
-            mode: None,
+/// Rank the food items behind a day's sodium, expanding recipes (and their
+/// component recipes) into ingredients scaled the same way recipe nutrition is
+pub fn day_sodium_breakdown(db: &Database, date: &str, limit: Option<usize>) -> Result<DaySodiumBreakdownResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let day = Day::get_by_date(&conn, date)
+        .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?
+        .ok_or_else(|| ToolError::NotFound(format!("Day not found: {}", date)))?;
+
+    let entries = MealEntry::get_for_day(&conn, day.id)
+        .map_err(|e| ToolError::Db(format!("Failed to get meal entries: {}", e)))?;
+
+    let mut by_food: HashMap<i64, (String, f64, usize)> = HashMap::new();
+    let mut by_adhoc: HashMap<String, (f64, usize)> = HashMap::new();
+
+    for entry in entries.iter().filter(|e| !e.planned) {
+        if entry.recipe_id.is_none() && entry.food_item_id.is_none() {
+            let name = entry.description.clone().unwrap_or_else(|| "Ad-hoc entry".to_string());
+            let adhoc = by_adhoc.entry(name).or_insert((0.0, 0));
+            adhoc.0 += entry.cached_nutrition.sodium;
+            adhoc.1 += 1;
+            continue;
+        }
+
+        let items = entry.expand_food_items(&conn)
+            .map_err(|e| ToolError::Db(format!("Failed to expand meal entry {}: {}", entry.id, e)))?;
+        let mut totals: HashMap<i64, (String, f64)> = HashMap::new();
+        for (food_item, servings) in items {
+            let total = totals.entry(food_item.id).or_insert_with(|| (food_item.name.clone(), 0.0));
+            total.1 += food_item.nutrition.sodium * servings;
+        }
+
+        for (food_item_id, (name, sodium)) in totals {
+            let food = by_food.entry(food_item_id).or_insert((name, 0.0, 0));
+            food.1 += sodium;
+            food.2 += 1;
+        }
+    }
+
+    let mut sources: Vec<SodiumSource> = by_food
+        .into_iter()
+        .map(|(id, (name, sodium_mg, entry_count))| SodiumSource {
+            food_item_id: Some(id),
+            name,
+            sodium_mg,
+            percent_of_day: 0.0,
+            entry_count,
+        })
+        .chain(by_adhoc.into_iter().map(|(name, (sodium_mg, entry_count))| SodiumSource {
+            food_item_id: None,
+            name,
+            sodium_mg,
+            percent_of_day: 0.0,
+            entry_count,
+        }))
+        .filter(|s| s.sodium_mg > 0.0)
+        .collect();
+
+    let total = sources.iter().fold(0.0, |sum, s| sum + s.sodium_mg);
+    for source in &mut sources {
+        source.percent_of_day = round_to((source.sodium_mg / total) * 100.0, 1);
+        source.sodium_mg = round_to(source.sodium_mg, 1);
+    }
+    sources.sort_by(|a, b| b.sodium_mg.total_cmp(&a.sodium_mg).then_with(|| a.name.cmp(&b.name)));
+    if let Some(limit) = limit {
+        sources.truncate(limit);
+    }
+
+    Ok(DaySodiumBreakdownResponse {
+        date: day.date,
+        total_sodium_mg: round_to(total, 1),
+        sources,
+    })
+}
+
+// ============================================================================
+// Day Budget
+// ============================================================================
+
+/// Daily goals used for a budget check
+#[derive(Debug, Serialize)]
+pub struct DayGoals {
+    pub calories: f64,
+    pub protein: f64,
+    /// Upper limit in mg
+    pub sodium: f64,
+}
+
+/// Response for day_budget_status
+#[derive(Debug, Serialize)]
+pub struct DayBudgetStatusResponse {
+    pub date: String,
+    /// False if nothing has been logged for the date yet
+    pub day_exists: bool,
+    pub consumed_calories: f64,
+    pub consumed_protein: f64,
+    pub consumed_sodium: f64,
+    pub goals: DayGoals,
+    /// Negative when over the calorie goal
+    pub remaining_calories: f64,
+    /// Protein still needed to reach the goal (0 once reached)
+    pub remaining_protein: f64,
+    /// Negative when over the sodium limit
+    pub remaining_sodium: f64,
+    /// Sodium mg per 1000 kcal consumed (None before any calories are logged)
+    pub sodium_density: Option<f64>,
+}
+
+/// Consumed vs remaining calories/protein/sodium for a day, from its cached nutrition
+///
+/// There is no exercise tracking yet, so calories are not netted against burn.
+pub fn day_budget_status(
+    db: &Database,
+    date: &str,
+    calorie_goal: f64,
+    protein_goal: f64,
+    sodium_limit: f64,
+) -> Result<DayBudgetStatusResponse, ToolError> {
+    if calorie_goal <= 0.0 || protein_goal < 0.0 || sodium_limit <= 0.0 {
+        return Err(ToolError::Validation("Goals must be positive".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let day = Day::get_by_date(&conn, date)
+        .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?;
+
+    let day_exists = day.is_some();
+    let consumed = day.map(|d| d.cached_nutrition).unwrap_or_default();
+
+    Ok(DayBudgetStatusResponse {
+        date: date.to_string(),
+        day_exists,
+        consumed_calories: round_to(consumed.calories, 2),
+        consumed_protein: round_to(consumed.protein, 2),
+        consumed_sodium: round_to(consumed.sodium, 2),
+        goals: DayGoals {
+            calories: calorie_goal,
+            protein: protein_goal,
+            sodium: sodium_limit,
+        },
+        remaining_calories: round_to(calorie_goal - consumed.calories, 2),
+        remaining_protein: round_to((protein_goal - consumed.protein).max(0.0), 2),
+        remaining_sodium: round_to(sodium_limit - consumed.sodium, 2),
+        sodium_density: consumed.sodium_density().map(|d| round_to(d, 2)),
+    })
+}
+
+// ============================================================================
+// Day Quality Score
+// ============================================================================
+
+/// Relative weight of each quality component (normalized to sum to 100)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct QualityWeights {
+    pub calories: f64,
+    pub protein: f64,
+    pub sodium: f64,
+    pub fiber: f64,
+}
+
+impl Default for QualityWeights {
+    fn default() -> Self {
+        Self { calories: 35.0, protein: 30.0, sodium: 20.0, fiber: 15.0 }
+    }
+}
+
+/// One component's share of the quality score
+#[derive(Debug, Serialize)]
+pub struct QualityComponent {
+    pub component: String,
+    pub value: f64,
+    pub target: f64,
+    /// 0.0 (missed entirely) to 1.0 (target met)
+    pub fraction: f64,
+    /// Maximum points this component can add (its normalized weight)
+    pub max_points: f64,
+    pub points: f64,
+}
+
+/// Response for compute_day_quality
+#[derive(Debug, Serialize)]
+pub struct DayQualityResponse {
+    pub date: String,
+    /// 0-100, the sum of component points
+    pub score: f64,
+    pub goals: DayGoals,
+    pub fiber_goal: f64,
+    pub components: Vec<QualityComponent>,
+}
+
+/// Score a day's nutrition against goals.
+/// - calories: full under the goal, falling to 0 at 150% of it
+/// - protein: proportional up to the goal
+/// - sodium: full under the limit, falling to 0 at twice the limit
+/// - fiber: proportional up to fiber_goal
+fn score_quality(
+    n: &Nutrition,
+    goals: &DayGoals,
+    fiber_goal: f64,
+    weights: &QualityWeights,
+) -> Result<(f64, Vec<QualityComponent>), ToolError> {
+    let raw = [weights.calories, weights.protein, weights.sodium, weights.fiber];
+    if raw.iter().any(|w| *w < 0.0) {
+        return Err(ToolError::Validation("Weights must not be negative".to_string()));
+    }
+    let total_weight: f64 = raw.iter().sum();
+    if total_weight <= 0.0 {
+        return Err(ToolError::Validation("At least one weight must be positive".to_string()));
+    }
+
+    let under = |value: f64, target: f64, zero_at: f64| {
+        if value <= target { 1.0 } else { (1.0 - (value - target) / (zero_at - target)).max(0.0) }
+    };
+    let up_to = |value: f64, target: f64| if target > 0.0 { (value / target).clamp(0.0, 1.0) } else { 1.0 };
+
+    let parts = [
+        ("calories", n.calories, goals.calories, under(n.calories, goals.calories, goals.calories * 1.5), weights.calories),
+        ("protein", n.protein, goals.protein, up_to(n.protein, goals.protein), weights.protein),
+        ("sodium", n.sodium, goals.sodium, under(n.sodium, goals.sodium, goals.sodium * 2.0), weights.sodium),
+        ("fiber", n.fiber, fiber_goal, up_to(n.fiber, fiber_goal), weights.fiber),
+    ];
+
+    let components: Vec<QualityComponent> = parts
+        .into_iter()
+        .map(|(component, value, target, fraction, weight)| {
+            let max_points = weight / total_weight * 100.0;
+            QualityComponent {
+                component: component.to_string(),
+                value: round_to(value, 2),
+                target,
+                fraction: round_to(fraction, 2),
+                max_points: round_to(max_points, 2),
+                points: round_to(fraction * max_points, 2),
+            }
+        })
+        .collect();
+
+    let score = round_to(components.iter().map(|c| c.points).sum::<f64>(), 1);
+    Ok((score, components))
+}
+
+/// Composite 0-100 quality score for a logged day from its cached nutrition:
+/// calories under goal, protein over goal, sodium under limit and fiber over
+/// fiber_goal, weighted by `weights`. Each component's points are returned so
+/// the score can be explained.
+///
+/// Exercise is not tracked yet, so it is not part of the score.
+pub fn compute_day_quality(
+    db: &Database,
+    date: &str,
+    goals: DayGoals,
+    fiber_goal: f64,
+    weights: QualityWeights,
+) -> Result<DayQualityResponse, ToolError> {
+    if goals.calories <= 0.0 || goals.protein < 0.0 || goals.sodium <= 0.0 || fiber_goal < 0.0 {
+        return Err(ToolError::Validation("Goals must be positive".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let day = Day::get_by_date(&conn, date)
+        .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?
+        .ok_or_else(|| ToolError::NotFound(format!("No day logged for {}", date)))?;
+
+    let (score, components) = score_quality(&day.cached_nutrition, &goals, fiber_goal, &weights)?;
+
+    Ok(DayQualityResponse {
+        date: date.to_string(),
+        score,
+        goals,
+        fiber_goal,
+        components,
+    })
+}
+
+// ============================================================================
+// Calorie Balance
+// ============================================================================
+
+/// Calories in a pound of body weight (the usual rule of thumb)
+const CALORIES_PER_LB: f64 = 3500.0;
+
+/// A weight reading at one end of a balance period
+#[derive(Debug, Serialize)]
+pub struct BalanceWeight {
+    pub timestamp: String,
+    pub lbs: f64,
+}
+
+/// Response for calorie_balance
+#[derive(Debug, Serialize)]
+pub struct CalorieBalanceResponse {
+    pub start_date: String,
+    pub end_date: String,
+    pub days_in_range: i64,
+    /// Days with calories logged; only these count toward intake and expenditure
+    pub days_logged: usize,
+    pub gross_calories: f64,
+    pub average_daily_calories: Option<f64>,
+    /// Daily expenditure supplied by the caller
+    pub tdee: Option<f64>,
+    /// tdee × days_logged
+    pub expenditure: Option<f64>,
+    /// gross_calories minus expenditure (negative is a deficit)
+    pub net_balance: Option<f64>,
+    /// net_balance / 3500
+    pub predicted_weight_change_lbs: Option<f64>,
+    pub first_weight: Option<BalanceWeight>,
+    pub last_weight: Option<BalanceWeight>,
+    pub actual_weight_change_lbs: Option<f64>,
+    /// Daily expenditure that would explain the actual weight change given the logged intake
+    pub implied_tdee: Option<f64>,
+}
+
+/// Total intake over a date range against expenditure, with the weight change it
+/// predicts (3500 kcal per lb) next to the actual change between the first and
+/// last weight readings in the range.
+///
+/// Exercise and energy expenditure are not tracked yet, so expenditure comes from
+/// the caller's tdee; without it only intake, actual change and implied TDEE are returned.
+pub fn calorie_balance(db: &Database, start_date: &str, end_date: &str, tdee: Option<f64>) -> Result<CalorieBalanceResponse, ToolError> {
+    use chrono::NaiveDate;
+    use crate::models::{Vital, VitalType};
+    use super::vitals::LBS_PER_KG;
+
+    let start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid start_date '{}'. Use YYYY-MM-DD", start_date)))?;
+    let end = NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid end_date '{}'. Use YYYY-MM-DD", end_date)))?;
+    if end < start {
+        return Err(ToolError::Validation("end_date must not be before start_date".to_string()));
+    }
+    if tdee.is_some_and(|t| t <= 0.0) {
+        return Err(ToolError::Validation("tdee must be greater than 0".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let days = Day::list(&conn, Some(start_date), Some(end_date), i64::MAX, 0)
+        .map_err(|e| ToolError::Db(format!("Failed to list days: {}", e)))?;
+    let logged: Vec<&Day> = days.iter().filter(|d| d.cached_nutrition.calories > 0.0).collect();
+    let days_logged = logged.len();
+    let gross_calories: f64 = logged.iter().map(|d| d.cached_nutrition.calories).sum();
+
+    // Oldest and newest weight in the range (list is newest first)
+    let weights = Vital::list_by_date_range(&conn, start_date, end_date, Some(VitalType::Weight))
+        .map_err(|e| ToolError::Db(format!("Failed to list weights: {}", e)))?;
+    let to_balance = |v: &Vital| BalanceWeight {
+        timestamp: v.timestamp.clone(),
+        lbs: round_to(if v.unit.eq_ignore_ascii_case("kg") { v.value1 * LBS_PER_KG } else { v.value1 }, 1),
+    };
+    let first_weight = weights.last().map(to_balance);
+    let last_weight = (weights.len() > 1).then(|| to_balance(&weights[0]));
+    let actual_weight_change_lbs = first_weight
+        .as_ref()
+        .zip(last_weight.as_ref())
+        .map(|(first, last)| round_to(last.lbs - first.lbs, 1));
+
+    let expenditure = tdee.map(|t| t * days_logged as f64);
+    let net_balance = expenditure.map(|e| gross_calories - e);
+    let implied_tdee = actual_weight_change_lbs
+        .filter(|_| days_logged > 0)
+        .map(|change| round_to((gross_calories - change * CALORIES_PER_LB) / days_logged as f64, 0));
+
+    Ok(CalorieBalanceResponse {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        days_in_range: (end - start).num_days() + 1,
+        days_logged,
+        gross_calories: round_to(gross_calories, 0),
+        average_daily_calories: (days_logged > 0).then(|| round_to(gross_calories / days_logged as f64, 0)),
+        tdee,
+        expenditure: expenditure.map(|e| round_to(e, 0)),
+        net_balance: net_balance.map(|b| round_to(b, 0)),
+        predicted_weight_change_lbs: net_balance.map(|b| round_to(b / CALORIES_PER_LB, 1)),
+        first_weight,
+        last_weight,
+        actual_weight_change_lbs,
+        implied_tdee,
+    })
+}
+
+// ============================================================================
+// Day Comparison
+// ============================================================================
+
+/// Change in one nutrient between two days
+#[derive(Debug, Serialize)]
+pub struct NutrientDelta {
+    pub nutrient: String,
+    pub value_a: f64,
+    pub value_b: f64,
+    /// value_b minus value_a
+    pub delta: f64,
+    /// None when value_a is zero
+    pub percent_change: Option<f64>,
+}
+
+/// One side of a day comparison
+#[derive(Debug, Serialize)]
+pub struct ComparedDay {
+    pub date: String,
+    pub meal_count: usize,
+    pub nutrition: Nutrition,
+}
+
+/// Response for compare_days
+#[derive(Debug, Serialize)]
+pub struct CompareDaysResponse {
+    pub day_a: ComparedDay,
+    pub day_b: ComparedDay,
+    pub deltas: Vec<NutrientDelta>,
+}
+
+/// Per-nutrient change from a to b, rounded to 2 decimals
+fn nutrient_deltas(a: &Nutrition, b: &Nutrition) -> Vec<NutrientDelta> {
+    let pairs = [
+        ("calories", a.calories, b.calories),
+        ("protein", a.protein, b.protein),
+        ("carbs", a.carbs, b.carbs),
+        ("fat", a.fat, b.fat),
+        ("fiber", a.fiber, b.fiber),
+        ("sugar", a.sugar, b.sugar),
+        ("sodium", a.sodium, b.sodium),
+        ("saturated_fat", a.saturated_fat, b.saturated_fat),
+        ("cholesterol", a.cholesterol, b.cholesterol),
+    ];
+
+    pairs
+        .iter()
+        .map(|&(nutrient, value_a, value_b)| NutrientDelta {
+            nutrient: nutrient.to_string(),
+            value_a: round_to(value_a, 2),
+            value_b: round_to(value_b, 2),
+            delta: round_to(value_b - value_a, 2),
+            percent_change: (value_a != 0.0)
+                .then(|| round_to((value_b - value_a) / value_a * 100.0, 2)),
+        })
+        .collect()
+}
+
+/// Compare the nutrition totals of two days (deltas are b minus a)
+pub fn compare_days(db: &Database, date_a: &str, date_b: &str) -> Result<CompareDaysResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let load = |date: &str| -> Result<ComparedDay, ToolError> {
+        let day = Day::get_by_date(&conn, date)
+            .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Day not found: {}", date)))?;
+        let meal_count = MealEntry::get_for_day(&conn, day.id)
+            .map_err(|e| ToolError::Db(format!("Failed to get meal entries: {}", e)))?
+            .iter()
+            .filter(|e| !e.planned)
+            .count();
+        Ok(ComparedDay {
+            date: day.date,
+            meal_count,
+            nutrition: day.cached_nutrition,
+        })
+    };
+
+    let day_a = load(date_a)?;
+    let day_b = load(date_b)?;
+
+    let deltas = nutrient_deltas(&day_a.nutrition, &day_b.nutrition);
+
+    Ok(CompareDaysResponse { day_a, day_b, deltas })
+}
+
+// ============================================================================
+// Planned vs Actual
+// ============================================================================
+
+/// Response for compare_planned_vs_actual
+#[derive(Debug, Serialize)]
+pub struct PlannedVsActualResponse {
+    pub date: String,
+    pub planned_entry_count: usize,
+    pub actual_entry_count: usize,
+    pub planned: Nutrition,
+    pub actual: Nutrition,
+    /// Adherence per nutrient (value_a = planned, value_b = actual, delta = actual minus planned)
+    pub deltas: Vec<NutrientDelta>,
+}
+
+/// Compare a day's planned entries with what was actually eaten
+pub fn compare_planned_vs_actual(db: &Database, date: &str) -> Result<PlannedVsActualResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let day = Day::get_by_date(&conn, date)
+        .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?
+        .ok_or_else(|| ToolError::NotFound(format!("Day not found: {}", date)))?;
+    let entries = MealEntry::get_for_day(&conn, day.id)
+        .map_err(|e| ToolError::Db(format!("Failed to get meal entries: {}", e)))?;
+
+    let (planned, actual): (Vec<_>, Vec<_>) = entries.into_iter().partition(|e| e.planned);
+    let planned_total: Nutrition = planned.iter().map(|e| e.cached_nutrition.clone()).sum();
+    let actual_total: Nutrition = actual.iter().map(|e| e.cached_nutrition.clone()).sum();
+
+    Ok(PlannedVsActualResponse {
+        date: day.date,
+        planned_entry_count: planned.len(),
+        actual_entry_count: actual.len(),
+        deltas: nutrient_deltas(&planned_total, &actual_total),
+        planned: planned_total,
+        actual: actual_total,
+    })
+}
+
+// ============================================================================
+// Day Timeline
+// ============================================================================
+
+/// A single event on a day's timeline
+#[derive(Debug, Serialize)]
+pub struct TimelineEvent {
+    pub timestamp: String,
+    /// "meal", "vital_group", or "vital"
+    pub event_type: String,
+    pub id: i64,
+    pub title: String,
+    /// Meal type for meals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meal_type: Option<String>,
+    /// Calories for meals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calories: Option<f64>,
+    /// Formatted readings for vitals and vital groups
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub readings: Vec<String>,
+}
+
+/// Response for get_day_timeline
+#[derive(Debug, Serialize)]
+pub struct DayTimelineResponse {
+    pub date: String,
+    pub events: Vec<TimelineEvent>,
+    pub count: usize,
+}
+
+/// Build a chronological view of a day: meals (by eaten_at) and vitals
+/// (grouped readings as one event, standalone readings individually)
+pub fn get_day_timeline(db: &Database, date: &str) -> Result<DayTimelineResponse, ToolError> {
+    use crate::models::{Vital, VitalGroup};
+
+    let conn = db.get_conn()?;
+
+    let mut events = Vec::new();
+
+    if let Some(day) = Day::get_by_date(&conn, date).map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))? {
+        let entries = MealEntry::get_details_for_day(&conn, day.id)
+            .map_err(|e| ToolError::Db(format!("Failed to get meal entries: {}", e)))?;
+
+        for entry in entries.into_iter().filter(|e| !e.planned) {
+            events.push(TimelineEvent {
+                // created_at is SQLite's UTC datetime('now'); eaten_at is local
+                timestamp: entry.eaten_at.unwrap_or_else(|| {
+                    crate::clock::canonical_timestamp(&format!("{}Z", entry.created_at))
+                        .unwrap_or(entry.created_at)
+                }),
+                event_type: "meal".to_string(),
+                id: entry.id,
+                title: entry.source_name,
+                meal_type: Some(entry.meal_type.as_str().to_string()),
+                calories: Some(round_to(entry.nutrition.calories, 2)),
+                readings: Vec::new(),
+            });
+        }
+    }
+
+    let vitals = Vital::list_by_date_range(&conn, date, date, None)
+        .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?;
+
+    let mut group_readings: std::collections::BTreeMap<i64, Vec<String>> = std::collections::BTreeMap::new();
+    for vital in &vitals {
+        let reading = format!("{}: {}", vital.vital_type.display_name(), vital.format_value());
+        match vital.group_id {
+            Some(gid) => group_readings.entry(gid).or_default().push(reading),
+            None => events.push(TimelineEvent {
+                timestamp: vital.timestamp.clone(),
+                event_type: "vital".to_string(),
+                id: vital.id,
+                title: vital.vital_type.display_name().to_string(),
+                meal_type: None,
+                calories: None,
+                readings: vec![reading],
+            }),
+        }
+    }
+
+    for (gid, readings) in group_readings {
+        let group = VitalGroup::get_by_id(&conn, gid)
+            .map_err(|e| ToolError::Db(format!("Failed to get vital group: {}", e)))?;
+        if let Some(group) = group {
+            events.push(TimelineEvent {
+                timestamp: group.timestamp,
+                event_type: "vital_group".to_string(),
+                id: group.id,
+                title: group.description.unwrap_or_else(|| "Vitals".to_string()),
+                meal_type: None,
+                calories: None,
+                readings,
+            });
+        }
+    }
+
+    // Stable sort keeps meals before vitals (and id order) at identical timestamps
+    events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let count = events.len();
+
+    Ok(DayTimelineResponse {
+        date: date.to_string(),
+        events,
+        count,
+    })
+}
+
+// ============================================================================
+// Day Statistics
+// ============================================================================
+
+/// An outlier reading (value outside standard deviation bounds)
+#[derive(Debug, Serialize)]
+pub struct Outlier {
+    pub date: String,
+    pub value: f64,
+    /// How many standard deviations from mean (positive = above, negative = below)
+    pub z_score: f64,
+}
+
+/// Statistics for a single nutritional value
+#[derive(Debug, Serialize)]
+pub struct NutritionStats {
+    pub count: i64,
+    pub sum: f64,
+    pub average: f64,
+    pub median: f64,
+    pub mode: Option<f64>,
+    pub standard_deviation: f64,
+    pub variance: f64,
+    pub min: f64,
+    pub max: f64,
+    pub range: f64,
+    /// 25th percentile (Q1)
+    pub percentile_25: f64,
+    /// 75th percentile (Q3)
+    pub percentile_75: f64,
+    /// Interquartile range (Q3 - Q1)
+    pub iqr: f64,
+    /// Coefficient of variation (SD/mean * 100) - relative variability
+    pub coefficient_of_variation: f64,
+    /// Days more than outlier_sd standard deviations from the mean
+    pub outliers: Vec<Outlier>,
+}
+
+/// Response for list_days_stats
+#[derive(Debug, Serialize)]
+pub struct ListDaysStatsResponse {
+    pub days_analyzed: i64,
+    /// Standard deviations from the mean beyond which a day is an outlier
+    pub outlier_sd: f64,
+    pub date_range: Option<DateRange>,
+    pub calories: NutritionStats,
+    pub protein: NutritionStats,
+    pub carbs: NutritionStats,
+    pub fat: NutritionStats,
+    pub fiber: NutritionStats,
+    pub sugar: NutritionStats,
+    pub sodium: NutritionStats,
+    pub saturated_fat: NutritionStats,
+    pub cholesterol: NutritionStats,
+    /// Per-meal-type breakdown, present when requested with by_meal_type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meal_types: Option<Vec<MealTypeStats>>,
+}
+
+/// Statistics for one meal type across the analyzed days
+#[derive(Debug, Serialize)]
+pub struct MealTypeStats {
+    pub meal_type: String,
+    pub entry_count: i64,
+    /// Calories from this meal type, over days that have at least one such entry
+    pub calories: NutritionStats,
+    pub protein: NutritionStats,
+    /// Calories from this meal type averaged over every analyzed day (0 on days without it)
+    pub average_calories_per_day: f64,
+    /// Share of all calories in the range eaten as this meal type
+    pub percent_of_calories: f64,
+}
+
+/// Date range for stats
+#[derive(Debug, Serialize)]
+pub struct DateRange {
+    pub start: String,
+    pub end: String,
+}
+
+/// Internal: day value for stats calculation
+struct DayValue {
+    date: String,
+    value: f64,
+}
+
+/// Calculate statistics for a list of day values
+fn calculate_stats(values: &[DayValue], outlier_sd: f64) -> NutritionStats {
+    if values.is_empty() {
+        return NutritionStats {
+            count: 0,
+            sum: 0.0,
+            average: 0.0,
+            median: 0.0,
+            mode: None,
             standard_deviation: 0.0,
             variance: 0.0,
             min: 0.0,
@@ -663,17 +2083,17 @@ fn calculate_stats(values: &[DayValue]) -> NutritionStats {
         0.0
     };
 
-    // Outliers (outside 1 standard deviation)
+    // Outliers (beyond outlier_sd standard deviations)
     let outliers: Vec<Outlier> = if standard_deviation > 0.0 {
         values
             .iter()
             .filter_map(|dv| {
                 let z_score = (dv.value - average) / standard_deviation;
-                if z_score.abs() > 1.0 {
+                if z_score.abs() > outlier_sd {
                     Some(Outlier {
                         date: dv.date.clone(),
                         value: dv.value,
-                        z_score: (z_score * 100.0).round() / 100.0, // Round to 2 decimals
+                        z_score: round_to(z_score, 2), // Round to 2 decimals
                     })
                 } else {
                     None
@@ -686,19 +2106,19 @@ fn calculate_stats(values: &[DayValue]) -> NutritionStats {
 
     NutritionStats {
         count,
-        sum: (sum * 100.0).round() / 100.0,
-        average: (average * 100.0).round() / 100.0,
-        median: (median * 100.0).round() / 100.0,
-        mode: mode.map(|m| (m * 100.0).round() / 100.0),
-        standard_deviation: (standard_deviation * 100.0).round() / 100.0,
-        variance: (variance * 100.0).round() / 100.0,
-        min: (min * 100.0).round() / 100.0,
-        max: (max * 100.0).round() / 100.0,
-        range: (range * 100.0).round() / 100.0,
-        percentile_25: (percentile_25 * 100.0).round() / 100.0,
-        percentile_75: (percentile_75 * 100.0).round() / 100.0,
-        iqr: (iqr * 100.0).round() / 100.0,
-        coefficient_of_variation: (coefficient_of_variation * 100.0).round() / 100.0,
+        sum: round_to(sum, 2),
+        average: round_to(average, 2),
+        median: round_to(median, 2),
+        mode: mode.map(|m| round_to(m, 2)),
+        standard_deviation: round_to(standard_deviation, 2),
+        variance: round_to(variance, 2),
+        min: round_to(min, 2),
+        max: round_to(max, 2),
+        range: round_to(range, 2),
+        percentile_25: round_to(percentile_25, 2),
+        percentile_75: round_to(percentile_75, 2),
+        iqr: round_to(iqr, 2),
+        coefficient_of_variation: round_to(coefficient_of_variation, 2),
         outliers,
     }
 }
@@ -730,26 +2150,34 @@ pub fn list_days_stats(
     db: &Database,
     start_date: Option<&str>,
     end_date: Option<&str>,
-) -> Result<ListDaysStatsResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    outlier_sd: f64,
+    by_meal_type: bool,
+) -> Result<ListDaysStatsResponse, ToolError> {
+    if !outlier_sd.is_finite() || outlier_sd <= 0.0 {
+        return Err(ToolError::Validation("outlier_sd must be greater than 0".to_string()));
+    }
+
+    let conn = db.get_conn()?;
 
     // Get all days in range with their cached nutrition
     let days = Day::list(&conn, start_date, end_date, 10000, 0)
-        .map_err(|e| format!("Failed to list days: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to list days: {}", e)))?;
 
     if days.is_empty() {
         return Ok(ListDaysStatsResponse {
             days_analyzed: 0,
+            outlier_sd,
             date_range: None,
-            calories: calculate_stats(&[]),
-            protein: calculate_stats(&[]),
-            carbs: calculate_stats(&[]),
-            fat: calculate_stats(&[]),
-            fiber: calculate_stats(&[]),
-            sugar: calculate_stats(&[]),
-            sodium: calculate_stats(&[]),
-            saturated_fat: calculate_stats(&[]),
-            cholesterol: calculate_stats(&[]),
+            calories: calculate_stats(&[], outlier_sd),
+            protein: calculate_stats(&[], outlier_sd),
+            carbs: calculate_stats(&[], outlier_sd),
+            fat: calculate_stats(&[], outlier_sd),
+            fiber: calculate_stats(&[], outlier_sd),
+            sugar: calculate_stats(&[], outlier_sd),
+            sodium: calculate_stats(&[], outlier_sd),
+            saturated_fat: calculate_stats(&[], outlier_sd),
+            cholesterol: calculate_stats(&[], outlier_sd),
+            meal_types: by_meal_type.then(Vec::new),
         });
     }
 
@@ -798,17 +2226,1085 @@ pub fn list_days_stats(
         _ => None,
     };
 
+    let meal_types = if by_meal_type {
+        Some(meal_type_stats(&conn, &calories, outlier_sd)?)
+    } else {
+        None
+    };
+
     Ok(ListDaysStatsResponse {
         days_analyzed: calories.len() as i64,
+        outlier_sd,
         date_range,
-        calories: calculate_stats(&calories),
-        protein: calculate_stats(&protein),
-        carbs: calculate_stats(&carbs),
-        fat: calculate_stats(&fat),
-        fiber: calculate_stats(&fiber),
-        sugar: calculate_stats(&sugar),
-        sodium: calculate_stats(&sodium),
-        saturated_fat: calculate_stats(&saturated_fat),
-        cholesterol: calculate_stats(&cholesterol),
+        calories: calculate_stats(&calories, outlier_sd),
+        protein: calculate_stats(&protein, outlier_sd),
+        carbs: calculate_stats(&carbs, outlier_sd),
+        fat: calculate_stats(&fat, outlier_sd),
+        fiber: calculate_stats(&fiber, outlier_sd),
+        sugar: calculate_stats(&sugar, outlier_sd),
+        sodium: calculate_stats(&sodium, outlier_sd),
+        saturated_fat: calculate_stats(&saturated_fat, outlier_sd),
+        cholesterol: calculate_stats(&cholesterol, outlier_sd),
+        meal_types,
     })
 }
+
+/// Aggregate eaten (non-planned) meal entries by meal type over the analyzed days
+fn meal_type_stats(
+    conn: &rusqlite::Connection,
+    day_calories: &[DayValue],
+    outlier_sd: f64,
+) -> Result<Vec<MealTypeStats>, ToolError> {
+    let (Some(first), Some(last)) = (
+        day_calories.iter().map(|d| d.date.as_str()).min(),
+        day_calories.iter().map(|d| d.date.as_str()).max(),
+    ) else {
+        return Ok(Vec::new());
+    };
+    let analyzed: std::collections::HashSet<&str> = day_calories.iter().map(|d| d.date.as_str()).collect();
+    let total_calories: f64 = day_calories.iter().map(|d| d.value).sum();
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT d.date, me.meal_type, COUNT(*), SUM(me.cached_calories), SUM(me.cached_protein)
+        FROM meal_entries me
+        JOIN days d ON d.id = me.day_id
+        WHERE me.planned = 0 AND d.date >= ?1 AND d.date <= ?2
+        GROUP BY d.date, me.meal_type
+        ORDER BY d.date
+        "#,
+    )?;
+    let rows: Vec<(String, String, i64, f64, f64)> = stmt
+        .query_map([first, last], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut result = Vec::new();
+    for meal_type in [MealType::Breakfast, MealType::Lunch, MealType::Dinner, MealType::Snack, MealType::Unspecified] {
+        let mut entry_count = 0;
+        let mut calories = Vec::new();
+        let mut protein = Vec::new();
+        for (date, _, count, cal, prot) in rows
+            .iter()
+            .filter(|(date, mt, ..)| mt == meal_type.as_str() && analyzed.contains(date.as_str()))
+        {
+            entry_count += count;
+            calories.push(DayValue { date: date.clone(), value: *cal });
+            protein.push(DayValue { date: date.clone(), value: *prot });
+        }
+        if entry_count == 0 {
+            continue;
+        }
+
+        let sum: f64 = calories.iter().map(|d| d.value).sum();
+        result.push(MealTypeStats {
+            meal_type: meal_type.as_str().to_string(),
+            entry_count,
+            calories: calculate_stats(&calories, outlier_sd),
+            protein: calculate_stats(&protein, outlier_sd),
+            average_calories_per_day: round_to(sum / day_calories.len() as f64, 2),
+            percent_of_calories: if total_calories > 0.0 {
+                round_to(sum / total_calories * 100.0, 2)
+            } else {
+                0.0
+            },
+        });
+    }
+
+    Ok(result)
+}
+
+// ============================================================================
+// Day Tags
+// ============================================================================
+
+/// A day whose notes carry the requested tag
+#[derive(Debug, Serialize)]
+pub struct TaggedDay {
+    #[serde(flatten)]
+    pub summary: DaySummary,
+    pub notes: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Response for list_days_with_tag
+#[derive(Debug, Serialize)]
+pub struct ListDaysWithTagResponse {
+    pub tag: String,
+    pub days: Vec<TaggedDay>,
+    pub total: usize,
+}
+
+/// How often a tag is used
+#[derive(Debug, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub day_count: usize,
+    pub last_used: String,
+}
+
+/// Response for list_all_tags
+#[derive(Debug, Serialize)]
+pub struct ListAllTagsResponse {
+    /// Most used first
+    pub tags: Vec<TagCount>,
+    pub total: usize,
+}
+
+/// Hashtags in free-text notes ("#sick", "#travel"), lowercased, without '#', deduplicated in order.
+/// A tag is '#' followed by letters, digits, '_' or '-', and must not directly follow a word character.
+fn extract_tags(notes: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let chars: Vec<char> = notes.chars().collect();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let starts_tag = chars[i] == '#' && (i == 0 || !chars[i - 1].is_alphanumeric());
+        if starts_tag {
+            let tag: String = chars[i + 1..]
+                .iter()
+                .take_while(|c| c.is_alphanumeric() || **c == '_' || **c == '-')
+                .collect();
+            i += tag.chars().count();
+            let tag = tag.to_lowercase();
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        i += 1;
+    }
+
+    tags
+}
+
+/// Days whose notes contain #tag (exact tag match, case-insensitive), newest first
+pub fn list_days_with_tag(db: &Database, tag: &str) -> Result<ListDaysWithTagResponse, ToolError> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    if tag.is_empty() {
+        return Err(ToolError::Validation("Tag cannot be empty".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    // Narrow to notes with any tag, then match in Rust: SQLite's lower() only
+    // folds ASCII, and #sick mustn't match #sickly
+    let candidates = Day::list_notes_containing(&conn, "#")
+        .map_err(|e| ToolError::Db(format!("Failed to search day notes: {}", e)))?;
+
+    let mut days = Vec::new();
+    for day in candidates {
+        let tags = extract_tags(day.notes.as_deref().unwrap_or(""));
+        if tags.contains(&tag) {
+            days.push(TaggedDay {
+                summary: day_summary(&conn, &day, false)?,
+                notes: day.notes,
+                tags,
+            });
+        }
+    }
+
+    let total = days.len();
+    Ok(ListDaysWithTagResponse { tag, days, total })
+}
+
+/// Every distinct tag used in day notes with how many days use it
+pub fn list_all_tags(db: &Database) -> Result<ListAllTagsResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let days = Day::list_notes_containing(&conn, "#")
+        .map_err(|e| ToolError::Db(format!("Failed to search day notes: {}", e)))?;
+
+    let mut counts: HashMap<String, TagCount> = HashMap::new();
+    for day in &days {
+        for tag in extract_tags(day.notes.as_deref().unwrap_or("")) {
+            let entry = counts.entry(tag.clone()).or_insert_with(|| TagCount {
+                tag,
+                day_count: 0,
+                last_used: day.date.clone(),
+            });
+            entry.day_count += 1;
+            if day.date > entry.last_used {
+                entry.last_used = day.date.clone();
+            }
+        }
+    }
+
+    let mut tags: Vec<TagCount> = counts.into_values().collect();
+    tags.sort_by(|a, b| b.day_count.cmp(&a.day_count).then_with(|| a.tag.cmp(&b.tag)));
+
+    let total = tags.len();
+    Ok(ListAllTagsResponse { tags, total })
+}
+
+// ============================================================================
+// Cronometer Import
+// ============================================================================
+
+/// Response for import_cronometer_csv
+#[derive(Debug, Serialize)]
+pub struct ImportCronometerCsvResponse {
+    pub file_path: String,
+    /// "servings" or "daily_summary"
+    pub export_type: String,
+    pub rows_read: usize,
+    pub food_items_created: usize,
+    pub food_items_matched: usize,
+    pub meals_logged: usize,
+    /// Rows already logged on that day (same source, meal and amount)
+    pub duplicates: usize,
+    pub skipped: usize,
+    pub errors: Vec<String>,
+    pub days_recalculated: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_range: Option<String>,
+}
+
+/// Map a Cronometer diary group to a meal type ("Snacks" and custom groups included)
+fn cronometer_meal_type(group: &str) -> MealType {
+    match group.trim().to_lowercase().as_str() {
+        "snacks" => MealType::Snack,
+        other => MealType::from_str(other),
+    }
+}
+
+/// Split a Cronometer amount such as "150.00 g" or "1.00 cup" into quantity and unit
+fn parse_cronometer_amount(amount: &str) -> Result<(f64, String), ToolError> {
+    let amount = amount.trim();
+    let (qty, unit) = match amount.split_once(char::is_whitespace) {
+        Some((q, u)) => (q, u.trim()),
+        None => (amount, ""),
+    };
+    let qty: f64 = qty
+        .replace(',', "")
+        .parse()
+        .map_err(|_| ToolError::Validation(format!("Invalid amount: '{}'", amount)))?;
+    if qty <= 0.0 || !qty.is_finite() {
+        return Err(ToolError::Validation(format!("Amount must be greater than 0: '{}'", amount)));
+    }
+    let unit = if unit.is_empty() { "serving" } else { unit };
+    Ok((qty, unit.to_string()))
+}
+
+/// Canonical serving for a Cronometer amount: (serving_size, serving_unit, how
+/// many of those servings the amount is). None if the unit is neither a weight,
+/// a volume nor a count.
+fn cronometer_canonical_serving(quantity: f64, unit: &str) -> Option<(f64, &'static str, f64)> {
+    use crate::nutrition::{categorize_unit, to_grams, to_ml, UnitCategory};
+
+    if let Some(grams) = to_grams(quantity, unit) {
+        Some((100.0, "g", grams / 100.0))
+    } else if let Some(ml) = to_ml(quantity, unit) {
+        Some((100.0, "ml", ml / 100.0))
+    } else if categorize_unit(unit) == UnitCategory::Count {
+        Some((1.0, "count", quantity))
+    } else {
+        None
+    }
+}
+
+/// Timestamp for an imported entry: the Time column when present, otherwise a
+/// typical hour for the meal so reclassify_meals and timelines stay sensible
+fn cronometer_eaten_at(date: &str, time: Option<&str>, meal_type: &MealType) -> String {
+    use chrono::NaiveTime;
+
+    if let Some(time) = time.map(str::trim).filter(|t| !t.is_empty()) {
+        for fmt in ["%I:%M %p", "%I:%M:%S %p", "%H:%M", "%H:%M:%S"] {
+            if let Ok(t) = NaiveTime::parse_from_str(time, fmt) {
+                return format!("{}T{}", date, t.format("%H:%M:%S"));
+            }
+        }
+    }
+    let hour = match meal_type {
+        MealType::Breakfast => 8,
+        MealType::Lunch => 12,
+        MealType::Dinner => 18,
+        MealType::Snack => 15,
+        MealType::Unspecified => 12,
+    };
+    format!("{}T{:02}:00:00", date, hour)
+}
+
+/// Import a Cronometer "Servings" or "Daily Summary" CSV export.
+///
+/// Servings rows become food items (found by exact name, or created on a
+/// 100 g / 100 ml / 1 count serving with the row's nutrition scaled to it)
+/// logged under the mapped meal group. New foods whose amount is in some other
+/// unit (e.g. "large egg") are skipped. A Daily Summary has no foods, so each day is logged as one
+/// ad-hoc entry holding the day's totals. Rows already present on a day before
+/// the import are counted as duplicates, so re-importing an overlapping export
+/// is safe while repeated rows within one export are each logged.
+pub fn import_cronometer_csv(db: &Database, file_path: &str, clock: &dyn Clock) -> Result<ImportCronometerCsvResponse, ToolError> {
+    use std::collections::HashSet;
+    use crate::models::{FoodItem, FoodItemCreate};
+    use crate::nutrition::calculate_nutrition_multiplier;
+    use super::csv::split_csv_line;
+    use super::food_items::non_canonical_serving;
+
+    const DAILY_SUMMARY_DESCRIPTION: &str = "Cronometer daily total";
+
+    let content = std::fs::read_to_string(file_path)
+        .map_err(|e| ToolError::Io(format!("Failed to open file '{}': {}", file_path, e)))?;
+    let mut lines = content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+
+    let header: HashMap<String, usize> = match lines.next() {
+        Some((_, line)) => split_csv_line(line.trim_start_matches('\u{feff}'))
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| (name.trim().to_lowercase(), i))
+            .collect(),
+        None => return Err(ToolError::Validation("File is empty".to_string())),
+    };
+
+    let is_servings = header.contains_key("food name");
+    let date_col = if is_servings { "day" } else { "date" };
+    for required in [date_col, "energy (kcal)"] {
+        if !header.contains_key(required) {
+            return Err(ToolError::Validation(format!(
+                "Missing column '{}' - is this a Cronometer Servings or Daily Summary export?",
+                required
+            )));
+        }
+    }
+    if is_servings && !header.contains_key("amount") {
+        return Err(ToolError::Validation("Missing column 'amount' in Cronometer Servings export".to_string()));
+    }
+
+    let mut response = ImportCronometerCsvResponse {
+        file_path: file_path.to_string(),
+        export_type: if is_servings { "servings" } else { "daily_summary" }.to_string(),
+        rows_read: 0,
+        food_items_created: 0,
+        food_items_matched: 0,
+        meals_logged: 0,
+        duplicates: 0,
+        skipped: 0,
+        errors: Vec::new(),
+        days_recalculated: 0,
+        date_range: None,
+    };
+    let mut first_date: Option<String> = None;
+    let mut last_date: Option<String> = None;
+
+    db.transaction(|conn| -> Result<(), ToolError> {
+        let mut touched_days: HashSet<i64> = HashSet::new();
+        let mut matched_items: HashSet<i64> = HashSet::new();
+        // Each day's entries from before this import, so identical rows within
+        // one export are all logged but a re-import finds them
+        let mut existing_by_day: HashMap<i64, Vec<MealEntry>> = HashMap::new();
+
+        for (line_num, line) in lines {
+            let row_num = line_num + 1;
+            response.rows_read += 1;
+            let fields = split_csv_line(line);
+            let text = |col: &str| -> Option<&str> {
+                header
+                    .get(col)
+                    .and_then(|&i| fields.get(i))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+            };
+            let number = |col: &str| -> Result<f64, String> {
+                text(col)
+                    .map(|v| v.parse::<f64>().map_err(|_| format!("Invalid {}: {}", col, v)))
+                    .transpose()
+                    .map(|v| v.unwrap_or(0.0))
+            };
+            let mut skip = |message: String| {
+                response.errors.push(format!("Row {}: {}", row_num, message));
+                response.skipped += 1;
+            };
+
+            let date = match text(date_col) {
+                Some(d) if chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok() => d.to_string(),
+                Some(d) => {
+                    skip(format!("Invalid date: {}", d));
+                    continue;
+                }
+                None => {
+                    skip("Missing date".to_string());
+                    continue;
+                }
+            };
+
+            let nutrition = (|| -> Result<Nutrition, String> {
+                Ok(Nutrition {
+                    calories: number("energy (kcal)")?,
+                    protein: number("protein (g)")?,
+                    carbs: number("carbs (g)")?,
+                    fat: number("fat (g)")?,
+                    fiber: number("fiber (g)")?,
+                    sodium: number("sodium (mg)")?,
+                    sugar: number("sugars (g)")?,
+                    saturated_fat: number("saturated (g)")?,
+                    cholesterol: number("cholesterol (mg)")?,
+                })
+            })();
+            let nutrition = match nutrition {
+                Ok(n) => n,
+                Err(e) => {
+                    skip(e);
+                    continue;
+                }
+            };
+
+            let day = Day::get_or_create(conn, &date)
+                .map_err(|e| ToolError::Db(format!("Row {}: Failed to get/create day: {}", row_num, e)))?;
+            let existing = match existing_by_day.entry(day.id) {
+                std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                std::collections::hash_map::Entry::Vacant(entry) => entry.insert(
+                    MealEntry::get_for_day(conn, day.id)
+                        .map_err(|e| ToolError::Db(format!("Row {}: Failed to load meal entries: {}", row_num, e)))?,
+                ),
+            };
+
+            let data = if is_servings {
+                let name = match text("food name") {
+                    Some(n) => n.to_string(),
+                    None => {
+                        skip("Missing food name".to_string());
+                        continue;
+                    }
+                };
+                let (quantity, unit) = match parse_cronometer_amount(text("amount").unwrap_or("")) {
+                    Ok(a) => a,
+                    Err(e) => {
+                        skip(e.to_string());
+                        continue;
+                    }
+                };
+                let meal_type = cronometer_meal_type(text("group").unwrap_or(""));
+
+                let found = FoodItem::find_by_name_brand(conn, &name, None)
+                    .map_err(|e| ToolError::Db(format!("Row {}: Failed to look up food item: {}", row_num, e)))?;
+                let (food_item_id, servings) = match found {
+                    Some(item) => {
+                        if matched_items.insert(item.id) {
+                            response.food_items_matched += 1;
+                        }
+                        let servings = calculate_nutrition_multiplier(
+                            quantity,
+                            &unit,
+                            item.serving_size,
+                            &item.serving_unit,
+                            item.grams_per_serving,
+                            item.ml_per_serving,
+                        );
+                        (item.id, servings)
+                    }
+                    None => {
+                        let Some((serving_size, serving_unit, servings)) = cronometer_canonical_serving(quantity, &unit) else {
+                            let problem = non_canonical_serving(quantity, &unit).unwrap_or_default();
+                            skip(format!("Can't create food item '{}' from amount {} {}: {}", name, quantity, unit, problem));
+                            continue;
+                        };
+                        let per_serving = nutrition.scale(1.0 / servings);
+                        let item = FoodItem::create(conn, &FoodItemCreate {
+                            name,
+                            brand: None,
+                            serving_size,
+                            serving_unit: serving_unit.to_string(),
+                            calories: per_serving.calories,
+                            protein: per_serving.protein,
+                            carbs: per_serving.carbs,
+                            fat: per_serving.fat,
+                            fiber: per_serving.fiber,
+                            sodium: per_serving.sodium,
+                            sugar: per_serving.sugar,
+                            saturated_fat: per_serving.saturated_fat,
+                            cholesterol: per_serving.cholesterol,
+                            preference: Default::default(),
+                            notes: Some("Imported from Cronometer".to_string()),
+                            base_unit_type: None,
+                            grams_per_serving: None,
+                            ml_per_serving: None,
+                        })
+                        .map_err(|e| ToolError::Db(format!("Row {}: Failed to create food item: {}", row_num, e)))?;
+                        matched_items.insert(item.id);
+                        response.food_items_created += 1;
+                        (item.id, servings)
+                    }
+                };
+
+                let duplicate = existing.iter().any(|e| {
+                    e.food_item_id == Some(food_item_id)
+                        && e.meal_type == meal_type
+                        && (e.servings - servings).abs() < 1e-6
+                });
+                if duplicate {
+                    response.duplicates += 1;
+                    continue;
+                }
+
+                MealEntryCreate {
+                    day_id: day.id,
+                    eaten_at: Some(cronometer_eaten_at(&date, text("time"), &meal_type)),
+                    meal_type,
+                    recipe_id: None,
+                    food_item_id: Some(food_item_id),
+                    description: None,
+                    inline_nutrition: None,
+                    servings,
+                    percent_eaten: None,
+                    notes: Some("Cronometer import".to_string()),
+                    planned: false,
+                }
+            } else {
+                let duplicate = existing
+                    .iter()
+                    .any(|e| e.description.as_deref() == Some(DAILY_SUMMARY_DESCRIPTION));
+                if duplicate {
+                    response.duplicates += 1;
+                    continue;
+                }
+
+                MealEntryCreate {
+                    day_id: day.id,
+                    eaten_at: Some(cronometer_eaten_at(&date, None, &MealType::Unspecified)),
+                    meal_type: MealType::Unspecified,
+                    recipe_id: None,
+                    food_item_id: None,
+                    description: Some(DAILY_SUMMARY_DESCRIPTION.to_string()),
+                    inline_nutrition: Some(nutrition),
+                    servings: 1.0,
+                    percent_eaten: None,
+                    notes: Some("Cronometer import".to_string()),
+                    planned: false,
+                }
+            };
+
+            MealEntry::insert(conn, &data, clock)
+                .map_err(|e| ToolError::Db(format!("Row {}: Failed to log meal: {}", row_num, e)))?;
+            response.meals_logged += 1;
+            touched_days.insert(day.id);
+
+            if first_date.as_ref().is_none_or(|d| date < *d) {
+                first_date = Some(date.clone());
+            }
+            if last_date.as_ref().is_none_or(|d| date > *d) {
+                last_date = Some(date);
+            }
+        }
+
+        for day_id in &touched_days {
+            recalculate_day_nutrition(conn, *day_id)
+                .map_err(|e| ToolError::Db(format!("Failed to recalculate day nutrition: {}", e)))?;
+        }
+        response.days_recalculated = touched_days.len();
+        Ok(())
+    })?;
+
+    response.date_range = first_date.zip(last_date).map(|(a, b)| format!("{} to {}", a, b));
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::test_support::{make_food, temp_db};
+
+    #[test]
+    fn test_list_days_stats_rejects_bad_outlier_sd() {
+        let db = temp_db("days-stats-outlier-sd");
+
+        assert!(list_days_stats(&db, None, None, 2.0, false).is_ok());
+        for outlier_sd in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(list_days_stats(&db, None, None, outlier_sd, false), Err(ToolError::Validation(_))));
+        }
+    }
+
+    #[test]
+    fn test_normalize_eaten_at() {
+        assert_eq!(normalize_eaten_at("2026-01-06", "12:30").unwrap(), "2026-01-06T12:30:00");
+        assert_eq!(normalize_eaten_at("2026-01-06", "2026-01-05 23:15").unwrap(), "2026-01-05T23:15:00");
+        assert_eq!(normalize_eaten_at("2026-01-06", "2026-01-06T07:00:00").unwrap(), "2026-01-06T07:00:00");
+        assert!(!normalize_eaten_at("2026-01-06", "2026-01-06T07:00:00Z").unwrap().ends_with('Z'));
+        assert!(normalize_eaten_at("2026-01-06", "2026-01-06").is_err());
+        assert!(normalize_eaten_at("2026-01-06", "noonish").is_err());
+    }
+
+    #[test]
+    fn test_log_meal_defaults_eaten_at_from_clock() {
+        use crate::clock::FixedClock;
+
+        let db = temp_db("log-meal-clock");
+
+        let food = db.with_conn(|conn| Ok(make_food(conn, "Apple", serde_json::json!({
+            "serving_size": 1.0,
+            "serving_unit": "count",
+            "calories": 95.0
+        })))).unwrap();
+
+        let clock = FixedClock(chrono::NaiveDate::from_ymd_opt(2026, 3, 2).unwrap().and_hms_opt(9, 15, 0).unwrap().and_utc());
+        let logged = log_meal(&db, LogMealRequest {
+            date: "2026-03-02".to_string(),
+            meal_type: "snack".to_string(),
+            food_item_id: Some(food.id),
+            servings: 1.0,
+            ..Default::default()
+        }, &clock).unwrap();
+        assert_eq!(logged.eaten_at.as_deref(), Some("2026-03-02T09:15:00"));
+    }
+
+    #[test]
+    fn test_quick_log_matches_name_case_insensitively() {
+        let db = temp_db("quick-log");
+
+        let request = |food_name: &str, new_item: Option<QuickLogNewItem>| QuickLogRequest {
+            date: "2026-03-01".to_string(),
+            meal_type: "breakfast".to_string(),
+            food_name: food_name.to_string(),
+            quantity: 2.0,
+            unit: "count".to_string(),
+            new_item,
+        };
+        let egg = QuickLogNewItem {
+            brand: None,
+            serving_size: 1.0,
+            serving_unit: "count".to_string(),
+            nutrition: Nutrition { calories: 70.0, protein: 6.0, ..Nutrition::zero() },
+        };
+
+        let created = quick_log(&db, request("Egg", Some(egg.clone())), &SystemClock).unwrap();
+        assert!(created.created);
+        assert_eq!(created.meal_entry.servings, 2.0);
+
+        // A branded item with the same name doesn't displace the unbranded one
+        db.with_conn(|conn| Ok(make_food(conn, "EGG", serde_json::json!({
+            "brand": "Farm Fresh",
+            "serving_size": 1.0,
+            "serving_unit": "count",
+            "calories": 80.0
+        })))).unwrap();
+
+        let exact = quick_log(&db, request("eGG", Some(egg)), &SystemClock).unwrap();
+        assert!(!exact.created);
+        assert_eq!(exact.food_item_id, created.food_item_id);
+
+        match quick_log(&db, request("Eg", None), &SystemClock) {
+            Err(ToolError::NotFound(message)) => assert!(message.contains("similar: ") && message.contains("Egg")),
+            other => panic!("expected NotFound, got {:?}", other.map(|r| r.food_item_id)),
+        }
+    }
+
+    #[test]
+    fn test_quick_log_converts_units_and_rolls_back() {
+        use crate::models::FoodItem;
+
+        let db = temp_db("quick-log-units");
+
+        let request = |food_name: &str, quantity: f64, unit: &str, new_item: Option<QuickLogNewItem>| QuickLogRequest {
+            date: "2026-03-01".to_string(),
+            meal_type: "lunch".to_string(),
+            food_name: food_name.to_string(),
+            quantity,
+            unit: unit.to_string(),
+            new_item,
+        };
+        let per_100g = |calories: f64| QuickLogNewItem {
+            brand: None,
+            serving_size: 100.0,
+            serving_unit: "g".to_string(),
+            nutrition: Nutrition { calories, ..Nutrition::zero() },
+        };
+
+        let rice = quick_log(&db, request("Rice", 150.0, "g", Some(per_100g(130.0))), &SystemClock).unwrap();
+        assert!(rice.created);
+        assert_eq!((rice.meal_entry.servings, rice.meal_entry.nutrition.calories), (1.5, 195.0));
+        assert_eq!(rice.meal_entry.meal_type, "lunch");
+
+        let again = quick_log(&db, request("rice", 2.0, "servings", None), &SystemClock).unwrap();
+        assert_eq!((again.created, again.meal_entry.servings), (false, 2.0));
+
+        // Rice has a gram weight but no volume, and isn't counted
+        for unit in ["cup", "count", "handful"] {
+            assert!(matches!(quick_log(&db, request("Rice", 1.0, unit, None), &SystemClock), Err(ToolError::Validation(_))));
+        }
+
+        // The new item is created inside the transaction, so a unit it can't take leaves nothing behind
+        assert!(matches!(quick_log(&db, request("Banana", 1.0, "count", Some(per_100g(89.0))), &SystemClock), Err(ToolError::Validation(_))));
+        assert!(db.with_conn(|conn| FoodItem::find_by_name_brand(conn, "Banana", None)).unwrap().is_none());
+
+        for bad in [request("Rice", 0.0, "g", None), request(" ", 1.0, "g", None), QuickLogRequest { date: "March 1".to_string(), ..request("Rice", 1.0, "g", None) }] {
+            assert!(matches!(quick_log(&db, bad, &SystemClock), Err(ToolError::Validation(_))));
+        }
+
+        let day = db.with_conn(|conn| Day::get_by_date(conn, "2026-03-01")).unwrap().unwrap();
+        assert_eq!(day.cached_nutrition.calories, 455.0);
+    }
+
+    #[test]
+    fn test_split_meal_entry_recalculates_day_and_checks_tolerance() {
+        use crate::models::MealEntryCreate;
+
+        let db = temp_db("split-meal");
+
+        // A 300 kcal combined breakfast plus a 100 kcal snack that the split leaves alone
+        let (oats, milk, breakfast) = db.with_conn(|conn| {
+            let oats = make_food(conn, "Oats", serde_json::json!({ "calories": 100.0 }));
+            let milk = make_food(conn, "Milk", serde_json::json!({ "calories": 60.0 }));
+            let day = Day::get_or_create(conn, "2026-03-01")?;
+            let entry = |meal_type: MealType, calories: f64| MealEntry::create(conn, &MealEntryCreate {
+                day_id: day.id, meal_type, recipe_id: None, food_item_id: None,
+                description: Some("Combined".to_string()), inline_nutrition: Some(Nutrition { calories, ..Nutrition::zero() }),
+                servings: 1.0, percent_eaten: None, notes: Some("From photo".to_string()),
+                eaten_at: Some("2026-03-01T08:00:00".to_string()), planned: false,
+            }, &SystemClock);
+            let breakfast = entry(MealType::Breakfast, 300.0)?;
+            entry(MealType::Snack, 100.0)?;
+            Ok((oats.id, milk.id, breakfast.id))
+        }).unwrap();
+        let part = |food_item_id: i64, servings: f64| SplitPart { recipe_id: None, food_item_id: Some(food_item_id), servings, percent_eaten: None };
+
+        // 2 x 100 + 1.5 x 60 = 290 kcal: 10 kcal short, inside a 5% (15 kcal) tolerance
+        let split = split_meal_entry(&db, breakfast, &[part(oats, 2.0), part(milk, 1.5)], 5.0, &SystemClock).unwrap().unwrap();
+        assert_eq!((split.parts_nutrition.calories, split.calorie_difference), (290.0, -10.0));
+        assert!(split.within_tolerance && split.warning.is_none());
+        assert_eq!(split.day_nutrition.calories, 390.0);
+        for entry in &split.new_entries {
+            assert_eq!((entry.meal_type.clone(), entry.eaten_at.as_deref(), entry.notes.as_deref()), (MealType::Breakfast, Some("2026-03-01T08:00:00"), Some("From photo")));
+        }
+
+        db.with_conn(|conn| {
+            let day = Day::get_by_date(conn, "2026-03-01")?.unwrap();
+            assert_eq!(day.cached_nutrition.calories, 390.0);
+            assert!(MealEntry::get_by_id(conn, breakfast)?.is_none());
+            assert_eq!(MealEntry::get_for_day(conn, day.id)?.len(), 3);
+            Ok(())
+        }).unwrap();
+
+        // Splitting the 200 kcal oats entry into 300 kcal is over 10% but still applied, with a warning
+        let oats_entry = split.new_entries[0].id;
+        let over = split_meal_entry(&db, oats_entry, &[part(oats, 3.0)], 10.0, &SystemClock).unwrap().unwrap();
+        assert!(!over.within_tolerance);
+        assert!(over.warning.unwrap().contains("over the 10% tolerance"));
+        assert_eq!(over.day_nutrition.calories, 490.0);
+
+        assert!(split_meal_entry(&db, oats_entry, &[part(oats, 1.0)], 5.0, &SystemClock).unwrap().is_none());
+        assert!(matches!(split_meal_entry(&db, over.new_entries[0].id, &[part(oats, 1.0)], -1.0, &SystemClock), Err(ToolError::Validation(_))));
+    }
+
+    #[test]
+    fn test_extract_tags() {
+        assert_eq!(extract_tags("Felt off #sick, ate light. #Travel #sick"), vec!["sick", "travel"]);
+        assert_eq!(extract_tags("#low-carb day #day_2"), vec!["low-carb", "day_2"]);
+    }
+
+    #[test]
+    fn test_list_days_with_tag_folds_unicode_case() {
+        use crate::models::DayUpdate;
+
+        let db = temp_db("days-unicode-tag");
+        db.with_conn(|conn| {
+            for (date, notes) in [("2024-03-01", "Dinner at #Café Rouge"), ("2024-03-02", "#cafés all day")] {
+                let day = Day::get_or_create(conn, date)?;
+                Day::update(conn, day.id, &DayUpdate { notes: Some(notes.to_string()) })?;
+            }
+            Ok(())
+        }).unwrap();
+
+        let found = list_days_with_tag(&db, "CAFÉ").unwrap();
+        assert_eq!(found.tag, "café");
+        assert_eq!(found.days.iter().map(|d| d.summary.date.as_str()).collect::<Vec<_>>(), vec!["2024-03-01"]);
+    }
+
+    #[test]
+    fn test_extract_tags_ignores_non_tags() {
+        assert!(extract_tags("Ate item#3 and a # alone").is_empty());
+        assert!(extract_tags("").is_empty());
+    }
+
+    #[test]
+    fn test_score_quality() {
+        let goals = DayGoals { calories: 2000.0, protein: 100.0, sodium: 2000.0 };
+        let weights = QualityWeights { calories: 1.0, protein: 1.0, sodium: 1.0, fiber: 1.0 };
+        let n = |calories: f64, protein: f64, sodium: f64, fiber: f64| Nutrition { calories, protein, sodium, fiber, ..Default::default() };
+
+        let (score, _) = score_quality(&n(1800.0, 120.0, 1500.0, 30.0), &goals, 25.0, &weights).unwrap();
+        assert_eq!(score, 100.0);
+
+        // 2500 kcal is halfway to 150% (0.5), half the protein (0.5), 3000 mg sodium (0.5), no fiber (0)
+        let (score, components) = score_quality(&n(2500.0, 50.0, 3000.0, 0.0), &goals, 25.0, &weights).unwrap();
+        assert_eq!(score, 37.5);
+        assert_eq!(components.iter().map(|c| c.points).collect::<Vec<_>>(), vec![12.5, 12.5, 12.5, 0.0]);
+
+        let zero = QualityWeights { calories: 0.0, protein: 0.0, sodium: 0.0, fiber: 0.0 };
+        assert!(score_quality(&n(0.0, 0.0, 0.0, 0.0), &goals, 25.0, &zero).is_err());
+    }
+
+    #[test]
+    fn test_parse_cronometer_amount() {
+        assert_eq!(parse_cronometer_amount("150.00 g").unwrap(), (150.0, "g".to_string()));
+        assert_eq!(parse_cronometer_amount("1.00 large egg").unwrap(), (1.0, "large egg".to_string()));
+        assert_eq!(parse_cronometer_amount("2").unwrap(), (2.0, "serving".to_string()));
+        assert!(parse_cronometer_amount("0.00 g").is_err());
+        assert!(parse_cronometer_amount("abc").is_err());
+        assert_eq!(cronometer_meal_type("Snacks"), MealType::Snack);
+        assert_eq!(cronometer_meal_type("Uncategorized"), MealType::Unspecified);
+    }
+
+    #[test]
+    fn test_import_cronometer_csv_stores_canonical_servings() {
+        use crate::models::FoodItem;
+
+        let db = temp_db("cronometer-units");
+        let csv_path = std::env::temp_dir().join(format!("uhm-test-cronometer-{}.csv", std::process::id()));
+        std::fs::write(&csv_path, "\
+Day,Group,Food Name,Amount,Energy (kcal),Protein (g)
+2026-03-01,Breakfast,Oats,50.00 g,190,6.5
+2026-03-01,Breakfast,Milk,1.00 cup,120,8
+2026-03-01,Breakfast,Egg,1.00 large egg,70,6
+2026-03-01,Lunch,Oats,100.00 g,380,13
+").unwrap();
+
+        let result = import_cronometer_csv(&db, csv_path.to_str().unwrap(), &SystemClock).unwrap();
+        let _ = std::fs::remove_file(&csv_path);
+        assert_eq!((result.food_items_created, result.food_items_matched, result.meals_logged), (2, 0, 3));
+        // "large egg" is no weight, volume or count, so the egg isn't guessed at
+        assert_eq!(result.skipped, 1);
+        assert!(result.errors[0].starts_with("Row 4: Can't create food item 'Egg'"));
+
+        db.with_conn(|conn| {
+            let oats = FoodItem::find_by_name_brand(conn, "Oats", None)?.unwrap();
+            assert_eq!((oats.serving_size, oats.serving_unit.as_str(), oats.nutrition.calories), (100.0, "g", 380.0));
+            let milk = FoodItem::find_by_name_brand(conn, "Milk", None)?.unwrap();
+            assert_eq!((milk.serving_size, milk.serving_unit.as_str()), (100.0, "ml"));
+            assert_eq!(milk.ml_per_serving, Some(100.0));
+            assert!((milk.nutrition.calories - 120.0 / 2.36588).abs() < 0.1);
+            assert!(FoodItem::find_by_name_brand(conn, "Egg", None)?.is_none());
+
+            let day = Day::get_by_date(conn, "2026-03-01")?.unwrap();
+            let servings: Vec<f64> = MealEntry::get_for_day(conn, day.id)?.iter().map(|e| round_to(e.servings, 3)).collect();
+            assert_eq!(servings, vec![0.5, 2.366, 1.0]);
+            assert!((day.cached_nutrition.calories - 690.0).abs() < 1e-6);
+            Ok(())
+        }).unwrap();
+    }
+
+    #[test]
+    fn test_import_cronometer_csv_repeated_rows_and_reimport() {
+        let db = temp_db("cronometer-reimport");
+        let csv_path = std::env::temp_dir().join(format!("uhm-test-cronometer-reimport-{}.csv", std::process::id()));
+        // Two identical snacks on one day are two separate servings
+        std::fs::write(&csv_path, "\
+Day,Time,Group,Food Name,Amount,Energy (kcal)
+2026-03-01,10:00 AM,Snacks,Apple,1.00 count,95
+2026-03-01,3:00 PM,Snacks,Apple,1.00 count,95
+2026-03-02,,Lunch,Rice,200.00 g,260
+").unwrap();
+
+        let first = import_cronometer_csv(&db, csv_path.to_str().unwrap(), &SystemClock).unwrap();
+        assert_eq!(first.export_type, "servings");
+        assert_eq!((first.meals_logged, first.duplicates, first.days_recalculated), (3, 0, 2));
+        assert_eq!((first.food_items_created, first.food_items_matched), (2, 0));
+        assert_eq!(first.date_range.as_deref(), Some("2026-03-01 to 2026-03-02"));
+
+        let second = import_cronometer_csv(&db, csv_path.to_str().unwrap(), &SystemClock).unwrap();
+        let _ = std::fs::remove_file(&csv_path);
+        assert_eq!((second.meals_logged, second.duplicates, second.days_recalculated), (0, 3, 0));
+        assert_eq!((second.food_items_created, second.food_items_matched), (0, 2));
+
+        db.with_conn(|conn| {
+            let day = Day::get_by_date(conn, "2026-03-01")?.unwrap();
+            let eaten_at: Vec<Option<String>> = MealEntry::get_for_day(conn, day.id)?.into_iter().map(|e| e.eaten_at).collect();
+            assert_eq!(eaten_at, vec![Some("2026-03-01T10:00:00".to_string()), Some("2026-03-01T15:00:00".to_string())]);
+            assert!((day.cached_nutrition.calories - 190.0).abs() < 1e-6);
+            Ok(())
+        }).unwrap();
+    }
+
+    #[test]
+    fn test_import_cronometer_csv_daily_summary() {
+        let db = temp_db("cronometer-summary");
+        let csv_path = std::env::temp_dir().join(format!("uhm-test-cronometer-summary-{}.csv", std::process::id()));
+        std::fs::write(&csv_path, "\
+Date,Energy (kcal),Protein (g),Sodium (mg)
+2026-03-01,1850,95,2100
+2026-03-02,2010,110,
+March 3,1900,90,2000
+").unwrap();
+
+        let first = import_cronometer_csv(&db, csv_path.to_str().unwrap(), &SystemClock).unwrap();
+        assert_eq!(first.export_type, "daily_summary");
+        assert_eq!((first.rows_read, first.meals_logged, first.skipped), (3, 2, 1));
+        assert!(first.errors[0].starts_with("Row 4: Invalid date"));
+
+        let second = import_cronometer_csv(&db, csv_path.to_str().unwrap(), &SystemClock).unwrap();
+        let _ = std::fs::remove_file(&csv_path);
+        assert_eq!((second.meals_logged, second.duplicates), (0, 2));
+
+        db.with_conn(|conn| {
+            let day = Day::get_by_date(conn, "2026-03-01")?.unwrap();
+            let entries = MealEntry::get_for_day(conn, day.id)?;
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].description.as_deref(), Some("Cronometer daily total"));
+            assert_eq!(entries[0].meal_type, MealType::Unspecified);
+            let n = &day.cached_nutrition;
+            assert_eq!((n.calories, n.protein, n.sodium), (1850.0, 95.0, 2100.0));
+
+            let day = Day::get_by_date(conn, "2026-03-02")?.unwrap();
+            assert_eq!((day.cached_nutrition.calories, day.cached_nutrition.sodium), (2010.0, 0.0));
+            Ok(())
+        }).unwrap();
+    }
+
+    #[test]
+    fn test_day_sodium_breakdown_expands_recipes() {
+        use crate::models::{Recipe, RecipeCreate, RecipeIngredient, RecipeIngredientCreate};
+
+        let db = temp_db("sodium-breakdown");
+
+        db.with_conn(|conn| {
+            let salt = make_food(conn, "Salt", serde_json::json!({ "serving_size": 1.0, "sodium": 400.0 }));
+            let broth = make_food(conn, "Broth", serde_json::json!({ "sodium": 200.0 }));
+
+            // 2 servings of soup: 800 mg from salt, 200 mg from broth
+            let soup = Recipe::create(conn, &RecipeCreate {
+                name: "Soup".to_string(),
+                servings_produced: 2.0,
+                yield_grams: None,
+                is_favorite: false,
+                notes: None,
+            })?;
+            for (food_item_id, quantity) in [(salt.id, 2.0), (broth.id, 100.0)] {
+                RecipeIngredient::create(conn, &RecipeIngredientCreate {
+                    recipe_id: soup.id,
+                    food_item_id,
+                    quantity,
+                    unit: "g".to_string(),
+                    notes: None,
+                })?;
+            }
+
+            let day = Day::get_or_create(conn, "2026-03-01")?;
+            let log = |recipe_id: Option<i64>, food_item_id: Option<i64>, percent_eaten: f64, planned: bool| {
+                MealEntry::create(conn, &MealEntryCreate {
+                    day_id: day.id,
+                    meal_type: MealType::Lunch,
+                    recipe_id,
+                    food_item_id,
+                    description: None,
+                    inline_nutrition: None,
+                    servings: 1.0,
+                    percent_eaten: Some(percent_eaten),
+                    notes: None,
+                    eaten_at: None,
+                    planned,
+                }, &SystemClock)
+            };
+            log(Some(soup.id), None, 50.0, false)?;
+            log(None, Some(broth.id), 100.0, false)?;
+            log(None, Some(salt.id), 100.0, true)?;
+            MealEntry::create(conn, &MealEntryCreate {
+                day_id: day.id,
+                meal_type: MealType::Snack,
+                recipe_id: None,
+                food_item_id: None,
+                description: Some("Chips".to_string()),
+                inline_nutrition: Some(Nutrition { sodium: 50.0, ..Nutrition::zero() }),
+                servings: 1.0,
+                percent_eaten: None,
+                notes: None,
+                eaten_at: None,
+                planned: false,
+            }, &SystemClock)?;
+            Ok(())
+        }).unwrap();
+
+        let result = day_sodium_breakdown(&db, "2026-03-01", None).unwrap();
+        assert_eq!(result.total_sodium_mg, 500.0);
+        let summary: Vec<(&str, f64, f64, usize)> = result.sources
+            .iter()
+            .map(|s| (s.name.as_str(), s.sodium_mg, s.percent_of_day, s.entry_count))
+            .collect();
+        assert_eq!(summary, vec![("Broth", 250.0, 50.0, 2), ("Salt", 200.0, 40.0, 1), ("Chips", 50.0, 10.0, 1)]);
+        assert_eq!(result.sources[2].food_item_id, None);
+
+        assert_eq!(day_sodium_breakdown(&db, "2026-03-01", Some(1)).unwrap().sources.len(), 1);
+        assert!(matches!(day_sodium_breakdown(&db, "2026-03-02", None), Err(ToolError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_calorie_balance() {
+        use crate::models::{Vital, VitalCreate, VitalType};
+
+        let db = temp_db("calorie-balance");
+
+        db.with_conn(|conn| {
+            // 03-02 has no day at all and 03-03 has nothing logged; neither counts
+            for (date, calories) in [("2026-03-01", 2000.0), ("2026-03-03", 0.0), ("2026-03-04", 2200.0)] {
+                conn.execute("INSERT INTO days (date, cached_calories) VALUES (?1, ?2)", rusqlite::params![date, calories])?;
+            }
+            for (timestamp, value1, unit) in [("2026-03-01T07:00:00", 80.0, "kg"), ("2026-03-04T07:00:00", 175.0, "lbs")] {
+                Vital::create(conn, &VitalCreate {
+                    vital_type: VitalType::Weight, timestamp: Some(timestamp.to_string()), value1, value2: None,
+                    unit: Some(unit.to_string()), group_id: None, notes: None, custom_type_id: None,
+                }, &SystemClock)?;
+            }
+            Ok(())
+        }).unwrap();
+
+        let result = calorie_balance(&db, "2026-03-01", "2026-03-04", Some(2500.0)).unwrap();
+        assert_eq!((result.days_in_range, result.days_logged), (4, 2));
+        assert_eq!(result.gross_calories, 4200.0);
+        assert_eq!(result.average_daily_calories, Some(2100.0));
+        assert_eq!(result.expenditure, Some(5000.0));
+        assert_eq!(result.net_balance, Some(-800.0));
+        assert_eq!(result.predicted_weight_change_lbs, Some(-0.2));
+        // 80 kg is 176.4 lbs
+        assert_eq!(result.first_weight.as_ref().map(|w| w.lbs), Some(176.4));
+        assert_eq!(result.actual_weight_change_lbs, Some(-1.4));
+        // (4200 + 1.4 * 3500) / 2 days
+        assert_eq!(result.implied_tdee, Some(4550.0));
+
+        // Without tdee only intake and the actual change are reported
+        let result = calorie_balance(&db, "2026-03-01", "2026-03-04", None).unwrap();
+        assert_eq!((result.expenditure, result.net_balance, result.predicted_weight_change_lbs), (None, None, None));
+        assert_eq!(result.implied_tdee, Some(4550.0));
+
+        // A range with no intake and a single weight has nothing to compare
+        let result = calorie_balance(&db, "2026-03-02", "2026-03-04", Some(2500.0)).unwrap();
+        assert_eq!(result.days_logged, 1);
+        assert!(result.last_weight.is_none());
+        assert_eq!((result.actual_weight_change_lbs, result.implied_tdee), (None, None));
+        let empty = calorie_balance(&db, "2026-02-01", "2026-02-28", None).unwrap();
+        assert_eq!((empty.days_logged, empty.average_daily_calories), (0, None));
+
+        assert!(matches!(calorie_balance(&db, "2026-03-04", "2026-03-01", None), Err(ToolError::Validation(_))));
+        assert!(matches!(calorie_balance(&db, "2026-03-01", "2026-03-04", Some(0.0)), Err(ToolError::Validation(_))));
+    }
+
+    #[test]
+    fn test_list_days_by_meal_type() {
+        let db = temp_db("list-days-meal-type");
+
+        let food = db.with_conn(|conn| Ok(make_food(conn, "Cracker", serde_json::json!({
+            "serving_size": 1.0,
+            "serving_unit": "count",
+            "calories": 100.0,
+            "protein": 2.0
+        })))).unwrap();
+        for (meal_type, servings, planned) in [("breakfast", 1.0, false), ("snack", 3.0, false), ("dinner", 5.0, true)] {
+            log_meal(&db, LogMealRequest {
+                date: "2026-03-01".to_string(),
+                meal_type: meal_type.to_string(),
+                food_item_id: Some(food.id),
+                servings,
+                planned,
+                ..Default::default()
+            }, &SystemClock).unwrap();
+        }
+
+        assert!(list_days(&db, None, None, 10, 0, false).unwrap().days[0].meal_types.is_none());
+
+        let day = list_days(&db, None, None, 10, 0, true).unwrap().days.remove(0);
+        let totals: Vec<(String, usize, f64, f64)> = day.meal_types.unwrap()
+            .into_iter()
+            .map(|t| (t.meal_type, t.entry_count, t.calories, t.percent_of_calories))
+            .collect();
+        // The planned dinner is not eaten yet
+        assert_eq!(totals, vec![
+            ("breakfast".to_string(), 1, 100.0, 25.0),
+            ("snack".to_string(), 1, 300.0, 75.0),
+        ]);
+    }
+}
@@ -0,0 +1,54 @@
+//! Error type shared by the tool functions
+//!
+//! Lets the MCP server tell a bad request (unknown id, invalid argument) apart
+//! from a failure on our side (database, file system).
+
+use thiserror::Error;
+
+use crate::db::DbError;
+
+/// Tool error types
+#[derive(Debug, Error)]
+pub enum ToolError {
+    /// The requested record doesn't exist
+    #[error("{0}")]
+    NotFound(String),
+
+    /// An argument or input file failed validation
+    #[error("{0}")]
+    Validation(String),
+
+    /// A database failure; reported as an MCP internal_error, not invalid_params
+    #[error("{0}")]
+    Db(String),
+
+    /// A file system failure; reported as an MCP internal_error, not invalid_params
+    #[error("{0}")]
+    Io(String),
+}
+
+impl ToolError {
+    /// Wrap a database failure with context. A stored value that can't be
+    /// decoded (e.g. malformed JSON) is bad data rather than a fault on our
+    /// side, so it is reported as a validation error.
+    pub fn db(context: &str, e: DbError) -> Self {
+        match e {
+            DbError::Sqlite(rusqlite::Error::FromSqlConversionFailure(..)) => {
+                ToolError::Validation(format!("{}: {}", context, e))
+            }
+            e => ToolError::Db(format!("{}: {}", context, e)),
+        }
+    }
+}
+
+impl From<DbError> for ToolError {
+    fn from(e: DbError) -> Self {
+        ToolError::db("Database error", e)
+    }
+}
+
+impl From<rusqlite::Error> for ToolError {
+    fn from(e: rusqlite::Error) -> Self {
+        DbError::from(e).into()
+    }
+}
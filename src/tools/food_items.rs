@@ -2,11 +2,14 @@
 //!
 //! Tools for managing food items in the database.
 
+use std::collections::HashMap;
 use serde::Serialize;
 
 use crate::db::Database;
-use crate::models::{FoodItem, FoodItemCreate, FoodItemUpdate, Preference};
-use crate::nutrition::BaseUnitType;
+use crate::models::{FoodItem, FoodItemCreate, FoodItemUpdate, Nutrition, Preference};
+use crate::nutrition::{categorize_unit, BaseUnitType, UnitCategory};
+use super::error::ToolError;
+use super::numeric::round_to;
 
 /// Response for add_food_item
 #[derive(Debug, Serialize)]
@@ -15,6 +18,9 @@ pub struct AddFoodItemResponse {
     pub name: String,
     pub brand: Option<String>,
     pub created_at: String,
+    /// Non-canonical serving format accepted because strict was off
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
 /// Response for search_food_items
@@ -33,6 +39,7 @@ pub struct FoodItemSummary {
     pub serving_size: f64,
     pub serving_unit: String,
     pub calories: f64,
+    pub protein: f64,
     pub preference: Preference,
 }
 
@@ -45,6 +52,7 @@ impl From<&FoodItem> for FoodItemSummary {
             serving_size: item.serving_size,
             serving_unit: item.serving_unit.clone(),
             calories: item.nutrition.calories,
+            protein: item.nutrition.protein,
             preference: item.preference,
         }
     }
@@ -67,6 +75,10 @@ pub struct FoodItemDetail {
     pub sugar: f64,
     pub saturated_fat: f64,
     pub cholesterol: f64,
+    /// Sodium mg per 1000 kcal (None for zero-calorie items)
+    pub sodium_density: Option<f64>,
+    /// Sodium density above the requested limit
+    pub sodium_dense: bool,
     pub preference: Preference,
     pub notes: Option<String>,
     /// Base unit type (weight, volume, or count)
@@ -90,7 +102,10 @@ impl FoodItemDetail {
         meal_usage_count: i64,
         used_in_recipes: Vec<String>,
         used_in_meal_dates: Vec<String>,
+        sodium_density_limit: f64,
     ) -> Self {
+        let sodium_density = item.nutrition.sodium_density().map(|d| round_to(d, 2));
+        let sodium_dense = item.nutrition.is_sodium_dense(sodium_density_limit);
         Self {
             id: item.id,
             name: item.name,
@@ -106,6 +121,8 @@ impl FoodItemDetail {
             sugar: item.nutrition.sugar,
             saturated_fat: item.nutrition.saturated_fat,
             cholesterol: item.nutrition.cholesterol,
+            sodium_density,
+            sodium_dense,
             preference: item.preference,
             notes: item.notes,
             base_unit_type: item.base_unit_type,
@@ -173,57 +190,102 @@ pub struct DeleteFoodItemSuccessResponse {
     pub deleted_id: i64,
 }
 
-/// Add a new food item
-pub fn add_food_item(db: &Database, data: FoodItemCreate) -> Result<AddFoodItemResponse, String> {
+/// Check the fields a new food item must have before it is inserted
+pub(crate) fn validate_food_item(data: &FoodItemCreate) -> Result<(), ToolError> {
     // Validate name
     let name = data.name.trim();
     if name.is_empty() {
-        return Err("Food item name cannot be empty".to_string());
+        return Err(ToolError::Validation("Food item name cannot be empty".to_string()));
     }
 
     // Validate serving info
     if data.serving_size <= 0.0 {
-        return Err("serving_size must be greater than 0".to_string());
+        return Err(ToolError::Validation("serving_size must be greater than 0".to_string()));
     }
     let unit = data.serving_unit.trim();
     if unit.is_empty() {
-        return Err("serving_unit cannot be empty".to_string());
+        return Err(ToolError::Validation("serving_unit cannot be empty".to_string()));
     }
 
     // Validate nutrition values are non-negative
     if data.calories < 0.0 {
-        return Err("calories cannot be negative".to_string());
+        return Err(ToolError::Validation("calories cannot be negative".to_string()));
     }
     if data.protein < 0.0 {
-        return Err("protein cannot be negative".to_string());
+        return Err(ToolError::Validation("protein cannot be negative".to_string()));
     }
     if data.carbs < 0.0 {
-        return Err("carbs cannot be negative".to_string());
+        return Err(ToolError::Validation("carbs cannot be negative".to_string()));
     }
     if data.fat < 0.0 {
-        return Err("fat cannot be negative".to_string());
+        return Err(ToolError::Validation("fat cannot be negative".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Explain why a serving isn't one of the canonical formats (100 g, 100 ml, 1 count)
+pub(crate) fn non_canonical_serving(serving_size: f64, serving_unit: &str) -> Option<String> {
+    let unit = serving_unit.trim().to_lowercase();
+    let (canonical_size, canonical_unit, kind) = match categorize_unit(&unit) {
+        UnitCategory::Weight => (100.0, "g", "Solids"),
+        UnitCategory::Volume => (100.0, "ml", "Liquids"),
+        UnitCategory::Count => (1.0, "count", "Countables"),
+        UnitCategory::Custom => {
+            return Some(format!(
+                "serving_unit '{}' is not a recognized unit. Use serving_size 100 + \"g\" for solids, \
+                 100 + \"ml\" for liquids, or 1 + \"count\" for countables, converting the label's nutrition",
+                serving_unit.trim()
+            ));
+        }
+    };
+
+    if unit == canonical_unit && (serving_size - canonical_size).abs() < 1e-9 {
+        return None;
+    }
+    Some(format!(
+        "{} must be stored as serving_size {} + serving_unit \"{}\" (got {} {}). \
+         Convert the label values: (value / serving amount in {}) * {}",
+        kind, canonical_size, canonical_unit, serving_size, serving_unit.trim(),
+        canonical_unit, canonical_size
+    ))
+}
+
+/// Add a food item. With `strict`, servings outside the canonical
+/// 100 g / 100 ml / 1 count formats are rejected; otherwise they are
+/// accepted and reported as warnings.
+pub fn add_food_item(db: &Database, data: FoodItemCreate, strict: bool) -> Result<AddFoodItemResponse, ToolError> {
+    validate_food_item(&data)?;
+
+    let mut warnings = Vec::new();
+    if let Some(problem) = non_canonical_serving(data.serving_size, &data.serving_unit) {
+        if strict {
+            return Err(ToolError::Validation(format!("{}. Pass strict: false to store it anyway.", problem)));
+        }
+        warnings.push(problem);
     }
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let conn = db.get_conn()?;
 
     let item = FoodItem::create(&conn, &data)
-        .map_err(|e| format!("Failed to create food item: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to create food item: {}", e)))?;
 
     Ok(AddFoodItemResponse {
         id: item.id,
         name: item.name,
         brand: item.brand,
         created_at: item.created_at,
+        warnings,
     })
 }
 
 /// Search food items by name or brand
-pub fn search_food_items(db: &Database, query: &str, limit: i64) -> Result<SearchFoodItemsResponse, String> {
+pub fn search_food_items(db: &Database, query: &str, limit: i64) -> Result<SearchFoodItemsResponse, ToolError> {
     let limit = limit.min(100).max(1);
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let conn = db.get_conn()?;
 
     let items = FoodItem::search(&conn, query, limit)
-        .map_err(|e| format!("Search failed: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Search failed: {}", e)))?;
 
     let summaries: Vec<FoodItemSummary> = items.iter().map(FoodItemSummary::from).collect();
     let total = summaries.len();
@@ -235,22 +297,22 @@ pub fn search_food_items(db: &Database, query: &str, limit: i64) -> Result<Searc
 }
 
 /// Get a food item by ID with usage information
-pub fn get_food_item(db: &Database, id: i64) -> Result<Option<FoodItemDetail>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn get_food_item(db: &Database, id: i64, sodium_density_limit: f64) -> Result<Option<FoodItemDetail>, ToolError> {
+    let conn = db.get_conn()?;
 
     let item = FoodItem::get_by_id(&conn, id)
-        .map_err(|e| format!("Failed to get food item: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?;
 
     match item {
         Some(item) => {
             let recipe_usage_count = FoodItem::get_recipe_usage_count(&conn, id)
-                .map_err(|e| format!("Failed to get recipe usage count: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to get recipe usage count: {}", e)))?;
             let meal_usage_count = FoodItem::get_meal_usage_count(&conn, id)
-                .map_err(|e| format!("Failed to get meal usage count: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to get meal usage count: {}", e)))?;
             let used_in_recipes = FoodItem::get_used_in_recipes(&conn, id)
-                .map_err(|e| format!("Failed to get recipe usage: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to get recipe usage: {}", e)))?;
             let used_in_meal_dates = FoodItem::get_used_in_meals(&conn, id)
-                .map_err(|e| format!("Failed to get meal usage: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to get meal usage: {}", e)))?;
 
             Ok(Some(FoodItemDetail::from_food_item(
                 item,
@@ -258,6 +320,7 @@ pub fn get_food_item(db: &Database, id: i64) -> Result<Option<FoodItemDetail>, S
                 meal_usage_count,
                 used_in_recipes,
                 used_in_meal_dates,
+                sodium_density_limit,
             )))
         }
         None => Ok(None),
@@ -272,18 +335,26 @@ pub fn list_food_items(
     sort_order: &str,
     limit: i64,
     offset: i64,
-) -> Result<ListFoodItemsResponse, String> {
+) -> Result<ListFoodItemsResponse, ToolError> {
     let limit = limit.min(200).max(1);
     let offset = offset.max(0);
     let pref = preference.map(Preference::from_str);
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    if !FoodItem::SORT_FIELDS.contains(&sort_by.to_lowercase().as_str()) {
+        return Err(ToolError::Validation(format!(
+            "Invalid sort_by: '{}'. Valid values: {}",
+            sort_by,
+            FoodItem::SORT_FIELDS.join(", ")
+        )));
+    }
+
+    let conn = db.get_conn()?;
 
     let items = FoodItem::list(&conn, pref, sort_by, sort_order, limit, offset)
-        .map_err(|e| format!("Failed to list food items: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to list food items: {}", e)))?;
 
     let total = FoodItem::count(&conn, pref)
-        .map_err(|e| format!("Failed to count food items: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to count food items: {}", e)))?;
 
     let summaries: Vec<FoodItemSummary> = items.iter().map(FoodItemSummary::from).collect();
 
@@ -300,19 +371,19 @@ pub fn update_food_item(
     db: &Database,
     id: i64,
     data: FoodItemUpdate,
-) -> Result<UpdateFoodItemResponse, String> {
+) -> Result<UpdateFoodItemResponse, ToolError> {
     use crate::models::cascade_recalculate_from_food_item;
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let conn = db.get_conn()?;
 
     let updated = FoodItem::update(&conn, id, &data)
-        .map_err(|e| format!("Failed to update food item: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to update food item: {}", e)))?;
 
     match updated {
         Some(item) => {
             // Cascade recalculation: updates all affected recipes and days
             let cascade_result = cascade_recalculate_from_food_item(&conn, id)
-                .map_err(|e| format!("Failed to cascade recalculation: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to cascade recalculation: {}", e)))?;
 
             Ok(UpdateFoodItemResponse {
                 success: true,
@@ -321,8 +392,172 @@ pub fn update_food_item(
                 days_recalculated: cascade_result.days_recalculated,
             })
         }
-        None => Err(format!("Food item not found with id: {}", id)),
+        None => Err(ToolError::NotFound(format!("Food item not found with id: {}", id))),
+    }
+}
+
+/// A recipe reached by a food item cascade
+#[derive(Debug, Serialize)]
+pub struct CascadeRecipePreview {
+    pub id: i64,
+    pub name: String,
+    /// True if the food item is an ingredient of this recipe, false if reached via a component
+    pub direct: bool,
+}
+
+/// A day reached by a food item cascade
+#[derive(Debug, Serialize)]
+pub struct CascadeDayPreview {
+    pub id: i64,
+    pub date: String,
+}
+
+/// Response for preview_food_item_cascade
+#[derive(Debug, Serialize)]
+pub struct FoodItemCascadePreviewResponse {
+    pub food_item_id: i64,
+    pub food_item_name: String,
+    pub recipe_count: usize,
+    pub day_count: usize,
+    /// Recipes in recalculation order (dependencies first)
+    pub recipes: Vec<CascadeRecipePreview>,
+    pub days: Vec<CascadeDayPreview>,
+}
+
+/// Preview which recipes and days would be recalculated if a food item changed (read-only)
+pub fn preview_food_item_cascade(db: &Database, id: i64) -> Result<FoodItemCascadePreviewResponse, ToolError> {
+    use crate::models::{find_cascade_targets, Day, Recipe};
+
+    let conn = db.get_conn()?;
+
+    let item = FoodItem::get_by_id(&conn, id)
+        .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+        .ok_or_else(|| ToolError::NotFound(format!("Food item not found with id: {}", id)))?;
+
+    let targets = find_cascade_targets(&conn, id)
+        .map_err(|e| ToolError::Db(format!("Failed to find cascade targets: {}", e)))?;
+
+    let mut recipes = Vec::with_capacity(targets.recipe_ids.len());
+    for recipe_id in &targets.recipe_ids {
+        if let Some(recipe) = Recipe::get_by_id(&conn, *recipe_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?
+        {
+            recipes.push(CascadeRecipePreview {
+                id: recipe.id,
+                name: recipe.name,
+                direct: targets.direct_recipe_ids.contains(recipe_id),
+            });
+        }
+    }
+
+    let mut days = Vec::with_capacity(targets.day_ids.len());
+    for day_id in &targets.day_ids {
+        if let Some(day) = Day::get_by_id(&conn, *day_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get day: {}", e)))?
+        {
+            days.push(CascadeDayPreview { id: day.id, date: day.date });
+        }
+    }
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+
+    Ok(FoodItemCascadePreviewResponse {
+        food_item_id: item.id,
+        food_item_name: item.name,
+        recipe_count: recipes.len(),
+        day_count: days.len(),
+        recipes,
+        days,
+    })
+}
+
+/// Response for rescale_food_item
+#[derive(Debug, Serialize)]
+pub struct RescaleFoodItemResponse {
+    pub id: i64,
+    pub name: String,
+    /// to_basis_grams / from_basis_grams
+    pub factor: f64,
+    pub serving_before: String,
+    pub serving_after: String,
+    pub nutrition_before: Nutrition,
+    pub nutrition_after: Nutrition,
+    pub recipes_recalculated: i64,
+    pub days_recalculated: i64,
+}
+
+/// Correct a food item whose nutrition was entered for a different gram basis than stored.
+///
+/// Every nutrition field is multiplied by `to_basis_grams / from_basis_grams`; with
+/// `update_serving` the serving also becomes `to_basis_grams` g. Nothing is inferred from
+/// the units: the caller asserts the true basis.
+pub fn rescale_food_item(
+    db: &Database,
+    id: i64,
+    from_basis_grams: f64,
+    to_basis_grams: f64,
+    update_serving: bool,
+) -> Result<RescaleFoodItemResponse, ToolError> {
+    use crate::models::cascade_recalculate_from_food_item;
+
+    if from_basis_grams <= 0.0 || to_basis_grams <= 0.0 {
+        return Err(ToolError::Validation("from_basis_grams and to_basis_grams must be greater than 0".to_string()));
     }
+    let factor = to_basis_grams / from_basis_grams;
+
+    db.transaction(|conn| {
+        let item = FoodItem::get_by_id(conn, id)
+            .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Food item not found with id: {}", id)))?;
+
+        let before = item.nutrition.clone();
+        let scaled = before.scale(factor);
+        let after = Nutrition {
+            calories: round_to(scaled.calories, 2),
+            protein: round_to(scaled.protein, 2),
+            carbs: round_to(scaled.carbs, 2),
+            fat: round_to(scaled.fat, 2),
+            fiber: round_to(scaled.fiber, 2),
+            sodium: round_to(scaled.sodium, 2),
+            sugar: round_to(scaled.sugar, 2),
+            saturated_fat: round_to(scaled.saturated_fat, 2),
+            cholesterol: round_to(scaled.cholesterol, 2),
+        };
+
+        let update = FoodItemUpdate {
+            serving_size: update_serving.then_some(to_basis_grams),
+            serving_unit: update_serving.then(|| "g".to_string()),
+            grams_per_serving: update_serving.then_some(to_basis_grams),
+            base_unit_type: update_serving.then_some(BaseUnitType::Weight),
+            calories: Some(after.calories),
+            protein: Some(after.protein),
+            carbs: Some(after.carbs),
+            fat: Some(after.fat),
+            fiber: Some(after.fiber),
+            sodium: Some(after.sodium),
+            sugar: Some(after.sugar),
+            saturated_fat: Some(after.saturated_fat),
+            cholesterol: Some(after.cholesterol),
+            ..Default::default()
+        };
+        let updated = FoodItem::update(conn, id, &update)
+            .map_err(|e| ToolError::Db(format!("Failed to update food item: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Food item not found with id: {}", id)))?;
+
+        let cascade = cascade_recalculate_from_food_item(conn, id)
+            .map_err(|e| ToolError::Db(format!("Failed to cascade recalculation: {}", e)))?;
+
+        Ok(RescaleFoodItemResponse {
+            id,
+            name: updated.name,
+            factor: round_to(factor, 4),
+            serving_before: format!("{} {}", item.serving_size, item.serving_unit),
+            serving_after: format!("{} {}", updated.serving_size, updated.serving_unit),
+            nutrition_before: before,
+            nutrition_after: updated.nutrition,
+            recipes_recalculated: cascade.recipes_recalculated,
+            days_recalculated: cascade.days_recalculated,
+        })
+    })
 }
 
 /// Response for update without cascade (used in batch mode)
@@ -339,11 +574,11 @@ pub fn update_food_item_no_cascade(
     db: &Database,
     id: i64,
     data: FoodItemUpdate,
-) -> Result<UpdateFoodItemNoCascadeResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<UpdateFoodItemNoCascadeResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     let updated = FoodItem::update(&conn, id, &data)
-        .map_err(|e| format!("Failed to update food item: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to update food item: {}", e)))?;
 
     match updated {
         Some(item) => {
@@ -353,7 +588,7 @@ pub fn update_food_item_no_cascade(
                 cascade_deferred: true,
             })
         }
-        None => Err(format!("Food item not found with id: {}", id)),
+        None => Err(ToolError::NotFound(format!("Food item not found with id: {}", id))),
     }
 }
 
@@ -371,7 +606,7 @@ pub struct BatchCascadeResponse {
 pub fn batch_cascade_recalculate(
     db: &Database,
     food_item_ids: &std::collections::HashSet<i64>,
-) -> Result<BatchCascadeResponse, String> {
+) -> Result<BatchCascadeResponse, ToolError> {
     use std::collections::HashSet;
     use crate::models::{recalculate_recipe_nutrition, recalculate_day_nutrition};
 
@@ -384,7 +619,7 @@ pub fn batch_cascade_recalculate(
         });
     }
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let conn = db.get_conn()?;
 
     // Step 1: Find ALL recipes using ANY of the changed food items
     let food_ids_str = food_item_ids
@@ -399,11 +634,11 @@ pub fn batch_cascade_recalculate(
             food_ids_str
         );
         let mut stmt = conn.prepare(&sql)
-            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to prepare query: {}", e)))?;
         let rows = stmt.query_map([], |row| row.get(0))
-            .map_err(|e| format!("Failed to query recipes: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to query recipes: {}", e)))?;
         rows.collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to collect recipe IDs: {}", e))?
+            .map_err(|e| ToolError::Db(format!("Failed to collect recipe IDs: {}", e)))?
     };
 
     if direct_recipe_ids.is_empty() {
@@ -424,11 +659,11 @@ pub fn batch_cascade_recalculate(
             let parent_ids: Vec<i64> = {
                 let mut stmt = conn.prepare(
                     "SELECT DISTINCT recipe_id FROM recipe_components WHERE component_recipe_id = ?1"
-                ).map_err(|e| format!("Failed to prepare parent query: {}", e))?;
+                ).map_err(|e| ToolError::Db(format!("Failed to prepare parent query: {}", e)))?;
                 let rows = stmt.query_map([recipe_id], |row| row.get(0))
-                    .map_err(|e| format!("Failed to query parents: {}", e))?;
+                    .map_err(|e| ToolError::Db(format!("Failed to query parents: {}", e)))?;
                 rows.collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| format!("Failed to collect parent IDs: {}", e))?
+                    .map_err(|e| ToolError::Db(format!("Failed to collect parent IDs: {}", e)))?
             };
             to_process.extend(parent_ids);
         }
@@ -436,13 +671,13 @@ pub fn batch_cascade_recalculate(
 
     // Step 3: Topologically sort recipes (dependencies first)
     let sorted_recipes = topological_sort_recipes_for_batch(&conn, &all_affected)
-        .map_err(|e| format!("Failed to sort recipes: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to sort recipes: {}", e)))?;
 
     // Step 4: Recalculate all affected recipes
     let mut recipes_recalculated = 0i64;
     for recipe_id in &sorted_recipes {
         recalculate_recipe_nutrition(&conn, *recipe_id)
-            .map_err(|e| format!("Failed to recalculate recipe {}: {}", recipe_id, e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to recalculate recipe {}: {}", recipe_id, e)))?;
         recipes_recalculated += 1;
     }
 
@@ -460,18 +695,18 @@ pub fn batch_cascade_recalculate(
             food_ids_str
         );
         let mut stmt = conn.prepare(&sql)
-            .map_err(|e| format!("Failed to prepare day query: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to prepare day query: {}", e)))?;
         let rows = stmt.query_map([], |row| row.get(0))
-            .map_err(|e| format!("Failed to query days: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to query days: {}", e)))?;
         rows.collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("Failed to collect day IDs: {}", e))?
+            .map_err(|e| ToolError::Db(format!("Failed to collect day IDs: {}", e)))?
     };
 
     // Step 6: Recalculate all affected days
     let mut days_recalculated = 0i64;
     for day_id in affected_day_ids {
         recalculate_day_nutrition(&conn, day_id)
-            .map_err(|e| format!("Failed to recalculate day {}: {}", day_id, e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to recalculate day {}: {}", day_id, e)))?;
         days_recalculated += 1;
     }
 
@@ -487,7 +722,7 @@ pub fn batch_cascade_recalculate(
 fn topological_sort_recipes_for_batch(
     conn: &rusqlite::Connection,
     recipe_ids: &std::collections::HashSet<i64>,
-) -> Result<Vec<i64>, String> {
+) -> Result<Vec<i64>, ToolError> {
     use std::collections::{HashMap, HashSet, VecDeque};
 
     if recipe_ids.is_empty() {
@@ -514,12 +749,10 @@ fn topological_sort_recipes_for_batch(
         ids_str, ids_str
     );
 
-    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&sql)?;
     let edges: Vec<(i64, i64)> = stmt
-        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
 
     for (parent_id, child_id) in edges {
         dependencies.entry(parent_id).or_default().insert(child_id);
@@ -564,8 +797,8 @@ fn topological_sort_recipes_for_batch(
 
 /// List food items with zero uses (not used in any recipe or meal entry)
 /// These food items are safe to delete
-pub fn list_unused_food_items(db: &Database) -> Result<ListUnusedFoodItemsResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn list_unused_food_items(db: &Database) -> Result<ListUnusedFoodItemsResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     // Find food items that are not used in any recipe_ingredients AND not used in any meal_entries
     let mut stmt = conn.prepare(
@@ -580,7 +813,7 @@ pub fn list_unused_food_items(db: &Database) -> Result<ListUnusedFoodItemsRespon
         )
         ORDER BY f.name ASC
         "#
-    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    ).map_err(|e| ToolError::Db(format!("Failed to prepare query: {}", e)))?;
 
     let items: Vec<UnusedFoodItemSummary> = stmt
         .query_map([], |row| {
@@ -592,9 +825,9 @@ pub fn list_unused_food_items(db: &Database) -> Result<ListUnusedFoodItemsRespon
                 created_at: row.get("created_at")?,
             })
         })
-        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .map_err(|e| ToolError::Db(format!("Failed to execute query: {}", e)))?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect results: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to collect results: {}", e)))?;
 
     let count = items.len();
 
@@ -605,29 +838,28 @@ pub fn list_unused_food_items(db: &Database) -> Result<ListUnusedFoodItemsRespon
 pub fn delete_food_item(
     db: &Database,
     id: i64,
-) -> Result<Result<DeleteFoodItemSuccessResponse, DeleteFoodItemBlockedResponse>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<Result<DeleteFoodItemSuccessResponse, DeleteFoodItemBlockedResponse>, ToolError> {
+    let conn = db.get_conn()?;
 
     // Check if food item exists
-    let food_item = FoodItem::get_by_id(&conn, id)
-        .map_err(|e| format!("Database error: {}", e))?;
+    let food_item = FoodItem::get_by_id(&conn, id)?;
     if food_item.is_none() {
-        return Err(format!("Food item not found with id: {}", id));
+        return Err(ToolError::NotFound(format!("Food item not found with id: {}", id)));
     }
 
     // Check if used in any recipes
     let recipe_usage_count = FoodItem::get_recipe_usage_count(&conn, id)
-        .map_err(|e| format!("Failed to check recipe usage: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to check recipe usage: {}", e)))?;
 
     // Check if used in any meal entries directly
     let meal_usage_count = FoodItem::get_meal_usage_count(&conn, id)
-        .map_err(|e| format!("Failed to check meal usage: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to check meal usage: {}", e)))?;
 
     if recipe_usage_count > 0 || meal_usage_count > 0 {
         let used_in_recipes = FoodItem::get_used_in_recipes(&conn, id)
-            .map_err(|e| format!("Failed to get recipe usage: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to get recipe usage: {}", e)))?;
         let used_in_meal_dates = FoodItem::get_used_in_meals(&conn, id)
-            .map_err(|e| format!("Failed to get meal usage: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to get meal usage: {}", e)))?;
 
         let mut reasons = Vec::new();
         if recipe_usage_count > 0 {
@@ -648,10 +880,835 @@ pub fn delete_food_item(
 
     // Delete the food item
     FoodItem::delete(&conn, id)
-        .map_err(|e| format!("Failed to delete food item: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to delete food item: {}", e)))?;
 
     Ok(Ok(DeleteFoodItemSuccessResponse {
         success: true,
         deleted_id: id,
     }))
 }
+
+// ============================================================================
+// Food Item Audit
+// ============================================================================
+
+/// Pure fat is ~9 kcal/g; the slack absorbs label rounding
+const MAX_KCAL_PER_GRAM: f64 = 9.5;
+
+/// Protein + carbs + fat may exceed the serving mass by this factor before it is flagged
+const MACRO_MASS_SLACK: f64 = 1.05;
+
+/// Sort orders accepted by audit_food_items
+const AUDIT_SORT_FIELDS: [&str; 2] = ["severity", "id"];
+
+/// One implausible value on a food item
+#[derive(Debug, Serialize)]
+pub struct FoodItemIssue {
+    pub food_item_id: i64,
+    pub name: String,
+    pub brand: Option<String>,
+    /// "negative_value", "calories_per_gram" or "macros_exceed_mass"
+    pub rule: String,
+    /// "high" (negative value), "medium" (calories per gram) or "low" (macro mass)
+    pub severity: String,
+    pub message: String,
+}
+
+/// Response for audit_food_items
+#[derive(Debug, Serialize)]
+pub struct AuditFoodItemsResponse {
+    pub items_checked: usize,
+    pub items_flagged: usize,
+    /// All issues found, before limit/offset
+    pub issue_count: usize,
+    pub sort_by: String,
+    pub limit: i64,
+    pub offset: i64,
+    pub issues: Vec<FoodItemIssue>,
+}
+
+/// Severity label and rank (higher is worse) for an audit rule
+fn audit_severity(rule: &str) -> (&'static str, u8) {
+    match rule {
+        "negative_value" => ("high", 3),
+        "calories_per_gram" => ("medium", 2),
+        _ => ("low", 1),
+    }
+}
+
+/// Check one serving's nutrition for values that can't be right.
+/// Returns (rule, message) pairs; mass rules only apply when grams_per_serving is known.
+fn nutrition_issues(n: &Nutrition, grams_per_serving: Option<f64>) -> Vec<(&'static str, String)> {
+    let mut issues = Vec::new();
+
+    let fields = [
+        ("calories", n.calories),
+        ("protein", n.protein),
+        ("carbs", n.carbs),
+        ("fat", n.fat),
+        ("fiber", n.fiber),
+        ("sodium", n.sodium),
+        ("sugar", n.sugar),
+        ("saturated_fat", n.saturated_fat),
+        ("cholesterol", n.cholesterol),
+    ];
+    for (field, value) in fields {
+        if value < 0.0 {
+            issues.push(("negative_value", format!("{} is negative ({})", field, value)));
+        }
+    }
+
+    if let Some(grams) = grams_per_serving.filter(|g| *g > 0.0) {
+        let kcal_per_gram = n.calories / grams;
+        if kcal_per_gram > MAX_KCAL_PER_GRAM {
+            issues.push((
+                "calories_per_gram",
+                format!(
+                    "{:.1} kcal/g ({} kcal in {} g) exceeds the ~9 kcal/g of pure fat",
+                    kcal_per_gram, n.calories, grams
+                ),
+            ));
+        }
+
+        let macro_grams = n.protein + n.carbs + n.fat;
+        if macro_grams > grams * MACRO_MASS_SLACK {
+            issues.push((
+                "macros_exceed_mass",
+                format!(
+                    "protein + carbs + fat = {} g but a serving weighs {} g",
+                    round_to(macro_grams, 1), grams
+                ),
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Flag food items with impossible nutrition: negative values, more than ~9 kcal
+/// per gram, or macros heavier than the serving itself.
+/// sort_by "severity" puts the worst issues first; "id" keeps food item order.
+pub fn audit_food_items(db: &Database, sort_by: &str, limit: i64, offset: i64) -> Result<AuditFoodItemsResponse, ToolError> {
+    let limit = limit.clamp(1, 200);
+    let offset = offset.max(0);
+    let sort_by = sort_by.to_lowercase();
+    if !AUDIT_SORT_FIELDS.contains(&sort_by.as_str()) {
+        return Err(ToolError::Validation(format!(
+            "Invalid sort_by: '{}'. Valid values: {}",
+            sort_by,
+            AUDIT_SORT_FIELDS.join(", ")
+        )));
+    }
+
+    let conn = db.get_conn()?;
+
+    let items = FoodItem::list_all(&conn)
+        .map_err(|e| ToolError::Db(format!("Failed to list food items: {}", e)))?;
+
+    let mut issues = Vec::new();
+    let mut items_flagged = 0;
+    for item in &items {
+        let found = nutrition_issues(&item.nutrition, item.grams_per_serving);
+        if !found.is_empty() {
+            items_flagged += 1;
+        }
+        issues.extend(found.into_iter().map(|(rule, message)| FoodItemIssue {
+            food_item_id: item.id,
+            name: item.name.clone(),
+            brand: item.brand.clone(),
+            rule: rule.to_string(),
+            severity: audit_severity(rule).0.to_string(),
+            message,
+        }));
+    }
+
+    if sort_by == "severity" {
+        // Stable sort keeps food item order within a severity
+        issues.sort_by_key(|issue| std::cmp::Reverse(audit_severity(&issue.rule).1));
+    }
+    let issue_count = issues.len();
+    let issues = issues.into_iter().skip(offset as usize).take(limit as usize).collect();
+
+    Ok(AuditFoodItemsResponse {
+        items_checked: items.len(),
+        items_flagged,
+        issue_count,
+        sort_by,
+        limit,
+        offset,
+        issues,
+    })
+}
+
+// ============================================================================
+// CSV Export / Import
+// ============================================================================
+
+/// Column order used by export_food_items_csv (import matches columns by header name)
+const FOOD_ITEM_CSV_COLUMNS: [&str; 19] = [
+    "id", "name", "brand", "serving_size", "serving_unit",
+    "calories", "protein", "carbs", "fat", "fiber", "sodium", "sugar",
+    "saturated_fat", "cholesterol", "preference", "notes",
+    "base_unit_type", "grams_per_serving", "ml_per_serving",
+];
+
+/// Response for export_food_items_csv
+#[derive(Debug, Serialize)]
+pub struct ExportFoodItemsCsvResponse {
+    pub output_path: String,
+    pub items_exported: usize,
+}
+
+/// Response for import_food_items_csv
+#[derive(Debug, Serialize)]
+pub struct ImportFoodItemsCsvResponse {
+    pub file_path: String,
+    pub mode: String,
+    pub rows_read: usize,
+    pub created: usize,
+    pub updated: usize,
+    /// Upsert rows that matched an existing item with identical values
+    pub unchanged: usize,
+    pub errors: Vec<String>,
+    pub recipes_recalculated: i64,
+    pub days_recalculated: i64,
+}
+
+/// Write every food item, with all serving and nutrition fields, to a CSV file
+pub fn export_food_items_csv(db: &Database, output_path: &str) -> Result<ExportFoodItemsCsvResponse, ToolError> {
+    use super::csv::escape_csv_field;
+
+    let conn = db.get_conn()?;
+
+    let items = FoodItem::list_all(&conn)
+        .map_err(|e| ToolError::Db(format!("Failed to list food items: {}", e)))?;
+
+    let mut out = FOOD_ITEM_CSV_COLUMNS.join(",");
+    out.push('\n');
+
+    for item in &items {
+        let n = &item.nutrition;
+        let fields = [
+            item.id.to_string(),
+            item.name.clone(),
+            item.brand.clone().unwrap_or_default(),
+            item.serving_size.to_string(),
+            item.serving_unit.clone(),
+            n.calories.to_string(),
+            n.protein.to_string(),
+            n.carbs.to_string(),
+            n.fat.to_string(),
+            n.fiber.to_string(),
+            n.sodium.to_string(),
+            n.sugar.to_string(),
+            n.saturated_fat.to_string(),
+            n.cholesterol.to_string(),
+            item.preference.as_str().to_string(),
+            item.notes.clone().unwrap_or_default(),
+            item.base_unit_type.map(|b| b.to_db_str().to_string()).unwrap_or_default(),
+            item.grams_per_serving.map(|g| g.to_string()).unwrap_or_default(),
+            item.ml_per_serving.map(|m| m.to_string()).unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| escape_csv_field(f)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+
+    std::fs::write(output_path, out)
+        .map_err(|e| ToolError::Io(format!("Failed to write file '{}': {}", output_path, e)))?;
+
+    Ok(ExportFoodItemsCsvResponse {
+        output_path: output_path.to_string(),
+        items_exported: items.len(),
+    })
+}
+
+/// Build a FoodItemCreate from one CSV row, using the header to locate columns
+fn parse_food_item_row(header: &HashMap<String, usize>, fields: &[String]) -> Result<FoodItemCreate, ToolError> {
+    let text = |col: &str| -> Option<String> {
+        header
+            .get(col)
+            .and_then(|&i| fields.get(i))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let number = |col: &str| -> Result<Option<f64>, ToolError> {
+        text(col)
+            .map(|v| v.parse::<f64>().map_err(|_| ToolError::Validation(format!("Invalid {}: {}", col, v))))
+            .transpose()
+    };
+
+    let base_unit_type = match text("base_unit_type") {
+        Some(v) => Some(
+            BaseUnitType::from_str(&v).ok_or_else(|| ToolError::Validation(format!("Invalid base_unit_type: {}", v)))?,
+        ),
+        None => None,
+    };
+
+    Ok(FoodItemCreate {
+        name: text("name").ok_or_else(|| ToolError::Validation("name is required".to_string()))?,
+        brand: text("brand"),
+        serving_size: number("serving_size")?.ok_or_else(|| ToolError::Validation("serving_size is required".to_string()))?,
+        serving_unit: text("serving_unit").ok_or_else(|| ToolError::Validation("serving_unit is required".to_string()))?,
+        calories: number("calories")?.unwrap_or(0.0),
+        protein: number("protein")?.unwrap_or(0.0),
+        carbs: number("carbs")?.unwrap_or(0.0),
+        fat: number("fat")?.unwrap_or(0.0),
+        fiber: number("fiber")?.unwrap_or(0.0),
+        sodium: number("sodium")?.unwrap_or(0.0),
+        sugar: number("sugar")?.unwrap_or(0.0),
+        saturated_fat: number("saturated_fat")?.unwrap_or(0.0),
+        cholesterol: number("cholesterol")?.unwrap_or(0.0),
+        preference: text("preference").map(|p| Preference::from_str(&p)).unwrap_or_default(),
+        notes: text("notes"),
+        base_unit_type,
+        grams_per_serving: number("grams_per_serving")?,
+        ml_per_serving: number("ml_per_serving")?,
+    })
+}
+
+/// True if an existing item already holds every value from an imported row.
+/// Notes are compared only when the file has a notes column; a blank cell
+/// then stands for no notes.
+fn food_item_matches(item: &FoodItem, row: &FoodItemCreate, has_notes: bool) -> bool {
+    let n = &item.nutrition;
+    item.serving_size == row.serving_size
+        && item.serving_unit == row.serving_unit
+        && n.calories == row.calories
+        && n.protein == row.protein
+        && n.carbs == row.carbs
+        && n.fat == row.fat
+        && n.fiber == row.fiber
+        && n.sodium == row.sodium
+        && n.sugar == row.sugar
+        && n.saturated_fat == row.saturated_fat
+        && n.cholesterol == row.cholesterol
+        && item.preference == row.preference
+        && (!has_notes || item.notes.as_deref().unwrap_or("") == row.notes.as_deref().unwrap_or(""))
+        && row.base_unit_type.is_none_or(|b| item.base_unit_type == Some(b))
+        && row.grams_per_serving.is_none_or(|g| item.grams_per_serving == Some(g))
+        && row.ml_per_serving.is_none_or(|m| item.ml_per_serving == Some(m))
+}
+
+/// Import food items from a CSV file in the export_food_items_csv layout.
+///
+/// mode "insert" creates every row as a new item; "upsert" updates the item with
+/// the same name and brand if one exists. Updated items are recalculated once at
+/// the end through batch_cascade_recalculate.
+pub fn import_food_items_csv(db: &Database, file_path: &str, mode: &str) -> Result<ImportFoodItemsCsvResponse, ToolError> {
+    use std::collections::HashSet;
+    use super::csv::split_csv_line;
+
+    let upsert = match mode.to_lowercase().as_str() {
+        "upsert" => true,
+        "insert" => false,
+        _ => return Err(ToolError::Validation(format!("Invalid mode '{}'. Must be 'upsert' or 'insert'", mode))),
+    };
+
+    let content = std::fs::read_to_string(file_path)
+        .map_err(|e| ToolError::Io(format!("Failed to open file '{}': {}", file_path, e)))?;
+
+    // Join physical lines into records so quoted notes may contain line breaks
+    let mut records: Vec<(usize, String)> = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (line_num, line) in content.lines().enumerate() {
+        let (start, mut record) = match pending.take() {
+            Some((start, mut record)) => {
+                record.push('\n');
+                (start, std::mem::take(&mut record))
+            }
+            None => (line_num + 1, String::new()),
+        };
+        record.push_str(line);
+        if record.matches('"').count() % 2 == 1 {
+            pending = Some((start, record));
+        } else if !record.trim().is_empty() {
+            records.push((start, record));
+        }
+    }
+    if let Some(record) = pending {
+        records.push(record);
+    }
+
+    let mut records = records.into_iter();
+    let header: HashMap<String, usize> = match records.next() {
+        Some((_, line)) => split_csv_line(&line)
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| (name.trim().to_lowercase(), i))
+            .collect(),
+        None => return Err(ToolError::Validation("File is empty".to_string())),
+    };
+    for required in ["name", "serving_size", "serving_unit"] {
+        if !header.contains_key(required) {
+            return Err(ToolError::Validation(format!("Missing required column: {}", required)));
+        }
+    }
+
+    let has_notes = header.contains_key("notes");
+
+    let mut rows_read = 0;
+    let mut created = 0;
+    let mut updated = 0;
+    let mut unchanged = 0;
+    let mut errors = Vec::new();
+    let mut changed_ids: HashSet<i64> = HashSet::new();
+
+    db.transaction(|conn| -> Result<(), ToolError> {
+        for (line_num, line) in records {
+            rows_read += 1;
+
+            let row = match parse_food_item_row(&header, &split_csv_line(&line))
+                .and_then(|row| validate_food_item(&row).map(|_| row))
+            {
+                Ok(row) => row,
+                Err(e) => {
+                    errors.push(format!("Row {}: {}", line_num, e));
+                    continue;
+                }
+            };
+
+            let existing = if upsert {
+                FoodItem::find_by_name_brand(conn, &row.name, row.brand.as_deref())
+                    .map_err(|e| ToolError::Db(format!("Failed to look up food item: {}", e)))?
+            } else {
+                None
+            };
+
+            match existing {
+                Some(item) if food_item_matches(&item, &row, has_notes) => unchanged += 1,
+                Some(item) => {
+                    let update = FoodItemUpdate {
+                        name: None,
+                        brand: None,
+                        serving_size: Some(row.serving_size),
+                        serving_unit: Some(row.serving_unit),
+                        calories: Some(row.calories),
+                        protein: Some(row.protein),
+                        carbs: Some(row.carbs),
+                        fat: Some(row.fat),
+                        fiber: Some(row.fiber),
+                        sodium: Some(row.sodium),
+                        sugar: Some(row.sugar),
+                        saturated_fat: Some(row.saturated_fat),
+                        cholesterol: Some(row.cholesterol),
+                        preference: Some(row.preference),
+                        // A blank notes cell clears the notes; no notes column leaves them
+                        notes: if has_notes { Some(row.notes.unwrap_or_default()) } else { None },
+                        base_unit_type: row.base_unit_type,
+                        grams_per_serving: row.grams_per_serving,
+                        ml_per_serving: row.ml_per_serving,
+                    };
+                    match FoodItem::update(conn, item.id, &update) {
+                        Ok(_) => {
+                            updated += 1;
+                            changed_ids.insert(item.id);
+                        }
+                        Err(e) => errors.push(format!("Row {}: Failed to update '{}': {}", line_num, item.name, e)),
+                    }
+                }
+                None => match FoodItem::create(conn, &row) {
+                    Ok(_) => created += 1,
+                    Err(e) => errors.push(format!("Row {}: Failed to create '{}': {}", line_num, row.name, e)),
+                },
+            }
+        }
+        Ok(())
+    })?;
+
+    // Recalculate after commit: the cascade runs on its own connection
+    let cascade = batch_cascade_recalculate(db, &changed_ids)?;
+
+    Ok(ImportFoodItemsCsvResponse {
+        file_path: file_path.to_string(),
+        mode: mode.to_lowercase(),
+        rows_read,
+        created,
+        updated,
+        unchanged,
+        errors,
+        recipes_recalculated: cascade.recipes_recalculated,
+        days_recalculated: cascade.days_recalculated,
+    })
+}
+
+// ============================================================================
+// USDA FoodData Central Import
+// ============================================================================
+
+/// FDC nutrient ids (and legacy nutrient numbers) for each food item field
+const FDC_NUTRIENTS: &[(&str, &[i64], &[&str])] = &[
+    // 1008 = Energy (kcal); 2047/2048 = Atwater general/specific energy (Foundation Foods)
+    ("calories", &[1008, 2047, 2048], &["208", "957", "958"]),
+    ("protein", &[1003], &["203"]),
+    ("fat", &[1004], &["204"]),
+    ("carbs", &[1005], &["205"]),
+    ("fiber", &[1079], &["291"]),
+    ("sodium", &[1093], &["307"]),
+    // 2000 = Sugars, total including NLEA; 1063 = Sugars, Total NLEA
+    ("sugar", &[2000, 1063], &["269", "269.3"]),
+    ("saturated_fat", &[1258], &["606"]),
+    ("cholesterol", &[1253], &["601"]),
+];
+
+/// One food created (or skipped) by import_usda_food
+#[derive(Debug, Serialize)]
+pub struct UsdaImportedFood {
+    pub fdc_id: Option<i64>,
+    pub name: String,
+    /// Id of the created food item, or of the existing one when skipped
+    pub food_item_id: i64,
+    /// True when a food item with the same name and brand already existed
+    pub skipped_existing: bool,
+    pub calories_per_100g: f64,
+    /// Fields with no matching nutrient in the FDC record (stored as 0)
+    pub missing_nutrients: Vec<String>,
+}
+
+/// Response for import_usda_food
+#[derive(Debug, Serialize)]
+pub struct ImportUsdaFoodResponse {
+    pub file_path: String,
+    pub foods_read: usize,
+    pub created: usize,
+    pub skipped: usize,
+    pub foods: Vec<UsdaImportedFood>,
+    pub errors: Vec<String>,
+}
+
+/// Pull (nutrient id, nutrient number, amount, unit) from any FDC foodNutrients entry layout
+fn fdc_nutrient_entry(entry: &serde_json::Value) -> Option<(Option<i64>, Option<String>, f64, String)> {
+    let nutrient = entry.get("nutrient");
+    let id = nutrient
+        .and_then(|n| n.get("id"))
+        .or_else(|| entry.get("nutrientId"))
+        .and_then(|v| v.as_i64());
+    let number = nutrient
+        .and_then(|n| n.get("number"))
+        .or_else(|| entry.get("nutrientNumber"))
+        .or_else(|| entry.get("number"))
+        .and_then(|v| v.as_str().map(String::from).or_else(|| v.as_f64().map(|f| f.to_string())));
+    let amount = entry.get("amount").or_else(|| entry.get("value")).and_then(|v| v.as_f64())?;
+    let unit = nutrient
+        .and_then(|n| n.get("unitName"))
+        .or_else(|| entry.get("unitName"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    Some((id, number, amount, unit))
+}
+
+/// Convert one FDC food object into a per-100g FoodItemCreate
+fn fdc_food_to_create(food: &serde_json::Value) -> Result<(FoodItemCreate, Vec<String>), ToolError> {
+    let name = food
+        .get("description")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ToolError::Validation("Food has no description".to_string()))?;
+
+    let entries: Vec<_> = food
+        .get("foodNutrients")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| ToolError::Validation(format!("'{}' has no foodNutrients", name)))?
+        .iter()
+        .filter_map(fdc_nutrient_entry)
+        .collect();
+
+    let mut values: HashMap<&str, f64> = HashMap::new();
+    let mut missing = Vec::new();
+    for (field, ids, numbers) in FDC_NUTRIENTS {
+        // Earlier ids in the list take priority
+        let found = ids
+            .iter()
+            .find_map(|id| entries.iter().find(|e| e.0 == Some(*id)))
+            .or_else(|| {
+                numbers
+                    .iter()
+                    .find_map(|num| entries.iter().find(|e| e.1.as_deref() == Some(*num)))
+            })
+            .filter(|e| !(*field == "calories" && e.3.eq_ignore_ascii_case("kj")));
+        match found {
+            Some(e) => {
+                values.insert(field, e.2);
+            }
+            None => missing.push(field.to_string()),
+        }
+    }
+
+    let get = |field: &str| values.get(field).copied().unwrap_or(0.0);
+    let fdc_id = food.get("fdcId").and_then(|v| v.as_i64());
+    let data_type = food.get("dataType").and_then(|v| v.as_str()).unwrap_or("unknown data type");
+    let notes = match fdc_id {
+        Some(id) => format!("USDA FoodData Central #{} ({})", id, data_type),
+        None => format!("USDA FoodData Central ({})", data_type),
+    };
+
+    let create = FoodItemCreate {
+        name,
+        brand: food.get("brandOwner").and_then(|v| v.as_str()).map(String::from),
+        serving_size: 100.0,
+        serving_unit: "g".to_string(),
+        calories: get("calories"),
+        protein: get("protein"),
+        carbs: get("carbs"),
+        fat: get("fat"),
+        fiber: get("fiber"),
+        sodium: get("sodium"),
+        sugar: get("sugar"),
+        saturated_fat: get("saturated_fat"),
+        cholesterol: get("cholesterol"),
+        preference: Preference::Neutral,
+        notes: Some(notes),
+        base_unit_type: None,
+        grams_per_serving: None,
+        ml_per_serving: None,
+    };
+    Ok((create, missing))
+}
+
+/// Import foods from a USDA FoodData Central JSON download
+///
+/// Accepts a single food object or a download wrapper such as
+/// {"FoundationFoods": [...]} / {"SRLegacyFoods": [...]}. FDC reports these
+/// data types per 100 g, so each food is stored as serving_size 100 g.
+/// Foods whose name and brand already exist are skipped.
+pub fn import_usda_food(db: &Database, file_path: &str) -> Result<ImportUsdaFoodResponse, ToolError> {
+    let contents = std::fs::read_to_string(file_path)
+        .map_err(|e| ToolError::Io(format!("Failed to read file '{}': {}", file_path, e)))?;
+    let json: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| ToolError::Validation(format!("Invalid JSON in '{}': {}", file_path, e)))?;
+
+    let foods: Vec<&serde_json::Value> = if json.get("foodNutrients").is_some() {
+        vec![&json]
+    } else if let Some(arr) = json.as_array() {
+        arr.iter().collect()
+    } else if let Some(obj) = json.as_object() {
+        obj.values()
+            .filter_map(|v| v.as_array())
+            .flatten()
+            .filter(|f| f.get("foodNutrients").is_some())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    if foods.is_empty() {
+        return Err(ToolError::Validation("No FoodData Central foods found in file (expected an object with foodNutrients)".to_string()));
+    }
+
+    let mut response = ImportUsdaFoodResponse {
+        file_path: file_path.to_string(),
+        foods_read: foods.len(),
+        created: 0,
+        skipped: 0,
+        foods: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    db.transaction(|conn| -> Result<(), ToolError> {
+        for (i, food) in foods.iter().enumerate() {
+            let (create, missing_nutrients) = match fdc_food_to_create(food) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    response.errors.push(format!("Food {}: {}", i + 1, e));
+                    continue;
+                }
+            };
+            if let Err(e) = validate_food_item(&create) {
+                response.errors.push(format!("Food {} ('{}'): {}", i + 1, create.name, e));
+                continue;
+            }
+
+            let existing = FoodItem::find_by_name_brand(conn, &create.name, create.brand.as_deref())?;
+            let (food_item_id, skipped_existing) = match existing {
+                Some(item) => {
+                    response.skipped += 1;
+                    (item.id, true)
+                }
+                None => match FoodItem::create(conn, &create) {
+                    Ok(item) => {
+                        response.created += 1;
+                        (item.id, false)
+                    }
+                    Err(e) => {
+                        response.errors.push(format!("Food {} ('{}'): Failed to create: {}", i + 1, create.name, e));
+                        continue;
+                    }
+                },
+            };
+
+            response.foods.push(UsdaImportedFood {
+                fdc_id: food.get("fdcId").and_then(|v| v.as_i64()),
+                name: create.name,
+                food_item_id,
+                skipped_existing,
+                calories_per_100g: create.calories,
+                missing_nutrients,
+            });
+        }
+        Ok(())
+    })?;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{make_food, temp_db};
+
+    #[test]
+    fn test_import_csv_blank_notes_clear_existing_notes() {
+        let db = temp_db("import-csv-notes");
+        let csv_path = std::env::temp_dir().join(format!("uhm-test-import-notes-{}.csv", std::process::id()));
+        db.with_conn(|conn| Ok(make_food(conn, "Oats", serde_json::json!({ "calories": 380.0, "notes": "rolled" })))).unwrap();
+
+        let import = |csv: &str| {
+            std::fs::write(&csv_path, csv).unwrap();
+            import_food_items_csv(&db, csv_path.to_str().unwrap(), "upsert").unwrap()
+        };
+        let notes = || db.with_conn(|conn| Ok(FoodItem::find_by_name_brand(conn, "Oats", None)?.unwrap().notes)).unwrap();
+
+        // No notes column: existing notes are neither compared nor touched
+        let result = import("name,serving_size,serving_unit,calories\nOats,100,g,380\n");
+        assert_eq!((result.updated, result.unchanged), (0, 1));
+        assert_eq!(notes().as_deref(), Some("rolled"));
+
+        let result = import("name,serving_size,serving_unit,calories,notes\nOats,100,g,380,\n");
+        assert_eq!((result.updated, result.unchanged), (1, 0));
+        assert_eq!(notes().unwrap_or_default(), "");
+
+        // Blank again: nothing left to change
+        let result = import("name,serving_size,serving_unit,calories,notes\nOats,100,g,380,\n");
+        assert_eq!((result.updated, result.unchanged), (0, 1));
+
+        let _ = std::fs::remove_file(&csv_path);
+    }
+
+    #[test]
+    fn test_non_canonical_serving() {
+        assert!(non_canonical_serving(100.0, "g").is_none());
+        assert!(non_canonical_serving(100.0, " ML ").is_none());
+        assert!(non_canonical_serving(1.0, "count").is_none());
+
+        // Right category, wrong size or spelling
+        assert!(non_canonical_serving(32.0, "g").unwrap().contains("serving_size 100"));
+        assert!(non_canonical_serving(2.0, "tbsp").unwrap().contains("\"ml\""));
+        assert!(non_canonical_serving(1.0, "each").unwrap().contains("\"count\""));
+
+        assert!(non_canonical_serving(1.0, "handful").unwrap().contains("not a recognized unit"));
+    }
+
+    #[test]
+    fn test_nutrition_issues() {
+        let rules = |n: &Nutrition, grams| nutrition_issues(n, grams).into_iter().map(|(r, _)| r).collect::<Vec<_>>();
+
+        // Olive oil: 884 kcal / 100 g is fine
+        let oil = Nutrition { calories: 884.0, fat: 100.0, ..Default::default() };
+        assert!(rules(&oil, Some(100.0)).is_empty());
+
+        let typo = Nutrition { calories: 9000.0, protein: 80.0, carbs: 30.0, fat: 5.0, ..Default::default() };
+        assert_eq!(rules(&typo, Some(100.0)), vec!["calories_per_gram", "macros_exceed_mass"]);
+        // Mass rules need a gram weight
+        assert!(rules(&typo, None).is_empty());
+
+        let negative = Nutrition { calories: 100.0, sodium: -5.0, ..Default::default() };
+        assert_eq!(rules(&negative, None), vec!["negative_value"]);
+    }
+
+    #[test]
+    fn test_audit_food_items_sorts_by_severity_and_pages() {
+        let db = temp_db("audit-food-items");
+        db.with_conn(|conn| {
+            // Too many calories and macros for 100 g: a medium and a low issue
+            make_food(conn, "Typo bar", serde_json::json!({
+                "grams_per_serving": 100.0, "calories": 9000.0, "protein": 80.0, "carbs": 30.0, "fat": 5.0
+            }));
+            make_food(conn, "Negative soup", serde_json::json!({ "calories": 50.0, "sodium": -5.0 }));
+            make_food(conn, "Plain rice", serde_json::json!({ "grams_per_serving": 100.0, "calories": 130.0, "carbs": 28.0 }));
+            Ok(())
+        }).unwrap();
+
+        let audit = |sort_by: &str, limit: i64, offset: i64| {
+            let result = audit_food_items(&db, sort_by, limit, offset).unwrap();
+            let issues = result.issues.iter().map(|i| (i.name.clone(), i.severity.clone())).collect::<Vec<_>>();
+            (result, issues)
+        };
+        let issue = |name: &str, severity: &str| (name.to_string(), severity.to_string());
+
+        let (result, issues) = audit("severity", 50, 0);
+        assert_eq!((result.items_checked, result.items_flagged, result.issue_count), (3, 2, 3));
+        assert_eq!(issues, vec![issue("Negative soup", "high"), issue("Typo bar", "medium"), issue("Typo bar", "low")]);
+
+        let (_, issues) = audit("ID", 50, 0);
+        assert_eq!(issues, vec![issue("Typo bar", "medium"), issue("Typo bar", "low"), issue("Negative soup", "high")]);
+
+        // issue_count stays the total; pages are cut after sorting
+        let (result, issues) = audit("severity", 1, 1);
+        assert_eq!(result.issue_count, 3);
+        assert_eq!(issues, vec![issue("Typo bar", "medium")]);
+        assert!(audit("severity", 50, 3).1.is_empty());
+
+        // Out-of-range paging is clamped rather than rejected
+        let (result, issues) = audit("severity", 0, -4);
+        assert_eq!((result.limit, result.offset), (1, 0));
+        assert_eq!(issues, vec![issue("Negative soup", "high")]);
+        assert_eq!(audit("severity", 1000, 0).0.limit, 200);
+
+        assert!(audit_food_items(&db, "name", 50, 0).is_err());
+    }
+
+    #[test]
+    fn test_preview_food_item_cascade() {
+        use crate::clock::SystemClock;
+        use crate::models::{
+            Day, MealEntry, MealEntryCreate, MealType, Recipe, RecipeComponent, RecipeComponentCreate, RecipeCreate,
+            RecipeIngredient, RecipeIngredientCreate,
+        };
+
+        let db = temp_db("cascade-preview");
+        let (oats_id, bowl_id, big_id) = db.with_conn(|conn| {
+            let oats = make_food(conn, "Oats", serde_json::json!({ "calories": 380.0 }));
+            let toast = make_food(conn, "Toast", serde_json::json!({ "calories": 250.0 }));
+            let recipe = |name: &str| Recipe::create(conn, &RecipeCreate {
+                name: name.to_string(), servings_produced: 1.0, yield_grams: None, is_favorite: false, notes: None,
+            });
+            let bowl = recipe("Bowl")?;
+            RecipeIngredient::create(conn, &RecipeIngredientCreate {
+                recipe_id: bowl.id, food_item_id: oats.id, quantity: 50.0, unit: "g".to_string(), notes: None,
+            })?;
+            let big = recipe("Big bowl")?;
+            RecipeComponent::create(conn, &RecipeComponentCreate { recipe_id: big.id, component_recipe_id: bowl.id, servings: 2.0, notes: None })?;
+
+            // Reached through the parent recipe, directly, and not at all
+            for (date, recipe_id, food_item_id) in [
+                ("2026-03-02", Some(big.id), None),
+                ("2026-03-01", None, Some(oats.id)),
+                ("2026-03-03", None, Some(toast.id)),
+            ] {
+                let day = Day::get_or_create(conn, date)?;
+                MealEntry::create(conn, &MealEntryCreate {
+                    day_id: day.id, meal_type: MealType::Breakfast, recipe_id, food_item_id,
+                    description: None, inline_nutrition: None, servings: 1.0, percent_eaten: None, notes: None,
+                    eaten_at: None, planned: false,
+                }, &SystemClock)?;
+            }
+            Ok((oats.id, bowl.id, big.id))
+        }).unwrap();
+
+        let preview = preview_food_item_cascade(&db, oats_id).unwrap();
+        assert_eq!(preview.food_item_name, "Oats");
+        assert_eq!((preview.recipe_count, preview.day_count), (2, 2));
+        // Dependencies first, so the component is recalculated before its parent
+        let recipes: Vec<(i64, bool)> = preview.recipes.iter().map(|r| (r.id, r.direct)).collect();
+        assert_eq!(recipes, vec![(bowl_id, true), (big_id, false)]);
+        let dates: Vec<&str> = preview.days.iter().map(|d| d.date.as_str()).collect();
+        assert_eq!(dates, vec!["2026-03-01", "2026-03-02"]);
+
+        // Nothing is recalculated by a preview
+        let bowl = db.with_conn(|conn| Ok(Recipe::get_by_id(conn, bowl_id)?.unwrap())).unwrap();
+        assert_eq!(bowl.cached_nutrition.calories, 0.0);
+
+        assert!(matches!(preview_food_item_cascade(&db, oats_id + 100), Err(ToolError::NotFound(_))));
+    }
+}
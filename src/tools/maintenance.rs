@@ -0,0 +1,728 @@
+//! Database Maintenance MCP Tools
+//!
+//! Tools for keeping the SQLite database file compact and healthy.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::clock::canonical_timestamp;
+use crate::db::{transaction_on, Database};
+use super::error::ToolError;
+
+/// Held while optimize_database runs so two optimizations can't overlap
+static OPTIMIZE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Response for optimize_database
+#[derive(Debug, Serialize)]
+pub struct OptimizeDatabaseResponse {
+    pub database_path: String,
+    /// Size of the database file plus its WAL before optimizing
+    pub size_before_bytes: u64,
+    pub size_after_bytes: u64,
+    pub reclaimed_bytes: i64,
+    /// WAL frames copied back into the database by the checkpoint
+    pub wal_frames_checkpointed: i64,
+}
+
+/// Combined size of the database file and its WAL (missing files count as 0)
+fn database_size(db_path: &Path) -> u64 {
+    let mut wal_path = PathBuf::from(db_path);
+    wal_path.as_mut_os_string().push("-wal");
+
+    [db_path, wal_path.as_path()]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Checkpoint the WAL, then run PRAGMA optimize and VACUUM to reclaim space
+pub fn optimize_database(db: &Database, db_path: &Path) -> Result<OptimizeDatabaseResponse, ToolError> {
+    let _guard = OPTIMIZE_LOCK
+        .try_lock()
+        .map_err(|_| ToolError::Validation("Database optimization is already running".to_string()))?;
+
+    let size_before_bytes = database_size(db_path);
+
+    let conn = db.get_conn()?;
+
+    if !conn.is_autocommit() {
+        return Err(ToolError::Validation("Cannot optimize while a transaction is open".to_string()));
+    }
+
+    // Fold the WAL back into the main file first. A busy result means another
+    // connection is reading or writing, and VACUUM would fail or block on it.
+    let (busy, _log_frames, checkpointed): (i64, i64, i64) = conn
+        .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .map_err(|e| ToolError::Db(format!("Failed to checkpoint WAL: {}", e)))?;
+
+    if busy != 0 {
+        return Err(ToolError::Validation("Database is busy with other readers or writers; try again shortly".to_string()));
+    }
+
+    conn.execute_batch("PRAGMA optimize;")
+        .map_err(|e| ToolError::Db(format!("Failed to run PRAGMA optimize: {}", e)))?;
+
+    conn.execute_batch("VACUUM;")
+        .map_err(|e| ToolError::Db(format!("Failed to VACUUM database: {}", e)))?;
+
+    let size_after_bytes = database_size(db_path);
+
+    Ok(OptimizeDatabaseResponse {
+        database_path: db_path.display().to_string(),
+        size_before_bytes,
+        size_after_bytes,
+        reclaimed_bytes: size_before_bytes as i64 - size_after_bytes as i64,
+        wal_frames_checkpointed: checkpointed,
+    })
+}
+
+/// Response for open_database
+#[derive(Debug, Serialize)]
+pub struct OpenDatabaseResponse {
+    pub database_path: String,
+    pub previous_path: String,
+    /// Schema version of the new database after migrations ran
+    pub schema_version: i32,
+    /// True if the UHM schema was created (the file was missing or had no schema)
+    pub created: bool,
+}
+
+/// Whether the SQLite file at `path` has been set up by UHM. Opened read-only
+/// so a file we then refuse is left untouched.
+fn has_uhm_schema(path: &Path) -> Result<bool, ToolError> {
+    let conn = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| ToolError::Db(format!("Failed to open '{}': {}", path.display(), e)))?;
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations')",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| ToolError::Validation(format!("'{}' is not a SQLite database: {}", path.display(), e)))
+}
+
+/// Open the UHM database at `new_path`, migrate it, and swap `db` over to it.
+/// A missing file or one without the UHM schema is only set up with `create`.
+/// The current database stays in use if any step fails.
+pub fn open_database(db: &Database, current_path: &Path, new_path: &Path, create: bool) -> Result<OpenDatabaseResponse, ToolError> {
+    if new_path.as_os_str().is_empty() {
+        return Err(ToolError::Validation("Database path cannot be empty".to_string()));
+    }
+    if new_path.is_dir() {
+        return Err(ToolError::Validation(format!("'{}' is a directory, not a database file", new_path.display())));
+    }
+    let parent = new_path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    if !parent.is_dir() {
+        return Err(ToolError::Validation(format!("Directory '{}' does not exist", parent.display())));
+    }
+
+    // Don't write a UHM schema into a missing file or someone else's database
+    // (a mistyped path) unless asked to
+    let created = !new_path.exists() || !has_uhm_schema(new_path)?;
+    if created && !create {
+        return Err(ToolError::Validation(format!(
+            "'{}' is not a UHM database; pass create=true to create one there",
+            new_path.display()
+        )));
+    }
+
+    let new_db = Database::new(new_path)
+        .map_err(|e| ToolError::Db(format!("Failed to open '{}': {}", new_path.display(), e)))?;
+
+    let schema_version = new_db
+        .with_conn(|conn| {
+            crate::db::migrations::run_migrations(conn)?;
+            crate::db::migrations::get_schema_version(conn)
+        })
+        .map_err(|e| ToolError::Db(format!("Failed to migrate '{}': {}", new_path.display(), e)))?;
+
+    db.replace_with(&new_db)?;
+
+    Ok(OpenDatabaseResponse {
+        database_path: new_path.display().to_string(),
+        previous_path: current_path.display().to_string(),
+        schema_version,
+        created,
+    })
+}
+
+// ============================================================================
+// Dangling Meal Entries
+// ============================================================================
+
+/// A meal entry whose recipe or food item no longer exists
+#[derive(Debug, Serialize)]
+pub struct DanglingMealEntry {
+    pub id: i64,
+    /// None if the day itself is gone too
+    pub date: Option<String>,
+    pub meal_type: String,
+    /// "recipe" or "food_item"
+    pub source_type: String,
+    pub missing_source_id: i64,
+    pub servings: f64,
+    pub eaten_at: Option<String>,
+    pub planned: bool,
+}
+
+/// Response for find_dangling_meal_entries
+#[derive(Debug, Serialize)]
+pub struct FindDanglingMealEntriesResponse {
+    /// Whether PRAGMA foreign_keys is on for server connections (it should always be)
+    pub foreign_keys_enforced: bool,
+    pub entries_scanned: i64,
+    pub dangling: Vec<DanglingMealEntry>,
+}
+
+/// Find meal entries whose recipe_id or food_item_id doesn't resolve, e.g.
+/// after a row was deleted out-of-band with foreign keys off
+pub fn find_dangling_meal_entries(db: &Database) -> Result<FindDanglingMealEntriesResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let foreign_keys_enforced: bool = conn
+        .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+        .map_err(|e| ToolError::Db(format!("Failed to read foreign_keys pragma: {}", e)))?;
+
+    let entries_scanned: i64 = conn
+        .query_row("SELECT COUNT(*) FROM meal_entries", [], |row| row.get(0))
+        .map_err(|e| ToolError::Db(format!("Failed to count meal entries: {}", e)))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT me.id, d.date, me.meal_type,
+                    CASE WHEN me.recipe_id IS NOT NULL AND r.id IS NULL THEN 'recipe' ELSE 'food_item' END,
+                    CASE WHEN me.recipe_id IS NOT NULL AND r.id IS NULL THEN me.recipe_id ELSE me.food_item_id END,
+                    me.servings, me.eaten_at, me.planned
+             FROM meal_entries me
+             LEFT JOIN days d ON d.id = me.day_id
+             LEFT JOIN recipes r ON r.id = me.recipe_id
+             LEFT JOIN food_items f ON f.id = me.food_item_id
+             WHERE (me.recipe_id IS NOT NULL AND r.id IS NULL)
+                OR (me.food_item_id IS NOT NULL AND f.id IS NULL)
+             ORDER BY d.date, me.id",
+        )
+        .map_err(|e| ToolError::Db(format!("Failed to query meal entries: {}", e)))?;
+
+    let dangling = stmt
+        .query_map([], |row| {
+            Ok(DanglingMealEntry {
+                id: row.get(0)?,
+                date: row.get(1)?,
+                meal_type: row.get(2)?,
+                source_type: row.get(3)?,
+                missing_source_id: row.get(4)?,
+                servings: row.get(5)?,
+                eaten_at: row.get(6)?,
+                planned: row.get(7)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| ToolError::Db(format!("Failed to read meal entries: {}", e)))?;
+
+    Ok(FindDanglingMealEntriesResponse {
+        foreign_keys_enforced,
+        entries_scanned,
+        dangling,
+    })
+}
+
+// ============================================================================
+// Timestamp Format Repair
+// ============================================================================
+
+/// Canonical stored timestamp layout (what the server writes by default)
+const CANONICAL_TIMESTAMP_FORMAT: &str = crate::clock::TIMESTAMP_FORMAT;
+
+/// (table, column) pairs holding reading/meal timestamps
+const TIMESTAMP_COLUMNS: &[(&str, &str)] = &[
+    ("vitals", "timestamp"),
+    ("vital_groups", "timestamp"),
+    ("meal_entries", "eaten_at"),
+];
+
+/// A stored timestamp that isn't in canonical form
+#[derive(Debug, Serialize)]
+pub struct TimestampIssue {
+    pub table: String,
+    pub column: String,
+    pub id: i64,
+    pub timestamp: String,
+    /// Canonical replacement, or None if the value can't be parsed
+    pub canonical: Option<String>,
+}
+
+/// Response for check_timestamp_formats
+#[derive(Debug, Serialize)]
+pub struct CheckTimestampFormatsResponse {
+    pub canonical_format: String,
+    pub rows_scanned: usize,
+    pub fixable: usize,
+    pub unfixable: usize,
+    pub issues: Vec<TimestampIssue>,
+}
+
+/// Response for fix_timestamp_formats
+#[derive(Debug, Serialize)]
+pub struct FixTimestampFormatsResponse {
+    pub fixed: usize,
+    /// Values left untouched because they couldn't be parsed
+    pub unfixable: Vec<TimestampIssue>,
+}
+
+/// Find every non-canonical timestamp across the timestamped tables
+fn find_timestamp_issues(conn: &rusqlite::Connection) -> Result<(usize, Vec<TimestampIssue>), ToolError> {
+    let mut rows_scanned = 0;
+    let mut issues = Vec::new();
+
+    for (table, column) in TIMESTAMP_COLUMNS {
+        let sql = format!("SELECT id, {col} FROM {table} WHERE {col} IS NOT NULL ORDER BY id", col = column, table = table);
+        let mut stmt = conn.prepare(&sql).map_err(|e| ToolError::Db(format!("Failed to scan {}: {}", table, e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| ToolError::Db(format!("Failed to scan {}: {}", table, e)))?;
+
+        rows_scanned += rows.len();
+        for (id, timestamp) in rows {
+            let canonical = canonical_timestamp(&timestamp);
+            if canonical.as_deref() != Some(timestamp.as_str()) {
+                issues.push(TimestampIssue {
+                    table: table.to_string(),
+                    column: column.to_string(),
+                    id,
+                    timestamp,
+                    canonical,
+                });
+            }
+        }
+    }
+
+    Ok((rows_scanned, issues))
+}
+
+/// Report timestamps in vitals, vital groups and meal entries that aren't canonical
+pub fn check_timestamp_formats(db: &Database) -> Result<CheckTimestampFormatsResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let (rows_scanned, issues) = find_timestamp_issues(&conn)?;
+    let fixable = issues.iter().filter(|i| i.canonical.is_some()).count();
+
+    Ok(CheckTimestampFormatsResponse {
+        canonical_format: "YYYY-MM-DDTHH:MM:SS (local time)".to_string(),
+        rows_scanned,
+        fixable,
+        unfixable: issues.len() - fixable,
+        issues,
+    })
+}
+
+/// Rewrite every parseable non-canonical timestamp in canonical form (one transaction).
+///
+/// UTC values from older versions are converted to this host's local time, so
+/// this only runs when asked; migrations leave stored timestamps alone.
+pub fn fix_timestamp_formats(db: &Database) -> Result<FixTimestampFormatsResponse, ToolError> {
+    db.transaction(|tx| {
+        let (_, issues) = find_timestamp_issues(tx)?;
+        let mut fixed = 0;
+        let mut unfixable = Vec::new();
+
+        for issue in issues {
+            let Some(ref canonical) = issue.canonical else {
+                unfixable.push(issue);
+                continue;
+            };
+            let sql = format!("UPDATE {} SET {} = ?1 WHERE id = ?2", issue.table, issue.column);
+            tx.execute(&sql, rusqlite::params![canonical, issue.id])
+                .map_err(|e| ToolError::Db(format!("Failed to update {} {}: {}", issue.table, issue.id, e)))?;
+            fixed += 1;
+        }
+
+        Ok(FixTimestampFormatsResponse { fixed, unfixable })
+    })
+}
+
+// ============================================================================
+// Data Archival
+// ============================================================================
+
+/// Response for archive_old_data
+#[derive(Debug, Serialize)]
+pub struct ArchiveOldDataResponse {
+    pub archive_path: String,
+    pub before_date: String,
+    pub days_archived: i64,
+    pub meal_entries_archived: i64,
+    pub vitals_archived: i64,
+    pub vital_groups_archived: i64,
+    /// Referenced food items and recipes (with their ingredients and nested
+    /// components) copied so the archive is readable on its own
+    pub food_items_copied: i64,
+    pub recipes_copied: i64,
+    pub recipe_ingredients_copied: i64,
+    pub recipe_components_copied: i64,
+    /// Custom vital types used by the archived vitals, copied so they keep their name and unit
+    pub custom_vital_types_copied: i64,
+    /// False unless force was set; nothing is removed from the main database otherwise
+    pub deleted: bool,
+    pub days_deleted: i64,
+    pub meal_entries_deleted: i64,
+    pub vitals_deleted: i64,
+    /// Groups still referenced by newer vitals are kept
+    pub vital_groups_deleted: i64,
+}
+
+/// Copy days, meal entries, vitals and vital groups dated before `before_date`
+/// into a new SQLite file, optionally deleting them from the main database.
+///
+/// Recipes referenced by the archived meals are copied (not moved) along with
+/// their ingredients, their component recipes at any depth, and every food
+/// item those reach: they are shared with retained data and are never deleted here.
+/// Custom vital types used by archived vitals are copied the same way.
+pub fn archive_old_data(
+    db: &Database,
+    before_date: &str,
+    archive_path: &Path,
+    force: bool,
+) -> Result<ArchiveOldDataResponse, ToolError> {
+    chrono::NaiveDate::parse_from_str(before_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid before_date '{}'. Use YYYY-MM-DD", before_date)))?;
+    if archive_path.exists() {
+        return Err(ToolError::Validation(format!("Archive file '{}' already exists; choose a new path", archive_path.display())));
+    }
+
+    let mut conn = db.get_conn()?;
+
+    conn.execute("ATTACH DATABASE ?1 AS archive", [archive_path.to_string_lossy()])
+        .map_err(|e| ToolError::Db(format!("Failed to create archive '{}': {}", archive_path.display(), e)))?;
+
+    // The transaction has to run on the connection the archive is attached to
+    let result = transaction_on(&mut conn, |tx| -> Result<ArchiveOldDataResponse, ToolError> {
+        let run = |sql: &str| -> Result<i64, ToolError> {
+            tx.execute(sql, [before_date])
+                .map(|n| n as i64)
+                .map_err(|e| ToolError::Db(format!("Archive failed: {}", e)))
+        };
+
+        run("CREATE TABLE archive.days AS SELECT * FROM main.days WHERE date < ?1")?;
+        run("CREATE TABLE archive.meal_entries AS SELECT * FROM main.meal_entries
+             WHERE day_id IN (SELECT id FROM main.days WHERE date < ?1)")?;
+        run("CREATE TABLE archive.vitals AS SELECT * FROM main.vitals WHERE timestamp < ?1")?;
+        run("CREATE TABLE archive.vital_groups AS SELECT * FROM main.vital_groups WHERE timestamp < ?1")?;
+        tx.execute_batch(
+            "CREATE TABLE archive.custom_vital_types AS SELECT * FROM main.custom_vital_types
+                 WHERE id IN (SELECT custom_type_id FROM archive.vitals);",
+        )
+        .map_err(|e| ToolError::Db(format!("Archive failed: {}", e)))?;
+        run("CREATE TABLE archive.recipes AS
+             WITH RECURSIVE used(id) AS (
+                 SELECT recipe_id FROM main.meal_entries
+                 WHERE day_id IN (SELECT id FROM main.days WHERE date < ?1)
+                 UNION
+                 SELECT rc.component_recipe_id FROM main.recipe_components rc JOIN used ON rc.recipe_id = used.id
+             )
+             SELECT * FROM main.recipes WHERE id IN (SELECT id FROM used)")?;
+        tx.execute_batch(
+            "CREATE TABLE archive.recipe_ingredients AS SELECT * FROM main.recipe_ingredients
+                 WHERE recipe_id IN (SELECT id FROM archive.recipes);
+             CREATE TABLE archive.recipe_components AS SELECT * FROM main.recipe_components
+                 WHERE recipe_id IN (SELECT id FROM archive.recipes);",
+        )
+        .map_err(|e| ToolError::Db(format!("Archive failed: {}", e)))?;
+        run("CREATE TABLE archive.food_items AS SELECT * FROM main.food_items WHERE id IN (
+                 SELECT food_item_id FROM main.meal_entries
+                 WHERE day_id IN (SELECT id FROM main.days WHERE date < ?1)
+                 UNION
+                 SELECT food_item_id FROM archive.recipe_ingredients)")?;
+
+        let count = |table: &str| -> Result<i64, ToolError> {
+            tx.query_row(&format!("SELECT COUNT(*) FROM archive.{}", table), [], |row| row.get(0))
+                .map_err(|e| ToolError::Db(format!("Failed to count archived {}: {}", table, e)))
+        };
+
+        let mut response = ArchiveOldDataResponse {
+            archive_path: archive_path.display().to_string(),
+            before_date: before_date.to_string(),
+            days_archived: count("days")?,
+            meal_entries_archived: count("meal_entries")?,
+            vitals_archived: count("vitals")?,
+            vital_groups_archived: count("vital_groups")?,
+            food_items_copied: count("food_items")?,
+            recipes_copied: count("recipes")?,
+            recipe_ingredients_copied: count("recipe_ingredients")?,
+            recipe_components_copied: count("recipe_components")?,
+            custom_vital_types_copied: count("custom_vital_types")?,
+            deleted: force,
+            days_deleted: 0,
+            meal_entries_deleted: 0,
+            vitals_deleted: 0,
+            vital_groups_deleted: 0,
+        };
+
+        if force {
+            // Meal entries go with their days (ON DELETE CASCADE)
+            response.meal_entries_deleted = response.meal_entries_archived;
+            response.days_deleted = run("DELETE FROM main.days WHERE date < ?1")?;
+            response.vitals_deleted = run("DELETE FROM main.vitals WHERE timestamp < ?1")?;
+            response.vital_groups_deleted = run(
+                "DELETE FROM main.vital_groups WHERE timestamp < ?1
+                 AND id NOT IN (SELECT group_id FROM main.vitals WHERE group_id IS NOT NULL)",
+            )?;
+        }
+
+        Ok(response)
+    });
+
+    let detached = conn.execute_batch("DETACH DATABASE archive");
+    if result.is_err() {
+        // Don't leave a half-written archive behind
+        let _ = std::fs::remove_file(archive_path);
+    }
+    detached.map_err(|e| ToolError::Db(format!("Failed to detach archive: {}", e)))?;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::test_support::{make_food, remove_db_files, temp_db, temp_db_path};
+
+    #[test]
+    fn test_fix_timestamp_formats_converts_utc_rows_on_request() {
+        let db = temp_db("fix-utc-timestamps");
+        db.with_conn(|conn| {
+            conn.execute_batch(
+                "INSERT INTO vital_groups (timestamp) VALUES ('2026-01-06T08:18:00Z');
+                 INSERT INTO vitals (vital_type, timestamp, value1, unit) VALUES ('weight', '2026-01-06T08:18:00Z', 180, 'lbs');
+                 INSERT INTO vitals (vital_type, timestamp, value1, unit) VALUES ('weight', '2026-01-07T09:00:00', 179, 'lbs');",
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        // Nothing is rewritten until the tool is run
+        assert_eq!(check_timestamp_formats(&db).unwrap().fixable, 2);
+        let fixed = fix_timestamp_formats(&db).unwrap();
+        assert_eq!((fixed.fixed, fixed.unfixable.len()), (2, 0));
+
+        let local = canonical_timestamp("2026-01-06T08:18:00Z").unwrap();
+        db.with_conn(|conn| {
+            let vitals: Vec<String> = conn
+                .prepare("SELECT timestamp FROM vitals ORDER BY id")?
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            assert_eq!(vitals, vec![local.clone(), "2026-01-07T09:00:00".to_string()]);
+            let group: String = conn.query_row("SELECT timestamp FROM vital_groups", [], |row| row.get(0))?;
+            assert_eq!(group, local);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_open_database_swaps_all_clones() {
+        let dir = std::env::temp_dir();
+        let db = temp_db("open-first");
+        let first = db.path().to_path_buf();
+        let second = temp_db_path("open-second");
+        let foreign = temp_db_path("open-foreign");
+        db.with_conn(|conn| {
+            conn.execute("INSERT INTO days (date) VALUES ('2026-01-01')", [])?;
+            Ok(())
+        }).unwrap();
+        let clone = db.clone();
+        let pinned = db.pinned().unwrap();
+
+        let missing_dir = dir.join(format!("uhm-test-no-such-dir-{}", std::process::id())).join("x.db");
+        assert!(matches!(open_database(&db, &first, &missing_dir, true), Err(ToolError::Validation(_))));
+        assert!(matches!(open_database(&db, &first, &dir, true), Err(ToolError::Validation(_))));
+
+        // Neither a missing file nor someone else's database is set up without create
+        assert!(matches!(open_database(&db, &first, &second, false), Err(ToolError::Validation(_))));
+        assert!(!second.exists());
+        rusqlite::Connection::open(&foreign).unwrap()
+            .execute_batch("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT)").unwrap();
+        assert!(matches!(open_database(&db, &first, &foreign, false), Err(ToolError::Validation(_))));
+
+        let result = open_database(&db, &first, &second, true).unwrap();
+        assert!(result.created);
+        assert!(result.schema_version > 0);
+
+        let day_count = |db: &Database| -> i64 {
+            db.with_conn(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM days", [], |row| row.get(0))?)).unwrap()
+        };
+        assert_eq!(day_count(&db), 0);
+        assert_eq!(day_count(&clone), 0);
+        // A handle pinned before the swap stays on the old file
+        assert_eq!(day_count(&pinned), 1);
+
+        let reopened = open_database(&db, &second, &first, false).unwrap();
+        assert!(!reopened.created);
+        assert_eq!(day_count(&db), 1);
+
+        drop(clone);
+        drop(pinned);
+        for path in [second, foreign] {
+            remove_db_files(&path);
+        }
+    }
+
+    #[test]
+    fn test_find_dangling_meal_entries() {
+        use crate::models::{Day, MealEntry, MealEntryCreate, MealType};
+
+        let db = temp_db("dangling");
+
+        let (kept_id, removed_id) = db.with_conn(|conn| {
+            let food = |name: &str| make_food(conn, name, serde_json::json!({ "calories": 10.0 }));
+            let day = Day::get_or_create(conn, "2026-04-01")?;
+            let mut ids = Vec::new();
+            for item in [food("Kept"), food("Removed")] {
+                let entry = MealEntry::create(conn, &MealEntryCreate {
+                    day_id: day.id,
+                    meal_type: MealType::Dinner,
+                    recipe_id: None,
+                    food_item_id: Some(item.id),
+                    description: None,
+                    inline_nutrition: None,
+                    servings: 1.0,
+                    percent_eaten: None,
+                    notes: None,
+                    eaten_at: None,
+                    planned: false,
+                }, &SystemClock)?;
+                ids.push((entry.id, item.id));
+            }
+            // Foreign keys are on, so the server itself can't orphan an entry
+            assert!(conn.execute("DELETE FROM food_items WHERE id = ?1", [ids[1].1]).is_err());
+            Ok((ids[0], ids[1]))
+        }).unwrap();
+
+        let clean = find_dangling_meal_entries(&db).unwrap();
+        assert!(clean.foreign_keys_enforced);
+        assert_eq!(clean.entries_scanned, 2);
+        assert!(clean.dangling.is_empty());
+
+        // Simulate an out-of-band delete from a connection with foreign keys off
+        let raw = rusqlite::Connection::open(db.path()).unwrap();
+        raw.execute_batch("PRAGMA foreign_keys = OFF").unwrap();
+        raw.execute("DELETE FROM food_items WHERE id = ?1", [removed_id.1]).unwrap();
+        drop(raw);
+
+        let result = find_dangling_meal_entries(&db).unwrap();
+        assert_eq!(result.dangling.len(), 1);
+        let entry = &result.dangling[0];
+        assert_eq!(entry.id, removed_id.0);
+        assert_ne!(entry.id, kept_id.0);
+        assert_eq!(entry.date.as_deref(), Some("2026-04-01"));
+        assert_eq!(entry.meal_type, "dinner");
+        assert_eq!(entry.source_type, "food_item");
+        assert_eq!(entry.missing_source_id, removed_id.1);
+    }
+
+    #[test]
+    fn test_archive_old_data_copies_recipe_trees_and_force_deletes() {
+        use crate::models::{
+            Day, MealEntry, MealEntryCreate, MealType, Recipe, RecipeComponent,
+            RecipeComponentCreate, RecipeCreate, RecipeIngredient, RecipeIngredientCreate,
+        };
+
+        let db = temp_db("archive");
+        let dry_archive = temp_db_path("archive-dry");
+        let archive = temp_db_path("archive-out");
+
+        db.with_conn(|conn| {
+            let food = |name: &str| make_food(conn, name, serde_json::json!({ "calories": 100.0 }));
+            let recipe = |name: &str| Recipe::create(conn, &RecipeCreate {
+                name: name.to_string(), servings_produced: 1.0, yield_grams: None, is_favorite: false, notes: None,
+            });
+            let ingredient = |recipe_id: i64, food_item_id: i64| RecipeIngredient::create(conn, &RecipeIngredientCreate {
+                recipe_id, food_item_id, quantity: 50.0, unit: "g".to_string(), notes: None,
+            });
+            let (oats, milk, toast) = (food("Oats"), food("Milk"), food("Toast"));
+            let bowl = recipe("Bowl")?;
+            ingredient(bowl.id, oats.id)?;
+            let big = recipe("Big bowl")?;
+            ingredient(big.id, milk.id)?;
+            RecipeComponent::create(conn, &RecipeComponentCreate { recipe_id: big.id, component_recipe_id: bowl.id, servings: 1.0, notes: None })?;
+
+            for (date, recipe_id, food_item_id) in [("2024-06-01", Some(big.id), None), ("2026-01-01", None, Some(toast.id))] {
+                let day = Day::get_or_create(conn, date)?;
+                MealEntry::create(conn, &MealEntryCreate {
+                    day_id: day.id, meal_type: MealType::Breakfast, recipe_id, food_item_id,
+                    description: None, inline_nutrition: None, servings: 1.0, percent_eaten: None, notes: None,
+                    eaten_at: None, planned: false,
+                }, &SystemClock)?;
+            }
+            Ok(())
+        }).unwrap();
+
+        let days = |db: &Database| -> i64 {
+            db.with_conn(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM days", [], |row| row.get(0))?)).unwrap()
+        };
+
+        // Without force the archive is written but nothing is removed
+        let dry = archive_old_data(&db, "2025-01-01", &dry_archive, false).unwrap();
+        assert!(!dry.deleted);
+        assert_eq!(dry.days_deleted, 0);
+        assert_eq!(days(&db), 2);
+
+        let result = archive_old_data(&db, "2025-01-01", &archive, true).unwrap();
+        assert_eq!((result.days_archived, result.meal_entries_archived), (1, 1));
+        assert_eq!((result.recipes_copied, result.recipe_ingredients_copied, result.recipe_components_copied), (2, 2, 1));
+        assert_eq!(result.food_items_copied, 2);
+        assert!(result.deleted);
+        assert_eq!((result.days_deleted, result.meal_entries_deleted), (1, 1));
+        assert_eq!(days(&db), 1);
+
+        // Recipes stay in the main database; the archive holds its own copy
+        let recipes = db.with_conn(|conn| Ok(Recipe::list_all(conn)?.len())).unwrap();
+        assert_eq!(recipes, 2);
+        let archived_components: i64 = rusqlite::Connection::open(&archive).unwrap()
+            .query_row("SELECT COUNT(*) FROM recipe_components", [], |row| row.get(0)).unwrap();
+        assert_eq!(archived_components, 1);
+
+        for path in [dry_archive, archive] {
+            remove_db_files(&path);
+        }
+    }
+
+    #[test]
+    fn test_archive_old_data_copies_custom_vital_types() {
+        use crate::tools::vitals::{add_vital, register_vital_type, VitalReading};
+
+        let db = temp_db("archive-custom");
+        let archive = temp_db_path("archive-custom-out");
+
+        register_vital_type(&db, "mood", "score", None).unwrap();
+        register_vital_type(&db, "energy", "score", None).unwrap();
+        for (vital_type, timestamp) in [("mood", "2024-06-01T08:00:00"), ("energy", "2026-01-01T08:00:00")] {
+            add_vital(&db, VitalReading {
+                vital_type: vital_type.to_string(), value1: 5.0, value2: None, unit: None,
+                timestamp: Some(timestamp.to_string()), group_id: None, notes: None,
+            }, &SystemClock).unwrap();
+        }
+
+        let result = archive_old_data(&db, "2025-01-01", &archive, true).unwrap();
+        assert_eq!((result.vitals_archived, result.custom_vital_types_copied), (1, 1));
+
+        // The archived reading still resolves to its type; the main database keeps both types
+        let archived_type: (String, String) = rusqlite::Connection::open(&archive).unwrap()
+            .query_row(
+                "SELECT t.name, t.unit FROM vitals v JOIN custom_vital_types t ON t.id = v.custom_type_id",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(archived_type, ("mood".to_string(), "score".to_string()));
+        let types: i64 = db.with_conn(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM custom_vital_types", [], |row| row.get(0))?)).unwrap();
+        assert_eq!(types, 2);
+
+        remove_db_files(&archive);
+    }
+}
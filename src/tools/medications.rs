@@ -2,12 +2,17 @@
 //!
 //! Tools for managing medications including prescriptions, supplements, OTC, and natural remedies.
 
+use std::collections::HashMap;
+
 use serde::Serialize;
 
+use crate::clock::Clock;
 use crate::db::Database;
 use crate::models::{
-    DosageUnit, MedType, Medication, MedicationCreate, MedicationDeprecate, MedicationUpdate,
+    DosageUnit, DoseStep, MedType, Medication, MedicationCreate, MedicationDeprecate, MedicationUpdate,
 };
+use super::error::ToolError;
+use super::numeric::round_to;
 
 /// Response for add_medication
 #[derive(Debug, Serialize)]
@@ -54,6 +59,11 @@ pub struct MedicationDetail {
     pub end_date: Option<String>,
     pub discontinue_reason: Option<String>,
     pub notes: Option<String>,
+    /// Dose steps for tapering medications
+    pub schedule: Option<Vec<DoseStep>>,
+    /// Dose in effect today (resolved from the schedule if present)
+    pub current_dose: f64,
+    pub current_dose_display: String,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -72,8 +82,10 @@ impl From<&Medication> for MedicationSummary {
     }
 }
 
-impl From<Medication> for MedicationDetail {
-    fn from(med: Medication) -> Self {
+impl MedicationDetail {
+    /// Build the detail view, resolving the current dose as of `today` (YYYY-MM-DD)
+    fn new(med: Medication, today: &str) -> Self {
+        let current_dose = med.current_dose(today);
         Self {
             id: med.id,
             name: med.name,
@@ -94,6 +106,9 @@ impl From<Medication> for MedicationDetail {
             end_date: med.end_date,
             discontinue_reason: med.discontinue_reason,
             notes: med.notes,
+            schedule: med.schedule,
+            current_dose,
+            current_dose_display: format!("{} {}", current_dose, med.dosage_unit.display_name()),
             created_at: med.created_at,
             updated_at: med.updated_at,
         }
@@ -103,6 +118,9 @@ impl From<Medication> for MedicationDetail {
 /// Response for list_medications
 #[derive(Debug, Serialize)]
 pub struct ListMedicationsResponse {
+    /// Date "active" was evaluated on (list_medications only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of_date: Option<String>,
     pub medications: Vec<MedicationSummary>,
     pub total: usize,
     pub active_count: i64,
@@ -161,23 +179,50 @@ pub struct ExportMedicationsResponse {
 // Tool Functions
 // ============================================================================
 
+/// Validate a dose schedule: ISO dates in strictly increasing order, positive doses
+fn validate_schedule(schedule: &[DoseStep]) -> Result<(), ToolError> {
+    let mut previous: Option<&str> = None;
+    for step in schedule {
+        if chrono::NaiveDate::parse_from_str(&step.start_date, "%Y-%m-%d").is_err() {
+            return Err(ToolError::Validation(format!("Invalid schedule date: '{}'. Use YYYY-MM-DD", step.start_date)));
+        }
+        if step.dosage_amount <= 0.0 {
+            return Err(ToolError::Validation(format!("Schedule dose on {} must be greater than 0", step.start_date)));
+        }
+        if let Some(prev) = previous {
+            if step.start_date.as_str() <= prev {
+                return Err(ToolError::Validation(format!(
+                    "Schedule dates must be in increasing order: {} does not come after {}",
+                    step.start_date, prev
+                )));
+            }
+        }
+        previous = Some(&step.start_date);
+    }
+    Ok(())
+}
+
 /// Add a new medication
-pub fn add_medication(db: &Database, data: MedicationCreate) -> Result<AddMedicationResponse, String> {
+pub fn add_medication(db: &Database, data: MedicationCreate) -> Result<AddMedicationResponse, ToolError> {
     // Validate name
     let name = data.name.trim();
     if name.is_empty() {
-        return Err("Medication name cannot be empty".to_string());
+        return Err(ToolError::Validation("Medication name cannot be empty".to_string()));
     }
 
     // Validate dosage
     if data.dosage_amount <= 0.0 {
-        return Err("Dosage amount must be greater than 0".to_string());
+        return Err(ToolError::Validation("Dosage amount must be greater than 0".to_string()));
+    }
+
+    if let Some(ref schedule) = data.schedule {
+        validate_schedule(schedule)?;
     }
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let conn = db.get_conn()?;
 
     let med = Medication::create(&conn, &data)
-        .map_err(|e| format!("Failed to create medication: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to create medication: {}", e)))?;
 
     Ok(AddMedicationResponse {
         id: med.id,
@@ -190,37 +235,46 @@ pub fn add_medication(db: &Database, data: MedicationCreate) -> Result<AddMedica
 }
 
 /// Get a medication by ID
-pub fn get_medication(db: &Database, id: i64) -> Result<Option<MedicationDetail>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn get_medication(db: &Database, id: i64, clock: &dyn Clock) -> Result<Option<MedicationDetail>, ToolError> {
+    let conn = db.get_conn()?;
 
     let med = Medication::get_by_id(&conn, id)
-        .map_err(|e| format!("Failed to get medication: {}", e))?;
+        .map_err(|e| ToolError::db("Failed to get medication", e))?;
 
-    Ok(med.map(MedicationDetail::from))
+    let today = clock.today();
+    Ok(med.map(|m| MedicationDetail::new(m, &today)))
 }
 
-/// List medications with optional filtering
+/// List medications with optional filtering.
+///
+/// "Active" is evaluated as of the clock's date: is_active, start_date on or
+/// before it, and end_date (if any) on or after it.
 pub fn list_medications(
     db: &Database,
     active_only: bool,
     med_type: Option<&str>,
-) -> Result<ListMedicationsResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    clock: &dyn Clock,
+) -> Result<ListMedicationsResponse, ToolError> {
+    let conn = db.get_conn()?;
 
+    let as_of = clock.today();
     let med_type_filter = med_type.map(MedType::from_str);
 
-    let meds = Medication::list(&conn, active_only, med_type_filter)
-        .map_err(|e| format!("Failed to list medications: {}", e))?;
-
-    let active_count = Medication::count(&conn, true)
-        .map_err(|e| format!("Failed to count medications: {}", e))?;
-    let total_count = Medication::count(&conn, false)
-        .map_err(|e| format!("Failed to count medications: {}", e))?;
-
-    let summaries: Vec<MedicationSummary> = meds.iter().map(MedicationSummary::from).collect();
+    let all = Medication::list(&conn, false, None)
+        .map_err(|e| ToolError::db("Failed to list medications", e))?;
+    let total_count = all.len() as i64;
+    let active_count = all.iter().filter(|m| m.is_active_on(&as_of)).count() as i64;
+
+    let summaries: Vec<MedicationSummary> = all
+        .iter()
+        .filter(|m| med_type_filter.is_none_or(|mt| m.med_type == mt))
+        .filter(|m| !active_only || m.is_active_on(&as_of))
+        .map(MedicationSummary::from)
+        .collect();
     let total = summaries.len();
 
     Ok(ListMedicationsResponse {
+        as_of_date: Some(as_of),
         medications: summaries,
         total,
         active_count,
@@ -233,21 +287,22 @@ pub fn search_medications(
     db: &Database,
     query: &str,
     active_only: bool,
-) -> Result<ListMedicationsResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<ListMedicationsResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     let meds = Medication::search(&conn, query, active_only)
-        .map_err(|e| format!("Failed to search medications: {}", e))?;
+        .map_err(|e| ToolError::db("Failed to search medications", e))?;
 
     let active_count = Medication::count(&conn, true)
-        .map_err(|e| format!("Failed to count medications: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to count medications: {}", e)))?;
     let total_count = Medication::count(&conn, false)
-        .map_err(|e| format!("Failed to count medications: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to count medications: {}", e)))?;
 
     let summaries: Vec<MedicationSummary> = meds.iter().map(MedicationSummary::from).collect();
     let total = summaries.len();
 
     Ok(ListMedicationsResponse {
+        as_of_date: None,
         medications: summaries,
         total,
         active_count,
@@ -261,15 +316,18 @@ pub fn update_medication(
     id: i64,
     data: MedicationUpdate,
     force: bool,
-) -> Result<Result<UpdateMedicationSuccessResponse, UpdateMedicationBlockedResponse>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<Result<UpdateMedicationSuccessResponse, UpdateMedicationBlockedResponse>, ToolError> {
+    if let Some(ref schedule) = data.schedule {
+        validate_schedule(schedule)?;
+    }
+
+    let conn = db.get_conn()?;
 
     // Check if medication exists
-    let existing = Medication::get_by_id(&conn, id)
-        .map_err(|e| format!("Database error: {}", e))?;
+    let existing = Medication::get_by_id(&conn, id)?;
 
     if existing.is_none() {
-        return Err(format!("Medication not found with id: {}", id));
+        return Err(ToolError::NotFound(format!("Medication not found with id: {}", id)));
     }
 
     // If not forced, block the update
@@ -282,7 +340,7 @@ pub fn update_medication(
     }
 
     let updated = Medication::update(&conn, id, &data)
-        .map_err(|e| format!("Failed to update medication: {}", e))?;
+        .map_err(|e| ToolError::db("Failed to update medication", e))?;
 
     match updated {
         Some(med) => Ok(Ok(UpdateMedicationSuccessResponse {
@@ -290,7 +348,7 @@ pub fn update_medication(
             updated_at: med.updated_at,
             warning: Some("Medication modified. Consider if this change should have been a deprecation + new entry instead.".to_string()),
         })),
-        None => Err("Medication not found or update failed".to_string()),
+        None => Err(ToolError::NotFound("Medication not found or update failed".to_string())),
     }
 }
 
@@ -300,15 +358,25 @@ pub fn deprecate_medication(
     id: i64,
     end_date: Option<&str>,
     reason: Option<&str>,
-) -> Result<DeprecateMedicationResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    clock: &dyn Clock,
+) -> Result<DeprecateMedicationResponse, ToolError> {
+    let conn = db.get_conn()?;
+    deprecate_one(&conn, id, end_date, reason, clock)
+}
 
+/// Deprecate one medication on an existing connection
+fn deprecate_one(
+    conn: &rusqlite::Connection,
+    id: i64,
+    end_date: Option<&str>,
+    reason: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<DeprecateMedicationResponse, ToolError> {
     // Check if medication exists
-    let existing = Medication::get_by_id(&conn, id)
-        .map_err(|e| format!("Database error: {}", e))?;
+    let existing = Medication::get_by_id(conn, id)?;
 
     if existing.is_none() {
-        return Err(format!("Medication not found with id: {}", id));
+        return Err(ToolError::NotFound(format!("Medication not found with id: {}", id)));
     }
 
     let data = MedicationDeprecate {
@@ -316,8 +384,8 @@ pub fn deprecate_medication(
         discontinue_reason: reason.map(String::from),
     };
 
-    let updated = Medication::deprecate(&conn, id, &data)
-        .map_err(|e| format!("Failed to deprecate medication: {}", e))?;
+    let updated = Medication::deprecate(conn, id, &data, clock)
+        .map_err(|e| ToolError::db("Failed to deprecate medication", e))?;
 
     match updated {
         Some(med) => Ok(DeprecateMedicationResponse {
@@ -327,20 +395,94 @@ pub fn deprecate_medication(
             end_date: med.end_date.unwrap_or_default(),
             discontinue_reason: med.discontinue_reason,
         }),
-        None => Err("Medication not found".to_string()),
+        None => Err(ToolError::NotFound("Medication not found".to_string())),
+    }
+}
+
+/// Which active medications deprecate_medications_bulk applies to. Every given
+/// field must match; doctor and pharmacy compare case-insensitively.
+#[derive(Debug, Clone, Default)]
+pub struct MedicationFilter {
+    pub prescribing_doctor: Option<String>,
+    pub pharmacy: Option<String>,
+    pub med_type: Option<String>,
+}
+
+impl MedicationFilter {
+    fn matches(&self, med: &Medication) -> bool {
+        let same = |want: Option<&str>, have: &Option<String>| match want {
+            Some(want) => have.as_deref().is_some_and(|h| h.trim().eq_ignore_ascii_case(want)),
+            None => true,
+        };
+        same(given(&self.prescribing_doctor), &med.prescribing_doctor)
+            && same(given(&self.pharmacy), &med.pharmacy)
+            && given(&self.med_type).is_none_or(|t| MedType::parse(t) == Some(med.med_type))
+    }
+}
+
+/// A filter field's trimmed value, treating blank strings as unset
+fn given(field: &Option<String>) -> Option<&str> {
+    field.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Response for deprecate_medications_bulk
+#[derive(Debug, Serialize)]
+pub struct DeprecateMedicationsBulkResponse {
+    pub deprecated: Vec<DeprecateMedicationResponse>,
+    pub count: usize,
+}
+
+/// Deprecate every active medication matching the filter in one transaction,
+/// e.g. everything from a prescriber or pharmacy being left
+pub fn deprecate_medications_bulk(
+    db: &Database,
+    filter: &MedicationFilter,
+    end_date: Option<&str>,
+    reason: Option<&str>,
+    clock: &dyn Clock,
+) -> Result<DeprecateMedicationsBulkResponse, ToolError> {
+    if given(&filter.prescribing_doctor).is_none() && given(&filter.pharmacy).is_none() && given(&filter.med_type).is_none() {
+        return Err(ToolError::Validation(
+            "Give at least one of prescribing_doctor, pharmacy or med_type".to_string(),
+        ));
     }
+    // An unknown med_type must not fall through to Other and sweep those up
+    if let Some(t) = given(&filter.med_type) {
+        if MedType::parse(t).is_none() {
+            return Err(ToolError::Validation(format!(
+                "Unknown med_type '{}'. Use one of: prescription, supplement, otc, natural, compound, medical_device, other",
+                t
+            )));
+        }
+    }
+
+    db.transaction(|conn| {
+        let active = Medication::list(conn, true, None)
+            .map_err(|e| ToolError::db("Failed to list medications", e))?;
+
+        let deprecated = active
+            .iter()
+            .filter(|m| filter.matches(m))
+            .map(|m| deprecate_one(conn, m.id, end_date, reason, clock))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DeprecateMedicationsBulkResponse {
+            count: deprecated.len(),
+            deprecated,
+        })
+    })
 }
 
 /// Reactivate a deprecated medication
-pub fn reactivate_medication(db: &Database, id: i64) -> Result<MedicationDetail, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn reactivate_medication(db: &Database, id: i64, clock: &dyn Clock) -> Result<MedicationDetail, ToolError> {
+    let conn = db.get_conn()?;
 
     let updated = Medication::reactivate(&conn, id)
-        .map_err(|e| format!("Failed to reactivate medication: {}", e))?;
+        .map_err(|e| ToolError::db("Failed to reactivate medication", e))?;
 
     match updated {
-        Some(med) => Ok(MedicationDetail::from(med)),
-        None => Err(format!("Medication not found with id: {}", id)),
+        Some(med) => Ok(MedicationDetail::new(med, &clock.today())),
+        None => Err(ToolError::NotFound(format!("Medication not found with id: {}", id))),
     }
 }
 
@@ -349,15 +491,14 @@ pub fn delete_medication(
     db: &Database,
     id: i64,
     force: bool,
-) -> Result<Result<DeleteMedicationSuccessResponse, DeleteMedicationBlockedResponse>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<Result<DeleteMedicationSuccessResponse, DeleteMedicationBlockedResponse>, ToolError> {
+    let conn = db.get_conn()?;
 
     // Check if medication exists
-    let existing = Medication::get_by_id(&conn, id)
-        .map_err(|e| format!("Database error: {}", e))?;
+    let existing = Medication::get_by_id(&conn, id)?;
 
     if existing.is_none() {
-        return Err(format!("Medication not found with id: {}", id));
+        return Err(ToolError::NotFound(format!("Medication not found with id: {}", id)));
     }
 
     // If not forced, block the delete
@@ -371,7 +512,7 @@ pub fn delete_medication(
     // In the future, check for associations here
     // For now, just delete
     Medication::delete(&conn, id)
-        .map_err(|e| format!("Failed to delete medication: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to delete medication: {}", e)))?;
 
     Ok(Ok(DeleteMedicationSuccessResponse {
         success: true,
@@ -379,18 +520,34 @@ pub fn delete_medication(
     }))
 }
 
-/// Export medications to markdown document
+/// Stands in for the patient name when allow_missing_patient is set
+const UNKNOWN_PATIENT: &str = "Unknown Patient";
+
+/// Export medications to markdown document.
+///
+/// A missing or blank patient name is an error unless allow_missing_patient is
+/// set, in which case the document is labelled "Unknown Patient".
 pub fn export_medications_markdown(
     db: &Database,
-    patient_name: &str,
-) -> Result<ExportMedicationsResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    patient_name: Option<&str>,
+    allow_missing_patient: bool,
+    clock: &dyn Clock,
+) -> Result<ExportMedicationsResponse, ToolError> {
+    let patient_name = match patient_name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => name,
+        None if allow_missing_patient => UNKNOWN_PATIENT,
+        None => {
+            return Err(ToolError::Validation("Patient name not set. Pass patient_name, or allow_missing_patient: true for a personal copy".to_string()))
+        }
+    };
+
+    let conn = db.get_conn()?;
 
     // Get all active medications
     let meds = Medication::list(&conn, true, None)
-        .map_err(|e| format!("Failed to list medications: {}", e))?;
+        .map_err(|e| ToolError::db("Failed to list medications", e))?;
 
-    let now = chrono::Utc::now();
+    let now = clock.now();
     let date_str = now.format("%Y-%m-%d").to_string();
     let time_str = now.format("%H:%M:%S UTC").to_string();
     let generated_at = now.format("%Y-%m-%d %H:%M:%S UTC").to_string();
@@ -421,7 +578,15 @@ pub fn export_medications_markdown(
 
         for med in type_meds {
             markdown.push_str(&format!("### {}\n\n", med.name));
-            markdown.push_str(&format!("- **Dosage:** {} {}\n", med.dosage_amount, med.dosage_unit.display_name()));
+            markdown.push_str(&format!("- **Dosage:** {} {}\n", med.current_dose(&date_str), med.dosage_unit.display_name()));
+
+            if let Some(ref schedule) = med.schedule {
+                let steps: Vec<String> = schedule
+                    .iter()
+                    .map(|s| format!("{} {} from {}", s.dosage_amount, med.dosage_unit.display_name(), s.start_date))
+                    .collect();
+                markdown.push_str(&format!("- **Taper Schedule:** {}\n", steps.join(" → ")));
+            }
 
             if let Some(ref freq) = med.frequency {
                 markdown.push_str(&format!("- **Frequency:** {}\n", freq));
@@ -474,3 +639,506 @@ pub fn export_medications_markdown(
         generated_at,
     })
 }
+
+// ============================================================================
+// Medication Timeline
+// ============================================================================
+
+/// Characters available for each timeline bar
+const TIMELINE_WIDTH: usize = 40;
+
+/// Response for generate_medication_timeline
+#[derive(Debug, Serialize)]
+pub struct MedicationTimelineResponse {
+    pub file_path: String,
+    pub medication_count: usize,
+    pub dose_change_count: usize,
+    /// First and last dates covered by the bars
+    pub range_start: Option<String>,
+    pub range_end: Option<String>,
+    pub generated_at: String,
+}
+
+/// Date part of a stored date or timestamp
+fn parse_day(value: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(value.get(..10).unwrap_or(value), "%Y-%m-%d").ok()
+}
+
+/// Render a span as a fixed-width bar within the timeline range
+fn timeline_bar(
+    start: chrono::NaiveDate,
+    end: chrono::NaiveDate,
+    range_start: chrono::NaiveDate,
+    range_end: chrono::NaiveDate,
+    width: usize,
+) -> String {
+    let total_days = (range_end - range_start).num_days().max(1) as f64;
+    let column = |d: chrono::NaiveDate| {
+        let pos = (d - range_start).num_days() as f64 / total_days * (width - 1) as f64;
+        (pos.round() as usize).min(width - 1)
+    };
+    let (from, to) = (column(start), column(end));
+    (0..width).map(|i| if i >= from && i <= to { '█' } else { '·' }).collect()
+}
+
+/// Write a markdown timeline of every medication's start/stop history.
+///
+/// Each medication is a bar from its start_date (or creation date) to its end_date,
+/// or to today while still active. Deprecated entries followed by a new entry with
+/// the same name are annotated as dose changes.
+pub fn generate_medication_timeline(
+    db: &Database,
+    output_path: &str,
+    clock: &dyn Clock,
+) -> Result<MedicationTimelineResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let meds = Medication::list(&conn, false, None)
+        .map_err(|e| ToolError::db("Failed to list medications", e))?;
+
+    let now = clock.now();
+    let today = clock.local_now().date();
+    let generated_at = now.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+
+    // Resolve each medication's span once
+    let spans: Vec<(&Medication, chrono::NaiveDate, chrono::NaiveDate)> = meds
+        .iter()
+        .map(|m| {
+            let start = m
+                .start_date
+                .as_deref()
+                .and_then(parse_day)
+                .or_else(|| parse_day(&m.created_at))
+                .unwrap_or(today);
+            let end = m.end_date.as_deref().and_then(parse_day).unwrap_or(today).max(start);
+            (m, start, end)
+        })
+        .collect();
+
+    let range_start = spans.iter().map(|(_, s, _)| *s).min();
+    let range_end = spans.iter().map(|(_, _, e)| *e).max();
+
+    // Dose changes: consecutive entries sharing a name where the earlier one was stopped
+    let mut by_name: HashMap<String, Vec<&(&Medication, chrono::NaiveDate, chrono::NaiveDate)>> = HashMap::new();
+    for span in &spans {
+        by_name.entry(span.0.name.to_lowercase()).or_default().push(span);
+    }
+    let mut dose_changes: HashMap<i64, String> = HashMap::new();
+    for entries in by_name.values_mut() {
+        entries.sort_by_key(|(m, start, _)| (*start, m.id));
+        for pair in entries.windows(2) {
+            let (prev, next) = (pair[0].0, pair[1].0);
+            if prev.end_date.is_some() && prev.dosage_amount != next.dosage_amount {
+                dose_changes.insert(
+                    next.id,
+                    format!(
+                        "dose change {} → {} {} on {}",
+                        prev.dosage_amount,
+                        next.dosage_amount,
+                        next.dosage_unit.display_name(),
+                        pair[1].1
+                    ),
+                );
+            }
+        }
+    }
+
+    let mut markdown = String::new();
+    markdown.push_str("# Medication Timeline\n\n");
+
+    match (range_start, range_end) {
+        (Some(range_start), Some(range_end)) => {
+            markdown.push_str(&format!("**Range:** {} to {}\n\n", range_start, range_end));
+
+            let mut grouped: HashMap<MedType, Vec<&(&Medication, chrono::NaiveDate, chrono::NaiveDate)>> = HashMap::new();
+            for span in &spans {
+                grouped.entry(span.0.med_type).or_default().push(span);
+            }
+            let mut types: Vec<MedType> = grouped.keys().cloned().collect();
+            types.sort_by_key(|t| t.sort_order());
+
+            for med_type in types {
+                let mut type_spans = grouped.remove(&med_type).unwrap_or_default();
+                type_spans.sort_by_key(|(m, start, _)| (*start, m.name.to_lowercase()));
+
+                markdown.push_str(&format!("## {}\n\n", med_type.display_name()));
+                markdown.push_str("```\n");
+                for (med, start, end) in &type_spans {
+                    let bar = timeline_bar(*start, *end, range_start, range_end, TIMELINE_WIDTH);
+                    let until = if med.end_date.is_some() { end.to_string() } else { "ongoing".to_string() };
+                    markdown.push_str(&format!(
+                        "{} {} {} {} ({} to {})\n",
+                        bar, med.name, med.dosage_amount, med.dosage_unit.display_name(), start, until
+                    ));
+                }
+                markdown.push_str("```\n\n");
+
+                let mut annotated = false;
+                for (med, _, _) in &type_spans {
+                    let mut notes = Vec::new();
+                    if let Some(change) = dose_changes.get(&med.id) {
+                        notes.push(change.clone());
+                    }
+                    if let Some(ref schedule) = med.schedule {
+                        for step in schedule {
+                            notes.push(format!(
+                                "scheduled {} {} from {}",
+                                step.dosage_amount,
+                                med.dosage_unit.display_name(),
+                                step.start_date
+                            ));
+                        }
+                    }
+                    if let Some(ref reason) = med.discontinue_reason {
+                        notes.push(format!("stopped: {}", reason));
+                    }
+                    if !notes.is_empty() {
+                        markdown.push_str(&format!(
+                            "- **{} {} {}:** {}\n",
+                            med.name, med.dosage_amount, med.dosage_unit.display_name(), notes.join("; ")
+                        ));
+                        annotated = true;
+                    }
+                }
+                if annotated {
+                    markdown.push('\n');
+                }
+            }
+        }
+        _ => markdown.push_str("*No medications on file.*\n\n"),
+    }
+
+    markdown.push_str("---\n\n");
+    markdown.push_str(&format!("*Generated: {}*\n", generated_at));
+
+    std::fs::write(output_path, markdown)
+        .map_err(|e| ToolError::Io(format!("Failed to write file '{}': {}", output_path, e)))?;
+
+    Ok(MedicationTimelineResponse {
+        file_path: output_path.to_string(),
+        medication_count: meds.len(),
+        dose_change_count: dose_changes.len(),
+        range_start: range_start.map(|d| d.to_string()),
+        range_end: range_end.map(|d| d.to_string()),
+        generated_at,
+    })
+}
+
+// ============================================================================
+// Total Daily Dose
+// ============================================================================
+
+/// Usual adult maximum for acetaminophen (paracetamol) in 24 hours
+const ACETAMINOPHEN_MAX_MG_PER_DAY: f64 = 4000.0;
+
+/// One medication's contribution to a total daily dose
+#[derive(Debug, Serialize)]
+pub struct DoseContribution {
+    pub medication_id: i64,
+    pub name: String,
+    /// Dose in effect today (schedule-aware), in dosage_unit
+    pub dose: f64,
+    pub dosage_unit: String,
+    pub frequency: String,
+    pub doses_per_day: f64,
+    pub daily_mg: f64,
+    /// PRN medications are counted at the most their frequency allows
+    pub as_needed: bool,
+}
+
+/// A matching medication left out of the total, and why
+#[derive(Debug, Serialize)]
+pub struct ExcludedDose {
+    pub medication_id: i64,
+    pub name: String,
+    pub reason: String,
+}
+
+/// Response for total_daily_dose
+#[derive(Debug, Serialize)]
+pub struct TotalDailyDoseResponse {
+    pub ingredient: String,
+    pub as_of_date: String,
+    pub total_mg: f64,
+    pub medications: Vec<DoseContribution>,
+    /// Matches that could not be added safely (non-mass unit or unreadable frequency)
+    pub excluded: Vec<ExcludedDose>,
+    pub warnings: Vec<String>,
+}
+
+/// Doses per day from a free-text frequency such as "twice daily", "BID",
+/// "every 6 hours as needed", "3x a day" or "weekly". None if it can't be read.
+/// Interval forms give the most doses the interval allows.
+pub fn doses_per_day(frequency: &str) -> Option<f64> {
+    let text = frequency.to_lowercase().replace(['-', ',', '.', '(', ')'], " ");
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let padded = format!(" {} ", words.join(" "));
+    let has = |phrase: &str| padded.contains(&format!(" {} ", phrase));
+    let is_hours = |w: &&str| matches!(*w, "h" | "hr" | "hrs" | "hour" | "hours");
+    let weekly = has("week") || has("weekly") || has("wk");
+
+    // Intervals: "every 6 hours", "every 4 to 6 hours", "q 8 h", "q6h"
+    for (i, word) in words.iter().enumerate() {
+        if let Some(n) = word.strip_prefix('q').and_then(|w| w.strip_suffix('h')).and_then(|n| n.parse::<f64>().ok()) {
+            return (n > 0.0).then(|| 24.0 / n);
+        }
+        if matches!(*word, "every" | "q") {
+            let n = words.get(i + 1).and_then(|w| w.parse::<f64>().ok());
+            if let Some(n) = n.filter(|n| *n > 0.0) {
+                if words[i + 1..].iter().take(4).any(is_hours) {
+                    return Some(24.0 / n);
+                }
+                if words.get(i + 2).is_some_and(|w| w.starts_with("day")) {
+                    return Some(1.0 / n);
+                }
+            }
+        }
+    }
+
+    // Latin abbreviations
+    for (abbrev, per_day) in [("qod", 0.5), ("qid", 4.0), ("tid", 3.0), ("bid", 2.0), ("qd", 1.0), ("qhs", 1.0), ("qam", 1.0), ("qpm", 1.0)] {
+        if has(abbrev) {
+            return Some(per_day);
+        }
+    }
+    if has("every other day") {
+        return Some(0.5);
+    }
+
+    // Counts: "once", "twice", "3 times", "3x", "3 x"
+    let count = words.iter().enumerate().find_map(|(i, w)| match *w {
+        "once" => Some(1.0),
+        "twice" => Some(2.0),
+        "thrice" => Some(3.0),
+        _ => match w.strip_suffix('x').and_then(|n| n.parse::<f64>().ok()) {
+            Some(n) => Some(n),
+            None => w.parse::<f64>().ok().filter(|_| {
+                words.get(i + 1).is_some_and(|next| matches!(*next, "x" | "time" | "times"))
+            }),
+        },
+    });
+    if let Some(n) = count {
+        return Some(if weekly { n / 7.0 } else { n });
+    }
+
+    if weekly {
+        return Some(1.0 / 7.0);
+    }
+    if has("monthly") {
+        return Some(1.0 / 30.0);
+    }
+    if has("daily") || has("day") || has("nightly") || has("bedtime") || has("morning") || has("evening") {
+        return Some(1.0);
+    }
+    None
+}
+
+/// Sum today's daily dose of an ingredient across active medications whose name
+/// contains it, converting mg/g/mcg to mg. Matches in other units, or with a
+/// frequency that can't be read, are listed as excluded rather than guessed.
+pub fn total_daily_dose(db: &Database, ingredient: &str, clock: &dyn Clock) -> Result<TotalDailyDoseResponse, ToolError> {
+    let ingredient = ingredient.trim();
+    if ingredient.is_empty() {
+        return Err(ToolError::Validation("ingredient is required".to_string()));
+    }
+    let today = clock.today();
+    let needle = ingredient.to_lowercase();
+
+    let conn = db.get_conn()?;
+    let meds = Medication::list(&conn, false, None)
+        .map_err(|e| ToolError::db("Failed to list medications", e))?;
+
+    let mut medications = Vec::new();
+    let mut excluded = Vec::new();
+    for med in meds.iter().filter(|m| m.is_active_on(&today) && m.name.to_lowercase().contains(&needle)) {
+        let exclude = |reason: String| ExcludedDose { medication_id: med.id, name: med.name.clone(), reason };
+
+        let Some(mg_per_unit) = med.dosage_unit.mg_per_unit() else {
+            excluded.push(exclude(format!(
+                "dose is in {}, which can't be converted to mg",
+                med.dosage_unit.display_name()
+            )));
+            continue;
+        };
+        let frequency = med.frequency.clone().unwrap_or_default();
+        let Some(per_day) = doses_per_day(&frequency) else {
+            excluded.push(exclude(if frequency.is_empty() {
+                "no frequency recorded".to_string()
+            } else {
+                format!("frequency '{}' not understood", frequency)
+            }));
+            continue;
+        };
+
+        let dose = med.current_dose(&today);
+        let lower = frequency.to_lowercase();
+        medications.push(DoseContribution {
+            medication_id: med.id,
+            name: med.name.clone(),
+            dose,
+            dosage_unit: med.dosage_unit.as_str().to_string(),
+            doses_per_day: round_to(per_day, 3),
+            daily_mg: round_to(dose * mg_per_unit * per_day, 3),
+            as_needed: lower.contains("prn") || lower.contains("as needed"),
+            frequency,
+        });
+    }
+
+    let total_mg = round_to(medications.iter().map(|m| m.daily_mg).sum(), 3);
+    let mut warnings = Vec::new();
+    if !excluded.is_empty() {
+        warnings.push(format!(
+            "{} matching medication(s) excluded from the total; the real total may be higher",
+            excluded.len()
+        ));
+    }
+    if ["acetaminophen", "paracetamol", "apap"].contains(&needle.as_str()) && total_mg > ACETAMINOPHEN_MAX_MG_PER_DAY {
+        warnings.push(format!(
+            "Total {} mg exceeds the usual adult maximum of {} mg per day",
+            total_mg, ACETAMINOPHEN_MAX_MG_PER_DAY
+        ));
+    }
+
+    Ok(TotalDailyDoseResponse {
+        ingredient: ingredient.to_string(),
+        as_of_date: today,
+        total_mg,
+        medications,
+        excluded,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::test_support::temp_db;
+
+    fn d(s: &str) -> chrono::NaiveDate {
+        chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_timeline_bar() {
+        let (lo, hi) = (d("2024-01-01"), d("2024-01-11"));
+        assert_eq!(timeline_bar(lo, hi, lo, hi, 5), "█████");
+        assert_eq!(timeline_bar(lo, d("2024-01-06"), lo, hi, 5), "███··");
+        assert_eq!(timeline_bar(hi, hi, lo, hi, 5), "····█");
+    }
+
+    #[test]
+    fn test_medication_filter_matches() {
+        let med = Medication {
+            id: 1,
+            name: "Lisinopril".to_string(),
+            med_type: MedType::Prescription,
+            dosage_amount: 10.0,
+            dosage_unit: DosageUnit::Mg,
+            instructions: None,
+            frequency: None,
+            prescribing_doctor: Some("Dr. Patel".to_string()),
+            prescribed_date: None,
+            pharmacy: Some("Main St Pharmacy".to_string()),
+            rx_number: None,
+            refills_remaining: None,
+            is_active: true,
+            start_date: None,
+            end_date: None,
+            discontinue_reason: None,
+            notes: None,
+            schedule: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+        };
+        let filter = |doctor: Option<&str>, pharmacy: Option<&str>, med_type: Option<&str>| MedicationFilter {
+            prescribing_doctor: doctor.map(String::from),
+            pharmacy: pharmacy.map(String::from),
+            med_type: med_type.map(String::from),
+        };
+
+        assert!(filter(Some("dr. patel "), None, None).matches(&med));
+        assert!(filter(None, Some("MAIN ST PHARMACY"), Some("rx")).matches(&med));
+        assert!(!filter(Some("Dr. Patel"), Some("Other Pharmacy"), None).matches(&med));
+        assert!(!filter(None, None, Some("supplement")).matches(&med));
+        // Blank fields are unset
+        assert!(filter(Some("  "), Some(""), Some("prescription")).matches(&med));
+    }
+
+    #[test]
+    fn test_deprecate_medications_bulk_rejects_unknown_med_type() {
+        let db = temp_db("bulk-medtype");
+
+        let filter = |med_type: &str| MedicationFilter { med_type: Some(med_type.to_string()), ..Default::default() };
+        assert!(matches!(
+            deprecate_medications_bulk(&db, &filter("suplement"), None, None, &SystemClock),
+            Err(ToolError::Validation(ref m)) if m.starts_with("Unknown med_type")
+        ));
+        assert!(matches!(deprecate_medications_bulk(&db, &filter(" "), None, None, &SystemClock), Err(ToolError::Validation(_))));
+        assert_eq!(deprecate_medications_bulk(&db, &filter("Other"), None, None, &SystemClock).unwrap().count, 0);
+    }
+
+    #[test]
+    fn test_validate_schedule() {
+        let step = |date: &str, amount: f64| DoseStep { start_date: date.to_string(), dosage_amount: amount };
+        assert!(validate_schedule(&[step("2024-03-01", 40.0), step("2024-03-08", 20.0)]).is_ok());
+        assert!(validate_schedule(&[step("2024-03-08", 40.0), step("2024-03-01", 20.0)]).is_err());
+        assert!(validate_schedule(&[step("2024-03-01", 40.0), step("2024-03-01", 20.0)]).is_err());
+        assert!(validate_schedule(&[step("March 1", 40.0)]).is_err());
+        assert!(validate_schedule(&[step("2024-03-01", 0.0)]).is_err());
+    }
+
+    #[test]
+    fn test_medication_schedule_round_trip_and_malformed_json() {
+        use crate::clock::FixedClock;
+
+        let db = temp_db("med-schedule");
+
+        let id = add_medication(&db, MedicationCreate {
+            name: "Prednisone".to_string(),
+            med_type: MedType::Prescription,
+            dosage_amount: 40.0,
+            dosage_unit: DosageUnit::Mg,
+            instructions: None,
+            frequency: None,
+            prescribing_doctor: None,
+            prescribed_date: None,
+            pharmacy: None,
+            rx_number: None,
+            refills_remaining: None,
+            start_date: Some("2024-03-01".to_string()),
+            notes: None,
+            schedule: Some(vec![
+                DoseStep { start_date: "2024-03-01".to_string(), dosage_amount: 40.0 },
+                DoseStep { start_date: "2024-03-08".to_string(), dosage_amount: 20.0 },
+            ]),
+        }).unwrap().id;
+
+        let on = |date: &str| get_medication(&db, id, &FixedClock::from_date(date).unwrap()).unwrap().unwrap();
+        assert_eq!(on("2024-03-07").current_dose, 40.0);
+        assert_eq!(on("2024-03-08").current_dose, 20.0);
+        assert_eq!(on("2024-03-08").schedule.map(|s| s.len()), Some(2));
+
+        db.with_conn(|conn| conn.execute("UPDATE medications SET schedule = '[{\"start_date\":' WHERE id = ?1", [id]).map_err(crate::db::DbError::from)).unwrap();
+        assert!(matches!(
+            get_medication(&db, id, &SystemClock),
+            Err(ToolError::Validation(ref m)) if m.contains("not valid JSON")
+        ));
+    }
+
+    #[test]
+    fn test_doses_per_day() {
+        assert_eq!(doses_per_day("Once daily"), Some(1.0));
+        assert_eq!(doses_per_day("twice a day"), Some(2.0));
+        assert_eq!(doses_per_day("BID"), Some(2.0));
+        assert_eq!(doses_per_day("3x daily"), Some(3.0));
+        assert_eq!(doses_per_day("4 times a day"), Some(4.0));
+        assert_eq!(doses_per_day("every 6 hours as needed"), Some(4.0));
+        assert_eq!(doses_per_day("q8h"), Some(3.0));
+        assert_eq!(doses_per_day("every other day"), Some(0.5));
+        assert_eq!(doses_per_day("once weekly"), Some(1.0 / 7.0));
+        assert_eq!(doses_per_day("at bedtime"), Some(1.0));
+        assert_eq!(doses_per_day("take 2 tablets daily"), Some(1.0));
+        assert_eq!(doses_per_day("PRN"), None);
+    }
+}
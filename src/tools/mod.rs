@@ -2,9 +2,15 @@
 //!
 //! MCP tool implementations for the Universal Health Manager.
 
+pub mod backup;
+mod csv;
 pub mod days;
+pub mod error;
 pub mod food_items;
+pub mod maintenance;
 pub mod medications;
+mod numeric;
 pub mod recipes;
+pub mod snapshot;
 pub mod status;
 pub mod vitals;
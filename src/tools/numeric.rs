@@ -0,0 +1,20 @@
+//! Numeric helpers shared by the tools
+
+/// Round to a number of decimal places
+pub(crate) fn round_to(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_to() {
+        assert_eq!(round_to(123.456, 0), 123.0);
+        assert_eq!(round_to(123.456, 1), 123.5);
+        assert_eq!(round_to(123.456, 2), 123.46);
+        assert_eq!(round_to(-0.126, 2), -0.13);
+    }
+}
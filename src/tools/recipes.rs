@@ -2,15 +2,22 @@
 //!
 //! Tools for managing recipes, recipe ingredients, and recipe components.
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+use crate::clock::Clock;
 use crate::db::Database;
 use crate::models::{
-    Nutrition, Recipe, RecipeCreate, RecipeIngredient, RecipeIngredientCreate,
-    RecipeIngredientDetail, RecipeIngredientUpdate, RecipeUpdate,
+    FoodItem, FoodItemCreate, Nutrition, PreparedBatch, PreparedBatchCreate, PreparedBatchDetail, Recipe, RecipeCreate, RecipeIngredient, RecipeIngredientCreate,
+    RecipeIngredientDetail, RecipeIngredientUpdate, RecipeUpdate, RECIPE_SORT_KEYS,
     RecipeComponent, RecipeComponentCreate, RecipeComponentDetail, RecipeComponentUpdate,
-    recalculate_recipe_nutrition, would_create_cycle,
+    deepest_component_chain, nesting_depth_after_adding, recalculate_recipe_nutrition, would_create_cycle,
+    DEFAULT_MAX_NESTING_DEPTH,
 };
+use crate::nutrition::{
+    calculate_nutrition_multiplier, infer_base_unit_type, parse_unit, to_grams, to_ml, BaseUnitType, UnitCategory,
+};
+use super::error::ToolError;
+use super::numeric::round_to;
 
 /// Response for create_recipe
 #[derive(Debug, Serialize)]
@@ -30,6 +37,18 @@ pub struct RecipeDetail {
     pub ingredients: Vec<RecipeIngredientDetail>,
     pub components: Vec<RecipeComponentDetail>,
     pub nutrition_per_serving: Nutrition,
+    /// Cooked weight of the whole batch, if recorded
+    pub yield_grams: Option<f64>,
+    /// Batch weight used for per-100g values: yield_grams when set, otherwise
+    /// the raw ingredient sum (None if an ingredient has no known gram weight)
+    pub batch_grams: Option<f64>,
+    /// "yield_grams" or "ingredient_sum"
+    pub batch_grams_source: Option<String>,
+    pub nutrition_per_100g: Option<Nutrition>,
+    /// Sodium mg per 1000 kcal (None for zero-calorie recipes)
+    pub sodium_density: Option<f64>,
+    /// Sodium density above the requested limit
+    pub sodium_dense: bool,
     pub notes: Option<String>,
     pub created_at: String,
     pub updated_at: String,
@@ -45,6 +64,8 @@ pub struct RecipeSummary {
     pub is_favorite: bool,
     pub calories_per_serving: f64,
     pub ingredient_count: usize,
+    /// Latest eaten_at of a meal entry logging this recipe
+    pub last_logged_at: Option<String>,
 }
 
 /// Unused recipe summary (safe to delete)
@@ -63,6 +84,33 @@ pub struct ListUnusedRecipesResponse {
     pub count: usize,
 }
 
+/// Response for toggle_recipe_favorite
+#[derive(Debug, Serialize)]
+pub struct ToggleRecipeFavoriteResponse {
+    pub id: i64,
+    pub name: String,
+    pub is_favorite: bool,
+}
+
+/// Recently used recipe summary
+#[derive(Debug, Serialize)]
+pub struct RecentRecipeSummary {
+    pub id: i64,
+    pub name: String,
+    pub servings_produced: f64,
+    pub is_favorite: bool,
+    pub calories_per_serving: f64,
+    pub last_logged_date: String,
+    pub times_logged: i64,
+}
+
+/// Response for list_recent_recipes
+#[derive(Debug, Serialize)]
+pub struct ListRecentRecipesResponse {
+    pub recipes: Vec<RecentRecipeSummary>,
+    pub count: usize,
+}
+
 /// Response for list_recipes
 #[derive(Debug, Serialize)]
 pub struct ListRecipesResponse {
@@ -154,22 +202,25 @@ pub struct RecipeDeleteSuccessResponse {
 // ============================================================================
 
 /// Create a new recipe
-pub fn create_recipe(db: &Database, data: RecipeCreate) -> Result<CreateRecipeResponse, String> {
+pub fn create_recipe(db: &Database, data: RecipeCreate) -> Result<CreateRecipeResponse, ToolError> {
     // Validate name
     let name = data.name.trim();
     if name.is_empty() {
-        return Err("Recipe name cannot be empty".to_string());
+        return Err(ToolError::Validation("Recipe name cannot be empty".to_string()));
     }
 
     // Validate servings
     if data.servings_produced <= 0.0 {
-        return Err("servings_produced must be greater than 0".to_string());
+        return Err(ToolError::Validation("servings_produced must be greater than 0".to_string()));
+    }
+    if data.yield_grams.is_some_and(|g| g <= 0.0) {
+        return Err(ToolError::Validation("yield_grams must be greater than 0".to_string()));
     }
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let conn = db.get_conn()?;
 
     let recipe = Recipe::create(&conn, &data)
-        .map_err(|e| format!("Failed to create recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to create recipe: {}", e)))?;
 
     Ok(CreateRecipeResponse {
         id: recipe.id,
@@ -178,23 +229,101 @@ pub fn create_recipe(db: &Database, data: RecipeCreate) -> Result<CreateRecipeRe
     })
 }
 
+/// Grams per ml from an ingredient note such as "1 cup = 120 g" or "120g ≈ 1 cup"
+fn notes_grams_per_ml(notes: &str) -> Option<f64> {
+    use crate::nutrition::{grams_per_unit, ml_per_unit};
+
+    // "<number> <unit>" or "<number><unit>"
+    let amount = |side: &str| -> Option<(f64, String)> {
+        let side = side.trim();
+        let split = side.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(side.len());
+        let quantity: f64 = side[..split].parse().ok()?;
+        Some((quantity, side[split..].trim().to_string()))
+    };
+
+    let (left, right) = notes.split_once('=').or_else(|| notes.split_once('≈'))?;
+    // Only the amount nearest the separator belongs to the conversion
+    let left = left.rsplit([',', ';', '(']).next()?;
+    let right = right.split([',', ';', ')']).next()?;
+    let ((qa, ua), (qb, ub)) = (amount(left)?, amount(right)?);
+
+    let grams_per_ml = match (grams_per_unit(&ua), ml_per_unit(&ub), ml_per_unit(&ua), grams_per_unit(&ub)) {
+        (Some(g), Some(ml), _, _) => qa * g / (qb * ml),
+        (_, _, Some(ml), Some(g)) => qb * g / (qa * ml),
+        _ => return None,
+    };
+    (grams_per_ml.is_finite() && grams_per_ml > 0.0).then_some(grams_per_ml)
+}
+
+/// "320 g ≈ 2.75 cups": cups to the nearest quarter, smaller amounts in tbsp or tsp
+fn friendly_volume(grams: f64, grams_per_ml: f64) -> String {
+    use crate::nutrition::units::{ML_PER_CUP, ML_PER_TBSP, ML_PER_TSP};
+
+    let ml = grams / grams_per_ml;
+    let (amount, unit, step) = if ml >= ML_PER_CUP / 4.0 {
+        (ml / ML_PER_CUP, "cup", 0.25)
+    } else if ml >= ML_PER_TBSP {
+        (ml / ML_PER_TBSP, "tbsp", 0.5)
+    } else {
+        (ml / ML_PER_TSP, "tsp", 0.25)
+    };
+    let amount = ((amount / step).round() * step).max(step);
+    let plural = if unit == "cup" && amount != 1.0 { "s" } else { "" };
+    format!("{} g ≈ {} {}{}", round_to(grams, 1), amount, unit, plural)
+}
+
+/// Fill in friendly_amount for gram-measured ingredients, using a conversion in
+/// the ingredient's notes first and the food item's grams/ml per serving second
+fn add_friendly_amounts(conn: &rusqlite::Connection, ingredients: &mut [RecipeIngredientDetail]) -> Result<(), ToolError> {
+    for ing in ingredients.iter_mut() {
+        let Some(grams) = to_grams(ing.quantity, &ing.unit) else {
+            continue;
+        };
+        let density = match ing.notes.as_deref().and_then(notes_grams_per_ml) {
+            Some(d) => Some(d),
+            None => FoodItem::get_by_id(conn, ing.food_item_id)
+                .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+                .and_then(|item| match (item.grams_per_serving, item.ml_per_serving) {
+                    (Some(g), Some(ml)) if g > 0.0 && ml > 0.0 => Some(g / ml),
+                    _ => None,
+                }),
+        };
+        ing.friendly_amount = Some(match density {
+            Some(d) => friendly_volume(grams, d),
+            None => format!("{} g", round_to(grams, 1)),
+        });
+    }
+    Ok(())
+}
+
 /// Get a recipe with full details
-pub fn get_recipe(db: &Database, id: i64) -> Result<Option<RecipeDetail>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn get_recipe(db: &Database, id: i64, sodium_density_limit: f64, friendly_units: bool) -> Result<Option<RecipeDetail>, ToolError> {
+    let conn = db.get_conn()?;
 
     let recipe = Recipe::get_by_id(&conn, id)
-        .map_err(|e| format!("Failed to get recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?;
 
     match recipe {
         Some(recipe) => {
-            let ingredients = RecipeIngredient::get_details_for_recipe(&conn, id)
-                .map_err(|e| format!("Failed to get ingredients: {}", e))?;
+            let mut ingredients = RecipeIngredient::get_details_for_recipe(&conn, id)
+                .map_err(|e| ToolError::Db(format!("Failed to get ingredients: {}", e)))?;
+            if friendly_units {
+                add_friendly_amounts(&conn, &mut ingredients)?;
+            }
 
             let components = RecipeComponent::get_details_for_recipe(&conn, id)
-                .map_err(|e| format!("Failed to get components: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to get components: {}", e)))?;
 
             let times_logged = Recipe::get_times_logged(&conn, id)
-                .map_err(|e| format!("Failed to get times logged: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to get times logged: {}", e)))?;
+
+            let sodium_density = recipe.cached_nutrition.sodium_density().map(|d| round_to(d, 2));
+            let sodium_dense = recipe.cached_nutrition.is_sodium_dense(sodium_density_limit);
+
+            let (batch_grams, batch_grams_source) = recipe_batch_grams(&conn, &recipe)?;
+            let nutrition_per_100g = batch_grams
+                .filter(|g| *g > 0.0)
+                .map(|g| recipe.cached_nutrition.scale(recipe.servings_produced * 100.0 / g));
 
             Ok(Some(RecipeDetail {
                 id: recipe.id,
@@ -204,6 +333,12 @@ pub fn get_recipe(db: &Database, id: i64) -> Result<Option<RecipeDetail>, String
                 ingredients,
                 components,
                 nutrition_per_serving: recipe.cached_nutrition,
+                yield_grams: recipe.yield_grams,
+                batch_grams,
+                batch_grams_source,
+                nutrition_per_100g,
+                sodium_density,
+                sodium_dense,
                 notes: recipe.notes,
                 created_at: recipe.created_at,
                 updated_at: recipe.updated_at,
@@ -214,6 +349,57 @@ pub fn get_recipe(db: &Database, id: i64) -> Result<Option<RecipeDetail>, String
     }
 }
 
+/// Weight of a whole batch and where it came from ("yield_grams" or "ingredient_sum")
+fn recipe_batch_grams(conn: &rusqlite::Connection, recipe: &Recipe) -> Result<(Option<f64>, Option<String>), ToolError> {
+    Ok(match recipe.yield_grams {
+        Some(grams) => (Some(grams), Some("yield_grams".to_string())),
+        None => {
+            let sum = ingredient_batch_grams(conn, recipe.id)?;
+            (sum, sum.map(|_| "ingredient_sum".to_string()))
+        }
+    })
+}
+
+/// Raw weight of a recipe batch: ingredient grams plus the share of each
+/// component's batch weight. Components use their own yield_grams when set.
+/// None if any ingredient's weight can't be determined.
+fn ingredient_batch_grams(conn: &rusqlite::Connection, recipe_id: i64) -> Result<Option<f64>, ToolError> {
+    let mut total = 0.0;
+
+    let ingredients = RecipeIngredient::get_for_recipe(conn, recipe_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get ingredients: {}", e)))?;
+    for ing in ingredients {
+        let food = FoodItem::get_by_id(conn, ing.food_item_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Food item not found with id: {}", ing.food_item_id)))?;
+        let grams = to_grams(ing.quantity, &ing.unit).or_else(|| {
+            food.grams_per_serving.map(|gps| gps * ing.food_item_servings(&food))
+        });
+        match grams {
+            Some(g) => total += g,
+            None => return Ok(None),
+        }
+    }
+
+    let components = RecipeComponent::get_for_recipe(conn, recipe_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get components: {}", e)))?;
+    for comp in components {
+        let sub = Recipe::get_by_id(conn, comp.component_recipe_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get component recipe: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Recipe not found with id: {}", comp.component_recipe_id)))?;
+        let sub_grams = match sub.yield_grams {
+            Some(g) => g,
+            None => match ingredient_batch_grams(conn, sub.id)? {
+                Some(g) => g,
+                None => return Ok(None),
+            },
+        };
+        total += sub_grams / sub.servings_produced * comp.servings;
+    }
+
+    Ok(Some(total))
+}
+
 /// List recipes with filtering
 pub fn list_recipes(
     db: &Database,
@@ -223,22 +409,31 @@ pub fn list_recipes(
     sort_order: &str,
     limit: i64,
     offset: i64,
-) -> Result<ListRecipesResponse, String> {
+) -> Result<ListRecipesResponse, ToolError> {
+    if !RECIPE_SORT_KEYS.contains(&sort_by.to_lowercase().as_str()) {
+        return Err(ToolError::Validation(format!(
+            "Invalid sort_by: '{}'. Valid values: {}",
+            sort_by,
+            RECIPE_SORT_KEYS.join(", ")
+        )));
+    }
     let limit = limit.min(200).max(1);
     let offset = offset.max(0);
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let conn = db.get_conn()?;
 
     let recipes = Recipe::list(&conn, query, favorites_only, sort_by, sort_order, limit, offset)
-        .map_err(|e| format!("Failed to list recipes: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to list recipes: {}", e)))?;
 
     let total = Recipe::count(&conn, favorites_only)
-        .map_err(|e| format!("Failed to count recipes: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to count recipes: {}", e)))?;
 
     let mut summaries = Vec::new();
     for recipe in recipes {
         let ingredients = RecipeIngredient::get_for_recipe(&conn, recipe.id)
-            .map_err(|e| format!("Failed to get ingredients: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to get ingredients: {}", e)))?;
+        let last_logged_at = Recipe::get_last_logged_at(&conn, recipe.id)
+            .map_err(|e| ToolError::Db(format!("Failed to get last logged time: {}", e)))?;
 
         summaries.push(RecipeSummary {
             id: recipe.id,
@@ -247,6 +442,7 @@ pub fn list_recipes(
             is_favorite: recipe.is_favorite,
             calories_per_serving: recipe.cached_nutrition.calories,
             ingredient_count: ingredients.len(),
+            last_logged_at,
         });
     }
 
@@ -258,16 +454,61 @@ pub fn list_recipes(
     })
 }
 
+/// Toggle a recipe's favorite flag (allowed even if the recipe has been logged)
+pub fn toggle_recipe_favorite(db: &Database, id: i64) -> Result<Option<ToggleRecipeFavoriteResponse>, ToolError> {
+    let conn = db.get_conn()?;
+
+    let recipe = Recipe::toggle_favorite(&conn, id)
+        .map_err(|e| ToolError::Db(format!("Failed to toggle favorite: {}", e)))?;
+
+    Ok(recipe.map(|r| ToggleRecipeFavoriteResponse {
+        id: r.id,
+        name: r.name,
+        is_favorite: r.is_favorite,
+    }))
+}
+
+/// List recipes ordered by their most recent meal entry, for quick re-logging
+pub fn list_recent_recipes(db: &Database, limit: i64) -> Result<ListRecentRecipesResponse, ToolError> {
+    let limit = limit.clamp(1, 200);
+
+    let conn = db.get_conn()?;
+
+    let recent = Recipe::list_recent(&conn, limit)
+        .map_err(|e| ToolError::Db(format!("Failed to list recent recipes: {}", e)))?;
+
+    let recipes: Vec<RecentRecipeSummary> = recent
+        .into_iter()
+        .map(|(recipe, last_logged_date, times_logged)| RecentRecipeSummary {
+            id: recipe.id,
+            name: recipe.name,
+            servings_produced: recipe.servings_produced,
+            is_favorite: recipe.is_favorite,
+            calories_per_serving: recipe.cached_nutrition.calories,
+            last_logged_date,
+            times_logged,
+        })
+        .collect();
+
+    let count = recipes.len();
+
+    Ok(ListRecentRecipesResponse { recipes, count })
+}
+
 /// Update a recipe (blocked if used in meal entries)
 pub fn update_recipe(
     db: &Database,
     id: i64,
     data: RecipeUpdate,
-) -> Result<Result<RecipeUpdateSuccessResponse, RecipeUpdateBlockedResponse>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<Result<RecipeUpdateSuccessResponse, RecipeUpdateBlockedResponse>, ToolError> {
+    if data.yield_grams.is_some_and(|g| g <= 0.0) {
+        return Err(ToolError::Validation("yield_grams must be greater than 0".to_string()));
+    }
+
+    let conn = db.get_conn()?;
 
     let times_logged = Recipe::get_times_logged(&conn, id)
-        .map_err(|e| format!("Failed to check usage: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to check usage: {}", e)))?;
 
     if times_logged > 0 {
         return Ok(Err(RecipeUpdateBlockedResponse {
@@ -277,7 +518,7 @@ pub fn update_recipe(
     }
 
     let updated = Recipe::update(&conn, id, &data)
-        .map_err(|e| format!("Failed to update recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to update recipe: {}", e)))?;
 
     match updated {
         Some(recipe) => Ok(Ok(RecipeUpdateSuccessResponse {
@@ -295,23 +536,22 @@ pub fn update_recipe(
 pub fn delete_recipe(
     db: &Database,
     id: i64,
-) -> Result<Result<RecipeDeleteSuccessResponse, RecipeDeleteBlockedResponse>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<Result<RecipeDeleteSuccessResponse, RecipeDeleteBlockedResponse>, ToolError> {
+    let conn = db.get_conn()?;
 
     // Check if recipe exists
-    let recipe = Recipe::get_by_id(&conn, id)
-        .map_err(|e| format!("Database error: {}", e))?;
+    let recipe = Recipe::get_by_id(&conn, id)?;
     if recipe.is_none() {
-        return Err(format!("Recipe not found with id: {}", id));
+        return Err(ToolError::NotFound(format!("Recipe not found with id: {}", id)));
     }
 
     // Check if logged in meal entries
     let times_logged = Recipe::get_times_logged(&conn, id)
-        .map_err(|e| format!("Failed to check meal usage: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to check meal usage: {}", e)))?;
 
     // Check if used as component in other recipes
     let component_usage_count = Recipe::get_component_usage_count(&conn, id)
-        .map_err(|e| format!("Failed to check component usage: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to check component usage: {}", e)))?;
 
     if times_logged > 0 || component_usage_count > 0 {
         let mut reasons = Vec::new();
@@ -330,7 +570,7 @@ pub fn delete_recipe(
 
     // Delete the recipe (cascades to recipe_ingredients and recipe_components where this is parent)
     Recipe::delete(&conn, id)
-        .map_err(|e| format!("Failed to delete recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to delete recipe: {}", e)))?;
 
     Ok(Ok(RecipeDeleteSuccessResponse {
         success: true,
@@ -340,8 +580,8 @@ pub fn delete_recipe(
 
 /// List recipes with zero uses (not logged in meals, not used as component in other recipes)
 /// These recipes are safe to delete
-pub fn list_unused_recipes(db: &Database) -> Result<ListUnusedRecipesResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn list_unused_recipes(db: &Database) -> Result<ListUnusedRecipesResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     // Find recipes that:
     // 1. Have no meal_entries referencing them
@@ -358,7 +598,7 @@ pub fn list_unused_recipes(db: &Database) -> Result<ListUnusedRecipesResponse, S
         )
         ORDER BY r.name ASC
         "#
-    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    ).map_err(|e| ToolError::Db(format!("Failed to prepare query: {}", e)))?;
 
     let recipes: Vec<UnusedRecipeSummary> = stmt
         .query_map([], |row| {
@@ -369,9 +609,9 @@ pub fn list_unused_recipes(db: &Database) -> Result<ListUnusedRecipesResponse, S
                 created_at: row.get("created_at")?,
             })
         })
-        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .map_err(|e| ToolError::Db(format!("Failed to execute query: {}", e)))?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect results: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to collect results: {}", e)))?;
 
     let count = recipes.len();
 
@@ -386,40 +626,40 @@ pub fn list_unused_recipes(db: &Database) -> Result<ListUnusedRecipesResponse, S
 pub fn add_recipe_ingredient(
     db: &Database,
     data: RecipeIngredientCreate,
-) -> Result<AddIngredientResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<AddIngredientResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     // Validate recipe exists
     let recipe = Recipe::get_by_id(&conn, data.recipe_id)
-        .map_err(|e| format!("Database error checking recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Database error checking recipe: {}", e)))?;
     if recipe.is_none() {
-        return Err(format!("Recipe not found with id: {}", data.recipe_id));
+        return Err(ToolError::NotFound(format!("Recipe not found with id: {}", data.recipe_id)));
     }
 
     // Validate food item exists
     use crate::models::FoodItem;
     let food_item = FoodItem::get_by_id(&conn, data.food_item_id)
-        .map_err(|e| format!("Database error checking food item: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Database error checking food item: {}", e)))?;
     if food_item.is_none() {
-        return Err(format!("Food item not found with id: {}", data.food_item_id));
+        return Err(ToolError::NotFound(format!("Food item not found with id: {}", data.food_item_id)));
     }
 
     // Check if ingredient already exists in recipe
     let existing = RecipeIngredient::get_for_recipe(&conn, data.recipe_id)
-        .map_err(|e| format!("Database error checking existing ingredients: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Database error checking existing ingredients: {}", e)))?;
     if existing.iter().any(|i| i.food_item_id == data.food_item_id) {
-        return Err(format!(
+        return Err(ToolError::Validation(format!(
             "Food item {} is already an ingredient in recipe {}. Use update_recipe_ingredient to modify quantity.",
             data.food_item_id, data.recipe_id
-        ));
+        )));
     }
 
     let ingredient = RecipeIngredient::create(&conn, &data)
-        .map_err(|e| format!("Failed to add ingredient: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to add ingredient: {}", e)))?;
 
     // Recalculate nutrition
     recalculate_recipe_nutrition(&conn, data.recipe_id)
-        .map_err(|e| format!("Failed to recalculate nutrition: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
 
     Ok(AddIngredientResponse {
         id: ingredient.id,
@@ -433,136 +673,118 @@ pub fn add_recipe_ingredient(
 /// Add multiple ingredients to a recipe in one call (batch operation)
 /// This is much faster than calling add_recipe_ingredient multiple times
 /// because it only recalculates nutrition once at the end.
+/// Invalid entries are reported per ingredient; a database error rolls back the whole batch.
 pub fn add_recipe_ingredients_batch(
     db: &Database,
     recipe_id: i64,
     ingredients: Vec<BatchIngredient>,
-) -> Result<BatchAddIngredientsResponse, String> {
+) -> Result<BatchAddIngredientsResponse, ToolError> {
     use crate::models::FoodItem;
     use std::collections::HashSet;
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
-
-    // Validate recipe exists
-    let recipe = Recipe::get_by_id(&conn, recipe_id)
-        .map_err(|e| format!("Database error checking recipe: {}", e))?
-        .ok_or_else(|| format!("Recipe not found with id: {}", recipe_id))?;
-
-    // Get existing ingredient food_item_ids for duplicate checking
-    let existing: HashSet<i64> = RecipeIngredient::get_for_recipe(&conn, recipe_id)
-        .map_err(|e| format!("Database error checking existing ingredients: {}", e))?
-        .into_iter()
-        .map(|i| i.food_item_id)
-        .collect();
-
-    let total_requested = ingredients.len();
-    let mut results = Vec::with_capacity(total_requested);
-    let mut successful = 0;
-    let mut failed = 0;
-
-    for ing in ingredients {
-        // Validate food item exists
-        let food_item = match FoodItem::get_by_id(&conn, ing.food_item_id) {
-            Ok(Some(fi)) => fi,
-            Ok(None) => {
+    db.transaction(|conn| {
+        // Validate recipe exists
+        let recipe = Recipe::get_by_id(conn, recipe_id)
+            .map_err(|e| ToolError::Db(format!("Database error checking recipe: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Recipe not found with id: {}", recipe_id)))?;
+
+        // Existing ingredient food_item_ids for duplicate checking, extended as
+        // the batch adds items so a repeat within the batch is skipped too
+        let mut existing: HashSet<i64> = RecipeIngredient::get_for_recipe(conn, recipe_id)
+            .map_err(|e| ToolError::Db(format!("Database error checking existing ingredients: {}", e)))?
+            .into_iter()
+            .map(|i| i.food_item_id)
+            .collect();
+
+        let total_requested = ingredients.len();
+        let mut results = Vec::with_capacity(total_requested);
+        let mut successful = 0;
+        let mut failed = 0;
+
+        for ing in ingredients {
+            // Validate food item exists
+            let food_item = match FoodItem::get_by_id(conn, ing.food_item_id)
+                .map_err(|e| ToolError::Db(format!("Database error checking food item {}: {}", ing.food_item_id, e)))?
+            {
+                Some(fi) => fi,
+                None => {
+                    results.push(BatchIngredientResult {
+                        food_item_id: ing.food_item_id,
+                        food_item_name: format!("Unknown (id: {})", ing.food_item_id),
+                        success: false,
+                        ingredient_id: None,
+                        error: Some(format!("Food item not found with id: {}", ing.food_item_id)),
+                    });
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            // Check for duplicate
+            if existing.contains(&ing.food_item_id) {
                 results.push(BatchIngredientResult {
                     food_item_id: ing.food_item_id,
-                    food_item_name: format!("Unknown (id: {})", ing.food_item_id),
+                    food_item_name: food_item.name,
                     success: false,
                     ingredient_id: None,
-                    error: Some(format!("Food item not found with id: {}", ing.food_item_id)),
+                    error: Some("Food item already exists in recipe".to_string()),
                 });
                 failed += 1;
                 continue;
             }
-            Err(e) => {
+
+            // Validate quantity
+            if ing.quantity <= 0.0 {
                 results.push(BatchIngredientResult {
                     food_item_id: ing.food_item_id,
-                    food_item_name: format!("Unknown (id: {})", ing.food_item_id),
+                    food_item_name: food_item.name,
                     success: false,
                     ingredient_id: None,
-                    error: Some(format!("Database error: {}", e)),
+                    error: Some("Quantity must be greater than 0".to_string()),
                 });
                 failed += 1;
                 continue;
             }
-        };
 
-        // Check for duplicate
-        if existing.contains(&ing.food_item_id) {
-            results.push(BatchIngredientResult {
+            // Create the ingredient
+            let data = RecipeIngredientCreate {
+                recipe_id,
                 food_item_id: ing.food_item_id,
-                food_item_name: food_item.name,
-                success: false,
-                ingredient_id: None,
-                error: Some("Food item already exists in recipe".to_string()),
-            });
-            failed += 1;
-            continue;
-        }
-
-        // Validate quantity
-        if ing.quantity <= 0.0 {
+                quantity: ing.quantity,
+                unit: ing.unit,
+                notes: ing.notes,
+            };
+
+            let ingredient = RecipeIngredient::create(conn, &data)
+                .map_err(|e| ToolError::Db(format!("Failed to add '{}', batch rolled back: {}", food_item.name, e)))?;
+            existing.insert(ing.food_item_id);
             results.push(BatchIngredientResult {
                 food_item_id: ing.food_item_id,
                 food_item_name: food_item.name,
-                success: false,
-                ingredient_id: None,
-                error: Some("Quantity must be greater than 0".to_string()),
+                success: true,
+                ingredient_id: Some(ingredient.id),
+                error: None,
             });
-            failed += 1;
-            continue;
+            successful += 1;
         }
 
-        // Create the ingredient
-        let data = RecipeIngredientCreate {
-            recipe_id,
-            food_item_id: ing.food_item_id,
-            quantity: ing.quantity,
-            unit: ing.unit,
-            notes: ing.notes,
+        // Recalculate nutrition ONCE at the end (only if we added at least one ingredient)
+        let nutrition = if successful > 0 {
+            recalculate_recipe_nutrition(conn, recipe_id)
+                .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?
+        } else {
+            recipe.cached_nutrition
         };
 
-        match RecipeIngredient::create(&conn, &data) {
-            Ok(ingredient) => {
-                results.push(BatchIngredientResult {
-                    food_item_id: ing.food_item_id,
-                    food_item_name: food_item.name,
-                    success: true,
-                    ingredient_id: Some(ingredient.id),
-                    error: None,
-                });
-                successful += 1;
-            }
-            Err(e) => {
-                results.push(BatchIngredientResult {
-                    food_item_id: ing.food_item_id,
-                    food_item_name: food_item.name,
-                    success: false,
-                    ingredient_id: None,
-                    error: Some(format!("Failed to create: {}", e)),
-                });
-                failed += 1;
-            }
-        }
-    }
-
-    // Recalculate nutrition ONCE at the end (only if we added at least one ingredient)
-    let nutrition = if successful > 0 {
-        recalculate_recipe_nutrition(&conn, recipe_id)
-            .map_err(|e| format!("Failed to recalculate nutrition: {}", e))?
-    } else {
-        recipe.cached_nutrition
-    };
-
-    Ok(BatchAddIngredientsResponse {
-        recipe_id,
-        recipe_name: recipe.name,
-        total_requested,
-        successful,
-        failed,
-        results,
-        nutrition_per_serving: nutrition,
+        Ok(BatchAddIngredientsResponse {
+            recipe_id,
+            recipe_name: recipe.name,
+            total_requested,
+            successful,
+            failed,
+            results,
+            nutrition_per_serving: nutrition,
+        })
     })
 }
 
@@ -571,41 +793,41 @@ pub fn update_recipe_ingredient(
     db: &Database,
     id: i64,
     data: RecipeIngredientUpdate,
-) -> Result<Option<RecipeIngredient>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<Option<RecipeIngredient>, ToolError> {
+    let conn = db.get_conn()?;
 
     // Get recipe_id before update for recalculation
     let recipe_id = RecipeIngredient::get_recipe_id(&conn, id)
-        .map_err(|e| format!("Failed to get recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?;
 
     let updated = RecipeIngredient::update(&conn, id, &data)
-        .map_err(|e| format!("Failed to update ingredient: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to update ingredient: {}", e)))?;
 
     // Recalculate nutrition if update succeeded
     if let (Some(_), Some(recipe_id)) = (&updated, recipe_id) {
         recalculate_recipe_nutrition(&conn, recipe_id)
-            .map_err(|e| format!("Failed to recalculate nutrition: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
     }
 
     Ok(updated)
 }
 
 /// Remove an ingredient from a recipe
-pub fn remove_recipe_ingredient(db: &Database, id: i64) -> Result<bool, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn remove_recipe_ingredient(db: &Database, id: i64) -> Result<bool, ToolError> {
+    let conn = db.get_conn()?;
 
     // Get recipe_id before delete for recalculation
     let recipe_id = RecipeIngredient::get_recipe_id(&conn, id)
-        .map_err(|e| format!("Failed to get recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?;
 
     let deleted = RecipeIngredient::delete(&conn, id)
-        .map_err(|e| format!("Failed to remove ingredient: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to remove ingredient: {}", e)))?;
 
     // Recalculate nutrition if delete succeeded
     if deleted {
         if let Some(recipe_id) = recipe_id {
             recalculate_recipe_nutrition(&conn, recipe_id)
-                .map_err(|e| format!("Failed to recalculate nutrition: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
         }
     }
 
@@ -613,11 +835,11 @@ pub fn remove_recipe_ingredient(db: &Database, id: i64) -> Result<bool, String>
 }
 
 /// Force recalculate recipe nutrition
-pub fn recalculate_nutrition(db: &Database, recipe_id: i64) -> Result<RecalculateNutritionResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn recalculate_nutrition(db: &Database, recipe_id: i64) -> Result<RecalculateNutritionResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     let nutrition = recalculate_recipe_nutrition(&conn, recipe_id)
-        .map_err(|e| format!("Failed to recalculate nutrition: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
 
     Ok(RecalculateNutritionResponse {
         recipe_id,
@@ -625,6 +847,145 @@ pub fn recalculate_nutrition(db: &Database, recipe_id: i64) -> Result<Recalculat
     })
 }
 
+// ============================================================================
+// Ingredient Unit Conversion
+// ============================================================================
+
+/// An ingredient moved from a volume unit to g or ml
+#[derive(Debug, Serialize)]
+pub struct ConvertedIngredient {
+    pub id: i64,
+    pub food_item_name: String,
+    pub from_quantity: f64,
+    pub from_unit: String,
+    pub to_quantity: f64,
+    pub to_unit: String,
+    /// Where the density came from: unit_annotation, ingredient_notes, food_item,
+    /// or none for volume-based food items converted to ml
+    pub density_source: String,
+}
+
+/// A volume-measured ingredient left as it was
+#[derive(Debug, Serialize)]
+pub struct UnconvertedIngredient {
+    pub id: i64,
+    pub food_item_name: String,
+    pub quantity: f64,
+    pub unit: String,
+    pub reason: String,
+}
+
+/// Response for convert_recipe_ingredient_units
+#[derive(Debug, Serialize)]
+pub struct ConvertIngredientUnitsResponse {
+    pub recipe_id: i64,
+    pub recipe_name: String,
+    pub converted: Vec<ConvertedIngredient>,
+    pub not_converted: Vec<UnconvertedIngredient>,
+    pub nutrition_per_serving: Nutrition,
+}
+
+/// Convert a volume amount to ml for volume-based foods, or to grams using the
+/// first density found: a gram annotation on the unit ("cup (120g)"), a
+/// conversion in the notes ("1 cup = 120 g"), then the food item's own g/ml.
+/// Returns (quantity, unit, density source), or None when no density exists.
+fn convert_volume_amount(
+    quantity: f64,
+    unit: &str,
+    notes: Option<&str>,
+    base_unit_type: Option<BaseUnitType>,
+    item_grams_per_ml: Option<f64>,
+) -> Option<(f64, &'static str, &'static str)> {
+    if base_unit_type == Some(BaseUnitType::Volume) {
+        return to_ml(quantity, unit).map(|ml| (round_to(ml, 1), "ml", "none"));
+    }
+    if parse_unit(unit).gram_weight.is_some() {
+        return to_grams(quantity, unit).map(|g| (round_to(g, 1), "g", "unit_annotation"));
+    }
+    let ml = to_ml(quantity, unit)?;
+    let (density, source) = match notes.and_then(notes_grams_per_ml) {
+        Some(d) => (d, "ingredient_notes"),
+        None => (item_grams_per_ml?, "food_item"),
+    };
+    Some((round_to(ml * density, 1), "g", source))
+}
+
+/// Move every volume-measured ingredient of a recipe to g (or ml for liquids
+/// stored per ml), recording the original measure in the ingredient's notes,
+/// then recalculate the recipe once. Ingredients with no density are reported.
+pub fn convert_recipe_ingredient_units(db: &Database, recipe_id: i64) -> Result<ConvertIngredientUnitsResponse, ToolError> {
+    db.transaction(|conn| {
+        let recipe = Recipe::get_by_id(conn, recipe_id)?
+            .ok_or_else(|| ToolError::NotFound(format!("Recipe not found with id: {}", recipe_id)))?;
+
+        let ingredients = RecipeIngredient::get_details_for_recipe(conn, recipe_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get ingredients: {}", e)))?;
+
+        let mut converted = Vec::new();
+        let mut not_converted = Vec::new();
+        for ing in ingredients {
+            let parsed = parse_unit(&ing.unit);
+            if parsed.category != UnitCategory::Volume || crate::nutrition::ml_per_unit(&parsed.base_unit) == Some(1.0) {
+                continue;
+            }
+            let item = FoodItem::get_by_id(conn, ing.food_item_id)
+                .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+                .ok_or_else(|| ToolError::NotFound(format!("Food item not found with id: {}", ing.food_item_id)))?;
+            let item_grams_per_ml = match (item.grams_per_serving, item.ml_per_serving) {
+                (Some(g), Some(ml)) if g > 0.0 && ml > 0.0 => Some(g / ml),
+                _ => None,
+            };
+
+            let Some((quantity, unit, source)) = convert_volume_amount(
+                ing.quantity, &ing.unit, ing.notes.as_deref(), item.base_unit_type, item_grams_per_ml,
+            ) else {
+                not_converted.push(UnconvertedIngredient {
+                    id: ing.id,
+                    food_item_name: ing.food_item_name,
+                    quantity: ing.quantity,
+                    unit: ing.unit,
+                    reason: "No density: add grams_per_serving and ml_per_serving to the food item, or a note like \"1 cup = 120 g\"".to_string(),
+                });
+                continue;
+            };
+
+            // Keep the original measure, in the same "1 cup = 120 g" form notes conversions use
+            let original = format!("{} {} = {} {}", ing.quantity, ing.unit, quantity, unit);
+            let notes = match ing.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+                Some(existing) => format!("{}; {}", existing, original),
+                None => original,
+            };
+            RecipeIngredient::update(conn, ing.id, &RecipeIngredientUpdate {
+                quantity: Some(quantity),
+                unit: Some(unit.to_string()),
+                notes: Some(notes),
+            })
+            .map_err(|e| ToolError::Db(format!("Failed to update ingredient {}: {}", ing.id, e)))?;
+
+            converted.push(ConvertedIngredient {
+                id: ing.id,
+                food_item_name: ing.food_item_name,
+                from_quantity: ing.quantity,
+                from_unit: ing.unit,
+                to_quantity: quantity,
+                to_unit: unit.to_string(),
+                density_source: source.to_string(),
+            });
+        }
+
+        let nutrition_per_serving = recalculate_recipe_nutrition(conn, recipe_id)
+            .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
+
+        Ok(ConvertIngredientUnitsResponse {
+            recipe_id,
+            recipe_name: recipe.name,
+            converted,
+            not_converted,
+            nutrition_per_serving,
+        })
+    })
+}
+
 // ============================================================================
 // Recipe Component Tools
 // ============================================================================
@@ -639,54 +1000,75 @@ pub struct AddComponentResponse {
     pub servings: f64,
 }
 
+/// Deepest allowed chain of component recipes: UHM_MAX_RECIPE_DEPTH if set to a
+/// positive number, otherwise DEFAULT_MAX_NESTING_DEPTH
+pub fn max_recipe_nesting_depth() -> usize {
+    std::env::var("UHM_MAX_RECIPE_DEPTH")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&d| d > 0)
+        .unwrap_or(DEFAULT_MAX_NESTING_DEPTH)
+}
+
 /// Add a recipe as a component of another recipe
 pub fn add_recipe_component(
     db: &Database,
     data: RecipeComponentCreate,
-) -> Result<AddComponentResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<AddComponentResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     // Validate parent recipe exists
     let parent_recipe = Recipe::get_by_id(&conn, data.recipe_id)
-        .map_err(|e| format!("Database error checking recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Database error checking recipe: {}", e)))?;
     if parent_recipe.is_none() {
-        return Err(format!("Recipe not found with id: {}", data.recipe_id));
+        return Err(ToolError::NotFound(format!("Recipe not found with id: {}", data.recipe_id)));
     }
 
     // Validate component recipe exists
     let component_recipe = Recipe::get_by_id(&conn, data.component_recipe_id)
-        .map_err(|e| format!("Database error checking component recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Database error checking component recipe: {}", e)))?;
     let component_recipe = match component_recipe {
         Some(r) => r,
-        None => return Err(format!("Component recipe not found with id: {}", data.component_recipe_id)),
+        None => return Err(ToolError::NotFound(format!("Component recipe not found with id: {}", data.component_recipe_id))),
     };
 
     // Check for circular reference
     if would_create_cycle(&conn, data.recipe_id, data.component_recipe_id)
-        .map_err(|e| format!("Failed to check for circular reference: {}", e))?
+        .map_err(|e| ToolError::Db(format!("Failed to check for circular reference: {}", e)))?
     {
-        return Err(format!(
+        return Err(ToolError::Validation(format!(
             "Cannot add component: would create circular reference (recipe {} already uses recipe {} directly or indirectly)",
             data.component_recipe_id, data.recipe_id
-        ));
+        )));
+    }
+
+    // Check nesting depth across every recipe that would contain the new chain
+    let max_depth = max_recipe_nesting_depth();
+    let depth = nesting_depth_after_adding(&conn, data.recipe_id, data.component_recipe_id)
+        .map_err(|e| ToolError::Db(format!("Failed to check nesting depth: {}", e)))?;
+    if depth > max_depth {
+        return Err(ToolError::Validation(format!(
+            "Cannot add component: recipes would be nested {} levels deep (max {}). Use recipe_nesting_depth to see the existing chains.",
+            depth, max_depth
+        )));
     }
 
     // Check if component already exists
     let existing = RecipeComponent::get_for_recipe(&conn, data.recipe_id)
-        .map_err(|e| format!("Database error checking existing components: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Database error checking existing components: {}", e)))?;
     if existing.iter().any(|c| c.component_recipe_id == data.component_recipe_id) {
-        return Err(format!(
+        return Err(ToolError::Validation(format!(
             "Recipe {} is already a component of recipe {}. Use update_recipe_component to modify servings.",
             data.component_recipe_id, data.recipe_id
-        ));
+        )));
     }
 
     let component = RecipeComponent::create(&conn, &data)
-        .map_err(|e| format!("Failed to add component: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to add component: {}", e)))?;
 
     // Recalculate nutrition
     recalculate_recipe_nutrition(&conn, data.recipe_id)
-        .map_err(|e| format!("Failed to recalculate nutrition: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
 
     Ok(AddComponentResponse {
         id: component.id,
@@ -697,48 +1079,1295 @@ pub fn add_recipe_component(
     })
 }
 
+/// One recipe in a nesting chain
+#[derive(Debug, Serialize)]
+pub struct NestingChainRecipe {
+    pub id: i64,
+    pub name: String,
+}
+
+/// Response for recipe_nesting_depth
+#[derive(Debug, Serialize)]
+pub struct RecipeNestingDepthResponse {
+    pub recipe_id: i64,
+    pub name: String,
+    /// Levels of component recipes below this one (0 = no components)
+    pub depth: usize,
+    pub max_depth: usize,
+    /// The deepest chain, from this recipe down to its innermost component
+    pub deepest_chain: Vec<NestingChainRecipe>,
+}
+
+/// How many levels of component recipes a recipe contains, with the deepest chain
+pub fn recipe_nesting_depth(db: &Database, recipe_id: i64) -> Result<RecipeNestingDepthResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let recipe = Recipe::get_by_id(&conn, recipe_id)?
+        .ok_or_else(|| ToolError::NotFound(format!("Recipe not found with id: {}", recipe_id)))?;
+
+    let chain = deepest_component_chain(&conn, recipe_id)
+        .map_err(|e| ToolError::Db(format!("Failed to walk components: {}", e)))?;
+    let deepest_chain = chain
+        .iter()
+        .map(|&id| {
+            let name = Recipe::get_by_id(&conn, id)?.map(|r| r.name).unwrap_or_default();
+            Ok(NestingChainRecipe { id, name })
+        })
+        .collect::<Result<Vec<_>, ToolError>>()?;
+
+    Ok(RecipeNestingDepthResponse {
+        recipe_id,
+        name: recipe.name,
+        depth: deepest_chain.len().saturating_sub(1),
+        max_depth: max_recipe_nesting_depth(),
+        deepest_chain,
+    })
+}
+
 /// Update a recipe component's servings
 pub fn update_recipe_component(
     db: &Database,
     id: i64,
     data: RecipeComponentUpdate,
-) -> Result<Option<RecipeComponent>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<Option<RecipeComponent>, ToolError> {
+    let conn = db.get_conn()?;
 
     // Get recipe_id before update for recalculation
     let recipe_id = RecipeComponent::get_recipe_id(&conn, id)
-        .map_err(|e| format!("Failed to get recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?;
 
     let updated = RecipeComponent::update(&conn, id, &data)
-        .map_err(|e| format!("Failed to update component: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to update component: {}", e)))?;
 
     // Recalculate nutrition if update succeeded
     if let (Some(_), Some(recipe_id)) = (&updated, recipe_id) {
         recalculate_recipe_nutrition(&conn, recipe_id)
-            .map_err(|e| format!("Failed to recalculate nutrition: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
     }
 
     Ok(updated)
 }
 
 /// Remove a component from a recipe
-pub fn remove_recipe_component(db: &Database, id: i64) -> Result<bool, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn remove_recipe_component(db: &Database, id: i64) -> Result<bool, ToolError> {
+    let conn = db.get_conn()?;
 
     // Get recipe_id before delete for recalculation
     let recipe_id = RecipeComponent::get_recipe_id(&conn, id)
-        .map_err(|e| format!("Failed to get recipe: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?;
 
     let deleted = RecipeComponent::delete(&conn, id)
-        .map_err(|e| format!("Failed to remove component: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to remove component: {}", e)))?;
 
     // Recalculate nutrition if delete succeeded
     if deleted {
         if let Some(recipe_id) = recipe_id {
             recalculate_recipe_nutrition(&conn, recipe_id)
-                .map_err(|e| format!("Failed to recalculate nutrition: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
         }
     }
 
     Ok(deleted)
 }
+
+// ============================================================================
+// Recipe Recommendations
+// ============================================================================
+
+/// A recipe suggestion for a remaining macro budget
+#[derive(Debug, Serialize)]
+pub struct RecipeRecommendation {
+    pub id: i64,
+    pub name: String,
+    pub is_favorite: bool,
+    pub score: f64,
+    /// True if one serving stays within the calorie and sodium limits
+    pub fits_budget: bool,
+    pub nutrition_per_serving: Nutrition,
+    pub notes: Vec<String>,
+}
+
+/// Response for recommend_recipes
+#[derive(Debug, Serialize)]
+pub struct RecommendRecipesResponse {
+    pub remaining_calories: f64,
+    pub min_protein: Option<f64>,
+    pub max_sodium: Option<f64>,
+    pub recipes_considered: usize,
+    pub recommendations: Vec<RecipeRecommendation>,
+}
+
+/// Suggest recipes whose per-serving nutrition fits a remaining budget.
+///
+/// Scoring (higher is better):
+/// - calories: up to 40 points for using the budget, minus 80 per budget-multiple exceeded
+/// - protein: 40 points at min_protein, rising to 60 at 150%
+/// - sodium: minus 50 per multiple of max_sodium exceeded
+pub fn recommend_recipes(
+    db: &Database,
+    remaining_calories: f64,
+    min_protein: Option<f64>,
+    max_sodium: Option<f64>,
+    limit: i64,
+) -> Result<RecommendRecipesResponse, ToolError> {
+    if remaining_calories <= 0.0 {
+        return Err(ToolError::Validation("remaining_calories must be greater than 0".to_string()));
+    }
+    let limit = limit.clamp(1, 50) as usize;
+
+    let conn = db.get_conn()?;
+
+    let recipes = Recipe::list(&conn, None, false, "name", "asc", 10000, 0)
+        .map_err(|e| ToolError::Db(format!("Failed to list recipes: {}", e)))?;
+
+    // Recipes with no calculated nutrition (no ingredients yet) can't be scored
+    let candidates: Vec<Recipe> = recipes
+        .into_iter()
+        .filter(|r| r.cached_nutrition.calories > 0.0)
+        .collect();
+    let recipes_considered = candidates.len();
+
+    let mut recommendations: Vec<RecipeRecommendation> = candidates
+        .into_iter()
+        .map(|recipe| {
+            let n = &recipe.cached_nutrition;
+            let mut notes = Vec::new();
+
+            let calorie_score = if n.calories <= remaining_calories {
+                40.0 * n.calories / remaining_calories
+            } else {
+                let over = (n.calories - remaining_calories) / remaining_calories;
+                notes.push(format!("Exceeds calories by {:.0}", n.calories - remaining_calories));
+                40.0 - 80.0 * over
+            };
+
+            let protein_score = match min_protein {
+                Some(target) if target > 0.0 => {
+                    if n.protein < target {
+                        notes.push(format!("{:.0}g short of protein target", target - n.protein));
+                    }
+                    60.0 * (n.protein / target).min(1.5) / 1.5
+                }
+                _ => 0.0,
+            };
+
+            let sodium_penalty = match max_sodium {
+                Some(limit) if limit > 0.0 && n.sodium > limit => {
+                    notes.push(format!("Exceeds sodium by {:.0}mg", n.sodium - limit));
+                    50.0 * (n.sodium - limit) / limit
+                }
+                _ => 0.0,
+            };
+
+            let fits_budget = n.calories <= remaining_calories
+                && max_sodium.is_none_or(|limit| n.sodium <= limit);
+
+            RecipeRecommendation {
+                id: recipe.id,
+                name: recipe.name,
+                is_favorite: recipe.is_favorite,
+                score: round_to(calorie_score + protein_score - sodium_penalty, 2),
+                fits_budget,
+                nutrition_per_serving: recipe.cached_nutrition,
+                notes,
+            }
+        })
+        .collect();
+
+    // Best fits first; favorites win ties
+    recommendations.sort_by(|a, b| {
+        b.fits_budget
+            .cmp(&a.fits_budget)
+            .then(b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal))
+            .then(b.is_favorite.cmp(&a.is_favorite))
+    });
+    recommendations.truncate(limit);
+
+    Ok(RecommendRecipesResponse {
+        remaining_calories,
+        min_protein,
+        max_sodium,
+        recipes_considered,
+        recommendations,
+    })
+}
+
+// ============================================================================
+// Recipe Lint
+// ============================================================================
+
+/// Gram quantities below this on a gram-based item are probably servings typed as grams
+const SUSPICIOUS_GRAM_QUANTITY: f64 = 5.0;
+
+/// A likely unit mistake on one recipe ingredient
+#[derive(Debug, Serialize)]
+pub struct IngredientUnitWarning {
+    pub ingredient_id: i64,
+    pub food_item_id: i64,
+    pub food_item_name: String,
+    pub quantity: f64,
+    pub unit: String,
+    /// The food item's serving, e.g. "100 g"
+    pub food_serving: String,
+    /// "tiny_quantity", "volume_on_solid", "weight_without_grams" or "unrecognized_unit"
+    pub issue: String,
+    pub message: String,
+    pub suggested_quantity: Option<f64>,
+    pub suggested_unit: Option<String>,
+}
+
+/// Response for validate_recipe_units
+#[derive(Debug, Serialize)]
+pub struct ValidateRecipeUnitsResponse {
+    pub recipe_id: i64,
+    pub recipe_name: String,
+    pub ingredients_checked: usize,
+    pub warning_count: usize,
+    pub warnings: Vec<IngredientUnitWarning>,
+}
+
+/// Check a single ingredient's unit against its food item.
+/// Returns (issue, message, suggested_quantity, suggested_unit).
+fn check_ingredient_unit(
+    quantity: f64,
+    unit: &str,
+    food: &FoodItem,
+) -> Option<(&'static str, String, Option<f64>, Option<String>)> {
+    let lower = unit.to_lowercase();
+    if matches!(lower.trim(), "serving" | "servings") {
+        return None;
+    }
+
+    let ingredient = parse_unit(unit);
+    let food_unit = parse_unit(&food.serving_unit);
+    let base_type = food
+        .base_unit_type
+        .unwrap_or_else(|| infer_base_unit_type(&food.serving_unit));
+    let serving = format!("{} {}", food.serving_size, food.serving_unit);
+
+    match ingredient.category {
+        UnitCategory::Weight => match food.grams_per_serving {
+            Some(grams_per_serving) => {
+                let grams = to_grams(quantity, unit)?;
+                if grams < SUSPICIOUS_GRAM_QUANTITY
+                    && grams_per_serving >= SUSPICIOUS_GRAM_QUANTITY * 10.0
+                {
+                    let meant = round_to(quantity * grams_per_serving, 2);
+                    return Some((
+                        "tiny_quantity",
+                        format!(
+                            "{} {} is only {:.0}% of one serving ({}); if you meant {} serving(s), use {} g",
+                            quantity, unit, grams / grams_per_serving * 100.0, serving, quantity, meant
+                        ),
+                        Some(meant),
+                        Some("g".to_string()),
+                    ));
+                }
+                None
+            }
+            None if ingredient.base_unit != food_unit.base_unit => Some((
+                "weight_without_grams",
+                format!(
+                    "Food item has no gram weight for its serving ({}), so {} is treated as servings",
+                    serving, unit
+                ),
+                Some(quantity),
+                Some("serving".to_string()),
+            )),
+            None => None,
+        },
+        UnitCategory::Volume => {
+            if ingredient.base_unit == food_unit.base_unit
+                || food.ml_per_serving.is_some()
+                || food_unit.category == UnitCategory::Volume
+            {
+                return None;
+            }
+            let suggested_unit = if base_type == BaseUnitType::Weight {
+                "g".to_string()
+            } else {
+                food_unit.base_unit.clone()
+            };
+            Some((
+                "volume_on_solid",
+                format!(
+                    "{} is a volume unit but this food is measured by {} ({}); weigh it instead",
+                    unit, base_type.to_db_str(), serving
+                ),
+                None,
+                Some(suggested_unit),
+            ))
+        }
+        UnitCategory::Count | UnitCategory::Custom => {
+            if ingredient.base_unit == food_unit.base_unit {
+                return None;
+            }
+            let meant = round_to(quantity * food.serving_size, 2);
+            Some((
+                "unrecognized_unit",
+                format!(
+                    "'{}' doesn't match the food's serving unit ({}), so it is treated as servings",
+                    unit, serving
+                ),
+                Some(meant),
+                Some(food_unit.base_unit.clone()),
+            ))
+        }
+    }
+}
+
+/// Flag recipe ingredients whose unit doesn't fit the food item's serving basis
+pub fn validate_recipe_units(db: &Database, recipe_id: i64) -> Result<Option<ValidateRecipeUnitsResponse>, ToolError> {
+    let conn = db.get_conn()?;
+
+    let recipe = match Recipe::get_by_id(&conn, recipe_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?
+    {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+
+    let ingredients = RecipeIngredient::get_for_recipe(&conn, recipe_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get ingredients: {}", e)))?;
+
+    let mut warnings = Vec::new();
+    for ingredient in &ingredients {
+        let food = match FoodItem::get_by_id(&conn, ingredient.food_item_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+        {
+            Some(f) => f,
+            None => continue,
+        };
+
+        if let Some((issue, message, suggested_quantity, suggested_unit)) =
+            check_ingredient_unit(ingredient.quantity, &ingredient.unit, &food)
+        {
+            warnings.push(IngredientUnitWarning {
+                ingredient_id: ingredient.id,
+                food_item_id: food.id,
+                food_item_name: food.name.clone(),
+                quantity: ingredient.quantity,
+                unit: ingredient.unit.clone(),
+                food_serving: format!("{} {}", food.serving_size, food.serving_unit),
+                issue: issue.to_string(),
+                message,
+                suggested_quantity,
+                suggested_unit,
+            });
+        }
+    }
+
+    Ok(Some(ValidateRecipeUnitsResponse {
+        recipe_id: recipe.id,
+        recipe_name: recipe.name,
+        ingredients_checked: ingredients.len(),
+        warning_count: warnings.len(),
+        warnings,
+    }))
+}
+
+// ============================================================================
+// Prepared Batches
+// ============================================================================
+
+/// Response for prepare_batch
+#[derive(Debug, Serialize)]
+pub struct PrepareBatchResponse {
+    pub id: i64,
+    pub recipe_id: i64,
+    pub recipe_name: String,
+    pub servings_prepared: f64,
+    pub prepared_date: String,
+}
+
+/// Response for list_prepared_batches
+#[derive(Debug, Serialize)]
+pub struct ListPreparedBatchesResponse {
+    pub batches: Vec<PreparedBatchDetail>,
+    pub count: usize,
+}
+
+/// Batch state after a portion was taken from it
+#[derive(Debug, Serialize)]
+pub struct BatchPortion {
+    pub batch_id: i64,
+    pub servings_taken: f64,
+    pub remaining_servings: f64,
+    /// Set when more was eaten than the batch had left
+    pub warning: Option<String>,
+}
+
+/// Response for log_recipe_portion
+#[derive(Debug, Serialize)]
+pub struct LogRecipePortionResponse {
+    pub meal: super::days::LogMealResponse,
+    /// None if the recipe has no open batch
+    pub batch: Option<BatchPortion>,
+}
+
+/// Record a cooked batch of a recipe (defaults to the recipe's servings_produced)
+pub fn prepare_batch(
+    db: &Database,
+    recipe_id: i64,
+    servings: Option<f64>,
+    prepared_date: Option<&str>,
+    notes: Option<String>,
+    clock: &dyn Clock,
+) -> Result<PrepareBatchResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let recipe = Recipe::get_by_id(&conn, recipe_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?
+        .ok_or_else(|| ToolError::NotFound(format!("Recipe not found with id: {}", recipe_id)))?;
+
+    let servings_prepared = servings.unwrap_or(recipe.servings_produced);
+    if servings_prepared <= 0.0 {
+        return Err(ToolError::Validation("Servings must be greater than 0".to_string()));
+    }
+
+    let prepared_date = match prepared_date {
+        Some(d) => chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| ToolError::Validation(format!("Invalid prepared_date '{}'. Use YYYY-MM-DD", d)))?
+            .format("%Y-%m-%d")
+            .to_string(),
+        None => clock.today(),
+    };
+
+    let batch = PreparedBatch::create(&conn, &PreparedBatchCreate {
+        recipe_id,
+        servings_prepared,
+        prepared_date,
+        notes,
+    }).map_err(|e| ToolError::Db(format!("Failed to create batch: {}", e)))?;
+
+    Ok(PrepareBatchResponse {
+        id: batch.id,
+        recipe_id,
+        recipe_name: recipe.name,
+        servings_prepared: batch.servings_prepared,
+        prepared_date: batch.prepared_date,
+    })
+}
+
+/// List prepared batches; finished (empty) batches only if asked
+pub fn list_prepared_batches(db: &Database, include_finished: bool) -> Result<ListPreparedBatchesResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let batches = PreparedBatch::list_details(&conn, include_finished)
+        .map_err(|e| ToolError::Db(format!("Failed to list batches: {}", e)))?;
+
+    Ok(ListPreparedBatchesResponse {
+        count: batches.len(),
+        batches,
+    })
+}
+
+/// Log servings of a recipe and take them out of a prepared batch.
+///
+/// Uses batch_id if given, otherwise the recipe's oldest batch with servings
+/// left. With no open batch the meal is still logged.
+///
+/// `meal.recipe_id` is the recipe and `meal.servings` the servings eaten.
+pub fn log_recipe_portion(
+    db: &Database,
+    meal: super::days::LogMealRequest,
+    batch_id: Option<i64>,
+    clock: &dyn Clock,
+) -> Result<LogRecipePortionResponse, ToolError> {
+    let recipe_id = meal.recipe_id
+        .ok_or_else(|| ToolError::Validation("log_recipe_portion needs a recipe_id".to_string()))?;
+    let servings_eaten = meal.servings;
+
+    // Logging the meal and consuming the portion succeed or fail together
+    db.transaction(|conn| {
+        let batch = match batch_id {
+            Some(id) => {
+                let batch = PreparedBatch::get_by_id(conn, id)
+                    .map_err(|e| ToolError::Db(format!("Failed to get batch: {}", e)))?
+                    .ok_or_else(|| ToolError::NotFound(format!("Prepared batch not found with id: {}", id)))?;
+                if batch.recipe_id != recipe_id {
+                    return Err(ToolError::Validation(format!("Prepared batch {} is not a batch of recipe {}", id, recipe_id)));
+                }
+                Some(batch)
+            }
+            None => PreparedBatch::find_open_for_recipe(conn, recipe_id)
+                .map_err(|e| ToolError::Db(format!("Failed to find batch: {}", e)))?,
+        };
+
+        let meal = super::days::log_meal_in(conn, meal, clock)?;
+
+        let batch = match batch {
+            Some(batch) => {
+                let updated = PreparedBatch::consume(conn, batch.id, servings_eaten)
+                    .map_err(|e| ToolError::Db(format!("Failed to update batch: {}", e)))?
+                    .ok_or_else(|| ToolError::NotFound("Prepared batch not found".to_string()))?;
+                let warning = if batch.remaining_servings <= 0.0 {
+                    Some(format!("Batch {} was already empty", batch.id))
+                } else if servings_eaten > batch.remaining_servings {
+                    Some(format!(
+                        "Only {} serving(s) were left in batch {}; it is now empty",
+                        batch.remaining_servings, batch.id
+                    ))
+                } else {
+                    None
+                };
+                Some(BatchPortion {
+                    batch_id: updated.id,
+                    servings_taken: batch.remaining_servings - updated.remaining_servings,
+                    remaining_servings: updated.remaining_servings,
+                    warning,
+                })
+            }
+            None => None,
+        };
+
+        Ok(LogRecipePortionResponse { meal, batch })
+    })
+}
+
+// ============================================================================
+// Recipe / Food Item Conversion
+// ============================================================================
+
+/// Response for recipe_to_food_item
+#[derive(Debug, Serialize)]
+pub struct RecipeToFoodItemResponse {
+    pub food_item_id: i64,
+    pub name: String,
+    pub serving_size: f64,
+    pub serving_unit: String,
+    pub nutrition: Nutrition,
+    pub source_recipe_id: i64,
+    pub notes: String,
+}
+
+/// Response for food_item_to_recipe
+#[derive(Debug, Serialize)]
+pub struct FoodItemToRecipeResponse {
+    pub recipe_id: i64,
+    pub name: String,
+    pub ingredient_id: i64,
+    pub source_food_item_id: i64,
+    pub nutrition_per_serving: Nutrition,
+    pub notes: String,
+}
+
+/// Create a food item from a recipe's nutrition so it can be used as a plain ingredient.
+///
+/// serving_basis "serving" stores one recipe serving as `1 count`; "100g" stores
+/// `100 g` using the batch weight (yield_grams, else the ingredient sum).
+/// The recipe itself is left unchanged.
+pub fn recipe_to_food_item(db: &Database, recipe_id: i64, serving_basis: &str) -> Result<RecipeToFoodItemResponse, ToolError> {
+    let basis = serving_basis.to_lowercase().replace(' ', "");
+    if basis != "serving" && basis != "100g" {
+        return Err(ToolError::Validation(format!("Invalid serving_basis: '{}'. Valid values: serving, 100g", serving_basis)));
+    }
+
+    let conn = db.get_conn()?;
+
+    let recipe = Recipe::get_by_id(&conn, recipe_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?
+        .ok_or_else(|| ToolError::NotFound(format!("Recipe not found with id: {}", recipe_id)))?;
+
+    let (batch_grams, batch_grams_source) = recipe_batch_grams(&conn, &recipe)?;
+    let grams_per_serving = batch_grams.filter(|g| *g > 0.0).map(|g| g / recipe.servings_produced);
+
+    let (serving_size, serving_unit, nutrition, notes) = if basis == "100g" {
+        let grams = grams_per_serving.ok_or_else(|| {
+            ToolError::Validation("Batch weight is unknown: set the recipe's yield_grams or use gram-convertible ingredient units, or use serving_basis \"serving\"".to_string())
+        })?;
+        let notes = format!(
+            "Converted from recipe #{} \"{}\": per 100 g of a {} g batch ({})",
+            recipe.id,
+            recipe.name,
+            round_to(batch_grams.unwrap_or_default(), 1),
+            batch_grams_source.unwrap_or_default()
+        );
+        (100.0, "g", recipe.cached_nutrition.scale(100.0 / grams), notes)
+    } else {
+        let notes = format!(
+            "Converted from recipe #{} \"{}\": 1 count = 1 of {} servings",
+            recipe.id, recipe.name, recipe.servings_produced
+        );
+        (1.0, "count", recipe.cached_nutrition.clone(), notes)
+    };
+
+    let n = &nutrition;
+    let data = FoodItemCreate {
+        name: recipe.name.clone(),
+        brand: None,
+        serving_size,
+        serving_unit: serving_unit.to_string(),
+        calories: round_to(n.calories, 2),
+        protein: round_to(n.protein, 2),
+        carbs: round_to(n.carbs, 2),
+        fat: round_to(n.fat, 2),
+        fiber: round_to(n.fiber, 2),
+        sodium: round_to(n.sodium, 2),
+        sugar: round_to(n.sugar, 2),
+        saturated_fat: round_to(n.saturated_fat, 2),
+        cholesterol: round_to(n.cholesterol, 2),
+        preference: Default::default(),
+        notes: Some(notes.clone()),
+        base_unit_type: None,
+        // A count item made from a weighable recipe keeps its weight so gram amounts convert
+        grams_per_serving: if basis == "serving" { grams_per_serving } else { None },
+        ml_per_serving: None,
+    };
+
+    let item = FoodItem::create(&conn, &data)
+        .map_err(|e| ToolError::Db(format!("Failed to create food item: {}", e)))?;
+
+    Ok(RecipeToFoodItemResponse {
+        food_item_id: item.id,
+        name: item.name,
+        serving_size: item.serving_size,
+        serving_unit: item.serving_unit,
+        nutrition: item.nutrition,
+        source_recipe_id: recipe.id,
+        notes,
+    })
+}
+
+/// Create a one-ingredient, one-serving recipe from a food item (one food serving,
+/// e.g. 100 g). Useful as a starting point for a recipe built around that food.
+pub fn food_item_to_recipe(db: &Database, food_item_id: i64) -> Result<FoodItemToRecipeResponse, ToolError> {
+    db.transaction(|conn| {
+        let food = FoodItem::get_by_id(conn, food_item_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Food item not found with id: {}", food_item_id)))?;
+
+        let notes = format!(
+            "Converted from food item #{} \"{}\": 1 serving = {} {}",
+            food.id, food.name, food.serving_size, food.serving_unit
+        );
+
+        let recipe = Recipe::create(conn, &RecipeCreate {
+            name: food.name.clone(),
+            servings_produced: 1.0,
+            yield_grams: food.grams_per_serving,
+            is_favorite: false,
+            notes: Some(notes.clone()),
+        })
+        .map_err(|e| ToolError::Db(format!("Failed to create recipe: {}", e)))?;
+
+        let ingredient = RecipeIngredient::create(conn, &RecipeIngredientCreate {
+            recipe_id: recipe.id,
+            food_item_id: food.id,
+            quantity: food.serving_size,
+            unit: parse_unit(&food.serving_unit).base_unit,
+            notes: None,
+        })
+        .map_err(|e| ToolError::Db(format!("Failed to add ingredient: {}", e)))?;
+
+        let nutrition = recalculate_recipe_nutrition(conn, recipe.id)
+            .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
+
+        Ok(FoodItemToRecipeResponse {
+            recipe_id: recipe.id,
+            name: recipe.name,
+            ingredient_id: ingredient.id,
+            source_food_item_id: food.id,
+            nutrition_per_serving: nutrition,
+            notes,
+        })
+    })
+}
+
+// ============================================================================
+// Recipe Export / Import
+// ============================================================================
+
+/// Version of the export_recipe JSON layout
+const RECIPE_EXPORT_VERSION: u32 = 1;
+
+/// Self-contained recipe document written by export_recipe (JSON format)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecipeExport {
+    pub format_version: u32,
+    pub recipe: ExportedRecipe,
+}
+
+/// A recipe with its ingredients' food items and nested component recipes inlined
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedRecipe {
+    pub name: String,
+    pub servings_produced: f64,
+    #[serde(default)]
+    pub yield_grams: Option<f64>,
+    pub notes: Option<String>,
+    /// Informational only; recalculated from ingredients on import
+    pub nutrition_per_serving: Nutrition,
+    pub ingredients: Vec<ExportedIngredient>,
+    #[serde(default)]
+    pub components: Vec<ExportedComponent>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedIngredient {
+    pub quantity: f64,
+    pub unit: String,
+    pub notes: Option<String>,
+    pub food_item: FoodItemCreate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedComponent {
+    pub servings: f64,
+    pub notes: Option<String>,
+    pub recipe: ExportedRecipe,
+}
+
+/// Response for export_recipe
+#[derive(Debug, Serialize)]
+pub struct ExportRecipeResponse {
+    pub recipe_id: i64,
+    pub name: String,
+    pub format: String,
+    pub file_path: String,
+}
+
+/// Response for import_recipe
+#[derive(Debug, Serialize)]
+pub struct ImportRecipeResponse {
+    pub file_path: String,
+    pub recipe_id: i64,
+    pub name: String,
+    pub food_items_created: usize,
+    pub food_items_reused: usize,
+    pub component_recipes_created: usize,
+    pub component_recipes_reused: usize,
+}
+
+/// Build the export structure for a recipe, inlining components recursively
+fn build_exported_recipe(conn: &rusqlite::Connection, recipe: Recipe) -> Result<ExportedRecipe, ToolError> {
+    let ingredient_rows = RecipeIngredient::get_for_recipe(conn, recipe.id)
+        .map_err(|e| ToolError::Db(format!("Failed to get ingredients: {}", e)))?;
+
+    let mut ingredients = Vec::with_capacity(ingredient_rows.len());
+    for ing in ingredient_rows {
+        let item = FoodItem::get_by_id(conn, ing.food_item_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Food item not found with id: {}", ing.food_item_id)))?;
+        ingredients.push(ExportedIngredient {
+            quantity: ing.quantity,
+            unit: ing.unit,
+            notes: ing.notes,
+            food_item: FoodItemCreate::from(&item),
+        });
+    }
+
+    let component_rows = RecipeComponent::get_for_recipe(conn, recipe.id)
+        .map_err(|e| ToolError::Db(format!("Failed to get components: {}", e)))?;
+
+    let mut components = Vec::with_capacity(component_rows.len());
+    for comp in component_rows {
+        let sub = Recipe::get_by_id(conn, comp.component_recipe_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get component recipe: {}", e)))?
+            .ok_or_else(|| ToolError::NotFound(format!("Recipe not found with id: {}", comp.component_recipe_id)))?;
+        components.push(ExportedComponent {
+            servings: comp.servings,
+            notes: comp.notes,
+            recipe: build_exported_recipe(conn, sub)?,
+        });
+    }
+
+    Ok(ExportedRecipe {
+        name: recipe.name,
+        servings_produced: recipe.servings_produced,
+        yield_grams: recipe.yield_grams,
+        notes: recipe.notes,
+        nutrition_per_serving: recipe.cached_nutrition,
+        ingredients,
+        components,
+    })
+}
+
+/// Render an exported recipe as a shareable markdown document
+fn recipe_markdown(recipe: &ExportedRecipe) -> String {
+    let mut md = String::new();
+
+    md.push_str(&format!("# {}\n\n", recipe.name));
+    md.push_str(&format!("**Servings:** {}\n\n", recipe.servings_produced));
+    if let Some(grams) = recipe.yield_grams {
+        md.push_str(&format!("**Cooked yield:** {} g\n\n", grams));
+    }
+
+    md.push_str("## Ingredients\n\n");
+    if recipe.ingredients.is_empty() {
+        md.push_str("_None_\n");
+    }
+    for ing in &recipe.ingredients {
+        let brand = ing.food_item.brand.as_deref().map(|b| format!(" ({})", b)).unwrap_or_default();
+        md.push_str(&format!("- {} {} {}{}", ing.quantity, ing.unit, ing.food_item.name, brand));
+        if let Some(ref notes) = ing.notes {
+            md.push_str(&format!(" - {}", notes));
+        }
+        md.push('\n');
+    }
+    md.push('\n');
+
+    if !recipe.components.is_empty() {
+        md.push_str("## Components\n\n");
+        for comp in &recipe.components {
+            md.push_str(&format!("- {} serving(s) of {}", comp.servings, comp.recipe.name));
+            if let Some(ref notes) = comp.notes {
+                md.push_str(&format!(" - {}", notes));
+            }
+            md.push('\n');
+        }
+        md.push('\n');
+    }
+
+    let n = &recipe.nutrition_per_serving;
+    md.push_str("## Nutrition per Serving\n\n");
+    md.push_str("| Nutrient | Amount |\n|---|---|\n");
+    md.push_str(&format!("| Calories | {} kcal |\n", round_to(n.calories, 1)));
+    md.push_str(&format!("| Protein | {} g |\n", round_to(n.protein, 1)));
+    md.push_str(&format!("| Carbs | {} g |\n", round_to(n.carbs, 1)));
+    md.push_str(&format!("| Fat | {} g |\n", round_to(n.fat, 1)));
+    md.push_str(&format!("| Saturated Fat | {} g |\n", round_to(n.saturated_fat, 1)));
+    md.push_str(&format!("| Fiber | {} g |\n", round_to(n.fiber, 1)));
+    md.push_str(&format!("| Sugar | {} g |\n", round_to(n.sugar, 1)));
+    md.push_str(&format!("| Sodium | {} mg |\n", round_to(n.sodium, 1)));
+    md.push_str(&format!("| Cholesterol | {} mg |\n", round_to(n.cholesterol, 1)));
+
+    if let Some(ref notes) = recipe.notes {
+        md.push_str(&format!("\n## Notes\n\n{}\n", notes));
+    }
+
+    for comp in &recipe.components {
+        md.push_str("\n---\n\n");
+        md.push_str(&recipe_markdown(&comp.recipe).replacen("# ", "## Component: ", 1));
+    }
+
+    md
+}
+
+/// Export a recipe to a markdown or JSON file
+///
+/// The JSON form inlines every food item and component recipe so import_recipe
+/// can recreate it in another database.
+pub fn export_recipe(
+    db: &Database,
+    recipe_id: i64,
+    format: &str,
+    output_path: &str,
+) -> Result<ExportRecipeResponse, ToolError> {
+    let format = format.to_lowercase();
+    if format != "markdown" && format != "json" {
+        return Err(ToolError::Validation(format!("Invalid format: '{}'. Valid formats: markdown, json", format)));
+    }
+
+    let conn = db.get_conn()?;
+
+    let recipe = Recipe::get_by_id(&conn, recipe_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?
+        .ok_or_else(|| ToolError::NotFound(format!("Recipe not found with id: {}", recipe_id)))?;
+    let name = recipe.name.clone();
+    let exported = build_exported_recipe(&conn, recipe)?;
+
+    let contents = if format == "json" {
+        let doc = RecipeExport {
+            format_version: RECIPE_EXPORT_VERSION,
+            recipe: exported,
+        };
+        serde_json::to_string_pretty(&doc).map_err(|e| ToolError::Io(format!("Serialization error: {}", e)))?
+    } else {
+        recipe_markdown(&exported)
+    };
+
+    std::fs::write(output_path, contents)
+        .map_err(|e| ToolError::Io(format!("Failed to write file '{}': {}", output_path, e)))?;
+
+    Ok(ExportRecipeResponse {
+        recipe_id,
+        name,
+        format,
+        file_path: output_path.to_string(),
+    })
+}
+
+/// Recreate an exported recipe, reusing food items (by name + brand) and
+/// component recipes (by name) that already exist. Returns the new recipe id.
+fn import_exported_recipe(
+    conn: &rusqlite::Connection,
+    recipe: &ExportedRecipe,
+    counts: &mut ImportRecipeResponse,
+) -> Result<i64, ToolError> {
+    use super::food_items::validate_food_item;
+
+    let name = recipe.name.trim();
+    if name.is_empty() {
+        return Err(ToolError::Validation("Recipe name cannot be empty".to_string()));
+    }
+    if recipe.servings_produced <= 0.0 {
+        return Err(ToolError::Validation(format!("Recipe '{}': servings_produced must be greater than 0", name)));
+    }
+
+    let created = Recipe::create(conn, &RecipeCreate {
+        name: name.to_string(),
+        servings_produced: recipe.servings_produced,
+        yield_grams: recipe.yield_grams,
+        is_favorite: false,
+        notes: recipe.notes.clone(),
+    }).map_err(|e| ToolError::Db(format!("Failed to create recipe '{}': {}", name, e)))?;
+
+    for ing in &recipe.ingredients {
+        if ing.quantity <= 0.0 {
+            return Err(ToolError::Validation(format!(
+                "Recipe '{}': quantity for '{}' must be greater than 0",
+                name, ing.food_item.name
+            )));
+        }
+        validate_food_item(&ing.food_item)?;
+
+        let existing = FoodItem::find_by_name_brand(conn, &ing.food_item.name, ing.food_item.brand.as_deref())?;
+        let food_item_id = match existing {
+            Some(item) => {
+                counts.food_items_reused += 1;
+                item.id
+            }
+            None => {
+                counts.food_items_created += 1;
+                FoodItem::create(conn, &ing.food_item)
+                    .map_err(|e| ToolError::Db(format!("Failed to create food item '{}': {}", ing.food_item.name, e)))?
+                    .id
+            }
+        };
+
+        RecipeIngredient::create(conn, &RecipeIngredientCreate {
+            recipe_id: created.id,
+            food_item_id,
+            quantity: ing.quantity,
+            unit: ing.unit.clone(),
+            notes: ing.notes.clone(),
+        }).map_err(|e| ToolError::Db(format!("Failed to add ingredient '{}': {}", ing.food_item.name, e)))?;
+    }
+
+    for comp in &recipe.components {
+        if comp.servings <= 0.0 {
+            return Err(ToolError::Validation(format!(
+                "Recipe '{}': servings of component '{}' must be greater than 0",
+                name, comp.recipe.name
+            )));
+        }
+
+        let existing = Recipe::find_by_name(conn, comp.recipe.name.trim())?;
+        let component_recipe_id = match existing {
+            Some(r) => {
+                counts.component_recipes_reused += 1;
+                r.id
+            }
+            None => {
+                counts.component_recipes_created += 1;
+                import_exported_recipe(conn, &comp.recipe, counts)?
+            }
+        };
+
+        RecipeComponent::create(conn, &RecipeComponentCreate {
+            recipe_id: created.id,
+            component_recipe_id,
+            servings: comp.servings,
+            notes: comp.notes.clone(),
+        }).map_err(|e| ToolError::Db(format!("Failed to add component '{}': {}", comp.recipe.name, e)))?;
+    }
+
+    recalculate_recipe_nutrition(conn, created.id)
+        .map_err(|e| ToolError::Db(format!("Failed to recalculate nutrition: {}", e)))?;
+
+    Ok(created.id)
+}
+
+/// Import a recipe from an export_recipe JSON file
+///
+/// Runs in a single transaction: either the whole recipe tree is created or nothing is.
+pub fn import_recipe(db: &Database, file_path: &str) -> Result<ImportRecipeResponse, ToolError> {
+    let contents = std::fs::read_to_string(file_path)
+        .map_err(|e| ToolError::Io(format!("Failed to read file '{}': {}", file_path, e)))?;
+    let doc: RecipeExport = serde_json::from_str(&contents)
+        .map_err(|e| ToolError::Validation(format!("Not a valid export_recipe JSON file: {}", e)))?;
+    if doc.format_version > RECIPE_EXPORT_VERSION {
+        return Err(ToolError::Validation(format!(
+            "Unsupported format_version {} (this server reads up to {})",
+            doc.format_version, RECIPE_EXPORT_VERSION
+        )));
+    }
+
+    db.transaction(|tx| {
+        let existing = Recipe::find_by_name(tx, doc.recipe.name.trim())?;
+        if let Some(r) = existing {
+            return Err(ToolError::Validation(format!("A recipe named '{}' already exists (id {})", r.name, r.id)));
+        }
+
+        let mut response = ImportRecipeResponse {
+            file_path: file_path.to_string(),
+            recipe_id: 0,
+            name: doc.recipe.name.trim().to_string(),
+            food_items_created: 0,
+            food_items_reused: 0,
+            component_recipes_created: 0,
+            component_recipes_reused: 0,
+        };
+        response.recipe_id = import_exported_recipe(tx, &doc.recipe, &mut response)?;
+        Ok(response)
+    })
+}
+
+// ============================================================================
+// Nutrition Calculator
+// ============================================================================
+
+/// One food item or recipe in a combine_nutrition calculation
+#[derive(Debug, Clone)]
+pub struct CombineItem {
+    pub food_item_id: Option<i64>,
+    pub recipe_id: Option<i64>,
+    pub quantity: f64,
+    pub unit: String,
+}
+
+/// Scaled nutrition of one item in a combination
+#[derive(Debug, Serialize)]
+pub struct CombinedItem {
+    pub source_type: String,
+    pub source_id: i64,
+    pub name: String,
+    pub quantity: f64,
+    pub unit: String,
+    /// Servings of the source the quantity works out to
+    pub servings: f64,
+    pub nutrition: Nutrition,
+}
+
+/// Response for combine_nutrition
+#[derive(Debug, Serialize)]
+pub struct CombineNutritionResponse {
+    pub items: Vec<CombinedItem>,
+    pub total: Nutrition,
+}
+
+/// Servings of a recipe in `quantity` `unit`: servings directly, or a weight
+/// divided by the recipe's per-serving batch weight
+fn recipe_servings(conn: &rusqlite::Connection, recipe: &Recipe, quantity: f64, unit: &str) -> Result<f64, ToolError> {
+    let unit = unit.trim();
+    if unit.eq_ignore_ascii_case("serving") || unit.eq_ignore_ascii_case("servings") {
+        return Ok(quantity);
+    }
+    let grams = to_grams(quantity, unit).ok_or_else(|| {
+        ToolError::Validation(format!("Recipe '{}' can only be measured in servings or a weight unit, not '{}'", recipe.name, unit))
+    })?;
+    let (batch_grams, _) = recipe_batch_grams(conn, recipe)?;
+    match batch_grams.filter(|g| *g > 0.0) {
+        Some(batch) => Ok(grams / (batch / recipe.servings_produced)),
+        None => Err(ToolError::Validation(format!(
+            "Batch weight of recipe '{}' is unknown: set its yield_grams or use servings",
+            recipe.name
+        ))),
+    }
+}
+
+/// Sum the nutrition of an ad-hoc combination of food items and recipes
+/// without saving anything, for "what if" questions
+pub fn combine_nutrition(db: &Database, items: &[CombineItem]) -> Result<CombineNutritionResponse, ToolError> {
+    if items.is_empty() {
+        return Err(ToolError::Validation("At least one item is required".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let mut combined = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        if item.quantity <= 0.0 || !item.quantity.is_finite() {
+            return Err(ToolError::Validation(format!("Item {}: quantity must be greater than 0", i + 1)));
+        }
+
+        let (source_type, source_id, name, servings, per_serving) = match (item.food_item_id, item.recipe_id) {
+            (Some(fid), None) => {
+                let food = FoodItem::get_by_id(&conn, fid)
+                    .map_err(|e| ToolError::Db(format!("Failed to get food item: {}", e)))?
+                    .ok_or_else(|| ToolError::NotFound(format!("Item {}: Food item not found with id: {}", i + 1, fid)))?;
+                let servings = calculate_nutrition_multiplier(
+                    item.quantity, &item.unit, food.serving_size, &food.serving_unit,
+                    food.grams_per_serving, food.ml_per_serving,
+                );
+                ("food_item", fid, food.name, servings, food.nutrition)
+            }
+            (None, Some(rid)) => {
+                let recipe = Recipe::get_by_id(&conn, rid)
+                    .map_err(|e| ToolError::Db(format!("Failed to get recipe: {}", e)))?
+                    .ok_or_else(|| ToolError::NotFound(format!("Item {}: Recipe not found with id: {}", i + 1, rid)))?;
+                let servings = recipe_servings(&conn, &recipe, item.quantity, &item.unit)?;
+                ("recipe", rid, recipe.name, servings, recipe.cached_nutrition)
+            }
+            _ => {
+                return Err(ToolError::Validation(format!(
+                    "Item {}: provide exactly one of food_item_id or recipe_id",
+                    i + 1
+                )))
+            }
+        };
+
+        combined.push(CombinedItem {
+            source_type: source_type.to_string(),
+            source_id,
+            name,
+            quantity: item.quantity,
+            unit: item.unit.clone(),
+            servings: round_to(servings, 3),
+            nutrition: per_serving.scale(servings),
+        });
+    }
+
+    let total = combined.iter().map(|c| c.nutrition.clone()).sum();
+
+    Ok(CombineNutritionResponse { items: combined, total })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{make_food, temp_db};
+
+    #[test]
+    fn test_add_recipe_component_nesting_limit() {
+        let db = temp_db("nesting");
+
+        let ids: Vec<i64> = (0..7)
+            .map(|i| create_recipe(&db, RecipeCreate {
+                name: format!("Level {}", i),
+                servings_produced: 1.0,
+                yield_grams: None,
+                is_favorite: false,
+                notes: None,
+            }).unwrap().id)
+            .collect();
+        let add = |recipe_id: i64, component_recipe_id: i64| add_recipe_component(&db, RecipeComponentCreate {
+            recipe_id,
+            component_recipe_id,
+            servings: 1.0,
+            notes: None,
+        });
+
+        // Level 0 through Level 5 is a legal 5-deep chain
+        for pair in ids[..6].windows(2) {
+            add(pair[0], pair[1]).unwrap();
+        }
+        let depth = recipe_nesting_depth(&db, ids[0]).unwrap();
+        assert_eq!(depth.depth, DEFAULT_MAX_NESTING_DEPTH);
+        assert_eq!(depth.deepest_chain.len(), 6);
+        assert_eq!(depth.deepest_chain[5].name, "Level 5");
+
+        // A sixth level is rejected, whether added at the bottom or above the top
+        assert!(matches!(add(ids[5], ids[6]), Err(ToolError::Validation(_))));
+        assert!(matches!(add(ids[6], ids[0]), Err(ToolError::Validation(_))));
+        assert_eq!(recipe_nesting_depth(&db, ids[0]).unwrap().depth, 5);
+    }
+
+    #[test]
+    fn test_add_recipe_ingredients_batch_skips_repeat_in_batch() {
+        let db = temp_db("batch-dup");
+
+        let oats = db.with_conn(|conn| Ok(make_food(conn, "Oats", serde_json::json!({
+            "serving_size": 40.0,
+            "calories": 150.0,
+            "protein": 5.0,
+            "carbs": 27.0,
+            "fat": 3.0
+        })))).unwrap();
+        let recipe = create_recipe(&db, RecipeCreate {
+            name: "Porridge".to_string(),
+            servings_produced: 1.0,
+            yield_grams: None,
+            is_favorite: false,
+            notes: None,
+        }).unwrap();
+
+        let item = |quantity: f64| BatchIngredient {
+            food_item_id: oats.id,
+            quantity,
+            unit: "g".to_string(),
+            notes: None,
+        };
+        let result = add_recipe_ingredients_batch(&db, recipe.id, vec![item(40.0), item(80.0)]).unwrap();
+        assert_eq!((result.successful, result.failed), (1, 1));
+        assert!(result.results[0].success);
+        assert_eq!(result.results[1].error.as_deref(), Some("Food item already exists in recipe"));
+        assert_eq!(result.nutrition_per_serving.calories, 150.0);
+    }
+
+    #[test]
+    fn test_combine_nutrition() {
+        let db = temp_db("combine-nutrition");
+        let (oats, bowl, loose) = db.with_conn(|conn| {
+            let oats = make_food(conn, "Oats", serde_json::json!({ "calories": 380.0, "protein": 13.0 }));
+            let recipe = |name: &str, yield_grams: Option<f64>| Recipe::create(conn, &RecipeCreate {
+                name: name.to_string(), servings_produced: 2.0, yield_grams, is_favorite: false, notes: None,
+            });
+            // 200 g of oats in a 400 g batch of two servings: 380 kcal and 200 g per serving
+            let bowl = recipe("Bowl", Some(400.0))?;
+            // No yield, and an ingredient with no known weight
+            let loose = recipe("Loose", None)?;
+            let egg = make_food(conn, "Egg", serde_json::json!({ "serving_size": 1.0, "serving_unit": "count", "calories": 70.0 }));
+            for (recipe_id, food_item_id, quantity, unit) in [(bowl.id, oats.id, 200.0, "g"), (loose.id, egg.id, 2.0, "count")] {
+                RecipeIngredient::create(conn, &RecipeIngredientCreate {
+                    recipe_id, food_item_id, quantity, unit: unit.to_string(), notes: None,
+                })?;
+                recalculate_recipe_nutrition(conn, recipe_id)?;
+            }
+            Ok((oats.id, bowl.id, loose.id))
+        }).unwrap();
+
+        let food = |quantity: f64, unit: &str| CombineItem { food_item_id: Some(oats), recipe_id: None, quantity, unit: unit.to_string() };
+        let recipe = |id: i64, quantity: f64, unit: &str| CombineItem { food_item_id: None, recipe_id: Some(id), quantity, unit: unit.to_string() };
+
+        let result = combine_nutrition(&db, &[food(50.0, "g"), recipe(bowl, 1.0, "serving"), recipe(bowl, 100.0, "g")]).unwrap();
+        let servings: Vec<(&str, f64, f64)> = result.items
+            .iter()
+            .map(|i| (i.source_type.as_str(), i.servings, i.nutrition.calories))
+            .collect();
+        assert_eq!(servings, vec![("food_item", 0.5, 190.0), ("recipe", 1.0, 380.0), ("recipe", 0.5, 190.0)]);
+        assert_eq!(result.total.calories, 760.0);
+        assert_eq!(result.total.protein, 26.0);
+
+        let validation = |items: &[CombineItem]| matches!(combine_nutrition(&db, items), Err(ToolError::Validation(_)));
+        assert!(validation(&[]));
+        assert!(validation(&[food(0.0, "g")]));
+        assert!(validation(&[CombineItem { food_item_id: Some(oats), recipe_id: Some(bowl), quantity: 1.0, unit: "g".to_string() }]));
+        // Recipes are measured in servings or by weight, and weight needs a known batch weight
+        assert!(validation(&[recipe(bowl, 1.0, "cup")]));
+        assert!(validation(&[recipe(loose, 100.0, "g")]));
+        assert!(combine_nutrition(&db, &[recipe(loose, 1.0, "servings")]).is_ok());
+
+        assert!(matches!(combine_nutrition(&db, &[recipe(bowl + 100, 1.0, "serving")]), Err(ToolError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_notes_grams_per_ml() {
+        let flour = notes_grams_per_ml("sifted, 1 cup = 120 g").unwrap();
+        assert!((flour - 120.0 / 236.588).abs() < 1e-9);
+        assert_eq!(notes_grams_per_ml("240g ≈ 1 cup"), notes_grams_per_ml("1 cup = 240 g"));
+        assert_eq!(notes_grams_per_ml("chopped"), None);
+        assert_eq!(notes_grams_per_ml("1 cup = 2 slices"), None);
+    }
+
+    #[test]
+    fn test_convert_volume_amount() {
+        // Liquids stored per ml need no density
+        assert_eq!(convert_volume_amount(2.0, "cup", None, Some(BaseUnitType::Volume), None), Some((473.2, "ml", "none")));
+        // Unit annotation, then notes, then the food item
+        assert_eq!(convert_volume_amount(2.0, "cup (120g)", None, Some(BaseUnitType::Weight), Some(0.5)), Some((240.0, "g", "unit_annotation")));
+        assert_eq!(convert_volume_amount(2.0, "cup", Some("1 cup = 120 g"), Some(BaseUnitType::Weight), Some(0.5)), Some((240.0, "g", "ingredient_notes")));
+        assert_eq!(convert_volume_amount(1.0, "tbsp", None, Some(BaseUnitType::Weight), Some(2.0)), Some((29.6, "g", "food_item")));
+        assert_eq!(convert_volume_amount(1.0, "cup", Some("chopped"), Some(BaseUnitType::Weight), None), None);
+    }
+
+    #[test]
+    fn test_friendly_volume() {
+        let flour = 120.0 / 236.588;
+        assert_eq!(friendly_volume(320.0, flour), "320 g ≈ 2.75 cups");
+        assert_eq!(friendly_volume(120.0, flour), "120 g ≈ 1 cup");
+        assert_eq!(friendly_volume(15.0, 1.0), "15 g ≈ 1 tbsp");
+        assert_eq!(friendly_volume(5.0, 1.0), "5 g ≈ 1 tsp");
+    }
+}
@@ -0,0 +1,106 @@
+//! Health Snapshot MCP Tool
+//!
+//! One-call dashboard overview composed from the vitals, days, and medication tools.
+
+use serde::Serialize;
+
+use super::days::{day_budget_status, DayBudgetStatusResponse};
+use super::error::ToolError;
+use super::medications::list_medications;
+use super::vitals::{detect_weight_plateau, get_latest_vitals, VitalSummary};
+use crate::clock::Clock;
+use crate::db::Database;
+
+/// Days of weight readings the trend is fitted over
+const TREND_WINDOW_DAYS: i64 = 14;
+
+/// Weekly change (lbs) below which weight counts as stable
+const STABLE_LBS_PER_WEEK: f64 = 0.25;
+
+/// Direction of recent weight change
+#[derive(Debug, Serialize)]
+pub struct WeightTrend {
+    /// "up", "down", or "stable"
+    pub direction: String,
+    pub slope_lbs_per_week: f64,
+    pub readings: usize,
+    pub plateaued: bool,
+}
+
+/// Response for health_snapshot
+#[derive(Debug, Serialize)]
+pub struct HealthSnapshotResponse {
+    pub date: String,
+    pub as_of: String,
+    /// Latest reading of each vital type
+    pub latest_vitals: Vec<VitalSummary>,
+    /// Today's nutrition against goals
+    pub today: Option<DayBudgetStatusResponse>,
+    pub active_medication_count: Option<i64>,
+    /// None until there are enough weight readings in the trend window
+    pub weight_trend: Option<WeightTrend>,
+    /// Sections that could not be built; the rest of the snapshot is still returned
+    pub errors: Vec<String>,
+}
+
+/// Latest vitals, today's nutrition vs goals, active medications and weight trend in one response.
+///
+/// Each section is independent: a failure is recorded in `errors` and that section left empty.
+/// Exercise is not tracked yet, so there is no exercise calories section.
+pub fn health_snapshot(
+    db: &Database,
+    clock: &dyn Clock,
+    calorie_goal: f64,
+    protein_goal: f64,
+    sodium_limit: f64,
+) -> Result<HealthSnapshotResponse, ToolError> {
+    let date = clock.today();
+    let mut errors = Vec::new();
+
+    let latest_vitals = match get_latest_vitals(db, clock) {
+        Ok(latest) => latest.vitals,
+        Err(e) => {
+            errors.push(format!("latest_vitals: {}", e));
+            Vec::new()
+        }
+    };
+
+    let today = day_budget_status(db, &date, calorie_goal, protein_goal, sodium_limit)
+        .map_err(|e| errors.push(format!("today: {}", e)))
+        .ok();
+
+    let active_medication_count = list_medications(db, true, None, clock)
+        .map(|meds| meds.active_count)
+        .map_err(|e| errors.push(format!("medications: {}", e)))
+        .ok();
+
+    let weight_trend = match detect_weight_plateau(db, TREND_WINDOW_DAYS, 2.0) {
+        Ok(plateau) => plateau.slope_lbs_per_week.map(|slope| WeightTrend {
+            direction: if slope >= STABLE_LBS_PER_WEEK {
+                "up"
+            } else if slope <= -STABLE_LBS_PER_WEEK {
+                "down"
+            } else {
+                "stable"
+            }
+            .to_string(),
+            slope_lbs_per_week: slope,
+            readings: plateau.readings,
+            plateaued: plateau.plateaued,
+        }),
+        Err(e) => {
+            errors.push(format!("weight_trend: {}", e));
+            None
+        }
+    };
+
+    Ok(HealthSnapshotResponse {
+        date,
+        as_of: clock.timestamp(),
+        latest_vitals,
+        today,
+        active_medication_count,
+        weight_trend,
+        errors,
+    })
+}
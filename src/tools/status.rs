@@ -8,6 +8,7 @@ use std::time::Instant;
 use sysinfo::{Pid, ProcessesToUpdate, System};
 
 use crate::build_info::BuildInfo;
+use crate::tools::vitals::DataFreshness;
 
 /// Meal logging instructions for AI assistants
 pub const MEAL_INSTRUCTIONS: &str = r#"
@@ -26,7 +27,9 @@ To log a meal, you need:
 
 ## Getting the Current Date
 
-**IMPORTANT:** When logging meals for "today" or any relative date, use the UCM (Universal Calendar Manager) MCP server to get accurate dates.
+**Shortcut:** day and meal tools accept `"today"`, `"yesterday"`, or `"-N"` (N days ago, e.g. `"-3"`) in place of a YYYY-MM-DD date, resolved against the server's clock. Use these for the common cases.
+
+**IMPORTANT:** For any other relative date ("last Tuesday", "the 3rd"), use the UCM (Universal Calendar Manager) MCP server to get accurate dates.
 
 **Tool:** `ucm_now`
 - Returns the current date and time in ISO format
@@ -138,6 +141,8 @@ Most packages show nutrition "per serving" (e.g., "per 2 tbsp (32g)"). Convert t
 3. Avoid "Branded Foods" unless specifically looking up a branded product
 4. Note the serving size and unit provided, then normalize to canonical units (per 100g for solids, per 100ml for liquids)
 
+**Or import directly:** download the food's JSON from FDC and call `import_usda_food` with the file path. It creates a per-100g food item from the Foundation Foods / SR Legacy nutrients.
+
 **Data types in order of reliability:**
 1. **Foundation Foods** - Analytically derived, most accurate
 2. **SR Legacy** (Standard Reference) - USDA's historical reference database
@@ -285,6 +290,12 @@ This helps when:
 - Debugging calculation errors
 - Adjusting recipes (e.g., "I want to use 6 scoops instead")
 
+### Checking a Recipe for Unit Mistakes
+
+Run `lint_recipe(recipe_id)` after building a recipe. It flags volume units on solid
+foods, units the food can't convert (silently treated as servings), and tiny gram
+quantities like `quantity: 1, unit: "g"` on a per-100g item, with a suggested fix for each.
+
 ---
 
 ## Step-by-Step Workflow
@@ -436,6 +447,20 @@ log_meal(
 )
 ```
 
+For a single food, `quick_log(food_name: "Egg", quantity: 2, unit: "count", meal_type: "breakfast")`
+finds the item by name and logs it in one call. If the item doesn't exist yet, pass
+`nutrition_if_new` with a canonical serving (100 g, 100 ml, or 1 count) to create it.
+
+### Importing a Cronometer History
+
+`import_cronometer_csv(file_path)` reads a Cronometer "Servings" export: each row
+is logged under its group (Snacks map to snack), reusing a food item with the same
+name or creating one from the row's amount and nutrition. Created items are stored
+per 100 g, 100 ml or 1 count; a new food measured in anything else (e.g. "large
+egg") is skipped and reported, so add it by hand first. A "Daily
+Summary" export has no foods and logs one ad-hoc entry per day instead.
+Re-importing an overlapping export skips entries already logged.
+
 ---
 
 ## Fixing Existing Recipe Ingredients
@@ -457,6 +482,13 @@ Then recalculate the recipe:
 recalculate_recipe_nutrition(recipe_id: 6)
 ```
 
+### Finding Bad Food Item Data
+
+`lint_food_items()` scans every food item for data-entry errors: negative values,
+more than ~9 kcal per gram (e.g. 9000 kcal per 100 g), or protein + carbs + fat
+heavier than the serving. Fix each with `update_food_item`; the change cascades
+to recipes and meals.
+
 ---
 
 ## Quick Conversion Checklist
@@ -532,6 +564,7 @@ Six months later, you won't remember if "248g" was 8 scoops or something else.
 | Add sub-recipe to recipe | `add_recipe_component` |
 | View recipe with nutrition | `get_recipe` |
 | Log meal to day | `log_meal` |
+| Log one-off food without a food item | `log_adhoc_meal` |
 | View day's meals | `get_day` |
 | List recent days | `list_days` |
 | Get nutrition statistics | `list_days_stats` |
@@ -545,6 +578,12 @@ Six months later, you won't remember if "248g" was 8 scoops or something else.
 2. `add_food_item(...)` - Add if needed
 3. `log_meal(date, "snack", food_item_id, servings)` - Log it
 
+### Logging a one-off restaurant meal
+Don't create a food item you'll never use again:
+```
+log_adhoc_meal(date, "dinner", description: "Pad thai at Thai Palace", calories: 950, protein: 35, carbs: 120, fat: 32, sodium: 2100)
+```
+
 ### Logging a homemade recipe
 1. Search/add all ingredients as food items
 2. `create_recipe(...)` - Create the recipe
@@ -552,6 +591,20 @@ Six months later, you won't remember if "248g" was 8 scoops or something else.
 4. `get_recipe(id)` - Verify nutrition looks correct
 5. `log_meal(date, meal_type, recipe_id, servings)` - Log it
 
+### Recording cooked yield
+Cooking changes weight (rice absorbs water, meat and sauces lose it), so the raw ingredient
+sum is a poor basis for per-100g values. Weigh the finished batch and record it:
+```
+update_recipe(id: 5, yield_grams: 1850)
+```
+`get_recipe` then reports `nutrition_per_100g` from the cooked weight (`batch_grams_source: "yield_grams"`).
+
+### Turning a recipe into an ingredient
+A sauce or spice mix used in many recipes can become a plain food item:
+`recipe_to_food_item(recipe_id: 12, serving_basis: "100g")` (or `"serving"` for 1 count per
+recipe serving). The reverse, `food_item_to_recipe(food_item_id: 40)`, makes a
+one-ingredient recipe to build on.
+
 ### Using recipe components (sub-recipes)
 Example: Creating a "Burrito Bowl" that uses a "Rice" sub-recipe:
 1. Create the rice recipe first with its ingredients
@@ -587,7 +640,9 @@ Returns for each nutrient (calories, protein, carbs, fat, fiber, sugar, sodium,
 - **min / max / range** - Lowest, highest, and spread
 - **percentile_25 / percentile_75 / iqr** - Quartiles and interquartile range
 - **coefficient_of_variation** - Relative variability (SD/mean × 100)
-- **outliers** - Days outside 1 standard deviation (with date, value, z-score)
+- **outliers** - Days more than `outlier_sd` standard deviations from the mean (with date, value, z-score). Defaults to 2; pass `outlier_sd: 1` for the old, more sensitive behavior
+
+Pass `by_meal_type: true` to add a `meal_types` block: calorie and protein stats per breakfast/lunch/dinner/snack, plus each type's `average_calories_per_day` and `percent_of_calories` (e.g. "snacks are 45% of my calories"). `list_days` takes the same flag for a per-day breakdown.
 
 **Why use this?** Much faster than fetching raw data and calculating in Claude Desktop. A single tool call returns all statistics instantly.
 
@@ -820,6 +875,29 @@ add_medication(
 )
 ```
 
+### Tapering Schedules
+
+For planned dose changes (e.g., a prednisone taper), record the steps once with `schedule` instead of deprecating and re-adding for every step:
+
+```
+add_medication(
+  name: "Prednisone",
+  med_type: "prescription",
+  dosage_amount: 40,
+  dosage_unit: "mg",
+  schedule: [
+    { start_date: "2026-02-01", dosage_amount: 40 },
+    { start_date: "2026-02-04", dosage_amount: 30 },
+    { start_date: "2026-02-07", dosage_amount: 20 },
+    { start_date: "2026-02-10", dosage_amount: 10 }
+  ]
+)
+```
+
+- Dates must be in increasing order
+- `get_medication` returns `current_dose` resolved for today
+- `export_medications_markdown` lists today's dose and the full taper
+
 ### Stopping a Medication
 
 ```
@@ -847,6 +925,10 @@ Returns a formatted markdown document with:
 - Medications grouped by type (prescriptions first)
 - Full details: dosage, frequency, doctor, pharmacy, instructions
 
+patient_name is required for a document going to a provider. For a quick personal
+copy, `export_medications_markdown(allow_missing_patient: true)` labels it
+"Unknown Patient" instead of failing.
+
 ## Quick Reference
 
 | Task | Tool |
@@ -860,6 +942,7 @@ Returns a formatted markdown document with:
 | Update (requires force) | `update_medication` |
 | Delete (requires force) | `delete_medication` |
 | Generate med list doc | `export_medications_markdown` |
+| Start/stop history for a provider | `generate_medication_timeline` |
 
 ## Medication Types
 
@@ -931,10 +1014,16 @@ list_medications(med_type: "prescription")
 list_medications(med_type: "supplement")
 ```
 
+**What was I taking on a past date:**
+```
+list_medications(as_of_date: "2024-06-01")
+```
+Active means is_active, start_date on or before the date, and no end_date before it.
+
 ## Notes
 
 - Dates use ISO format: YYYY-MM-DD
-- `export_medications_markdown` only includes active medications
+- `export_medications_markdown` only includes active medications; `generate_medication_timeline` covers stopped ones too
 - Deprecated medications are hidden by default but preserved
 - The force flag protects against accidental changes
 "#;
@@ -953,6 +1042,8 @@ The vitals system tracks:
 - **Heart Rate** - Beats per minute (bpm)
 - **Oxygen Saturation** - SpO2 percentage
 - **Glucose** - Blood sugar in mg/dL
+- **Water** - Water intake in ml
+- **Custom types** - Anything else you register (mood, peak flow, pain level, ...)
 
 ## Key Concepts
 
@@ -1035,18 +1126,30 @@ The vital remains but is no longer linked to any group.
 | heart_rate | BPM | - | bpm |
 | oxygen_saturation | SpO2 % | - | % |
 | glucose | mg/dL | - | mg/dL |
+| water | ml | - | ml |
 
 ### Type Aliases
 You can use these shortcuts when specifying vital_type:
 - `bp` = blood_pressure
 - `hr` or `pulse` = heart_rate
 - `o2` or `spo2` = oxygen_saturation
+- `hydration` = water
+
+### Custom Types
+Register a type once, then use its name as vital_type (names are case-insensitive):
+```
+register_vital_type(name: "peak_flow", unit: "L/min")
+add_vital(vital_type: "peak_flow", value1: 450)
+list_vitals_stats(vital_type: "peak_flow")
+```
+Give `value2_label` when registering if readings carry a second value. Custom values may be zero or negative.
 
 ## Quick Reference
 
 | Task | Tool |
 |------|------|
 | Record a vital | `add_vital` |
+| Record several readings at once | `add_vitals_bulk` |
 | Get vital details | `get_vital` |
 | Update a vital | `update_vital` |
 | Delete a vital | `delete_vital` |
@@ -1055,6 +1158,20 @@ You can use these shortcuts when specifying vital_type:
 | List by date range | `list_vitals_by_date_range` |
 | Get latest of each type | `get_latest_vitals` |
 | Get statistics by type | `list_vitals_stats` |
+| Add a custom vital type | `register_vital_type` |
+| List built-in and custom types | `list_vital_types` |
+| Daily reading counts for a year | `vitals_calendar` |
+| Days with no readings (adherence) | `vital_tracking_gaps` |
+| Weekly BP averages for the doctor | `weekly_bp_averages` |
+| Estimated A1c from glucose | `estimate_a1c` |
+| Days since the last weight/BP/glucose reading | `uhm_status` (`data_freshness`) |
+| Log water intake | `log_water` |
+| Day's water total vs goal | `daily_water_total` |
+| Day-over-day weight changes | `weight_change_series` |
+| Does one vital track another (e.g. weight vs BP) | `vital_correlation` |
+| Check for a weight plateau | `detect_weight_plateau` |
+| Heart rate recovery after exercise (group the post-exercise HR readings first) | `exercise_recovery` |
+| Time in HR zones during a workout (group the session's HR readings first) | `hr_zone_breakdown` |
 | Create group | `create_vital_group` |
 | View group with vitals | `get_vital_group` |
 | List groups | `list_vital_groups` |
@@ -1099,6 +1216,7 @@ list_vitals_by_date_range(
   vital_type: "blood_pressure"  // optional filter
 )
 ```
+Both dates are inclusive: every reading on Jan 31 is returned, whatever the time.
 
 ### Analyze Vital Trends with Statistics
 Use `list_vitals_stats` to get comprehensive statistics for any vital type:
@@ -1119,7 +1237,7 @@ list_vitals_stats(
 - **min / max / range** - Lowest, highest, and spread
 - **percentile_25 / percentile_75 / iqr** - Quartiles and interquartile range
 - **coefficient_of_variation** - Relative variability (SD/mean × 100)
-- **outliers** - Readings outside 1 standard deviation (with timestamp, value, z-score)
+- **outliers** - Readings more than `outlier_sd` standard deviations from the mean (with timestamp, value, z-score). Defaults to 2; pass `outlier_sd: 1` for the old, more sensitive behavior
 
 **Type-specific extras:**
 
@@ -1127,8 +1245,10 @@ list_vitals_stats(
 |------------|-----------------|
 | **weight** | total_change, avg_change_per_reading |
 | **blood_pressure** | Separate stats for systolic, diastolic, and pulse_pressure |
+| **heart_rate** | Separate resting and active stats (active = readings in groups whose description or notes mention exercise/workout) |
 | **oxygen_saturation** | below_95_count, below_90_count (concerning readings) |
 | **glucose** | low_count (<70), high_count (>180) |
+| **water** | days_logged, average_daily_total |
 
 **Example use cases:**
 - "What's my average blood pressure this month?"
@@ -1163,6 +1283,10 @@ pub struct UhmStatus {
     pub uptime_seconds: u64,
     pub process_id: u32,
     pub memory_usage_bytes: u64,
+
+    /// Age of the latest weight, BP and glucose readings (omitted if the database can't be read)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_freshness: Option<DataFreshness>,
 }
 
 /// Status tracker for collecting runtime information
@@ -1180,6 +1304,11 @@ impl StatusTracker {
         }
     }
 
+    /// Update the reported database path after the database is switched
+    pub fn set_database_path(&mut self, database_path: PathBuf) {
+        self.database_path = database_path;
+    }
+
     /// Get the current status
     pub fn get_status(&self) -> UhmStatus {
         let build_info = BuildInfo::current();
@@ -1208,6 +1337,7 @@ impl StatusTracker {
             uptime_seconds: self.start_time.elapsed().as_secs(),
             process_id: pid,
             memory_usage_bytes,
+            data_freshness: None,
         }
     }
 }
@@ -5,8 +5,12 @@
 use std::collections::HashMap;
 use serde::Serialize;
 
+use crate::clock::{canonical_datetime, canonical_timestamp, Clock};
 use crate::db::Database;
-use crate::models::{Vital, VitalCreate, VitalGroup, VitalGroupCreate, VitalType, VitalUpdate};
+use crate::models::{CustomVitalType, CustomVitalTypeCreate, Vital, VitalCreate, VitalGroup, VitalGroupCreate, VitalType, VitalUpdate};
+use super::csv::split_csv_line;
+use super::numeric::round_to;
+use super::error::ToolError;
 
 /// Response for create_vital_group
 #[derive(Debug, Serialize)]
@@ -89,8 +93,8 @@ impl From<&Vital> for VitalSummary {
     fn from(vital: &Vital) -> Self {
         Self {
             id: vital.id,
-            vital_type: vital.vital_type.as_str().to_string(),
-            vital_type_display: vital.vital_type.display_name().to_string(),
+            vital_type: vital.type_name().to_string(),
+            vital_type_display: vital.type_display_name().to_string(),
             value: vital.format_value(),
             timestamp: vital.timestamp.clone(),
             group_id: vital.group_id,
@@ -104,8 +108,8 @@ impl From<Vital> for VitalDetail {
         let value_formatted = vital.format_value();
         Self {
             id: vital.id,
-            vital_type: vital.vital_type.as_str().to_string(),
-            vital_type_display: vital.vital_type.display_name().to_string(),
+            vital_type: vital.type_name().to_string(),
+            vital_type_display: vital.type_display_name().to_string(),
             value1: vital.value1,
             value2: vital.value2,
             value_formatted,
@@ -157,8 +161,9 @@ pub fn create_vital_group(
     description: Option<&str>,
     timestamp: Option<&str>,
     notes: Option<&str>,
-) -> Result<CreateVitalGroupResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    clock: &dyn Clock,
+) -> Result<CreateVitalGroupResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     let data = VitalGroupCreate {
         description: description.map(String::from),
@@ -166,8 +171,8 @@ pub fn create_vital_group(
         notes: notes.map(String::from),
     };
 
-    let group = VitalGroup::create(&conn, &data)
-        .map_err(|e| format!("Failed to create vital group: {}", e))?;
+    let group = VitalGroup::create(&conn, &data, clock)
+        .map_err(|e| ToolError::Db(format!("Failed to create vital group: {}", e)))?;
 
     Ok(CreateVitalGroupResponse {
         id: group.id,
@@ -178,16 +183,16 @@ pub fn create_vital_group(
 }
 
 /// Get a vital group by ID with its vitals
-pub fn get_vital_group(db: &Database, id: i64) -> Result<Option<VitalGroupDetail>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn get_vital_group(db: &Database, id: i64) -> Result<Option<VitalGroupDetail>, ToolError> {
+    let conn = db.get_conn()?;
 
     let group = VitalGroup::get_by_id(&conn, id)
-        .map_err(|e| format!("Failed to get vital group: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get vital group: {}", e)))?;
 
     match group {
         Some(g) => {
             let vitals = VitalGroup::get_vitals(&conn, id)
-                .map_err(|e| format!("Failed to get group vitals: {}", e))?;
+                .map_err(|e| ToolError::Db(format!("Failed to get group vitals: {}", e)))?;
 
             let vital_summaries: Vec<VitalSummary> = vitals.iter().map(VitalSummary::from).collect();
 
@@ -205,16 +210,16 @@ pub fn get_vital_group(db: &Database, id: i64) -> Result<Option<VitalGroupDetail
 }
 
 /// List vital groups
-pub fn list_vital_groups(db: &Database, limit: Option<i64>) -> Result<ListVitalGroupsResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn list_vital_groups(db: &Database, limit: Option<i64>) -> Result<ListVitalGroupsResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     let groups = VitalGroup::list(&conn, limit)
-        .map_err(|e| format!("Failed to list vital groups: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to list vital groups: {}", e)))?;
 
     let mut summaries = Vec::new();
     for group in &groups {
         let vitals = VitalGroup::get_vitals(&conn, group.id)
-            .map_err(|e| format!("Failed to get group vitals: {}", e))?;
+            .map_err(|e| ToolError::Db(format!("Failed to get group vitals: {}", e)))?;
 
         let vital_types: Vec<String> = vitals
             .iter()
@@ -245,8 +250,8 @@ pub fn update_vital_group(
     id: i64,
     description: Option<&str>,
     notes: Option<&str>,
-) -> Result<Option<VitalGroupDetail>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<Option<VitalGroupDetail>, ToolError> {
+    let conn = db.get_conn()?;
 
     let updated = VitalGroup::update(
         &conn,
@@ -254,7 +259,7 @@ pub fn update_vital_group(
         description.map(String::from),
         notes.map(String::from),
     )
-    .map_err(|e| format!("Failed to update vital group: {}", e))?;
+    .map_err(|e| ToolError::Db(format!("Failed to update vital group: {}", e)))?;
 
     match updated {
         Some(_) => get_vital_group(db, id),
@@ -262,20 +267,37 @@ pub fn update_vital_group(
     }
 }
 
+/// Correct a vital group's timestamp. Accepts the same forms as
+/// fix_timestamp_formats and stores the canonical local YYYY-MM-DDTHH:MM:SS.
+/// The group's vitals keep their own timestamps.
+pub fn set_vital_group_timestamp(db: &Database, id: i64, timestamp: &str) -> Result<VitalGroupDetail, ToolError> {
+    let canonical = canonical_timestamp(timestamp)
+        .ok_or_else(|| ToolError::Validation(format!("Invalid timestamp '{}'. Use YYYY-MM-DDTHH:MM:SS", timestamp)))?;
+
+    let conn = db.get_conn()?;
+    let updated = VitalGroup::set_timestamp(&conn, id, &canonical)
+        .map_err(|e| ToolError::Db(format!("Failed to set vital group timestamp: {}", e)))?;
+    drop(conn);
+
+    match updated {
+        Some(_) => get_vital_group(db, id)?.ok_or_else(|| ToolError::NotFound(format!("Vital group not found with id: {}", id))),
+        None => Err(ToolError::NotFound(format!("Vital group not found with id: {}", id))),
+    }
+}
+
 /// Delete a vital group (unlinks vitals but doesn't delete them)
-pub fn delete_vital_group(db: &Database, id: i64) -> Result<DeleteResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn delete_vital_group(db: &Database, id: i64) -> Result<DeleteResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     // Check if group exists
-    let existing = VitalGroup::get_by_id(&conn, id)
-        .map_err(|e| format!("Database error: {}", e))?;
+    let existing = VitalGroup::get_by_id(&conn, id)?;
 
     if existing.is_none() {
-        return Err(format!("Vital group not found with id: {}", id));
+        return Err(ToolError::NotFound(format!("Vital group not found with id: {}", id)));
     }
 
     VitalGroup::delete(&conn, id)
-        .map_err(|e| format!("Failed to delete vital group: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to delete vital group: {}", e)))?;
 
     Ok(DeleteResponse {
         success: true,
@@ -283,67 +305,255 @@ pub fn delete_vital_group(db: &Database, id: i64) -> Result<DeleteResponse, Stri
     })
 }
 
+// ============================================================================
+// Duplicate Vital Groups
+// ============================================================================
+
+/// Two vital groups recorded close together with readings of the same type
+#[derive(Debug, Serialize)]
+pub struct DuplicateVitalGroupPair {
+    pub first: VitalGroupSummary,
+    pub second: VitalGroupSummary,
+    pub minutes_apart: i64,
+    /// Vital types present in both groups
+    pub shared_types: Vec<String>,
+}
+
+/// Response for find_duplicate_vital_groups
+#[derive(Debug, Serialize)]
+pub struct FindDuplicateVitalGroupsResponse {
+    pub window_minutes: i64,
+    pub groups_checked: usize,
+    pub pairs: Vec<DuplicateVitalGroupPair>,
+    /// Groups whose timestamp couldn't be parsed
+    pub skipped_group_ids: Vec<i64>,
+}
+
+/// Response for merge_vital_groups
+#[derive(Debug, Serialize)]
+pub struct MergeVitalGroupsResponse {
+    pub removed_id: i64,
+    pub vitals_moved: usize,
+    pub group: VitalGroupDetail,
+}
+
+/// Find pairs of vital groups whose timestamps are within window_minutes of each
+/// other and that contain at least one vital type in common. Groups with no
+/// overlapping types (e.g. a weight group next to a BP group) are not reported.
+pub fn find_duplicate_vital_groups(db: &Database, window_minutes: i64) -> Result<FindDuplicateVitalGroupsResponse, ToolError> {
+    if window_minutes < 0 {
+        return Err(ToolError::Validation("window_minutes must not be negative".to_string()));
+    }
+    let conn = db.get_conn()?;
+
+    let groups = VitalGroup::list(&conn, None)
+        .map_err(|e| ToolError::Db(format!("Failed to list vital groups: {}", e)))?;
+    let groups_checked = groups.len();
+
+    let mut skipped_group_ids = Vec::new();
+    let mut timed = Vec::new();
+    for group in groups {
+        let parsed = canonical_timestamp(&group.timestamp)
+            .and_then(|ts| chrono::NaiveDateTime::parse_from_str(&ts, crate::clock::TIMESTAMP_FORMAT).ok());
+        let Some(at) = parsed else {
+            skipped_group_ids.push(group.id);
+            continue;
+        };
+        let vitals = VitalGroup::get_vitals(&conn, group.id)
+            .map_err(|e| ToolError::Db(format!("Failed to get group vitals: {}", e)))?;
+        let types: std::collections::HashSet<VitalType> = vitals.iter().map(|v| v.vital_type).collect();
+        timed.push((at, group, vitals.len(), types));
+    }
+    timed.sort_by_key(|(at, group, _, _)| (*at, group.id));
+
+    let summary = |group: &VitalGroup, count: usize, types: &std::collections::HashSet<VitalType>| {
+        let mut vital_types: Vec<String> = types.iter().map(|t| t.display_name().to_string()).collect();
+        vital_types.sort();
+        VitalGroupSummary {
+            id: group.id,
+            description: group.description.clone(),
+            timestamp: group.timestamp.clone(),
+            vital_count: count,
+            vital_types,
+        }
+    };
+
+    let mut pairs = Vec::new();
+    for (i, (at_a, group_a, count_a, types_a)) in timed.iter().enumerate() {
+        for (at_b, group_b, count_b, types_b) in &timed[i + 1..] {
+            let minutes_apart = (*at_b - *at_a).num_minutes();
+            if minutes_apart > window_minutes {
+                break;
+            }
+            let mut shared_types: Vec<String> = types_a
+                .intersection(types_b)
+                .map(|t| t.display_name().to_string())
+                .collect();
+            if shared_types.is_empty() {
+                continue;
+            }
+            shared_types.sort();
+            pairs.push(DuplicateVitalGroupPair {
+                first: summary(group_a, *count_a, types_a),
+                second: summary(group_b, *count_b, types_b),
+                minutes_apart,
+                shared_types,
+            });
+        }
+    }
+
+    Ok(FindDuplicateVitalGroupsResponse {
+        window_minutes,
+        groups_checked,
+        pairs,
+        skipped_group_ids,
+    })
+}
+
+/// Move every vital from remove_id into keep_id, then delete the emptied group.
+/// Exercises are not tracked in this database, so there are no exercise links to
+/// check before merging.
+pub fn merge_vital_groups(db: &Database, keep_id: i64, remove_id: i64) -> Result<MergeVitalGroupsResponse, ToolError> {
+    if keep_id == remove_id {
+        return Err(ToolError::Validation("keep_id and remove_id must be different groups".to_string()));
+    }
+
+    let vitals_moved = db.transaction(|conn| -> Result<usize, ToolError> {
+        for id in [keep_id, remove_id] {
+            VitalGroup::get_by_id(conn, id)?
+                .ok_or_else(|| ToolError::NotFound(format!("Vital group not found with id: {}", id)))?;
+        }
+
+        let vitals = VitalGroup::get_vitals(conn, remove_id)
+            .map_err(|e| ToolError::Db(format!("Failed to get group vitals: {}", e)))?;
+        for vital in &vitals {
+            Vital::assign_to_group(conn, vital.id, Some(keep_id))
+                .map_err(|e| ToolError::Db(format!("Failed to move vital {}: {}", vital.id, e)))?;
+        }
+
+        VitalGroup::delete(conn, remove_id)
+            .map_err(|e| ToolError::Db(format!("Failed to delete vital group: {}", e)))?;
+        Ok(vitals.len())
+    })?;
+
+    let group = get_vital_group(db, keep_id)?
+        .ok_or_else(|| ToolError::NotFound(format!("Vital group not found with id: {}", keep_id)))?;
+
+    Ok(MergeVitalGroupsResponse {
+        removed_id: remove_id,
+        vitals_moved,
+        group,
+    })
+}
+
 // ============================================================================
 // Vital Tool Functions
 // ============================================================================
 
-/// Add a new vital reading
-pub fn add_vital(
-    db: &Database,
+/// Resolve a vital type name to a built-in type or a registered custom type
+fn resolve_vital_type(
+    conn: &rusqlite::Connection,
+    vital_type: &str,
+) -> Result<(VitalType, Option<CustomVitalType>), ToolError> {
+    VitalType::lookup(conn, vital_type)
+        .map_err(|e| ToolError::Db(format!("Failed to look up vital type: {}", e)))?
+        .ok_or_else(|| ToolError::Validation(format!("Invalid vital type: '{}'. Valid types: weight, blood_pressure (bp), heart_rate (hr), oxygen_saturation (o2/spo2), glucose, water (hydration), or a type added with register_vital_type", vital_type)))
+}
+
+/// Reject a date-range bound that isn't YYYY-MM-DD
+fn check_date(field: &str, value: &str) -> Result<(), ToolError> {
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| ToolError::Validation(format!("Invalid {} '{}'. Use YYYY-MM-DD", field, value)))
+}
+
+/// Check a reading's values against its type: custom types accept any finite
+/// value but only take value2 when they have a value2_label; built-in types
+/// need positive values
+fn validate_values(value1: Option<f64>, value2: Option<f64>, custom: Option<&CustomVitalType>) -> Result<(), ToolError> {
+    if let Some(custom) = custom {
+        // Custom scales may include zero or negatives, so only require real numbers
+        if value1.is_some_and(|v1| !v1.is_finite()) || value2.is_some_and(|v2| !v2.is_finite()) {
+            return Err(ToolError::Validation("Values must be finite numbers".to_string()));
+        }
+        if value2.is_some() && custom.value2_label.is_none() {
+            return Err(ToolError::Validation(format!("Vital type '{}' has no second value; register it with a value2_label to record one", custom.name)));
+        }
+    } else {
+        // Validate positive values
+        if value1.is_some_and(|v1| v1 <= 0.0) {
+            return Err(ToolError::Validation("Value must be greater than 0".to_string()));
+        }
+        if value2.is_some_and(|v2| v2 <= 0.0) {
+            return Err(ToolError::Validation("Value2 must be greater than 0".to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Check a reading's type, values and group before insert
+fn validate_vital(
+    conn: &rusqlite::Connection,
     vital_type: &str,
     value1: f64,
     value2: Option<f64>,
-    unit: Option<&str>,
-    timestamp: Option<&str>,
     group_id: Option<i64>,
-    notes: Option<&str>,
-) -> Result<AddVitalResponse, String> {
-    let vt = VitalType::from_str(vital_type)
-        .ok_or_else(|| format!("Invalid vital type: '{}'. Valid types: weight, blood_pressure (bp), heart_rate (hr), oxygen_saturation (o2/spo2), glucose", vital_type))?;
+) -> Result<(VitalType, Option<CustomVitalType>), ToolError> {
+    let (vt, custom) = resolve_vital_type(conn, vital_type)?;
 
     // Validate value2 for blood pressure
     if vt == VitalType::BloodPressure && value2.is_none() {
-        return Err("Blood pressure requires both systolic (value1) and diastolic (value2) values".to_string());
+        return Err(ToolError::Validation("Blood pressure requires both systolic (value1) and diastolic (value2) values".to_string()));
     }
 
-    // Validate positive values
-    if value1 <= 0.0 {
-        return Err("Value must be greater than 0".to_string());
-    }
-    if let Some(v2) = value2 {
-        if v2 <= 0.0 {
-            return Err("Value2 must be greater than 0".to_string());
-        }
-    }
+    validate_values(Some(value1), value2, custom.as_ref())?;
 
     // Validate group exists if specified
     if let Some(gid) = group_id {
-        let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
-        let group = VitalGroup::get_by_id(&conn, gid)
-            .map_err(|e| format!("Database error: {}", e))?;
+        let group = VitalGroup::get_by_id(conn, gid)?;
         if group.is_none() {
-            return Err(format!("Vital group not found with id: {}", gid));
+            return Err(ToolError::NotFound(format!("Vital group not found with id: {}", gid)));
         }
     }
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    Ok((vt, custom))
+}
+
+/// A single reading for add_vital or add_vitals_bulk
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct VitalReading {
+    pub vital_type: String,
+    pub value1: f64,
+    pub value2: Option<f64>,
+    pub unit: Option<String>,
+    pub timestamp: Option<String>,
+    pub group_id: Option<i64>,
+    pub notes: Option<String>,
+}
+
+/// Add a new vital reading
+pub fn add_vital(db: &Database, reading: VitalReading, clock: &dyn Clock) -> Result<AddVitalResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let (vt, custom) = validate_vital(&conn, &reading.vital_type, reading.value1, reading.value2, reading.group_id)?;
 
     let data = VitalCreate {
         vital_type: vt,
-        timestamp: timestamp.map(String::from),
-        value1,
-        value2,
-        unit: unit.map(String::from),
-        group_id,
-        notes: notes.map(String::from),
+        timestamp: reading.timestamp,
+        value1: reading.value1,
+        value2: reading.value2,
+        unit: reading.unit.or_else(|| custom.as_ref().map(|c| c.unit.clone())),
+        group_id: reading.group_id,
+        notes: reading.notes,
+        custom_type_id: custom.map(|c| c.id),
     };
 
-    let vital = Vital::create(&conn, &data)
-        .map_err(|e| format!("Failed to create vital: {}", e))?;
+    let vital = Vital::create(&conn, &data, clock)
+        .map_err(|e| ToolError::Db(format!("Failed to create vital: {}", e)))?;
 
     Ok(AddVitalResponse {
         id: vital.id,
-        vital_type: vital.vital_type.as_str().to_string(),
+        vital_type: vital.type_name().to_string(),
         value: vital.format_value(),
         timestamp: vital.timestamp,
         group_id: vital.group_id,
@@ -351,12 +561,95 @@ pub fn add_vital(
     })
 }
 
+/// Result for a single reading in bulk add
+#[derive(Debug, Serialize)]
+pub struct BulkVitalResult {
+    /// Position in the request
+    pub index: usize,
+    pub vital_type: String,
+    pub success: bool,
+    pub id: Option<i64>,
+    pub value: Option<String>,
+    pub timestamp: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Response for add_vitals_bulk
+#[derive(Debug, Serialize)]
+pub struct AddVitalsBulkResponse {
+    pub total_requested: usize,
+    pub successful: usize,
+    pub failed: usize,
+    pub results: Vec<BulkVitalResult>,
+}
+
+/// Add several vital readings in one transaction.
+/// Invalid readings are reported per item; a database error rolls back the whole batch.
+pub fn add_vitals_bulk(db: &Database, readings: Vec<VitalReading>, clock: &dyn Clock) -> Result<AddVitalsBulkResponse, ToolError> {
+    db.transaction(|conn| {
+        let total_requested = readings.len();
+        let mut results = Vec::with_capacity(total_requested);
+        let mut successful = 0;
+        let mut failed = 0;
+
+        for (index, r) in readings.into_iter().enumerate() {
+            let (vt, custom) = match validate_vital(conn, &r.vital_type, r.value1, r.value2, r.group_id) {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    results.push(BulkVitalResult {
+                        index,
+                        vital_type: r.vital_type,
+                        success: false,
+                        id: None,
+                        value: None,
+                        timestamp: r.timestamp,
+                        error: Some(e.to_string()),
+                    });
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            let data = VitalCreate {
+                vital_type: vt,
+                timestamp: r.timestamp,
+                value1: r.value1,
+                value2: r.value2,
+                unit: r.unit.or_else(|| custom.as_ref().map(|c| c.unit.clone())),
+                group_id: r.group_id,
+                notes: r.notes,
+                custom_type_id: custom.map(|c| c.id),
+            };
+
+            let vital = Vital::create(conn, &data, clock)
+                .map_err(|e| ToolError::Db(format!("Failed to create reading {}, batch rolled back: {}", index, e)))?;
+            results.push(BulkVitalResult {
+                index,
+                vital_type: vital.type_name().to_string(),
+                success: true,
+                id: Some(vital.id),
+                value: Some(vital.format_value()),
+                timestamp: Some(vital.timestamp),
+                error: None,
+            });
+            successful += 1;
+        }
+
+        Ok(AddVitalsBulkResponse {
+            total_requested,
+            successful,
+            failed,
+            results,
+        })
+    })
+}
+
 /// Get a vital by ID
-pub fn get_vital(db: &Database, id: i64) -> Result<Option<VitalDetail>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn get_vital(db: &Database, id: i64) -> Result<Option<VitalDetail>, ToolError> {
+    let conn = db.get_conn()?;
 
     let vital = Vital::get_by_id(&conn, id)
-        .map_err(|e| format!("Failed to get vital: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get vital: {}", e)))?;
 
     Ok(vital.map(VitalDetail::from))
 }
@@ -366,14 +659,14 @@ pub fn list_vitals_by_type(
     db: &Database,
     vital_type: &str,
     limit: Option<i64>,
-) -> Result<ListVitalsResponse, String> {
-    let vt = VitalType::from_str(vital_type)
-        .ok_or_else(|| format!("Invalid vital type: '{}'", vital_type))?;
+) -> Result<ListVitalsResponse, ToolError> {
+    let conn = db.get_conn()?;
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
-
-    let vitals = Vital::list_by_type(&conn, vt, limit)
-        .map_err(|e| format!("Failed to list vitals: {}", e))?;
+    let vitals = match resolve_vital_type(&conn, vital_type)? {
+        (_, Some(custom)) => Vital::list_by_custom_type(&conn, custom.id, limit),
+        (vt, None) => Vital::list_by_type(&conn, vt, limit),
+    }
+    .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?;
 
     let summaries: Vec<VitalSummary> = vitals.iter().map(VitalSummary::from).collect();
     let total = summaries.len();
@@ -385,11 +678,11 @@ pub fn list_vitals_by_type(
 }
 
 /// List recent vitals across all types
-pub fn list_recent_vitals(db: &Database, limit: i64) -> Result<ListVitalsResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn list_recent_vitals(db: &Database, limit: i64) -> Result<ListVitalsResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     let vitals = Vital::list_recent(&conn, limit)
-        .map_err(|e| format!("Failed to list vitals: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?;
 
     let summaries: Vec<VitalSummary> = vitals.iter().map(VitalSummary::from).collect();
     let total = summaries.len();
@@ -406,17 +699,25 @@ pub fn list_vitals_by_date_range(
     start_date: &str,
     end_date: &str,
     vital_type: Option<&str>,
-) -> Result<ListVitalsResponse, String> {
-    let vt = match vital_type {
-        Some(t) => Some(VitalType::from_str(t)
-            .ok_or_else(|| format!("Invalid vital type: '{}'", t))?),
-        None => None,
-    };
+) -> Result<ListVitalsResponse, ToolError> {
+    check_date("start_date", start_date)?;
+    check_date("end_date", end_date)?;
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let conn = db.get_conn()?;
+
+    let (vt, custom) = match vital_type {
+        Some(t) => {
+            let (vt, custom) = resolve_vital_type(&conn, t)?;
+            (Some(vt), custom)
+        }
+        None => (None, None),
+    };
 
-    let vitals = Vital::list_by_date_range(&conn, start_date, end_date, vt)
-        .map_err(|e| format!("Failed to list vitals: {}", e))?;
+    let mut vitals = Vital::list_by_date_range(&conn, start_date, end_date, vt)
+        .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?;
+    if let Some(custom) = custom {
+        vitals.retain(|v| v.custom_type_id == Some(custom.id));
+    }
 
     let summaries: Vec<VitalSummary> = vitals.iter().map(VitalSummary::from).collect();
     let total = summaries.len();
@@ -428,17 +729,63 @@ pub fn list_vitals_by_date_range(
 }
 
 /// Get the latest reading for each vital type
-pub fn get_latest_vitals(db: &Database) -> Result<LatestVitalsResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn get_latest_vitals(db: &Database, clock: &dyn Clock) -> Result<LatestVitalsResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     let vitals = Vital::get_latest_by_type(&conn)
-        .map_err(|e| format!("Failed to get latest vitals: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to get latest vitals: {}", e)))?;
 
     let summaries: Vec<VitalSummary> = vitals.iter().map(VitalSummary::from).collect();
 
     Ok(LatestVitalsResponse {
         vitals: summaries,
-        as_of: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        as_of: clock.timestamp(),
+    })
+}
+
+/// When a vital type was last recorded
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadingAge {
+    pub last_timestamp: String,
+    pub last_date: String,
+    pub days_since: i64,
+}
+
+/// Latest weight, blood pressure and glucose readings and how old they are
+#[derive(Debug, Clone, Serialize)]
+pub struct DataFreshness {
+    pub as_of: String,
+    pub weight: Option<ReadingAge>,
+    pub blood_pressure: Option<ReadingAge>,
+    pub glucose: Option<ReadingAge>,
+}
+
+/// How many days since the latest weight, BP and glucose readings, from get_latest_vitals,
+/// so a client can say "your last weight was 8 days ago". None where a type was never recorded.
+pub fn data_freshness(db: &Database, clock: &dyn Clock) -> Result<DataFreshness, ToolError> {
+    let latest = get_latest_vitals(db, clock)?;
+    let today = clock.local_now().date();
+
+    let age = |vt: VitalType| {
+        latest
+            .vitals
+            .iter()
+            .find(|v| v.vital_type == vt.as_str())
+            .and_then(|v| {
+                let date = chrono::NaiveDate::parse_from_str(v.timestamp.get(..10)?, "%Y-%m-%d").ok()?;
+                Some(ReadingAge {
+                    last_timestamp: v.timestamp.clone(),
+                    last_date: date.to_string(),
+                    days_since: (today - date).num_days(),
+                })
+            })
+    };
+
+    Ok(DataFreshness {
+        as_of: today.to_string(),
+        weight: age(VitalType::Weight),
+        blood_pressure: age(VitalType::BloodPressure),
+        glucose: age(VitalType::Glucose),
     })
 }
 
@@ -450,28 +797,20 @@ pub fn update_vital(
     value2: Option<f64>,
     unit: Option<&str>,
     notes: Option<&str>,
-) -> Result<Option<UpdateVitalResponse>, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<Option<UpdateVitalResponse>, ToolError> {
+    let conn = db.get_conn()?;
 
     // Check if vital exists
-    let existing = Vital::get_by_id(&conn, id)
-        .map_err(|e| format!("Database error: {}", e))?;
+    let existing = Vital::get_by_id(&conn, id)?
+        .ok_or_else(|| ToolError::NotFound(format!("Vital not found with id: {}", id)))?;
 
-    if existing.is_none() {
-        return Err(format!("Vital not found with id: {}", id));
-    }
-
-    // Validate positive values
-    if let Some(v1) = value1 {
-        if v1 <= 0.0 {
-            return Err("Value1 must be greater than 0".to_string());
-        }
-    }
-    if let Some(v2) = value2 {
-        if v2 <= 0.0 {
-            return Err("Value2 must be greater than 0".to_string());
-        }
-    }
+    // Hold new values to the same rules as the reading's type did on insert
+    let custom = match existing.custom_type_id {
+        Some(type_id) => CustomVitalType::get_by_id(&conn, type_id)
+            .map_err(|e| ToolError::Db(format!("Failed to look up vital type: {}", e)))?,
+        None => None,
+    };
+    validate_values(value1, value2, custom.as_ref())?;
 
     let data = VitalUpdate {
         value1,
@@ -482,7 +821,7 @@ pub fn update_vital(
     };
 
     let updated = Vital::update(&conn, id, &data)
-        .map_err(|e| format!("Failed to update vital: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to update vital: {}", e)))?;
 
     Ok(updated.map(|v| UpdateVitalResponse {
         success: true,
@@ -495,41 +834,56 @@ pub fn assign_vital_to_group(
     db: &Database,
     vital_id: i64,
     group_id: Option<i64>,
-) -> Result<VitalDetail, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+) -> Result<VitalDetail, ToolError> {
+    let conn = db.get_conn()?;
 
     // Validate group exists if specified
     if let Some(gid) = group_id {
-        let group = VitalGroup::get_by_id(&conn, gid)
-            .map_err(|e| format!("Database error: {}", e))?;
+        let group = VitalGroup::get_by_id(&conn, gid)?;
         if group.is_none() {
-            return Err(format!("Vital group not found with id: {}", gid));
+            return Err(ToolError::NotFound(format!("Vital group not found with id: {}", gid)));
         }
     }
 
     let updated = Vital::assign_to_group(&conn, vital_id, group_id)
-        .map_err(|e| format!("Failed to assign vital to group: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to assign vital to group: {}", e)))?;
+
+    match updated {
+        Some(v) => Ok(VitalDetail::from(v)),
+        None => Err(ToolError::NotFound(format!("Vital not found with id: {}", vital_id))),
+    }
+}
+
+/// Correct a vital's timestamp (e.g. an import from a device with a wrong clock)
+/// without deleting it, so its group link is kept. Accepts the same forms as
+/// fix_timestamp_formats and stores the canonical local YYYY-MM-DDTHH:MM:SS.
+pub fn set_vital_timestamp(db: &Database, id: i64, timestamp: &str) -> Result<VitalDetail, ToolError> {
+    let canonical = canonical_timestamp(timestamp)
+        .ok_or_else(|| ToolError::Validation(format!("Invalid timestamp '{}'. Use YYYY-MM-DDTHH:MM:SS", timestamp)))?;
+
+    let conn = db.get_conn()?;
+    let updated = Vital::set_timestamp(&conn, id, &canonical)
+        .map_err(|e| ToolError::Db(format!("Failed to set vital timestamp: {}", e)))?;
 
     match updated {
         Some(v) => Ok(VitalDetail::from(v)),
-        None => Err(format!("Vital not found with id: {}", vital_id)),
+        None => Err(ToolError::NotFound(format!("Vital not found with id: {}", id))),
     }
 }
 
 /// Delete a vital reading
-pub fn delete_vital(db: &Database, id: i64) -> Result<DeleteResponse, String> {
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+pub fn delete_vital(db: &Database, id: i64) -> Result<DeleteResponse, ToolError> {
+    let conn = db.get_conn()?;
 
     // Check if vital exists
-    let existing = Vital::get_by_id(&conn, id)
-        .map_err(|e| format!("Database error: {}", e))?;
+    let existing = Vital::get_by_id(&conn, id)?;
 
     if existing.is_none() {
-        return Err(format!("Vital not found with id: {}", id));
+        return Err(ToolError::NotFound(format!("Vital not found with id: {}", id)));
     }
 
     Vital::delete(&conn, id)
-        .map_err(|e| format!("Failed to delete vital: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to delete vital: {}", e)))?;
 
     Ok(DeleteResponse {
         success: true,
@@ -570,39 +924,39 @@ pub struct OmronImportResponse {
 }
 
 /// Parse Omron date format "Jan 6 2026" to "2026-01-06"
-fn parse_omron_date(date_str: &str) -> Result<String, String> {
+fn parse_omron_date(date_str: &str) -> Result<String, ToolError> {
     let parts: Vec<&str> = date_str.split_whitespace().collect();
     if parts.len() != 3 {
-        return Err(format!("Invalid date format: {}", date_str));
+        return Err(ToolError::Validation(format!("Invalid date format: {}", date_str)));
     }
 
     let month = match parts[0].to_lowercase().as_str() {
         "jan" => "01", "feb" => "02", "mar" => "03", "apr" => "04",
         "may" => "05", "jun" => "06", "jul" => "07", "aug" => "08",
         "sep" => "09", "oct" => "10", "nov" => "11", "dec" => "12",
-        _ => return Err(format!("Invalid month: {}", parts[0])),
+        _ => return Err(ToolError::Validation(format!("Invalid month: {}", parts[0]))),
     };
 
-    let day: u32 = parts[1].parse().map_err(|_| format!("Invalid day: {}", parts[1]))?;
-    let year: u32 = parts[2].parse().map_err(|_| format!("Invalid year: {}", parts[2]))?;
+    let day: u32 = parts[1].parse().map_err(|_| ToolError::Validation(format!("Invalid day: {}", parts[1])))?;
+    let year: u32 = parts[2].parse().map_err(|_| ToolError::Validation(format!("Invalid year: {}", parts[2])))?;
 
     Ok(format!("{:04}-{}-{:02}", year, month, day))
 }
 
 /// Parse Omron time format "8:18 am" to "08:18:00"
-fn parse_omron_time(time_str: &str) -> Result<String, String> {
+fn parse_omron_time(time_str: &str) -> Result<String, ToolError> {
     let parts: Vec<&str> = time_str.split_whitespace().collect();
     if parts.len() != 2 {
-        return Err(format!("Invalid time format: {}", time_str));
+        return Err(ToolError::Validation(format!("Invalid time format: {}", time_str)));
     }
 
     let time_parts: Vec<&str> = parts[0].split(':').collect();
     if time_parts.len() != 2 {
-        return Err(format!("Invalid time format: {}", time_str));
+        return Err(ToolError::Validation(format!("Invalid time format: {}", time_str)));
     }
 
-    let mut hour: u32 = time_parts[0].parse().map_err(|_| format!("Invalid hour: {}", time_parts[0]))?;
-    let minute: u32 = time_parts[1].parse().map_err(|_| format!("Invalid minute: {}", time_parts[1]))?;
+    let mut hour: u32 = time_parts[0].parse().map_err(|_| ToolError::Validation(format!("Invalid hour: {}", time_parts[0])))?;
+    let minute: u32 = time_parts[1].parse().map_err(|_| ToolError::Validation(format!("Invalid minute: {}", time_parts[1])))?;
 
     let am_pm = parts[1].to_lowercase();
     if am_pm == "pm" && hour != 12 {
@@ -620,7 +974,7 @@ fn bp_reading_exists(
     timestamp: &str,
     systolic: f64,
     diastolic: f64,
-) -> Result<bool, String> {
+) -> Result<bool, ToolError> {
     let count: i64 = conn
         .query_row(
             r#"SELECT COUNT(*) FROM vitals
@@ -631,7 +985,7 @@ fn bp_reading_exists(
             rusqlite::params![timestamp, systolic, diastolic],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to check for BP duplicates: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to check for BP duplicates: {}", e)))?;
     Ok(count > 0)
 }
 
@@ -640,7 +994,7 @@ fn hr_reading_exists(
     conn: &rusqlite::Connection,
     timestamp: &str,
     pulse: f64,
-) -> Result<bool, String> {
+) -> Result<bool, ToolError> {
     let count: i64 = conn
         .query_row(
             r#"SELECT COUNT(*) FROM vitals
@@ -650,22 +1004,20 @@ fn hr_reading_exists(
             rusqlite::params![timestamp, pulse],
             |row| row.get(0),
         )
-        .map_err(|e| format!("Failed to check for HR duplicates: {}", e))?;
+        .map_err(|e| ToolError::Db(format!("Failed to check for HR duplicates: {}", e)))?;
     Ok(count > 0)
 }
 
 /// Import Omron BP CSV file
-pub fn import_omron_bp_csv(db: &Database, file_path: &str) -> Result<OmronImportResponse, String> {
+pub fn import_omron_bp_csv(db: &Database, file_path: &str, clock: &dyn Clock) -> Result<OmronImportResponse, ToolError> {
     use std::fs::File;
     use std::io::{BufRead, BufReader};
 
     // Read the file
     let file = File::open(file_path)
-        .map_err(|e| format!("Failed to open file '{}': {}", file_path, e))?;
+        .map_err(|e| ToolError::Io(format!("Failed to open file '{}': {}", file_path, e)))?;
     let reader = BufReader::new(file);
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
-
     let mut readings = Vec::new();
     let mut errors = Vec::new();
     let mut skipped = 0;
@@ -673,165 +1025,170 @@ pub fn import_omron_bp_csv(db: &Database, file_path: &str) -> Result<OmronImport
     let mut first_date: Option<String> = None;
     let mut last_date: Option<String> = None;
 
-    for (line_num, line_result) in reader.lines().enumerate() {
-        let line = line_result.map_err(|e| format!("Error reading line {}: {}", line_num + 1, e))?;
-
-        // Skip header row
-        if line_num == 0 && line.starts_with("Date,") {
-            continue;
-        }
-
-        // Skip empty lines
-        if line.trim().is_empty() {
-            continue;
-        }
+    db.transaction(|conn| -> Result<(), ToolError> {
+        for (line_num, line_result) in reader.lines().enumerate() {
+            let line = line_result.map_err(|e| ToolError::Io(format!("Error reading line {}: {}", line_num + 1, e)))?;
 
-        // Parse CSV row
-        let fields: Vec<&str> = line.split(',').collect();
-        if fields.len() < 5 {
-            errors.push(format!("Row {}: Not enough fields", line_num + 1));
-            skipped += 1;
-            continue;
-        }
+            // Skip header row
+            if line_num == 0 && line.starts_with("Date,") {
+                continue;
+            }
 
-        // Parse date and time
-        let date = match parse_omron_date(fields[0].trim()) {
-            Ok(d) => d,
-            Err(e) => {
-                errors.push(format!("Row {}: {}", line_num + 1, e));
-                skipped += 1;
+            // Skip empty lines
+            if line.trim().is_empty() {
                 continue;
             }
-        };
 
-        let time = match parse_omron_time(fields[1].trim()) {
-            Ok(t) => t,
-            Err(e) => {
-                errors.push(format!("Row {}: {}", line_num + 1, e));
+            // Parse CSV row
+            let fields: Vec<&str> = line.split(',').collect();
+            if fields.len() < 5 {
+                errors.push(format!("Row {}: Not enough fields", line_num + 1));
                 skipped += 1;
                 continue;
             }
-        };
 
-        let timestamp = format!("{}T{}", date, time);
+            // Parse date and time
+            let date = match parse_omron_date(fields[0].trim()) {
+                Ok(d) => d,
+                Err(e) => {
+                    errors.push(format!("Row {}: {}", line_num + 1, e));
+                    skipped += 1;
+                    continue;
+                }
+            };
 
-        // Track date range
-        if first_date.is_none() {
-            first_date = Some(date.clone());
-        }
-        last_date = Some(date.clone());
+            let time = match parse_omron_time(fields[1].trim()) {
+                Ok(t) => t,
+                Err(e) => {
+                    errors.push(format!("Row {}: {}", line_num + 1, e));
+                    skipped += 1;
+                    continue;
+                }
+            };
 
-        // Parse vitals
-        let systolic: i32 = match fields[2].trim().parse() {
-            Ok(v) => v,
-            Err(_) => {
-                errors.push(format!("Row {}: Invalid systolic value", line_num + 1));
-                skipped += 1;
-                continue;
-            }
-        };
+            let timestamp = format!("{}T{}", date, time);
 
-        let diastolic: i32 = match fields[3].trim().parse() {
-            Ok(v) => v,
-            Err(_) => {
-                errors.push(format!("Row {}: Invalid diastolic value", line_num + 1));
-                skipped += 1;
-                continue;
+            // Track date range
+            if first_date.is_none() {
+                first_date = Some(date.clone());
             }
-        };
+            last_date = Some(date.clone());
+
+            // Parse vitals
+            let systolic: i32 = match fields[2].trim().parse() {
+                Ok(v) => v,
+                Err(_) => {
+                    errors.push(format!("Row {}: Invalid systolic value", line_num + 1));
+                    skipped += 1;
+                    continue;
+                }
+            };
 
-        let pulse: i32 = match fields[4].trim().parse() {
-            Ok(v) => v,
-            Err(_) => {
-                errors.push(format!("Row {}: Invalid pulse value", line_num + 1));
-                skipped += 1;
-                continue;
-            }
-        };
+            let diastolic: i32 = match fields[3].trim().parse() {
+                Ok(v) => v,
+                Err(_) => {
+                    errors.push(format!("Row {}: Invalid diastolic value", line_num + 1));
+                    skipped += 1;
+                    continue;
+                }
+            };
 
-        // Get TruRead status
-        let truread = if fields.len() > 7 {
-            let tr = fields[7].trim();
-            if tr == "-" { "single".to_string() } else { tr.to_lowercase() }
-        } else {
-            "single".to_string()
-        };
+            let pulse: i32 = match fields[4].trim().parse() {
+                Ok(v) => v,
+                Err(_) => {
+                    errors.push(format!("Row {}: Invalid pulse value", line_num + 1));
+                    skipped += 1;
+                    continue;
+                }
+            };
 
-        // Check for duplicate reading (same timestamp + BP values OR same timestamp + HR value)
-        let bp_exists = match bp_reading_exists(&conn, &timestamp, systolic as f64, diastolic as f64) {
-            Ok(exists) => exists,
-            Err(e) => {
-                errors.push(format!("Row {}: {}", line_num + 1, e));
-                skipped += 1;
-                continue;
-            }
-        };
+            // Get TruRead status
+            let truread = if fields.len() > 7 {
+                let tr = fields[7].trim();
+                if tr == "-" { "single".to_string() } else { tr.to_lowercase() }
+            } else {
+                "single".to_string()
+            };
 
-        let hr_exists = match hr_reading_exists(&conn, &timestamp, pulse as f64) {
-            Ok(exists) => exists,
-            Err(e) => {
-                errors.push(format!("Row {}: {}", line_num + 1, e));
-                skipped += 1;
+            // Check for duplicate reading (same timestamp + BP values OR same timestamp + HR value)
+            let bp_exists = match bp_reading_exists(conn, &timestamp, systolic as f64, diastolic as f64) {
+                Ok(exists) => exists,
+                Err(e) => {
+                    errors.push(format!("Row {}: {}", line_num + 1, e));
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            let hr_exists = match hr_reading_exists(conn, &timestamp, pulse as f64) {
+                Ok(exists) => exists,
+                Err(e) => {
+                    errors.push(format!("Row {}: {}", line_num + 1, e));
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            // If either BP or HR already exists, consider it a duplicate
+            if bp_exists || hr_exists {
+                duplicates += 1;
                 continue;
             }
-        };
 
-        // If either BP or HR already exists, consider it a duplicate
-        if bp_exists || hr_exists {
-            duplicates += 1;
-            continue;
-        }
-
-        // Create vital group for this reading
-        let group_data = VitalGroupCreate {
-            description: Some(format!("Omron BP reading")),
-            timestamp: Some(timestamp.clone()),
-            notes: if truread != "single" { Some(format!("TruRead: {}", truread)) } else { None },
-        };
+            // Create vital group for this reading
+            let group_data = VitalGroupCreate {
+                description: Some(format!("Omron BP reading")),
+                timestamp: Some(timestamp.clone()),
+                notes: if truread != "single" { Some(format!("TruRead: {}", truread)) } else { None },
+            };
 
-        let group = VitalGroup::create(&conn, &group_data)
-            .map_err(|e| format!("Row {}: Failed to create group: {}", line_num + 1, e))?;
-
-        // Create BP vital
-        let bp_data = VitalCreate {
-            vital_type: VitalType::BloodPressure,
-            timestamp: Some(timestamp.clone()),
-            value1: systolic as f64,
-            value2: Some(diastolic as f64),
-            unit: Some("mmHg".to_string()),
-            group_id: Some(group.id),
-            notes: None,
-        };
+            let group = VitalGroup::create(conn, &group_data, clock)
+                .map_err(|e| ToolError::Db(format!("Row {}: Failed to create group: {}", line_num + 1, e)))?;
+
+            // Create BP vital
+            let bp_data = VitalCreate {
+                vital_type: VitalType::BloodPressure,
+                timestamp: Some(timestamp.clone()),
+                value1: systolic as f64,
+                value2: Some(diastolic as f64),
+                unit: Some("mmHg".to_string()),
+                group_id: Some(group.id),
+                notes: None,
+                custom_type_id: None,
+            };
 
-        let bp_vital = Vital::create(&conn, &bp_data)
-            .map_err(|e| format!("Row {}: Failed to create BP vital: {}", line_num + 1, e))?;
-
-        // Create HR vital
-        let hr_data = VitalCreate {
-            vital_type: VitalType::HeartRate,
-            timestamp: Some(timestamp.clone()),
-            value1: pulse as f64,
-            value2: None,
-            unit: Some("bpm".to_string()),
-            group_id: Some(group.id),
-            notes: None,
-        };
+            let bp_vital = Vital::create(conn, &bp_data, clock)
+                .map_err(|e| ToolError::Db(format!("Row {}: Failed to create BP vital: {}", line_num + 1, e)))?;
+
+            // Create HR vital
+            let hr_data = VitalCreate {
+                vital_type: VitalType::HeartRate,
+                timestamp: Some(timestamp.clone()),
+                value1: pulse as f64,
+                value2: None,
+                unit: Some("bpm".to_string()),
+                group_id: Some(group.id),
+                notes: None,
+                custom_type_id: None,
+            };
 
-        let hr_vital = Vital::create(&conn, &hr_data)
-            .map_err(|e| format!("Row {}: Failed to create HR vital: {}", line_num + 1, e))?;
-
-        readings.push(OmronImportRow {
-            row_num: line_num + 1,
-            timestamp,
-            systolic,
-            diastolic,
-            pulse,
-            truread,
-            group_id: group.id,
-            bp_vital_id: bp_vital.id,
-            hr_vital_id: hr_vital.id,
-        });
-    }
+            let hr_vital = Vital::create(conn, &hr_data, clock)
+                .map_err(|e| ToolError::Db(format!("Row {}: Failed to create HR vital: {}", line_num + 1, e)))?;
+
+            readings.push(OmronImportRow {
+                row_num: line_num + 1,
+                timestamp,
+                systolic,
+                diastolic,
+                pulse,
+                truread,
+                group_id: group.id,
+                bp_vital_id: bp_vital.id,
+                hr_vital_id: hr_vital.id,
+            });
+        }
+        Ok(())
+    })?;
 
     let imported = readings.len();
     let total_rows = imported + duplicates + skipped;
@@ -854,49 +1211,575 @@ pub fn import_omron_bp_csv(db: &Database, file_path: &str) -> Result<OmronImport
 }
 
 // ============================================================================
-// Vital Statistics
+// Withings CSV Import
 // ============================================================================
 
-/// An outlier reading (value outside standard deviation bounds)
+/// Pounds per kilogram (Withings exports weight in kg; UHM stores lbs)
+pub(crate) const LBS_PER_KG: f64 = 2.20462;
+
+/// Response for Withings CSV import
 #[derive(Debug, Serialize)]
-pub struct VitalOutlier {
-    pub timestamp: String,
-    pub value: f64,
-    /// How many standard deviations from mean (positive = above, negative = below)
-    pub z_score: f64,
+pub struct WithingsImportResponse {
+    pub success: bool,
+    pub file_path: String,
+    pub kind: String,
+    pub total_rows: usize,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+    pub errors: Vec<String>,
+    pub date_range: String,
 }
 
-/// Statistics for a single vital value
-#[derive(Debug, Serialize)]
-pub struct SingleValueStats {
-    pub count: i64,
-    pub average: f64,
-    pub median: f64,
-    pub mode: Option<f64>,
-    pub standard_deviation: f64,
-    pub variance: f64,
-    pub min: f64,
-    pub max: f64,
-    pub range: f64,
-    /// 25th percentile (Q1)
-    pub percentile_25: f64,
-    /// 75th percentile (Q3)
-    pub percentile_75: f64,
-    /// Interquartile range (Q3 - Q1)
-    pub iqr: f64,
-    /// Coefficient of variation (SD/mean * 100) - relative variability
-    pub coefficient_of_variation: f64,
-    /// Readings outside 1 standard deviation
-    pub outliers: Vec<VitalOutlier>,
+/// Parse Withings timestamp "2026-01-06 08:18:42" to "2026-01-06T08:18:42"
+fn parse_withings_timestamp(ts: &str) -> Result<String, ToolError> {
+    chrono::NaiveDateTime::parse_from_str(ts.trim(), "%Y-%m-%d %H:%M:%S")
+        .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string())
+        .map_err(|_| ToolError::Validation(format!("Invalid timestamp: {}", ts)))
 }
 
-/// Statistics for blood pressure (dual values)
-#[derive(Debug, Serialize)]
-pub struct BloodPressureStats {
-    pub count: i64,
-    pub systolic: SingleValueStats,
-    pub diastolic: SingleValueStats,
-    /// Pulse pressure (systolic - diastolic) stats
+/// Check if a weight reading already exists with matching timestamp and value
+fn weight_reading_exists(
+    conn: &rusqlite::Connection,
+    timestamp: &str,
+    weight: f64,
+) -> Result<bool, ToolError> {
+    let count: i64 = conn
+        .query_row(
+            r#"SELECT COUNT(*) FROM vitals
+               WHERE vital_type = 'weight'
+               AND timestamp = ?1
+               AND value1 = ?2"#,
+            rusqlite::params![timestamp, weight],
+            |row| row.get(0),
+        )
+        .map_err(|e| ToolError::Db(format!("Failed to check for weight duplicates: {}", e)))?;
+    Ok(count > 0)
+}
+
+/// Parse an optional numeric Withings field (empty means not measured)
+fn parse_withings_value(fields: &[String], idx: usize, name: &str) -> Result<Option<f64>, ToolError> {
+    match fields.get(idx).map(|f| f.trim()) {
+        None | Some("") => Ok(None),
+        Some(v) => v.parse().map(Some).map_err(|_| ToolError::Validation(format!("Invalid {} value: {}", name, v))),
+    }
+}
+
+/// Import a Withings (Nokia Health) export: weight.csv (kind "weight") or bp.csv (kind "bp")
+pub fn import_withings_csv(db: &Database, file_path: &str, kind: &str, clock: &dyn Clock) -> Result<WithingsImportResponse, ToolError> {
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+
+    let is_weight = match kind.to_lowercase().as_str() {
+        "weight" => true,
+        "bp" | "blood_pressure" => false,
+        _ => return Err(ToolError::Validation(format!("Invalid kind: '{}'. Must be 'weight' or 'bp'", kind))),
+    };
+
+    let file = File::open(file_path)
+        .map_err(|e| ToolError::Io(format!("Failed to open file '{}': {}", file_path, e)))?;
+    let mut lines = BufReader::new(file).lines();
+
+    // Locate columns by header name; Withings has reordered columns between exports
+    let header = match lines.next() {
+        Some(line) => split_csv_line(&line.map_err(|e| ToolError::Io(format!("Error reading header: {}", e)))?),
+        None => return Err(ToolError::Validation("File is empty".to_string())),
+    };
+    let column = |prefix: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().to_lowercase().starts_with(prefix))
+            .ok_or_else(|| ToolError::Validation(format!("Missing column '{}' - is this a Withings {} export?", prefix, kind)))
+    };
+
+    let date_col = column("date")?;
+    let (weight_col, sys_col, dia_col, hr_col) = if is_weight {
+        (column("weight")?, 0, 0, 0)
+    } else {
+        (0, column("systolic")?, column("diastolic")?, column("heart rate")?)
+    };
+
+    let mut imported = 0;
+    let mut errors = Vec::new();
+    let mut skipped = 0;
+    let mut duplicates = 0;
+    let mut first_date: Option<String> = None;
+    let mut last_date: Option<String> = None;
+
+    db.transaction(|conn| -> Result<(), ToolError> {
+        for (idx, line_result) in lines.enumerate() {
+            let row_num = idx + 2;
+            let line = line_result.map_err(|e| ToolError::Io(format!("Error reading line {}: {}", row_num, e)))?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields = split_csv_line(&line);
+
+            let timestamp = match fields.get(date_col).map(|d| parse_withings_timestamp(d)) {
+                Some(Ok(ts)) => ts,
+                Some(Err(e)) => {
+                    errors.push(format!("Row {}: {}", row_num, e));
+                    skipped += 1;
+                    continue;
+                }
+                None => {
+                    errors.push(format!("Row {}: Not enough fields", row_num));
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            // Track date range (Withings exports newest first)
+            let date = timestamp[..10].to_string();
+            if first_date.as_ref().is_none_or(|d| date < *d) {
+                first_date = Some(date.clone());
+            }
+            if last_date.as_ref().is_none_or(|d| date > *d) {
+                last_date = Some(date);
+            }
+
+            if is_weight {
+                let kg = match parse_withings_value(&fields, weight_col, "weight") {
+                    Ok(Some(v)) if v > 0.0 => v,
+                    Ok(_) => {
+                        // Body composition rows without a weight measurement
+                        skipped += 1;
+                        continue;
+                    }
+                    Err(e) => {
+                        errors.push(format!("Row {}: {}", row_num, e));
+                        skipped += 1;
+                        continue;
+                    }
+                };
+                let lbs = round_to(kg * LBS_PER_KG, 1);
+
+                match weight_reading_exists(conn, &timestamp, lbs) {
+                    Ok(true) => {
+                        duplicates += 1;
+                        continue;
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        errors.push(format!("Row {}: {}", row_num, e));
+                        skipped += 1;
+                        continue;
+                    }
+                }
+
+                let data = VitalCreate {
+                    vital_type: VitalType::Weight,
+                    timestamp: Some(timestamp),
+                    value1: lbs,
+                    value2: None,
+                    unit: Some("lbs".to_string()),
+                    group_id: None,
+                    notes: Some("Withings import".to_string()),
+                    custom_type_id: None,
+                };
+                Vital::create(conn, &data, clock)
+                    .map_err(|e| ToolError::Db(format!("Row {}: Failed to create weight vital: {}", row_num, e)))?;
+                imported += 1;
+            } else {
+                let values = (
+                    parse_withings_value(&fields, sys_col, "systolic"),
+                    parse_withings_value(&fields, dia_col, "diastolic"),
+                    parse_withings_value(&fields, hr_col, "heart rate"),
+                );
+                let (systolic, diastolic, pulse) = match values {
+                    (Ok(Some(s)), Ok(Some(d)), Ok(p)) => (s, d, p),
+                    (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                        errors.push(format!("Row {}: {}", row_num, e));
+                        skipped += 1;
+                        continue;
+                    }
+                    _ => {
+                        errors.push(format!("Row {}: Missing systolic or diastolic value", row_num));
+                        skipped += 1;
+                        continue;
+                    }
+                };
+
+                let bp_exists = bp_reading_exists(conn, &timestamp, systolic, diastolic);
+                let hr_exists = match pulse {
+                    Some(p) => hr_reading_exists(conn, &timestamp, p),
+                    None => Ok(false),
+                };
+                match (bp_exists, hr_exists) {
+                    (Ok(false), Ok(false)) => {}
+                    (Ok(_), Ok(_)) => {
+                        duplicates += 1;
+                        continue;
+                    }
+                    (Err(e), _) | (_, Err(e)) => {
+                        errors.push(format!("Row {}: {}", row_num, e));
+                        skipped += 1;
+                        continue;
+                    }
+                }
+
+                let group_data = VitalGroupCreate {
+                    description: Some("Withings BP reading".to_string()),
+                    timestamp: Some(timestamp.clone()),
+                    notes: None,
+                };
+                let group = VitalGroup::create(conn, &group_data, clock)
+                    .map_err(|e| ToolError::Db(format!("Row {}: Failed to create group: {}", row_num, e)))?;
+
+                let bp_data = VitalCreate {
+                    vital_type: VitalType::BloodPressure,
+                    timestamp: Some(timestamp.clone()),
+                    value1: systolic,
+                    value2: Some(diastolic),
+                    unit: Some("mmHg".to_string()),
+                    group_id: Some(group.id),
+                    notes: None,
+                    custom_type_id: None,
+                };
+                Vital::create(conn, &bp_data, clock)
+                    .map_err(|e| ToolError::Db(format!("Row {}: Failed to create BP vital: {}", row_num, e)))?;
+
+                if let Some(pulse) = pulse {
+                    let hr_data = VitalCreate {
+                        vital_type: VitalType::HeartRate,
+                        timestamp: Some(timestamp.clone()),
+                        value1: pulse,
+                        value2: None,
+                        unit: Some("bpm".to_string()),
+                        group_id: Some(group.id),
+                        notes: None,
+                        custom_type_id: None,
+                    };
+                    Vital::create(conn, &hr_data, clock)
+                        .map_err(|e| ToolError::Db(format!("Row {}: Failed to create HR vital: {}", row_num, e)))?;
+                }
+                imported += 1;
+            }
+        }
+        Ok(())
+    })?;
+
+    let total_rows = imported + duplicates + skipped;
+    let date_range = match (first_date, last_date) {
+        (Some(start), Some(end)) => format!("{} to {}", start, end),
+        _ => "N/A".to_string(),
+    };
+
+    Ok(WithingsImportResponse {
+        success: errors.is_empty(),
+        file_path: file_path.to_string(),
+        kind: if is_weight { "weight" } else { "bp" }.to_string(),
+        total_rows,
+        imported,
+        duplicates,
+        skipped,
+        errors: if errors.len() > 10 { errors[..10].to_vec() } else { errors },
+        date_range,
+    })
+}
+
+// ============================================================================
+// Mapped CSV Import
+// ============================================================================
+
+/// mg/dL per mmol/L of glucose
+const MG_DL_PER_MMOL_L: f64 = 18.0;
+
+/// Which CSV columns hold what, for import_vitals_csv_mapped. A column is a
+/// header name (case-insensitive) or a 0-based index.
+#[derive(Debug, Clone)]
+pub struct CsvColumnMapping {
+    pub timestamp: String,
+    /// chrono format for the timestamp column (e.g. "%m/%d/%Y %H:%M"); a date-only
+    /// format gives midnight. None accepts ISO 8601 variants.
+    pub timestamp_format: Option<String>,
+    /// Column holding each row's vital type
+    pub vital_type: Option<String>,
+    /// Vital type for every row, when the file has no type column
+    pub fixed_vital_type: Option<String>,
+    pub value1: String,
+    pub value2: Option<String>,
+    /// Column holding each row's unit
+    pub unit: Option<String>,
+    /// Unit for every row, when the file has no unit column
+    pub fixed_unit: Option<String>,
+    /// False when the first line is data rather than headers
+    pub has_header: bool,
+}
+
+/// Response for import_vitals_csv_mapped
+#[derive(Debug, Serialize)]
+pub struct MappedImportResponse {
+    pub success: bool,
+    pub file_path: String,
+    pub total_rows: usize,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+    pub errors: Vec<String>,
+    pub date_range: String,
+    /// Imported readings per vital type
+    pub by_type: std::collections::BTreeMap<String, usize>,
+}
+
+/// Find a mapped column: a header name first, then a 0-based index
+fn resolve_csv_column(header: Option<&[String]>, spec: &str) -> Result<usize, ToolError> {
+    let spec = spec.trim();
+    if let Some(pos) = header.and_then(|h| h.iter().position(|c| c.trim().eq_ignore_ascii_case(spec))) {
+        return Ok(pos);
+    }
+    spec.parse::<usize>().map_err(|_| match header {
+        Some(_) => ToolError::Validation(format!("No column named '{}' in the header", spec)),
+        None => ToolError::Validation(format!("Column '{}' must be a 0-based index when has_header is false", spec)),
+    })
+}
+
+/// Parse a timestamp with an explicit chrono format (date-only formats give
+/// midnight), or any ISO 8601 variant when no format is given
+fn parse_mapped_timestamp(value: &str, format: Option<&str>) -> Option<String> {
+    let value = value.trim();
+    let Some(format) = format else {
+        return canonical_timestamp(value);
+    };
+    let dt = chrono::NaiveDateTime::parse_from_str(value, format).ok().or_else(|| {
+        chrono::NaiveDate::parse_from_str(value, format).ok().and_then(|d| d.and_hms_opt(0, 0, 0))
+    })?;
+    canonical_timestamp(&dt.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+/// Convert a value to the vital type's stored unit where UHM knows the factor
+/// (kg to lbs, mmol/L to mg/dL, fl oz/cups/L to ml); other units pass through
+fn convert_to_default_unit(vital_type: VitalType, value: f64, unit: &str) -> (f64, String) {
+    let unit = unit.trim();
+    match vital_type {
+        VitalType::Weight if unit.eq_ignore_ascii_case("kg") => {
+            (round_to(value * LBS_PER_KG, 1), "lbs".to_string())
+        }
+        VitalType::Glucose if unit.eq_ignore_ascii_case("mmol/l") => {
+            (round_to(value * MG_DL_PER_MMOL_L, 0), "mg/dL".to_string())
+        }
+        VitalType::Water => match crate::nutrition::ml_per_unit(unit) {
+            Some(factor) => (round_to(value * factor, 0), "ml".to_string()),
+            None => (value, unit.to_string()),
+        },
+        _ => (value, unit.to_string()),
+    }
+}
+
+/// Check if a reading with the same type, timestamp and values already exists
+fn vital_reading_exists(conn: &rusqlite::Connection, data: &VitalCreate, timestamp: &str) -> Result<bool, ToolError> {
+    let count: i64 = conn
+        .query_row(
+            r#"SELECT COUNT(*) FROM vitals
+               WHERE vital_type = ?1
+               AND custom_type_id IS ?2
+               AND timestamp = ?3
+               AND value1 = ?4
+               AND value2 IS ?5"#,
+            rusqlite::params![data.vital_type.as_str(), data.custom_type_id, timestamp, data.value1, data.value2],
+            |row| row.get(0),
+        )
+        .map_err(|e| ToolError::Db(format!("Failed to check for duplicates: {}", e)))?;
+    Ok(count > 0)
+}
+
+/// Import vitals from any CSV using a column mapping. Rows are validated like
+/// add_vital (custom types included), converted to the type's stored unit where
+/// possible, and skipped as duplicates when an identical reading exists.
+pub fn import_vitals_csv_mapped(
+    db: &Database,
+    file_path: &str,
+    mapping: &CsvColumnMapping,
+    clock: &dyn Clock,
+) -> Result<MappedImportResponse, ToolError> {
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+
+    if mapping.vital_type.is_some() == mapping.fixed_vital_type.is_some() {
+        return Err(ToolError::Validation("Give exactly one of vital_type (a column) or fixed_vital_type".to_string()));
+    }
+    if mapping.unit.is_some() && mapping.fixed_unit.is_some() {
+        return Err(ToolError::Validation("Give at most one of unit (a column) or fixed_unit".to_string()));
+    }
+
+    let file = File::open(file_path)
+        .map_err(|e| ToolError::Io(format!("Failed to open file '{}': {}", file_path, e)))?;
+    let mut lines = BufReader::new(file).lines();
+
+    let header = if mapping.has_header {
+        match lines.next() {
+            Some(line) => Some(split_csv_line(&line.map_err(|e| ToolError::Io(format!("Error reading header: {}", e)))?)),
+            None => return Err(ToolError::Validation("File is empty".to_string())),
+        }
+    } else {
+        None
+    };
+    let column = |spec: &str| resolve_csv_column(header.as_deref(), spec);
+    let ts_col = column(&mapping.timestamp)?;
+    let type_col = mapping.vital_type.as_deref().map(column).transpose()?;
+    let value1_col = column(&mapping.value1)?;
+    let value2_col = mapping.value2.as_deref().map(column).transpose()?;
+    let unit_col = mapping.unit.as_deref().map(column).transpose()?;
+    let first_row = if mapping.has_header { 2 } else { 1 };
+
+    let mut imported = 0;
+    let mut errors = Vec::new();
+    let mut skipped = 0;
+    let mut duplicates = 0;
+    let mut by_type: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+    let mut first_date: Option<String> = None;
+    let mut last_date: Option<String> = None;
+
+    db.transaction(|conn| -> Result<(), ToolError> {
+        for (idx, line_result) in lines.enumerate() {
+            let row_num = idx + first_row;
+            let line = line_result.map_err(|e| ToolError::Io(format!("Error reading line {}: {}", row_num, e)))?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields = split_csv_line(&line);
+            let field = |col: usize| fields.get(col).map(|f| f.trim()).unwrap_or("");
+
+            let row = (|| -> Result<(VitalCreate, String), ToolError> {
+                let timestamp = parse_mapped_timestamp(field(ts_col), mapping.timestamp_format.as_deref())
+                    .ok_or_else(|| ToolError::Validation(format!("Invalid timestamp: '{}'", field(ts_col))))?;
+                let type_name = match type_col {
+                    Some(col) => field(col),
+                    None => mapping.fixed_vital_type.as_deref().unwrap_or_default(),
+                };
+                let number = |col: usize, name: &str| -> Result<Option<f64>, ToolError> {
+                    match field(col) {
+                        "" => Ok(None),
+                        v => v.parse().map(Some).map_err(|_| ToolError::Validation(format!("Invalid {}: '{}'", name, v))),
+                    }
+                };
+                let raw1 = number(value1_col, "value1")?
+                    .ok_or_else(|| ToolError::Validation("Missing value1".to_string()))?;
+                let raw2 = value2_col.map(|col| number(col, "value2")).transpose()?.flatten();
+
+                let unit = match (unit_col, &mapping.fixed_unit) {
+                    (Some(col), _) => Some(field(col).to_string()).filter(|u| !u.is_empty()),
+                    (None, fixed) => fixed.clone(),
+                };
+                let (vt, custom) = validate_vital(conn, type_name, raw1, raw2, None)?;
+                let unit = unit
+                    .or_else(|| custom.as_ref().map(|c| c.unit.clone()))
+                    .unwrap_or_else(|| vt.default_unit().to_string());
+                let (value1, stored_unit) = convert_to_default_unit(vt, raw1, &unit);
+                let value2 = raw2.map(|v| convert_to_default_unit(vt, v, &unit).0);
+
+                Ok((VitalCreate {
+                    vital_type: vt,
+                    timestamp: Some(timestamp.clone()),
+                    value1,
+                    value2,
+                    unit: Some(stored_unit),
+                    group_id: None,
+                    notes: Some("CSV import".to_string()),
+                    custom_type_id: custom.as_ref().map(|c| c.id),
+                }, custom.map(|c| c.name).unwrap_or_else(|| vt.as_str().to_string())))
+            })();
+
+            let (data, type_name) = match row {
+                Ok(row) => row,
+                Err(e) => {
+                    errors.push(format!("Row {}: {}", row_num, e));
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let timestamp = data.timestamp.clone().unwrap_or_default();
+
+            if vital_reading_exists(conn, &data, &timestamp)? {
+                duplicates += 1;
+                continue;
+            }
+
+            let date = timestamp.get(..10).unwrap_or(&timestamp).to_string();
+            if first_date.as_ref().is_none_or(|d| date < *d) {
+                first_date = Some(date.clone());
+            }
+            if last_date.as_ref().is_none_or(|d| date > *d) {
+                last_date = Some(date);
+            }
+
+            Vital::create(conn, &data, clock)
+                .map_err(|e| ToolError::Db(format!("Row {}: Failed to create vital: {}", row_num, e)))?;
+            *by_type.entry(type_name).or_insert(0) += 1;
+            imported += 1;
+        }
+        Ok(())
+    })?;
+
+    let total_rows = imported + duplicates + skipped;
+    let date_range = match (first_date, last_date) {
+        (Some(start), Some(end)) => format!("{} to {}", start, end),
+        _ => "N/A".to_string(),
+    };
+
+    Ok(MappedImportResponse {
+        success: errors.is_empty(),
+        file_path: file_path.to_string(),
+        total_rows,
+        imported,
+        duplicates,
+        skipped,
+        errors: if errors.len() > 10 { errors[..10].to_vec() } else { errors },
+        date_range,
+        by_type,
+    })
+}
+
+// ============================================================================
+// Vital Statistics
+// ============================================================================
+
+/// An outlier reading (value outside standard deviation bounds)
+#[derive(Debug, Serialize)]
+pub struct VitalOutlier {
+    pub timestamp: String,
+    pub value: f64,
+    /// How many standard deviations from mean (positive = above, negative = below)
+    pub z_score: f64,
+}
+
+/// Statistics for a single vital value
+#[derive(Debug, Serialize)]
+pub struct SingleValueStats {
+    pub count: i64,
+    pub average: f64,
+    pub median: f64,
+    pub mode: Option<f64>,
+    pub standard_deviation: f64,
+    pub variance: f64,
+    pub min: f64,
+    pub max: f64,
+    pub range: f64,
+    /// 25th percentile (Q1)
+    pub percentile_25: f64,
+    /// 75th percentile (Q3)
+    pub percentile_75: f64,
+    /// Interquartile range (Q3 - Q1)
+    pub iqr: f64,
+    /// Coefficient of variation (SD/mean * 100) - relative variability
+    pub coefficient_of_variation: f64,
+    /// Readings more than outlier_sd standard deviations from the mean
+    pub outliers: Vec<VitalOutlier>,
+}
+
+/// Statistics for blood pressure (dual values)
+#[derive(Debug, Serialize)]
+pub struct BloodPressureStats {
+    pub count: i64,
+    /// "mmHg" or "kPa"; readings are stored in mmHg and converted for display
+    pub unit: String,
+    pub systolic: SingleValueStats,
+    pub diastolic: SingleValueStats,
+    /// Pulse pressure (systolic - diastolic) stats
     pub pulse_pressure: SingleValueStats,
 }
 
@@ -917,7 +1800,14 @@ pub struct WeightStats {
 pub struct HeartRateStats {
     pub count: i64,
     pub unit: String,
+    /// All readings
     pub stats: SingleValueStats,
+    /// Readings not in an exercise group (ungrouped, or any other group)
+    pub resting: SingleValueStats,
+    /// Readings in an exercise group, i.e. one whose description or notes
+    /// contain "exercise" or "workout" (SQL LIKE, so ASCII case-insensitive);
+    /// a group labelled "Post-run" or "Gym" is not recognised
+    pub active: SingleValueStats,
 }
 
 /// Statistics for oxygen saturation
@@ -944,11 +1834,43 @@ pub struct GlucoseStats {
     pub high_count: i64,
 }
 
+/// Statistics for water intake
+#[derive(Debug, Serialize)]
+pub struct WaterStats {
+    pub count: i64,
+    pub unit: String,
+    /// Per-drink stats
+    pub stats: SingleValueStats,
+    /// Days with at least one water entry
+    pub days_logged: i64,
+    /// Average of each logged day's total intake, in ml
+    pub average_daily_total: f64,
+}
+
+/// Statistics for a custom vital type
+#[derive(Debug, Serialize)]
+pub struct CustomVitalStats {
+    pub name: String,
+    pub count: i64,
+    pub unit: String,
+    pub stats: SingleValueStats,
+    /// Label of the second value, when the type has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value2_label: Option<String>,
+    /// Stats of the second value, over the readings that have one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value2: Option<SingleValueStats>,
+}
+
 /// Response for list_vitals_stats
 #[derive(Debug, Serialize)]
 pub struct ListVitalsStatsResponse {
     pub vital_type: String,
     pub readings_analyzed: i64,
+    /// Standard deviations from the mean beyond which a reading is an outlier
+    pub outlier_sd: f64,
+    /// Decimal places values are rounded to (coefficient_of_variation gets one more)
+    pub precision: u32,
     pub date_range: Option<VitalDateRange>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub weight: Option<WeightStats>,
@@ -960,6 +1882,10 @@ pub struct ListVitalsStatsResponse {
     pub oxygen_saturation: Option<OxygenSaturationStats>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub glucose: Option<GlucoseStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub water: Option<WaterStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom: Option<CustomVitalStats>,
 }
 
 /// Date range for stats
@@ -975,6 +1901,15 @@ struct TimestampedValue {
     value: f64,
 }
 
+/// Decimal places that suit each vital's stats (readings are whole or one-decimal numbers)
+fn default_stats_precision(vital_type: VitalType, bp_unit: BpDisplayUnit) -> u32 {
+    match vital_type {
+        VitalType::BloodPressure if bp_unit == BpDisplayUnit::Kpa => 2,
+        VitalType::Water => 0,
+        _ => 1,
+    }
+}
+
 /// Calculate percentile using linear interpolation
 fn vital_percentile(sorted: &[f64], p: f64) -> f64 {
     if sorted.is_empty() {
@@ -998,7 +1933,7 @@ fn vital_percentile(sorted: &[f64], p: f64) -> f64 {
 }
 
 /// Calculate statistics for a list of timestamped values
-fn calculate_single_stats(values: &[TimestampedValue]) -> SingleValueStats {
+fn calculate_single_stats(values: &[TimestampedValue], outlier_sd: f64, precision: u32) -> SingleValueStats {
     if values.is_empty() {
         return SingleValueStats {
             count: 0,
@@ -1077,17 +2012,17 @@ fn calculate_single_stats(values: &[TimestampedValue]) -> SingleValueStats {
         0.0
     };
 
-    // Outliers (outside 1 standard deviation)
+    // Outliers (beyond outlier_sd standard deviations)
     let outliers: Vec<VitalOutlier> = if standard_deviation > 0.0 {
         values
             .iter()
             .filter_map(|tv| {
                 let z_score = (tv.value - average) / standard_deviation;
-                if z_score.abs() > 1.0 {
+                if z_score.abs() > outlier_sd {
                     Some(VitalOutlier {
                         timestamp: tv.timestamp.clone(),
                         value: tv.value,
-                        z_score: (z_score * 100.0).round() / 100.0,
+                        z_score: round_to(z_score, 2),
                     })
                 } else {
                     None
@@ -1100,55 +2035,120 @@ fn calculate_single_stats(values: &[TimestampedValue]) -> SingleValueStats {
 
     SingleValueStats {
         count,
-        average: (average * 100.0).round() / 100.0,
-        median: (median * 100.0).round() / 100.0,
-        mode: mode.map(|m| (m * 100.0).round() / 100.0),
-        standard_deviation: (standard_deviation * 100.0).round() / 100.0,
-        variance: (variance * 100.0).round() / 100.0,
-        min: (min * 100.0).round() / 100.0,
-        max: (max * 100.0).round() / 100.0,
-        range: (range * 100.0).round() / 100.0,
-        percentile_25: (percentile_25 * 100.0).round() / 100.0,
-        percentile_75: (percentile_75 * 100.0).round() / 100.0,
-        iqr: (iqr * 100.0).round() / 100.0,
-        coefficient_of_variation: (coefficient_of_variation * 100.0).round() / 100.0,
+        average: round_to(average, precision),
+        median: round_to(median, precision),
+        mode: mode.map(|m| round_to(m, precision)),
+        standard_deviation: round_to(standard_deviation, precision),
+        variance: round_to(variance, precision),
+        min: round_to(min, precision),
+        max: round_to(max, precision),
+        range: round_to(range, precision),
+        percentile_25: round_to(percentile_25, precision),
+        percentile_75: round_to(percentile_75, precision),
+        iqr: round_to(iqr, precision),
+        coefficient_of_variation: round_to(coefficient_of_variation, precision + 1),
         outliers,
     }
 }
 
+/// kPa per mmHg
+const KPA_PER_MMHG: f64 = 0.133322;
+
+/// Convert a blood pressure value from mmHg to kPa
+pub fn bp_to_kpa(mmhg: f64) -> f64 {
+    mmhg * KPA_PER_MMHG
+}
+
+/// Unit blood pressure is displayed in (storage is always mmHg)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BpDisplayUnit {
+    MmHg,
+    Kpa,
+}
+
+impl BpDisplayUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BpDisplayUnit::MmHg => "mmHg",
+            BpDisplayUnit::Kpa => "kPa",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "mmhg" => Some(BpDisplayUnit::MmHg),
+            "kpa" => Some(BpDisplayUnit::Kpa),
+            _ => None,
+        }
+    }
+}
+
 /// Get comprehensive statistics for vitals by type
 pub fn list_vitals_stats(
     db: &Database,
     vital_type: &str,
     start_date: Option<&str>,
     end_date: Option<&str>,
-) -> Result<ListVitalsStatsResponse, String> {
-    let vt = VitalType::from_str(vital_type)
-        .ok_or_else(|| format!("Invalid vital type: '{}'. Valid types: weight, blood_pressure (bp), heart_rate (hr), oxygen_saturation (o2/spo2), glucose", vital_type))?;
+    outlier_sd: f64,
+    display_unit: Option<&str>,
+    precision: Option<u32>,
+) -> Result<ListVitalsStatsResponse, ToolError> {
+    if !outlier_sd.is_finite() || outlier_sd <= 0.0 {
+        return Err(ToolError::Validation("outlier_sd must be greater than 0".to_string()));
+    }
+    let bp_unit = match display_unit {
+        Some(u) => BpDisplayUnit::from_str(u)
+            .ok_or_else(|| ToolError::Validation(format!("Invalid display_unit: '{}'. Valid units: mmHg, kPa", u)))?,
+        None => BpDisplayUnit::MmHg,
+    };
 
-    let conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    if precision.is_some_and(|p| p > 6) {
+        return Err(ToolError::Validation("precision must be between 0 and 6".to_string()));
+    }
+    if let Some(start) = start_date {
+        check_date("start_date", start)?;
+    }
+    if let Some(end) = end_date {
+        check_date("end_date", end)?;
+    }
+
+    let conn = db.get_conn()?;
+
+    let (vt, custom) = resolve_vital_type(&conn, vital_type)?;
+    let precision = precision.unwrap_or_else(|| default_stats_precision(vt, bp_unit));
+    let type_name = custom.as_ref().map(|c| c.name.clone()).unwrap_or_else(|| vt.as_str().to_string());
 
     // Get all vitals of this type in date range
-    let vitals = if start_date.is_some() || end_date.is_some() {
+    let mut vitals = if start_date.is_some() || end_date.is_some() {
         let start = start_date.unwrap_or("1900-01-01");
         let end = end_date.unwrap_or("2100-12-31");
         Vital::list_by_date_range(&conn, start, end, Some(vt))
-            .map_err(|e| format!("Failed to list vitals: {}", e))?
+            .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?
+    } else if let Some(custom) = &custom {
+        Vital::list_by_custom_type(&conn, custom.id, Some(10000))
+            .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?
     } else {
         Vital::list_by_type(&conn, vt, Some(10000))
-            .map_err(|e| format!("Failed to list vitals: {}", e))?
+            .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?
     };
+    if let Some(custom) = &custom {
+        vitals.retain(|v| v.custom_type_id == Some(custom.id));
+    }
 
     if vitals.is_empty() {
         return Ok(ListVitalsStatsResponse {
-            vital_type: vt.as_str().to_string(),
+            vital_type: type_name,
             readings_analyzed: 0,
+            outlier_sd,
+            precision,
             date_range: None,
             weight: None,
             blood_pressure: None,
             heart_rate: None,
             oxygen_saturation: None,
             glucose: None,
+            water: None,
+            custom: None,
         });
     }
 
@@ -1172,7 +2172,7 @@ pub fn list_vitals_stats(
                 })
                 .collect();
 
-            let stats = calculate_single_stats(&values);
+            let stats = calculate_single_stats(&values, outlier_sd, precision);
 
             // Calculate weight change
             let (total_change, avg_change) = if values.len() >= 2 {
@@ -1197,27 +2197,33 @@ pub fn list_vitals_stats(
             Ok(ListVitalsStatsResponse {
                 vital_type: vt.as_str().to_string(),
                 readings_analyzed,
+                outlier_sd,
+                precision,
                 date_range,
                 weight: Some(WeightStats {
                     count: readings_analyzed,
                     unit,
                     stats,
-                    total_change: (total_change * 100.0).round() / 100.0,
-                    avg_change_per_reading: (avg_change * 100.0).round() / 100.0,
+                    total_change: round_to(total_change, precision),
+                    avg_change_per_reading: round_to(avg_change, precision),
                 }),
                 blood_pressure: None,
                 heart_rate: None,
                 oxygen_saturation: None,
                 glucose: None,
+                water: None,
+                custom: None,
             })
         }
 
         VitalType::BloodPressure => {
+            let convert = |mmhg: f64| if bp_unit == BpDisplayUnit::Kpa { bp_to_kpa(mmhg) } else { mmhg };
+
             let systolic_values: Vec<TimestampedValue> = vitals
                 .iter()
                 .map(|v| TimestampedValue {
                     timestamp: v.timestamp.clone(),
-                    value: v.value1,
+                    value: convert(v.value1),
                 })
                 .collect();
 
@@ -1225,7 +2231,7 @@ pub fn list_vitals_stats(
                 .iter()
                 .filter_map(|v| v.value2.map(|val| TimestampedValue {
                     timestamp: v.timestamp.clone(),
-                    value: val,
+                    value: convert(val),
                 }))
                 .collect();
 
@@ -1234,21 +2240,24 @@ pub fn list_vitals_stats(
                 .iter()
                 .filter_map(|v| v.value2.map(|d| TimestampedValue {
                     timestamp: v.timestamp.clone(),
-                    value: v.value1 - d,
+                    value: convert(v.value1 - d),
                 }))
                 .collect();
 
-            let systolic_stats = calculate_single_stats(&systolic_values);
-            let diastolic_stats = calculate_single_stats(&diastolic_values);
-            let pulse_pressure_stats = calculate_single_stats(&pulse_pressure_values);
+            let systolic_stats = calculate_single_stats(&systolic_values, outlier_sd, precision);
+            let diastolic_stats = calculate_single_stats(&diastolic_values, outlier_sd, precision);
+            let pulse_pressure_stats = calculate_single_stats(&pulse_pressure_values, outlier_sd, precision);
 
             Ok(ListVitalsStatsResponse {
                 vital_type: vt.as_str().to_string(),
                 readings_analyzed,
+                outlier_sd,
+                precision,
                 date_range,
                 weight: None,
                 blood_pressure: Some(BloodPressureStats {
                     count: readings_analyzed,
+                    unit: bp_unit.as_str().to_string(),
                     systolic: systolic_stats,
                     diastolic: diastolic_stats,
                     pulse_pressure: pulse_pressure_stats,
@@ -1256,6 +2265,8 @@ pub fn list_vitals_stats(
                 heart_rate: None,
                 oxygen_saturation: None,
                 glucose: None,
+                water: None,
+                custom: None,
             })
         }
 
@@ -1268,12 +2279,31 @@ pub fn list_vitals_stats(
                 })
                 .collect();
 
-            let stats = calculate_single_stats(&values);
+            let stats = calculate_single_stats(&values, outlier_sd, precision);
             let unit = vitals.first().map(|v| v.unit.clone()).unwrap_or("bpm".to_string());
 
+            // Split out post-exercise spikes so the resting trend stays meaningful
+            let exercise_groups = VitalGroup::exercise_group_ids(&conn)
+                .map_err(|e| ToolError::Db(format!("Failed to get exercise groups: {}", e)))?;
+            let mut resting_values = Vec::new();
+            let mut active_values = Vec::new();
+            for v in &vitals {
+                let tv = TimestampedValue {
+                    timestamp: v.timestamp.clone(),
+                    value: v.value1,
+                };
+                if v.group_id.is_some_and(|g| exercise_groups.contains(&g)) {
+                    active_values.push(tv);
+                } else {
+                    resting_values.push(tv);
+                }
+            }
+
             Ok(ListVitalsStatsResponse {
                 vital_type: vt.as_str().to_string(),
                 readings_analyzed,
+                outlier_sd,
+                precision,
                 date_range,
                 weight: None,
                 blood_pressure: None,
@@ -1281,9 +2311,13 @@ pub fn list_vitals_stats(
                     count: readings_analyzed,
                     unit,
                     stats,
+                    resting: calculate_single_stats(&resting_values, outlier_sd, precision),
+                    active: calculate_single_stats(&active_values, outlier_sd, precision),
                 }),
                 oxygen_saturation: None,
                 glucose: None,
+                water: None,
+                custom: None,
             })
         }
 
@@ -1296,7 +2330,7 @@ pub fn list_vitals_stats(
                 })
                 .collect();
 
-            let stats = calculate_single_stats(&values);
+            let stats = calculate_single_stats(&values, outlier_sd, precision);
             let unit = vitals.first().map(|v| v.unit.clone()).unwrap_or("%".to_string());
 
             // Count concerning readings
@@ -1306,6 +2340,8 @@ pub fn list_vitals_stats(
             Ok(ListVitalsStatsResponse {
                 vital_type: vt.as_str().to_string(),
                 readings_analyzed,
+                outlier_sd,
+                precision,
                 date_range,
                 weight: None,
                 blood_pressure: None,
@@ -1318,6 +2354,8 @@ pub fn list_vitals_stats(
                     below_90_count,
                 }),
                 glucose: None,
+                water: None,
+                custom: None,
             })
         }
 
@@ -1330,7 +2368,7 @@ pub fn list_vitals_stats(
                 })
                 .collect();
 
-            let stats = calculate_single_stats(&values);
+            let stats = calculate_single_stats(&values, outlier_sd, precision);
             let unit = vitals.first().map(|v| v.unit.clone()).unwrap_or("mg/dL".to_string());
 
             // Count concerning readings
@@ -1340,6 +2378,8 @@ pub fn list_vitals_stats(
             Ok(ListVitalsStatsResponse {
                 vital_type: vt.as_str().to_string(),
                 readings_analyzed,
+                outlier_sd,
+                precision,
                 date_range,
                 weight: None,
                 blood_pressure: None,
@@ -1352,7 +2392,1997 @@ pub fn list_vitals_stats(
                     low_count,
                     high_count,
                 }),
+                water: None,
+                custom: None,
+            })
+        }
+        VitalType::Water => {
+            let values: Vec<TimestampedValue> = vitals
+                .iter()
+                .map(|v| TimestampedValue {
+                    timestamp: v.timestamp.clone(),
+                    value: v.value1,
+                })
+                .collect();
+
+            let stats = calculate_single_stats(&values, outlier_sd, precision);
+            let unit = vitals.first().map(|v| v.unit.clone()).unwrap_or("ml".to_string());
+
+            // Total per calendar day, in ml
+            let mut daily_totals: HashMap<&str, f64> = HashMap::new();
+            for v in &vitals {
+                let day = v.timestamp.get(..10).unwrap_or(&v.timestamp);
+                let ml = v.value1 * crate::nutrition::ml_per_unit(&v.unit).unwrap_or(1.0);
+                *daily_totals.entry(day).or_insert(0.0) += ml;
+            }
+            let days_logged = daily_totals.len() as i64;
+            let average_daily_total = daily_totals.values().sum::<f64>() / days_logged as f64;
+
+            Ok(ListVitalsStatsResponse {
+                vital_type: vt.as_str().to_string(),
+                readings_analyzed,
+                outlier_sd,
+                precision,
+                date_range,
+                weight: None,
+                blood_pressure: None,
+                heart_rate: None,
+                oxygen_saturation: None,
+                glucose: None,
+                water: Some(WaterStats {
+                    count: readings_analyzed,
+                    unit,
+                    stats,
+                    days_logged,
+                    average_daily_total: round_to(average_daily_total, precision),
+                }),
+                custom: None,
+            })
+        }
+        VitalType::Custom => {
+            let custom = custom.ok_or_else(|| ToolError::Validation("Custom readings need a registered vital type name".to_string()))?;
+            let values: Vec<TimestampedValue> = vitals
+                .iter()
+                .map(|v| TimestampedValue {
+                    timestamp: v.timestamp.clone(),
+                    value: v.value1,
+                })
+                .collect();
+            let value2s: Vec<TimestampedValue> = vitals
+                .iter()
+                .filter_map(|v| v.value2.map(|value| TimestampedValue {
+                    timestamp: v.timestamp.clone(),
+                    value,
+                }))
+                .collect();
+
+            let stats = calculate_single_stats(&values, outlier_sd, precision);
+            let value2 = (!value2s.is_empty()).then(|| calculate_single_stats(&value2s, outlier_sd, precision));
+
+            Ok(ListVitalsStatsResponse {
+                vital_type: type_name,
+                readings_analyzed,
+                outlier_sd,
+                precision,
+                date_range,
+                weight: None,
+                blood_pressure: None,
+                heart_rate: None,
+                oxygen_saturation: None,
+                glucose: None,
+                water: None,
+                custom: Some(CustomVitalStats {
+                    name: custom.name,
+                    count: readings_analyzed,
+                    unit: custom.unit,
+                    stats,
+                    value2_label: custom.value2_label,
+                    value2,
+                }),
+            })
+        }
+    }
+}
+
+// ============================================================================
+// Custom Vital Types
+// ============================================================================
+
+/// Response for register_vital_type
+#[derive(Debug, Serialize)]
+pub struct RegisterVitalTypeResponse {
+    pub vital_type: CustomVitalType,
+}
+
+/// One entry in list_vital_types
+#[derive(Debug, Serialize)]
+pub struct VitalTypeInfo {
+    pub name: String,
+    pub display_name: String,
+    pub unit: String,
+    pub value_label: String,
+    pub value2_label: Option<String>,
+    pub custom: bool,
+    /// Registry ID of a custom type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_type_id: Option<i64>,
+    pub reading_count: i64,
+}
+
+/// Response for list_vital_types
+#[derive(Debug, Serialize)]
+pub struct ListVitalTypesResponse {
+    pub vital_types: Vec<VitalTypeInfo>,
+    pub total: usize,
+}
+
+/// Register a custom vital type so add_vital and list_vitals_stats accept its name
+pub fn register_vital_type(
+    db: &Database,
+    name: &str,
+    unit: &str,
+    value2_label: Option<&str>,
+) -> Result<RegisterVitalTypeResponse, ToolError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ToolError::Validation("name must not be empty".to_string()));
+    }
+    if VitalType::from_str(name).is_some() || name.eq_ignore_ascii_case(VitalType::Custom.as_str()) {
+        return Err(ToolError::Validation(format!("'{}' is a built-in vital type name", name)));
+    }
+    let value2_label = value2_label.map(str::trim).filter(|l| !l.is_empty());
+
+    let conn = db.get_conn()?;
+
+    if let Some(existing) = CustomVitalType::get_by_name(&conn, name)? {
+        return Err(ToolError::Validation(format!("Vital type '{}' is already registered (id {})", existing.name, existing.id)));
+    }
+
+    let vital_type = CustomVitalType::create(&conn, &CustomVitalTypeCreate {
+        name: name.to_string(),
+        unit: unit.trim().to_string(),
+        value2_label: value2_label.map(String::from),
+    })
+    .map_err(|e| ToolError::Db(format!("Failed to register vital type: {}", e)))?;
+
+    Ok(RegisterVitalTypeResponse { vital_type })
+}
+
+/// List built-in and custom vital types with how many readings each has
+pub fn list_vital_types(db: &Database) -> Result<ListVitalTypesResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let mut builtin_counts: HashMap<String, i64> = HashMap::new();
+    let mut custom_counts: HashMap<i64, i64> = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT vital_type, custom_type_id, COUNT(*) FROM vitals GROUP BY vital_type, custom_type_id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?, row.get::<_, i64>(2)?))
+    })?;
+    for row in rows {
+        match row? {
+            (_, Some(custom_id), count) => { custom_counts.insert(custom_id, count); }
+            (vital_type, None, count) => { builtin_counts.insert(vital_type, count); }
+        }
+    }
+
+    let mut vital_types: Vec<VitalTypeInfo> = [
+        VitalType::Weight,
+        VitalType::BloodPressure,
+        VitalType::HeartRate,
+        VitalType::OxygenSaturation,
+        VitalType::Glucose,
+        VitalType::Water,
+    ]
+    .into_iter()
+    .map(|vt| {
+        let (value_label, value2_label) = vt.value_labels();
+        VitalTypeInfo {
+            name: vt.as_str().to_string(),
+            display_name: vt.display_name().to_string(),
+            unit: vt.default_unit().to_string(),
+            value_label: value_label.to_string(),
+            value2_label: value2_label.map(String::from),
+            custom: false,
+            custom_type_id: None,
+            reading_count: builtin_counts.get(vt.as_str()).copied().unwrap_or(0),
+        }
+    })
+    .collect();
+
+    let customs = CustomVitalType::list(&conn)
+        .map_err(|e| ToolError::Db(format!("Failed to list vital types: {}", e)))?;
+    vital_types.extend(customs.into_iter().map(|c| VitalTypeInfo {
+        reading_count: custom_counts.get(&c.id).copied().unwrap_or(0),
+        display_name: c.name.clone(),
+        name: c.name,
+        unit: c.unit,
+        value_label: "Value".to_string(),
+        value2_label: c.value2_label,
+        custom: true,
+        custom_type_id: Some(c.id),
+    }));
+
+    let total = vital_types.len();
+    Ok(ListVitalTypesResponse { vital_types, total })
+}
+
+// ============================================================================
+// Vital Histogram
+// ============================================================================
+
+/// Upper bound on bins per histogram, so a tiny bin_width can't explode the response
+const MAX_HISTOGRAM_BINS: usize = 500;
+
+/// One histogram bar covering [low, high)
+#[derive(Debug, Serialize, PartialEq)]
+pub struct HistogramBin {
+    pub low: f64,
+    pub high: f64,
+    pub count: usize,
+}
+
+/// Binned counts for one measured value
+#[derive(Debug, Serialize)]
+pub struct Histogram {
+    /// "value" for single-value vitals, "systolic" / "diastolic" for BP
+    pub series: String,
+    pub unit: String,
+    pub reading_count: usize,
+    /// Contiguous bins from the lowest to the highest reading, empty bins included
+    pub bins: Vec<HistogramBin>,
+}
+
+/// Response for vital_histogram
+#[derive(Debug, Serialize)]
+pub struct VitalHistogramResponse {
+    pub vital_type: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub bin_width: f64,
+    /// One histogram, or two (systolic, diastolic) for blood pressure
+    pub histograms: Vec<Histogram>,
+}
+
+/// Count values into contiguous bins of `bin_width` aligned to multiples of it
+fn histogram_bins(values: &[f64], bin_width: f64) -> Result<Vec<HistogramBin>, ToolError> {
+    let Some(min) = values.iter().copied().reduce(f64::min) else {
+        return Ok(Vec::new());
+    };
+    let max = values.iter().copied().fold(min, f64::max);
+
+    let first = (min / bin_width).floor() as i64;
+    let last = (max / bin_width).floor() as i64;
+    let bin_count = (last - first + 1) as usize;
+    if bin_count > MAX_HISTOGRAM_BINS {
+        return Err(ToolError::Validation(format!(
+            "bin_width {} gives {} bins (max {}); use a wider bin",
+            bin_width, bin_count, MAX_HISTOGRAM_BINS
+        )));
+    }
+
+    let mut bins: Vec<HistogramBin> = (first..=last)
+        .map(|i| HistogramBin {
+            low: round_to(i as f64 * bin_width, 6),
+            high: round_to((i + 1) as f64 * bin_width, 6),
+            count: 0,
+        })
+        .collect();
+    for v in values {
+        let idx = ((v / bin_width).floor() as i64 - first) as usize;
+        bins[idx].count += 1;
+    }
+    Ok(bins)
+}
+
+/// Binned counts of a vital's readings (value1, plus value2 for BP) for bar charts
+pub fn vital_histogram(
+    db: &Database,
+    vital_type: &str,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+    bin_width: f64,
+) -> Result<VitalHistogramResponse, ToolError> {
+    if bin_width <= 0.0 || !bin_width.is_finite() {
+        return Err(ToolError::Validation("bin_width must be greater than 0".to_string()));
+    }
+    if let Some(start) = start_date {
+        check_date("start_date", start)?;
+    }
+    if let Some(end) = end_date {
+        check_date("end_date", end)?;
+    }
+
+    let conn = db.get_conn()?;
+
+    let (vt, custom) = resolve_vital_type(&conn, vital_type)?;
+
+    let mut vitals = if start_date.is_some() || end_date.is_some() {
+        let start = start_date.unwrap_or("1900-01-01");
+        let end = end_date.unwrap_or("2100-12-31");
+        Vital::list_by_date_range(&conn, start, end, Some(vt))
+            .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?
+    } else if let Some(custom) = &custom {
+        Vital::list_by_custom_type(&conn, custom.id, Some(10000))
+            .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?
+    } else {
+        Vital::list_by_type(&conn, vt, Some(10000))
+            .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?
+    };
+    if let Some(custom) = &custom {
+        vitals.retain(|v| v.custom_type_id == Some(custom.id));
+    }
+
+    let unit = vitals.first().map(|v| v.unit.clone()).unwrap_or_default();
+    let histogram = |series: &str, values: Vec<f64>| -> Result<Histogram, ToolError> {
+        Ok(Histogram {
+            series: series.to_string(),
+            unit: unit.clone(),
+            reading_count: values.len(),
+            bins: histogram_bins(&values, bin_width)?,
+        })
+    };
+
+    let value2_label = custom.as_ref().and_then(|c| c.value2_label.as_deref());
+    let histograms = if vt == VitalType::BloodPressure {
+        vec![
+            histogram("systolic", vitals.iter().map(|v| v.value1).collect())?,
+            histogram("diastolic", vitals.iter().filter_map(|v| v.value2).collect())?,
+        ]
+    } else if let Some(label) = value2_label {
+        vec![
+            histogram("value", vitals.iter().map(|v| v.value1).collect())?,
+            histogram(label, vitals.iter().filter_map(|v| v.value2).collect())?,
+        ]
+    } else {
+        vec![histogram("value", vitals.iter().map(|v| v.value1).collect())?]
+    };
+
+    Ok(VitalHistogramResponse {
+        vital_type: custom.map(|c| c.name).unwrap_or_else(|| vt.as_str().to_string()),
+        start_date: start_date.map(String::from),
+        end_date: end_date.map(String::from),
+        bin_width,
+        histograms,
+    })
+}
+
+// ============================================================================
+// Vital Calendar (Heatmap)
+// ============================================================================
+
+/// Reading count for a single day
+#[derive(Debug, Serialize)]
+pub struct VitalCalendarDay {
+    pub date: String,
+    pub count: i64,
+    /// Average value1 for the day (only when a vital type is specified)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_value: Option<f64>,
+}
+
+/// Response for vitals_calendar
+#[derive(Debug, Serialize)]
+pub struct VitalsCalendarResponse {
+    pub year: i32,
+    pub vital_type: Option<String>,
+    pub total_readings: i64,
+    pub days_with_readings: usize,
+    /// Only days with at least one reading are included
+    pub days: Vec<VitalCalendarDay>,
+}
+
+/// Count readings per day across a year, for calendar heatmap visualization
+pub fn vitals_calendar(
+    db: &Database,
+    year: i32,
+    vital_type: Option<&str>,
+) -> Result<VitalsCalendarResponse, ToolError> {
+    let conn = db.get_conn()?;
+
+    let (vt, custom) = match vital_type {
+        Some(t) => {
+            let (vt, custom) = resolve_vital_type(&conn, t)?;
+            (Some(vt), custom)
+        }
+        None => (None, None),
+    };
+
+    let start = format!("{:04}-01-01", year);
+    let end = format!("{:04}-01-01", year + 1);
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT substr(timestamp, 1, 10) AS day, COUNT(*) AS count, AVG(value1) AS avg_value
+        FROM vitals
+        WHERE timestamp >= ?1 AND timestamp < ?2
+          AND (?3 IS NULL OR vital_type = ?3)
+          AND (?4 IS NULL OR custom_type_id = ?4)
+        GROUP BY day
+        ORDER BY day
+        "#
+    ).map_err(|e| ToolError::Db(format!("Failed to prepare query: {}", e)))?;
+
+    let type_str = vt.map(|t| t.as_str());
+    let custom_type_id = custom.as_ref().map(|c| c.id);
+    let days: Vec<VitalCalendarDay> = stmt
+        .query_map(rusqlite::params![start, end, type_str, custom_type_id], |row| {
+            let avg: Option<f64> = row.get("avg_value")?;
+            Ok(VitalCalendarDay {
+                date: row.get("day")?,
+                count: row.get("count")?,
+                avg_value: if type_str.is_some() {
+                    avg.map(|a| round_to(a, 2))
+                } else {
+                    None
+                },
+            })
+        })
+        .map_err(|e| ToolError::Db(format!("Failed to execute query: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ToolError::Db(format!("Failed to collect results: {}", e)))?;
+
+    let total_readings = days.iter().map(|d| d.count).sum();
+
+    Ok(VitalsCalendarResponse {
+        year,
+        vital_type: custom.map(|c| c.name).or_else(|| type_str.map(|s| s.to_string())),
+        total_readings,
+        days_with_readings: days.len(),
+        days,
+    })
+}
+
+// ============================================================================
+// Vital Tracking Gaps
+// ============================================================================
+
+/// Longest run of consecutive days without a reading
+#[derive(Debug, Serialize, PartialEq)]
+pub struct GapStreak {
+    pub start_date: String,
+    pub end_date: String,
+    pub days: i64,
+}
+
+/// Response for vital_tracking_gaps
+#[derive(Debug, Serialize)]
+pub struct VitalTrackingGapsResponse {
+    pub vital_type: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub total_days: i64,
+    pub days_with_readings: i64,
+    /// Dates in the range with no reading of this type
+    pub missing_dates: Vec<String>,
+    pub longest_gap: Option<GapStreak>,
+}
+
+/// Longest run of consecutive dates in an ascending list of missing dates
+fn longest_gap(missing: &[chrono::NaiveDate]) -> Option<GapStreak> {
+    let mut best: Option<(chrono::NaiveDate, chrono::NaiveDate)> = None;
+    let mut run_start = *missing.first()?;
+    let mut prev = run_start;
+
+    for &date in &missing[1..] {
+        if date != prev + chrono::Duration::days(1) {
+            if best.is_none_or(|(s, e)| prev - run_start > e - s) {
+                best = Some((run_start, prev));
+            }
+            run_start = date;
+        }
+        prev = date;
+    }
+    if best.is_none_or(|(s, e)| prev - run_start > e - s) {
+        best = Some((run_start, prev));
+    }
+
+    best.map(|(start, end)| GapStreak {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        days: (end - start).num_days() + 1,
+    })
+}
+
+/// Dates between start_date and end_date (inclusive) with no reading of the given type
+pub fn vital_tracking_gaps(
+    db: &Database,
+    vital_type: &str,
+    start_date: &str,
+    end_date: &str,
+) -> Result<VitalTrackingGapsResponse, ToolError> {
+    let start = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid start_date '{}'. Use YYYY-MM-DD", start_date)))?;
+    let end = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid end_date '{}'. Use YYYY-MM-DD", end_date)))?;
+    if end < start {
+        return Err(ToolError::Validation("end_date must be on or after start_date".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let (vt, custom) = resolve_vital_type(&conn, vital_type)?;
+
+    let end_exclusive = (end + chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT substr(timestamp, 1, 10) AS day
+        FROM vitals
+        WHERE vital_type = ?1 AND (?4 IS NULL OR custom_type_id = ?4)
+          AND timestamp >= ?2 AND timestamp < ?3
+        GROUP BY day
+        "#
+    ).map_err(|e| ToolError::Db(format!("Failed to prepare query: {}", e)))?;
+
+    let custom_type_id = custom.as_ref().map(|c| c.id);
+    let logged: std::collections::HashSet<String> = stmt
+        .query_map(rusqlite::params![vt.as_str(), start_date, end_exclusive, custom_type_id], |row| row.get(0))
+        .map_err(|e| ToolError::Db(format!("Failed to execute query: {}", e)))?
+        .collect::<Result<_, _>>()
+        .map_err(|e| ToolError::Db(format!("Failed to collect results: {}", e)))?;
+
+    let missing: Vec<chrono::NaiveDate> = start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .filter(|d| !logged.contains(&d.format("%Y-%m-%d").to_string()))
+        .collect();
+
+    let total_days = (end - start).num_days() + 1;
+
+    Ok(VitalTrackingGapsResponse {
+        vital_type: custom.map(|c| c.name).unwrap_or_else(|| vt.as_str().to_string()),
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        total_days,
+        days_with_readings: total_days - missing.len() as i64,
+        longest_gap: longest_gap(&missing),
+        missing_dates: missing.iter().map(|d| d.format("%Y-%m-%d").to_string()).collect(),
+    })
+}
+
+/// A weight reading offered as a backfill hint
+#[derive(Debug, Serialize)]
+pub struct NearbyWeight {
+    pub date: String,
+    pub value: f64,
+    pub unit: String,
+}
+
+/// A meal-logged day with no weight reading
+#[derive(Debug, Serialize)]
+pub struct DayMissingWeight {
+    pub date: String,
+    /// Most recent weight before this date
+    pub previous_weight: Option<NearbyWeight>,
+    /// First weight after this date
+    pub next_weight: Option<NearbyWeight>,
+}
+
+/// Response for days_missing_weight
+#[derive(Debug, Serialize)]
+pub struct DaysMissingWeightResponse {
+    pub start_date: String,
+    pub end_date: String,
+    /// Days in the range with at least one (non-planned) meal entry
+    pub meal_days: usize,
+    pub count: usize,
+    pub days: Vec<DayMissingWeight>,
+}
+
+/// Days in a range that have meals logged but no weight reading, each with the
+/// nearest recorded weights on either side as a backfill hint
+pub fn days_missing_weight(db: &Database, start_date: &str, end_date: &str) -> Result<DaysMissingWeightResponse, ToolError> {
+    let start = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid start_date '{}'. Use YYYY-MM-DD", start_date)))?;
+    let end = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid end_date '{}'. Use YYYY-MM-DD", end_date)))?;
+    if end < start {
+        return Err(ToolError::Validation("end_date must be on or after start_date".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT d.date,
+               EXISTS (
+                   SELECT 1 FROM vitals v
+                   WHERE v.vital_type = 'weight' AND substr(v.timestamp, 1, 10) = d.date
+               ) AS has_weight
+        FROM days d
+        WHERE d.date >= ?1 AND d.date <= ?2
+          AND EXISTS (SELECT 1 FROM meal_entries me WHERE me.day_id = d.id AND me.planned = 0)
+        ORDER BY d.date
+        "#
+    )?;
+    let meal_days: Vec<(String, bool)> = stmt
+        .query_map(rusqlite::params![start_date, end_date], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let nearby = |sql: &str, date: &str| -> Result<Option<NearbyWeight>, ToolError> {
+        let result = conn.query_row(sql, [date], |row| {
+            let timestamp: String = row.get(0)?;
+            Ok(NearbyWeight {
+                date: timestamp.chars().take(10).collect(),
+                value: row.get(1)?,
+                unit: row.get(2)?,
             })
+        });
+        match result {
+            Ok(weight) => Ok(Some(weight)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(ToolError::Db(format!("Failed to find nearby weight: {}", e))),
+        }
+    };
+
+    let mut days = Vec::new();
+    for (date, _) in meal_days.iter().filter(|(_, has_weight)| !has_weight) {
+        days.push(DayMissingWeight {
+            previous_weight: nearby(
+                "SELECT timestamp, value1, unit FROM vitals WHERE vital_type = 'weight' AND substr(timestamp, 1, 10) < ?1 ORDER BY timestamp DESC LIMIT 1",
+                date,
+            )?,
+            next_weight: nearby(
+                "SELECT timestamp, value1, unit FROM vitals WHERE vital_type = 'weight' AND substr(timestamp, 1, 10) > ?1 ORDER BY timestamp ASC LIMIT 1",
+                date,
+            )?,
+            date: date.clone(),
+        });
+    }
+
+    Ok(DaysMissingWeightResponse {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        meal_days: meal_days.len(),
+        count: days.len(),
+        days,
+    })
+}
+
+// ============================================================================
+// Weekly Blood Pressure Averages
+// ============================================================================
+
+/// ACC/AHA 2017 blood pressure category for a systolic/diastolic pair (mmHg)
+pub fn classify_bp(systolic: f64, diastolic: f64) -> &'static str {
+    if systolic > 180.0 || diastolic > 120.0 {
+        "hypertensive_crisis"
+    } else if systolic >= 140.0 || diastolic >= 90.0 {
+        "stage_2_hypertension"
+    } else if systolic >= 130.0 || diastolic >= 80.0 {
+        "stage_1_hypertension"
+    } else if systolic >= 120.0 {
+        "elevated"
+    } else {
+        "normal"
+    }
+}
+
+/// Average blood pressure for one week
+#[derive(Debug, Serialize)]
+pub struct WeeklyBpAverage {
+    /// ISO week containing the week's Monday, e.g. "2026-W03"
+    pub week: String,
+    /// First day of the week (Monday or Sunday, per week_start)
+    pub week_start: String,
+    /// Last day of the week
+    pub week_end: String,
+    pub count: usize,
+    pub avg_systolic: f64,
+    pub avg_diastolic: f64,
+    /// classify_bp applied to the weekly averages
+    pub classification: String,
+}
+
+/// Response for weekly_bp_averages
+#[derive(Debug, Serialize)]
+pub struct WeeklyBpAveragesResponse {
+    pub start_date: String,
+    pub end_date: String,
+    pub unit: String,
+    /// "monday" or "sunday"
+    pub week_start: String,
+    pub total_readings: usize,
+    /// Weeks with at least one reading, oldest first
+    pub weeks: Vec<WeeklyBpAverage>,
+}
+
+/// Parse a first-day-of-week setting: "mon"/"monday" or "sun"/"sunday"
+pub fn parse_week_start(value: &str) -> Result<chrono::Weekday, ToolError> {
+    match value.trim().to_lowercase().as_str() {
+        "mon" | "monday" => Ok(chrono::Weekday::Mon),
+        "sun" | "sunday" => Ok(chrono::Weekday::Sun),
+        _ => Err(ToolError::Validation(format!("Invalid week_start '{}'. Must be 'mon' or 'sun'", value))),
+    }
+}
+
+/// First day of the week containing `date`, for weeks starting on `week_start`
+fn week_bucket(date: chrono::NaiveDate, week_start: chrono::Weekday) -> chrono::NaiveDate {
+    date.week(week_start).first_day()
+}
+
+/// Average blood pressure readings per week between two dates (inclusive).
+/// Weeks run Monday-Sunday (ISO) or Sunday-Saturday depending on `week_start`.
+pub fn weekly_bp_averages(
+    db: &Database,
+    start_date: &str,
+    end_date: &str,
+    week_start: chrono::Weekday,
+) -> Result<WeeklyBpAveragesResponse, ToolError> {
+    use chrono::Datelike;
+
+    let start = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid start_date '{}'. Use YYYY-MM-DD", start_date)))?;
+    let end = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid end_date '{}'. Use YYYY-MM-DD", end_date)))?;
+    if end < start {
+        return Err(ToolError::Validation("end_date must be on or after start_date".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let readings = Vital::list_by_date_range(&conn, start_date, end_date, Some(VitalType::BloodPressure))
+        .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?;
+
+    // first day of week -> (systolic values, diastolic values)
+    let mut by_week: std::collections::BTreeMap<chrono::NaiveDate, (Vec<f64>, Vec<f64>)> = std::collections::BTreeMap::new();
+    let mut total_readings = 0;
+    for reading in &readings {
+        let (Some(ts), Some(diastolic)) = (canonical_datetime(&reading.timestamp), reading.value2) else {
+            continue;
+        };
+        let entry = by_week.entry(week_bucket(ts.date(), week_start)).or_default();
+        entry.0.push(reading.value1);
+        entry.1.push(diastolic);
+        total_readings += 1;
+    }
+
+    let weeks = by_week
+        .into_iter()
+        .map(|(first_day, (systolic, diastolic))| {
+            let days_to_monday = (7 - first_day.weekday().num_days_from_monday()) % 7;
+            let iso = (first_day + chrono::Duration::days(days_to_monday as i64)).iso_week();
+            let avg_systolic = round_to(systolic.iter().sum::<f64>() / systolic.len() as f64, 1);
+            let avg_diastolic = round_to(diastolic.iter().sum::<f64>() / diastolic.len() as f64, 1);
+            WeeklyBpAverage {
+                week: format!("{}-W{:02}", iso.year(), iso.week()),
+                week_start: first_day.format("%Y-%m-%d").to_string(),
+                week_end: (first_day + chrono::Duration::days(6)).format("%Y-%m-%d").to_string(),
+                count: systolic.len(),
+                avg_systolic,
+                avg_diastolic,
+                classification: classify_bp(avg_systolic, avg_diastolic).to_string(),
+            }
+        })
+        .collect();
+
+    Ok(WeeklyBpAveragesResponse {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        unit: "mmHg".to_string(),
+        week_start: if week_start == chrono::Weekday::Sun { "sunday" } else { "monday" }.to_string(),
+        total_readings,
+        weeks,
+    })
+}
+
+// ============================================================================
+// Estimated A1c
+// ============================================================================
+
+/// Fewer glucose readings than this make the estimate unreliable
+const MIN_A1C_READINGS: usize = 14;
+
+/// ADAG estimated A1c (%) from mean glucose in mg/dL
+pub fn adag_a1c(avg_glucose_mg_dl: f64) -> f64 {
+    (avg_glucose_mg_dl + 46.7) / 28.7
+}
+
+/// Response for estimate_a1c
+#[derive(Debug, Serialize)]
+pub struct EstimateA1cResponse {
+    pub start_date: String,
+    pub end_date: String,
+    pub reading_count: usize,
+    /// Timestamps of the first and last readings used
+    pub first_reading: Option<String>,
+    pub last_reading: Option<String>,
+    pub avg_glucose_mg_dl: Option<f64>,
+    /// (avg + 46.7) / 28.7
+    pub estimated_a1c_percent: Option<f64>,
+    pub warning: Option<String>,
+}
+
+/// Estimate A1c from the mean glucose between two dates (inclusive) using the ADAG formula
+pub fn estimate_a1c(db: &Database, start_date: &str, end_date: &str) -> Result<EstimateA1cResponse, ToolError> {
+    let start = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid start_date '{}'. Use YYYY-MM-DD", start_date)))?;
+    let end = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid end_date '{}'. Use YYYY-MM-DD", end_date)))?;
+    if end < start {
+        return Err(ToolError::Validation("end_date must be on or after start_date".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let readings = Vital::list_by_date_range(&conn, start_date, end_date, Some(VitalType::Glucose))
+        .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?;
+
+    let reading_count = readings.len();
+    // Readings come back newest first
+    let first_reading = readings.last().map(|v| v.timestamp.clone());
+    let last_reading = readings.first().map(|v| v.timestamp.clone());
+
+    // Readings logged in mmol/L are averaged in mg/dL
+    let mg_dl = |v: &Vital| if v.unit.eq_ignore_ascii_case("mmol/l") { v.value1 * MG_DL_PER_MMOL_L } else { v.value1 };
+    let avg = (reading_count > 0)
+        .then(|| readings.iter().map(mg_dl).sum::<f64>() / reading_count as f64);
+
+    let warning = if reading_count == 0 {
+        Some("No glucose readings in this range".to_string())
+    } else if reading_count < MIN_A1C_READINGS {
+        Some(format!(
+            "Only {} glucose reading(s); at least {} spread over the period are needed for a meaningful estimate",
+            reading_count, MIN_A1C_READINGS
+        ))
+    } else {
+        None
+    };
+
+    Ok(EstimateA1cResponse {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        reading_count,
+        first_reading,
+        last_reading,
+        avg_glucose_mg_dl: avg.map(|a| round_to(a, 1)),
+        estimated_a1c_percent: avg.map(|a| round_to(adag_a1c(a), 1)),
+        warning,
+    })
+}
+
+// ============================================================================
+// Water Intake
+// ============================================================================
+
+/// Response for log_water
+#[derive(Debug, Serialize)]
+pub struct LogWaterResponse {
+    pub id: i64,
+    pub amount_ml: f64,
+    pub timestamp: String,
+    /// Water logged so far on the reading's date, including this entry
+    pub day_total_ml: f64,
+}
+
+/// Response for daily_water_total
+#[derive(Debug, Serialize)]
+pub struct DailyWaterTotalResponse {
+    pub date: String,
+    pub entry_count: i64,
+    pub total_ml: f64,
+    pub goal_ml: f64,
+    /// Amount still needed to reach the goal (0 once reached)
+    pub remaining_ml: f64,
+    pub percent_of_goal: f64,
+    pub goal_met: bool,
+}
+
+/// Sum a date's water vitals in ml, returning (total, entry_count)
+fn water_total_for_date(conn: &rusqlite::Connection, date: &str) -> Result<(f64, i64), ToolError> {
+    let mut stmt = conn.prepare(
+        "SELECT value1, unit FROM vitals WHERE vital_type = 'water' AND substr(timestamp, 1, 10) = ?1"
+    ).map_err(|e| ToolError::Db(format!("Failed to prepare query: {}", e)))?;
+
+    let entries: Vec<(f64, String)> = stmt
+        .query_map([date], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| ToolError::Db(format!("Failed to query water intake: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ToolError::Db(format!("Failed to read water intake: {}", e)))?;
+
+    // Entries added through add_vital may use another volume unit
+    let total = entries
+        .iter()
+        .map(|(value, unit)| value * crate::nutrition::ml_per_unit(unit).unwrap_or(1.0))
+        .sum();
+
+    Ok((total, entries.len() as i64))
+}
+
+/// Log a water intake reading in ml
+pub fn log_water(db: &Database, amount_ml: f64, timestamp: Option<&str>, clock: &dyn Clock) -> Result<LogWaterResponse, ToolError> {
+    let reading = VitalReading {
+        vital_type: "water".to_string(),
+        value1: amount_ml,
+        value2: None,
+        unit: Some("ml".to_string()),
+        timestamp: timestamp.map(String::from),
+        group_id: None,
+        notes: None,
+    };
+    let added = add_vital(db, reading, clock)?;
+
+    let conn = db.get_conn()?;
+    let date = added.timestamp.get(..10).unwrap_or(&added.timestamp);
+    let (day_total, _) = water_total_for_date(&conn, date)?;
+
+    Ok(LogWaterResponse {
+        id: added.id,
+        amount_ml,
+        timestamp: added.timestamp,
+        day_total_ml: round_to(day_total, 2),
+    })
+}
+
+/// Total a day's water intake and compare it to a goal
+pub fn daily_water_total(db: &Database, date: &str, goal_ml: f64) -> Result<DailyWaterTotalResponse, ToolError> {
+    if goal_ml <= 0.0 {
+        return Err(ToolError::Validation("goal_ml must be greater than 0".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+    let (total, entry_count) = water_total_for_date(&conn, date)?;
+
+    Ok(DailyWaterTotalResponse {
+        date: date.to_string(),
+        entry_count,
+        total_ml: round_to(total, 2),
+        goal_ml,
+        remaining_ml: round_to((goal_ml - total).max(0.0), 2),
+        percent_of_goal: round_to(total / goal_ml * 100.0, 2),
+        goal_met: total >= goal_ml,
+    })
+}
+
+// ============================================================================
+// Weight Plateau
+// ============================================================================
+
+/// Response for detect_weight_plateau
+#[derive(Debug, Serialize)]
+pub struct WeightPlateauResponse {
+    pub window_days: i64,
+    pub threshold_lbs: f64,
+    /// Window ends at the most recent weight reading
+    pub window_start: Option<String>,
+    pub window_end: Option<String>,
+    pub readings: usize,
+    pub average_lbs: Option<f64>,
+    pub range_lbs: Option<f64>,
+    /// Least-squares trend across the window
+    pub slope_lbs_per_week: Option<f64>,
+    pub plateaued: bool,
+    pub message: String,
+}
+
+/// Least-squares fit of y = slope * x + intercept, returning (slope, intercept)
+fn linear_regression(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    if points.len() < 2 {
+        return None;
+    }
+
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if sxx == 0.0 {
+        return None;
+    }
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+
+    let slope = sxy / sxx;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+/// Check whether weight has stalled over the last window_days.
+///
+/// Plateaued means the window's range is within threshold_lbs and the fitted
+/// trend would move weight by less than threshold_lbs across the whole window.
+pub fn detect_weight_plateau(db: &Database, window_days: i64, threshold_lbs: f64) -> Result<WeightPlateauResponse, ToolError> {
+    if window_days < 2 {
+        return Err(ToolError::Validation("window_days must be at least 2".to_string()));
+    }
+    if threshold_lbs <= 0.0 {
+        return Err(ToolError::Validation("threshold_lbs must be greater than 0".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let vitals = Vital::list_by_type(&conn, VitalType::Weight, None)
+        .map_err(|e| ToolError::Db(format!("Failed to list weights: {}", e)))?;
+
+    // (time, lbs) pairs, newest first
+    let readings: Vec<(chrono::NaiveDateTime, f64)> = vitals
+        .iter()
+        .filter_map(|v| {
+            let lbs = if v.unit.eq_ignore_ascii_case("kg") { v.value1 * LBS_PER_KG } else { v.value1 };
+            canonical_datetime(&v.timestamp).map(|t| (t, lbs))
+        })
+        .collect();
+
+    let mut response = WeightPlateauResponse {
+        window_days,
+        threshold_lbs,
+        window_start: None,
+        window_end: None,
+        readings: 0,
+        average_lbs: None,
+        range_lbs: None,
+        slope_lbs_per_week: None,
+        plateaued: false,
+        message: String::new(),
+    };
+
+    let Some(&(end, _)) = readings.iter().max_by_key(|r| r.0) else {
+        response.message = "No weight readings recorded".to_string();
+        return Ok(response);
+    };
+    let start = end - chrono::Duration::days(window_days);
+    let window: Vec<(chrono::NaiveDateTime, f64)> = readings.into_iter().filter(|r| r.0 >= start).collect();
+
+    response.window_start = Some(start.format("%Y-%m-%dT%H:%M:%S").to_string());
+    response.window_end = Some(end.format("%Y-%m-%dT%H:%M:%S").to_string());
+    response.readings = window.len();
+
+    let values: Vec<f64> = window.iter().map(|r| r.1).collect();
+    let average = values.iter().sum::<f64>() / values.len() as f64;
+    let range = values.iter().cloned().fold(f64::MIN, f64::max) - values.iter().cloned().fold(f64::MAX, f64::min);
+    response.average_lbs = Some(round_to(average, 2));
+    response.range_lbs = Some(round_to(range, 2));
+
+    if window.len() < 3 {
+        response.message = format!(
+            "Only {} reading(s) in the last {} days; need at least 3 to judge a plateau",
+            window.len(), window_days
+        );
+        return Ok(response);
+    }
+
+    let points: Vec<(f64, f64)> = window
+        .iter()
+        .map(|(t, lbs)| ((*t - start).num_seconds() as f64 / 86400.0, *lbs))
+        .collect();
+    let slope_per_day = linear_regression(&points).map(|(slope, _)| slope).unwrap_or(0.0);
+    response.slope_lbs_per_week = Some(round_to(slope_per_day * 7.0, 2));
+
+    response.plateaued = range <= threshold_lbs && (slope_per_day * window_days as f64).abs() < threshold_lbs;
+    response.message = if response.plateaued {
+        format!(
+            "Plateau: weight has stayed within {:.1} lbs of {:.1} lbs for {} days",
+            range, average, window_days
+        )
+    } else {
+        format!("Not a plateau: range {:.1} lbs, trend {:+.2} lbs/week", range, slope_per_day * 7.0)
+    };
+
+    Ok(response)
+}
+
+// ============================================================================
+// Weight Change Series
+// ============================================================================
+
+/// One day's weight in a weight change series
+#[derive(Debug, Serialize)]
+pub struct WeightChangeDay {
+    pub date: String,
+    /// The day's last reading, in lbs
+    pub weight_lbs: f64,
+    /// Change since the previous recorded day (which may be several days back)
+    pub change_lbs: Option<f64>,
+    /// Days since that previous recorded day
+    pub days_since_previous: Option<i64>,
+    /// Change since the latest recorded day at least 7 days earlier
+    pub change_7d_lbs: Option<f64>,
+}
+
+/// Response for weight_change_series
+#[derive(Debug, Serialize)]
+pub struct WeightChangeSeriesResponse {
+    pub start_date: String,
+    pub end_date: String,
+    pub days: Vec<WeightChangeDay>,
+    pub count: usize,
+    /// Last day's weight minus the first day's
+    pub total_change_lbs: Option<f64>,
+}
+
+/// Day-over-day changes for (date, lbs) pairs sorted by date. Earlier days are
+/// only used as the baseline for days on or after start.
+fn weight_changes(daily: &[(chrono::NaiveDate, f64)], start: chrono::NaiveDate) -> Vec<WeightChangeDay> {
+    daily
+        .iter()
+        .enumerate()
+        .filter(|(_, (date, _))| *date >= start)
+        .map(|(i, &(date, lbs))| {
+            let previous = i.checked_sub(1).map(|p| daily[p]);
+            let week_ago = date - chrono::Duration::days(7);
+            let baseline_7d = daily[..i].iter().rev().find(|(d, _)| *d <= week_ago);
+            WeightChangeDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                weight_lbs: round_to(lbs, 1),
+                change_lbs: previous.map(|(_, prev)| round_to(lbs - prev, 1)),
+                days_since_previous: previous.map(|(d, _)| (date - d).num_days()),
+                change_7d_lbs: baseline_7d.map(|(_, base)| round_to(lbs - base, 1)),
+            }
+        })
+        .collect()
+}
+
+/// Per-day weights over a date range with the change from the previous recorded
+/// weight and a 7-day rolling change. Readings before start_date serve as the
+/// baseline for the first days.
+pub fn weight_change_series(db: &Database, start_date: &str, end_date: &str) -> Result<WeightChangeSeriesResponse, ToolError> {
+    let start = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid start_date '{}'. Use YYYY-MM-DD", start_date)))?;
+    let end = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|_| ToolError::Validation(format!("Invalid end_date '{}'. Use YYYY-MM-DD", end_date)))?;
+    if end < start {
+        return Err(ToolError::Validation("end_date must be on or after start_date".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let vitals = Vital::list_by_type(&conn, VitalType::Weight, None)
+        .map_err(|e| ToolError::Db(format!("Failed to list weights: {}", e)))?;
+
+    // Last reading of each day, oldest day first
+    let mut by_date: std::collections::BTreeMap<chrono::NaiveDate, (chrono::NaiveDateTime, f64)> = std::collections::BTreeMap::new();
+    for v in &vitals {
+        let Some(t) = canonical_datetime(&v.timestamp) else { continue };
+        if t.date() > end {
+            continue;
+        }
+        let lbs = if v.unit.eq_ignore_ascii_case("kg") { v.value1 * LBS_PER_KG } else { v.value1 };
+        let entry = by_date.entry(t.date()).or_insert((t, lbs));
+        if t > entry.0 {
+            *entry = (t, lbs);
+        }
+    }
+    let daily: Vec<(chrono::NaiveDate, f64)> = by_date.into_iter().map(|(d, (_, lbs))| (d, lbs)).collect();
+
+    let days = weight_changes(&daily, start);
+    let total_change_lbs = match (days.first(), days.last()) {
+        (Some(first), Some(last)) if days.len() > 1 => Some(round_to(last.weight_lbs - first.weight_lbs, 1)),
+        _ => None,
+    };
+
+    Ok(WeightChangeSeriesResponse {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        count: days.len(),
+        days,
+        total_change_lbs,
+    })
+}
+
+// ============================================================================
+// Vital Correlation
+// ============================================================================
+
+/// One day with both vitals recorded
+#[derive(Debug, Serialize)]
+pub struct CorrelationPair {
+    pub date: String,
+    pub value_a: f64,
+    pub value_b: f64,
+}
+
+/// Response for vital_correlation
+#[derive(Debug, Serialize)]
+pub struct VitalCorrelationResponse {
+    pub type_a: String,
+    pub type_b: String,
+    pub start_date: String,
+    pub end_date: String,
+    /// Days with readings of both types
+    pub n: usize,
+    /// Pearson's r between the daily averages; None with fewer than 3 days or no variation
+    pub r: Option<f64>,
+    pub interpretation: String,
+    pub pairs: Vec<CorrelationPair>,
+}
+
+/// Pearson correlation coefficient of (x, y) pairs
+fn pearson_r(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxy: f64 = pairs.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let sxx: f64 = pairs.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let syy: f64 = pairs.iter().map(|p| (p.1 - mean_y).powi(2)).sum();
+    if sxx == 0.0 || syy == 0.0 {
+        return None;
+    }
+    Some(sxy / (sxx * syy).sqrt())
+}
+
+/// Conventional strength label for a correlation coefficient
+fn correlation_label(r: f64) -> String {
+    let strength = match r.abs() {
+        a if a < 0.1 => return "negligible".to_string(),
+        a if a < 0.3 => "weak",
+        a if a < 0.5 => "moderate",
+        a if a < 0.7 => "strong",
+        _ => "very strong",
+    };
+    format!("{} {}", strength, if r > 0.0 { "positive" } else { "negative" })
+}
+
+/// Daily averages of value1 (systolic for blood pressure, lbs for weight)
+fn daily_vital_averages(
+    conn: &rusqlite::Connection,
+    vital_type: &str,
+    start_date: &str,
+    end_date: &str,
+) -> Result<(String, std::collections::BTreeMap<String, f64>), ToolError> {
+    let (vt, custom) = resolve_vital_type(conn, vital_type)?;
+    let mut vitals = Vital::list_by_date_range(conn, start_date, end_date, Some(vt))
+        .map_err(|e| ToolError::Db(format!("Failed to list vitals: {}", e)))?;
+    if let Some(custom) = &custom {
+        vitals.retain(|v| v.custom_type_id == Some(custom.id));
+    }
+
+    let mut sums: std::collections::BTreeMap<String, (f64, usize)> = std::collections::BTreeMap::new();
+    for v in &vitals {
+        let value = if vt == VitalType::Weight && v.unit.eq_ignore_ascii_case("kg") { v.value1 * LBS_PER_KG } else { v.value1 };
+        let day = v.timestamp.get(..10).unwrap_or(&v.timestamp).to_string();
+        let entry = sums.entry(day).or_insert((0.0, 0));
+        entry.0 += value;
+        entry.1 += 1;
+    }
+
+    let name = custom.map(|c| c.name).unwrap_or_else(|| vt.as_str().to_string());
+    Ok((name, sums.into_iter().map(|(d, (sum, count))| (d, sum / count as f64)).collect()))
+}
+
+/// Pearson correlation between the daily averages of two vital types, over days
+/// that have readings of both. Blood pressure uses systolic.
+pub fn vital_correlation(
+    db: &Database,
+    type_a: &str,
+    type_b: &str,
+    start_date: &str,
+    end_date: &str,
+) -> Result<VitalCorrelationResponse, ToolError> {
+    check_date("start_date", start_date)?;
+    check_date("end_date", end_date)?;
+
+    let conn = db.get_conn()?;
+
+    let (name_a, daily_a) = daily_vital_averages(&conn, type_a, start_date, end_date)?;
+    let (name_b, daily_b) = daily_vital_averages(&conn, type_b, start_date, end_date)?;
+    if name_a == name_b {
+        return Err(ToolError::Validation("type_a and type_b must be different vital types".to_string()));
+    }
+
+    // Days missing either type drop out of the pairing
+    let paired: Vec<(&String, f64, f64)> = daily_a
+        .iter()
+        .filter_map(|(date, &a)| daily_b.get(date).map(|&b| (date, a, b)))
+        .collect();
+    let n = paired.len();
+
+    let r = if n >= 3 {
+        let values: Vec<(f64, f64)> = paired.iter().map(|&(_, a, b)| (a, b)).collect();
+        pearson_r(&values).map(|r| round_to(r, 3))
+    } else {
+        None
+    };
+    let pairs: Vec<CorrelationPair> = paired
+        .into_iter()
+        .map(|(date, a, b)| CorrelationPair {
+            date: date.clone(),
+            value_a: round_to(a, 1),
+            value_b: round_to(b, 1),
+        })
+        .collect();
+    let interpretation = match r {
+        Some(r) => correlation_label(r),
+        None if n < 3 => format!("Not enough data: {} day(s) with both readings, need at least 3", n),
+        None => "Undefined: one of the vitals did not vary".to_string(),
+    };
+
+    Ok(VitalCorrelationResponse {
+        type_a: name_a,
+        type_b: name_b,
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        n,
+        r,
+        interpretation,
+        pairs,
+    })
+}
+
+// ============================================================================
+// Heart Rate Recovery
+// ============================================================================
+
+/// How far a reading may sit from the 1- or 2-minute mark and still count
+const RECOVERY_MARK_TOLERANCE_SECS: i64 = 30;
+
+/// One HR reading in a recovery series
+#[derive(Debug, Serialize)]
+pub struct RecoveryReading {
+    pub vital_id: i64,
+    pub timestamp: String,
+    pub bpm: f64,
+    /// Seconds after the peak reading (negative before it)
+    pub seconds_after_peak: i64,
+}
+
+/// Response for exercise_recovery
+#[derive(Debug, Serialize)]
+pub struct ExerciseRecoveryResponse {
+    pub group_id: i64,
+    pub group_description: Option<String>,
+    /// Whether the group is labelled as exercise/workout
+    pub is_exercise_group: bool,
+    pub readings: Vec<RecoveryReading>,
+    pub peak_bpm: Option<f64>,
+    pub peak_timestamp: Option<String>,
+    /// Peak HR minus HR about 1 minute after the peak
+    pub recovery_1min: Option<f64>,
+    /// Peak HR minus HR about 2 minutes after the peak
+    pub recovery_2min: Option<f64>,
+    pub message: String,
+}
+
+/// Reading closest to `mark` seconds after the peak, within tolerance
+fn reading_near_mark(readings: &[RecoveryReading], mark: i64) -> Option<&RecoveryReading> {
+    readings
+        .iter()
+        .filter(|r| (r.seconds_after_peak - mark).abs() <= RECOVERY_MARK_TOLERANCE_SECS)
+        .min_by_key(|r| (r.seconds_after_peak - mark).abs())
+}
+
+/// Heart rate recovery from the HR readings in a post-exercise vital group.
+///
+/// Exercises are not tracked in this database, so the post-exercise readings
+/// are identified by their vital group. Recovery is the peak HR minus the HR
+/// closest to 1 and 2 minutes after the peak (within 30 seconds).
+pub fn exercise_recovery(db: &Database, group_id: i64) -> Result<Option<ExerciseRecoveryResponse>, ToolError> {
+    let conn = db.get_conn()?;
+
+    let Some(group) = VitalGroup::get_by_id(&conn, group_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get vital group: {}", e)))?
+    else {
+        return Ok(None);
+    };
+
+    let is_exercise_group = VitalGroup::exercise_group_ids(&conn)
+        .map_err(|e| ToolError::Db(format!("Failed to check exercise groups: {}", e)))?
+        .contains(&group_id);
+
+    let mut hr: Vec<(chrono::NaiveDateTime, &Vital)> = Vec::new();
+    let vitals = VitalGroup::get_vitals(&conn, group_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get group vitals: {}", e)))?;
+    for v in &vitals {
+        if v.vital_type != VitalType::HeartRate {
+            continue;
+        }
+        if let Some(t) = canonical_datetime(&v.timestamp) {
+            hr.push((t, v));
+        }
+    }
+    hr.sort_by_key(|(t, _)| *t);
+
+    let mut response = ExerciseRecoveryResponse {
+        group_id,
+        group_description: group.description,
+        is_exercise_group,
+        readings: Vec::new(),
+        peak_bpm: None,
+        peak_timestamp: None,
+        recovery_1min: None,
+        recovery_2min: None,
+        message: String::new(),
+    };
+
+    // First reading with the highest bpm
+    let Some(&(peak_time, peak)) = hr
+        .iter()
+        .reduce(|best, r| if r.1.value1 > best.1.value1 { r } else { best })
+    else {
+        response.message = "No heart rate readings in this group".to_string();
+        return Ok(Some(response));
+    };
+
+    response.peak_bpm = Some(peak.value1);
+    response.peak_timestamp = Some(peak.timestamp.clone());
+    response.readings = hr
+        .iter()
+        .map(|(t, v)| RecoveryReading {
+            vital_id: v.id,
+            timestamp: v.timestamp.clone(),
+            bpm: v.value1,
+            seconds_after_peak: (*t - peak_time).num_seconds(),
+        })
+        .collect();
+
+    response.recovery_1min = reading_near_mark(&response.readings, 60).map(|r| peak.value1 - r.bpm);
+    response.recovery_2min = reading_near_mark(&response.readings, 120).map(|r| peak.value1 - r.bpm);
+
+    response.message = match (response.recovery_1min, response.recovery_2min) {
+        (None, None) => "No readings near 1 or 2 minutes after the peak; record HR at those marks to measure recovery".to_string(),
+        (Some(one), None) => format!("HR dropped {:.0} bpm in the first minute", one),
+        (None, Some(two)) => format!("HR dropped {:.0} bpm in two minutes", two),
+        (Some(one), Some(two)) => format!("HR dropped {:.0} bpm after 1 minute and {:.0} bpm after 2 minutes", one, two),
+    };
+
+    Ok(Some(response))
+}
+
+// ============================================================================
+// Heart Rate Zones
+// ============================================================================
+
+/// Gaps between HR readings longer than this are not counted toward any zone
+const MAX_ZONE_INTERVAL_MINUTES: f64 = 10.0;
+
+/// Share of max HR covered by each zone, for display
+const ZONE_LABELS: [&str; 5] = ["<60%", "60-70%", "70-80%", "80-90%", ">90%"];
+
+/// Intensity zone (1-5) for a heart rate as a fraction of max HR
+pub fn hr_zone(bpm: f64, max_hr: f64) -> usize {
+    let fraction = bpm / max_hr;
+    if fraction < 0.6 {
+        1
+    } else if fraction < 0.7 {
+        2
+    } else if fraction < 0.8 {
+        3
+    } else if fraction <= 0.9 {
+        4
+    } else {
+        5
+    }
+}
+
+/// Time spent in one zone
+#[derive(Debug, Serialize)]
+pub struct ZoneMinutes {
+    pub zone: usize,
+    /// Share of max HR, e.g. "70-80%"
+    pub range: String,
+    pub minutes: f64,
+    pub percent: f64,
+}
+
+/// Response for hr_zone_breakdown
+#[derive(Debug, Serialize)]
+pub struct HrZoneBreakdownResponse {
+    pub group_id: i64,
+    pub group_description: Option<String>,
+    /// Whether the group is labelled as exercise/workout
+    pub is_exercise_group: bool,
+    pub max_hr: f64,
+    pub reading_count: usize,
+    pub total_minutes: f64,
+    pub zones: Vec<ZoneMinutes>,
+    pub message: String,
+}
+
+/// Time-in-zone from the HR readings in an exercise vital group.
+///
+/// Exercises and segments are not tracked in this database, so the session is the
+/// vital group: each interval between consecutive HR readings is credited to the
+/// zone of the reading that starts it. Zones are Z1 <60%, Z2 60-70%, Z3 70-80%,
+/// Z4 80-90% and Z5 >90% of max_hr.
+pub fn hr_zone_breakdown(db: &Database, group_id: i64, max_hr: f64) -> Result<Option<HrZoneBreakdownResponse>, ToolError> {
+    if !max_hr.is_finite() || max_hr <= 0.0 {
+        return Err(ToolError::Validation("max_hr must be a finite number greater than 0".to_string()));
+    }
+
+    let conn = db.get_conn()?;
+
+    let Some(group) = VitalGroup::get_by_id(&conn, group_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get vital group: {}", e)))?
+    else {
+        return Ok(None);
+    };
+
+    let is_exercise_group = VitalGroup::exercise_group_ids(&conn)
+        .map_err(|e| ToolError::Db(format!("Failed to check exercise groups: {}", e)))?
+        .contains(&group_id);
+
+    let vitals = VitalGroup::get_vitals(&conn, group_id)
+        .map_err(|e| ToolError::Db(format!("Failed to get group vitals: {}", e)))?;
+    let mut hr: Vec<(chrono::NaiveDateTime, f64)> = vitals
+        .iter()
+        .filter(|v| v.vital_type == VitalType::HeartRate)
+        .filter_map(|v| canonical_datetime(&v.timestamp).map(|t| (t, v.value1)))
+        .collect();
+    hr.sort_by_key(|(t, _)| *t);
+
+    let mut minutes = [0.0; 5];
+    for pair in hr.windows(2) {
+        let interval = (pair[1].0 - pair[0].0).num_seconds() as f64 / 60.0;
+        if interval <= MAX_ZONE_INTERVAL_MINUTES {
+            minutes[hr_zone(pair[0].1, max_hr) - 1] += interval;
+        }
+    }
+    let total: f64 = minutes.iter().sum();
+
+    let zones = minutes
+        .iter()
+        .enumerate()
+        .map(|(i, m)| ZoneMinutes {
+            zone: i + 1,
+            range: ZONE_LABELS[i].to_string(),
+            minutes: round_to(*m, 1),
+            percent: if total > 0.0 { round_to(m / total * 100.0, 1) } else { 0.0 },
+        })
+        .collect();
+
+    let message = if hr.len() < 2 {
+        "Need at least two heart rate readings in this group to measure time in zone".to_string()
+    } else if total == 0.0 {
+        format!("Readings are more than {} minutes apart; record HR more often during the session", MAX_ZONE_INTERVAL_MINUTES)
+    } else {
+        let top = (0..5).max_by(|a, b| minutes[*a].total_cmp(&minutes[*b])).unwrap_or(0);
+        format!("{:.0} minutes tracked, mostly in zone {} ({} of max HR)", total, top + 1, ZONE_LABELS[top])
+    };
+
+    Ok(Some(HrZoneBreakdownResponse {
+        group_id,
+        group_description: group.description,
+        is_exercise_group,
+        max_hr,
+        reading_count: hr.len(),
+        total_minutes: round_to(total, 1),
+        zones,
+        message,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::test_support::temp_db;
+
+    fn dates(list: &[&str]) -> Vec<chrono::NaiveDate> {
+        list.iter().map(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap()).collect()
+    }
+
+    #[test]
+    fn test_longest_gap() {
+        assert_eq!(longest_gap(&[]), None);
+
+        let gap = longest_gap(&dates(&["2024-02-27", "2024-03-01", "2024-03-02", "2024-03-03", "2024-03-10"])).unwrap();
+        assert_eq!(gap.start_date, "2024-03-01");
+        assert_eq!(gap.end_date, "2024-03-03");
+        assert_eq!(gap.days, 3);
+
+        // Runs across month boundaries and at the end of the list count
+        let gap = longest_gap(&dates(&["2024-01-05", "2024-02-28", "2024-02-29", "2024-03-01"])).unwrap();
+        assert_eq!(gap.start_date, "2024-02-28");
+        assert_eq!(gap.days, 3);
+    }
+
+    #[test]
+    fn test_hr_zone() {
+        assert_eq!(hr_zone(100.0, 180.0), 1);
+        assert_eq!(hr_zone(108.0, 180.0), 2); // exactly 60%
+        assert_eq!(hr_zone(130.0, 180.0), 3);
+        assert_eq!(hr_zone(162.0, 180.0), 4); // exactly 90%
+        assert_eq!(hr_zone(170.0, 180.0), 5);
+    }
+
+    #[test]
+    fn test_exercise_recovery() {
+        let db = temp_db("hr-recovery");
+
+        let group = create_vital_group(&db, Some("Post-run"), Some("2024-03-01T07:00:00"), None, &SystemClock).unwrap().id;
+        // Peak at 07:00:10; 07:01:20 is within 30 seconds of the 1-minute mark, nothing near 2 minutes
+        for (timestamp, value1) in [("2024-03-01T07:00:00", 150.0), ("2024-03-01T07:00:10", 160.0), ("2024-03-01T07:01:20", 135.0), ("2024-03-01T07:03:30", 110.0)] {
+            add_vital(&db, VitalReading {
+                vital_type: "heart_rate".to_string(), value1, value2: None, unit: None,
+                timestamp: Some(timestamp.to_string()), group_id: Some(group), notes: None,
+            }, &SystemClock).unwrap();
+        }
+
+        let result = exercise_recovery(&db, group).unwrap().unwrap();
+        assert!(!result.is_exercise_group);
+        assert_eq!(result.peak_bpm, Some(160.0));
+        assert_eq!(result.peak_timestamp.as_deref(), Some("2024-03-01T07:00:10"));
+        let offsets: Vec<i64> = result.readings.iter().map(|r| r.seconds_after_peak).collect();
+        assert_eq!(offsets, vec![-10, 0, 70, 200]);
+        assert_eq!((result.recovery_1min, result.recovery_2min), (Some(25.0), None));
+
+        let empty = create_vital_group(&db, None, None, None, &SystemClock).unwrap().id;
+        assert_eq!(exercise_recovery(&db, empty).unwrap().unwrap().peak_bpm, None);
+        assert!(exercise_recovery(&db, empty + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_hr_zone_breakdown() {
+        let db = temp_db("hr-zones");
+
+        let group = create_vital_group(&db, Some("Evening workout"), Some("2024-03-01T18:00:00"), None, &SystemClock).unwrap().id;
+        // 10 minutes at 100 bpm (Z1), 5 at 150 (Z4), then a closing reading
+        for (timestamp, value1) in [("2024-03-01T18:00:00", 100.0), ("2024-03-01T18:10:00", 150.0), ("2024-03-01T18:15:00", 120.0)] {
+            add_vital(&db, VitalReading {
+                vital_type: "heart_rate".to_string(), value1, value2: None, unit: None,
+                timestamp: Some(timestamp.to_string()), group_id: Some(group), notes: None,
+            }, &SystemClock).unwrap();
+        }
+
+        let result = hr_zone_breakdown(&db, group, 180.0).unwrap().unwrap();
+        assert!(result.is_exercise_group);
+        assert_eq!((result.reading_count, result.total_minutes), (3, 15.0));
+        let minutes: Vec<f64> = result.zones.iter().map(|z| z.minutes).collect();
+        assert_eq!(minutes, vec![10.0, 0.0, 0.0, 5.0, 0.0]);
+
+        assert!(hr_zone_breakdown(&db, group + 1, 180.0).unwrap().is_none());
+        for max_hr in [0.0, -180.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(hr_zone_breakdown(&db, group, max_hr), Err(ToolError::Validation(_))));
+        }
+    }
+
+    #[test]
+    fn test_classify_bp() {
+        assert_eq!(classify_bp(115.0, 75.0), "normal");
+        assert_eq!(classify_bp(125.0, 78.0), "elevated");
+        assert_eq!(classify_bp(125.0, 82.0), "stage_1_hypertension");
+        assert_eq!(classify_bp(142.0, 70.0), "stage_2_hypertension");
+        assert_eq!(classify_bp(185.0, 100.0), "hypertensive_crisis");
+    }
+
+    #[test]
+    fn test_adag_a1c() {
+        // ADAG reference points: 154 mg/dL ~ 7%, 126 mg/dL ~ 6%
+        assert_eq!(round_to(adag_a1c(154.0), 1), 7.0);
+        assert_eq!(round_to(adag_a1c(126.0), 1), 6.0);
+    }
+
+    #[test]
+    fn test_bp_to_kpa() {
+        assert!((bp_to_kpa(120.0) - 15.9986).abs() < 0.001);
+        assert!((bp_to_kpa(80.0) - 10.6658).abs() < 0.001);
+        assert_eq!(bp_to_kpa(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_bp_to_kpa_thresholds() {
+        // Stage 2 hypertension cutoffs 140/90 mmHg
+        assert!((bp_to_kpa(140.0) - 18.665).abs() < 0.001);
+        assert!((bp_to_kpa(90.0) - 11.999).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_bp_display_unit_from_str() {
+        assert_eq!(BpDisplayUnit::from_str("kPa"), Some(BpDisplayUnit::Kpa));
+        assert_eq!(BpDisplayUnit::from_str("MMHG"), Some(BpDisplayUnit::MmHg));
+        assert_eq!(BpDisplayUnit::from_str("psi"), None);
+    }
+
+    #[test]
+    fn test_week_bucket_sunday_reading() {
+        let date = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        // Sunday 2026-01-18 closes the Monday week but opens the Sunday week
+        let sunday = date("2026-01-18");
+        assert_eq!(week_bucket(sunday, chrono::Weekday::Mon), date("2026-01-12"));
+        assert_eq!(week_bucket(sunday, chrono::Weekday::Sun), date("2026-01-18"));
+        // The Saturday before shares a Monday week with it but not a Sunday week
+        let saturday = date("2026-01-17");
+        assert_eq!(week_bucket(saturday, chrono::Weekday::Mon), date("2026-01-12"));
+        assert_eq!(week_bucket(saturday, chrono::Weekday::Sun), date("2026-01-11"));
+        assert_eq!(parse_week_start("Sunday").unwrap(), chrono::Weekday::Sun);
+        assert!(parse_week_start("tue").is_err());
+    }
+
+    #[test]
+    fn test_histogram_bins() {
+        let bins = histogram_bins(&[118.0, 121.0, 129.9, 141.0], 10.0).unwrap();
+        let ranges: Vec<(f64, f64, usize)> = bins.iter().map(|b| (b.low, b.high, b.count)).collect();
+        assert_eq!(ranges, vec![(110.0, 120.0, 1), (120.0, 130.0, 2), (130.0, 140.0, 0), (140.0, 150.0, 1)]);
+        // A value on a boundary belongs to the bin it starts
+        assert_eq!(histogram_bins(&[120.0], 5.0).unwrap(), vec![HistogramBin { low: 120.0, high: 125.0, count: 1 }]);
+        assert!(histogram_bins(&[], 5.0).unwrap().is_empty());
+        assert!(histogram_bins(&[0.0, 1000.0], 0.1).is_err());
+    }
+
+    #[test]
+    fn test_resolve_csv_column() {
+        let header: Vec<String> = ["Date", "Reading", "Units"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(resolve_csv_column(Some(&header), "reading").unwrap(), 1);
+        assert_eq!(resolve_csv_column(Some(&header), "2").unwrap(), 2);
+        assert!(resolve_csv_column(Some(&header), "Value").is_err());
+        assert_eq!(resolve_csv_column(None, " 0 ").unwrap(), 0);
+        assert!(resolve_csv_column(None, "Date").is_err());
+    }
+
+    #[test]
+    fn test_parse_mapped_timestamp() {
+        assert_eq!(parse_mapped_timestamp("03/05/2024 7:30", Some("%m/%d/%Y %H:%M")).as_deref(), Some("2024-03-05T07:30:00"));
+        assert_eq!(parse_mapped_timestamp("05.03.2024", Some("%d.%m.%Y")).as_deref(), Some("2024-03-05T00:00:00"));
+        assert_eq!(parse_mapped_timestamp("2024-03-05 07:30", None).as_deref(), Some("2024-03-05T07:30:00"));
+        assert_eq!(parse_mapped_timestamp("March 5", Some("%m/%d/%Y")), None);
+    }
+
+    #[test]
+    fn test_convert_to_default_unit() {
+        assert_eq!(convert_to_default_unit(VitalType::Weight, 80.0, "KG"), (176.4, "lbs".to_string()));
+        assert_eq!(convert_to_default_unit(VitalType::Glucose, 5.5, "mmol/L"), (99.0, "mg/dL".to_string()));
+        assert_eq!(convert_to_default_unit(VitalType::Water, 1.0, "cup"), (237.0, "ml".to_string()));
+        assert_eq!(convert_to_default_unit(VitalType::HeartRate, 60.0, "bpm"), (60.0, "bpm".to_string()));
+    }
+
+    #[test]
+    fn test_pearson_r() {
+        let r = pearson_r(&[(1.0, 2.0), (2.0, 4.0), (3.0, 6.1)]).unwrap();
+        assert!(r > 0.99);
+        let r = pearson_r(&[(1.0, 3.0), (2.0, 2.0), (3.0, 1.0)]).unwrap();
+        assert!((r + 1.0).abs() < 1e-9);
+        assert_eq!(pearson_r(&[(1.0, 5.0), (2.0, 5.0), (3.0, 5.0)]), None);
+        assert_eq!(pearson_r(&[(1.0, 5.0)]), None);
+
+        assert_eq!(correlation_label(0.05), "negligible");
+        assert_eq!(correlation_label(-0.42), "moderate negative");
+        assert_eq!(correlation_label(0.85), "very strong positive");
+    }
+
+    #[test]
+    fn test_weight_changes() {
+        let d = |s: &str| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let daily = vec![
+            (d("2024-02-25"), 182.0),
+            (d("2024-03-01"), 181.0),
+            (d("2024-03-02"), 180.4),
+            (d("2024-03-08"), 179.5),
+            (d("2024-03-10"), 179.9),
+        ];
+        let days = weight_changes(&daily, d("2024-03-01"));
+        assert_eq!(days.len(), 4);
+
+        // The reading before start is only a baseline
+        assert_eq!(days[0].change_lbs, Some(-1.0));
+        assert_eq!(days[0].days_since_previous, Some(5));
+        assert_eq!(days[0].change_7d_lbs, None);
+
+        // Delta is from the previous recorded day, not the previous calendar day
+        assert_eq!(days[2].date, "2024-03-08");
+        assert_eq!(days[2].change_lbs, Some(-0.9));
+        assert_eq!(days[2].days_since_previous, Some(6));
+        assert_eq!(days[2].change_7d_lbs, Some(-1.5));
+        assert_eq!(days[3].change_7d_lbs, Some(-0.5));
+
+        assert!(weight_changes(&daily, d("2024-04-01")).is_empty());
+    }
+
+    #[test]
+    fn test_find_and_merge_duplicate_groups() {
+        let db = temp_db("dup-groups");
+
+        let group = |ts: &str| create_vital_group(&db, None, Some(ts), None, &SystemClock).unwrap().id;
+        let reading = |group_id: i64, vital_type: VitalType, value1: f64, value2: Option<f64>| {
+            db.with_conn(|conn| Vital::create(conn, &VitalCreate {
+                vital_type, timestamp: None, value1, value2, unit: None, group_id: Some(group_id), notes: None, custom_type_id: None,
+            }, &SystemClock)).unwrap();
+        };
+        let (a, b, c) = (group("2024-03-01T08:00:00"), group("2024-03-01 08:03"), group("2024-03-01T08:04:00"));
+        reading(a, VitalType::BloodPressure, 120.0, Some(80.0));
+        reading(b, VitalType::BloodPressure, 122.0, Some(81.0));
+        reading(b, VitalType::HeartRate, 64.0, None);
+        reading(c, VitalType::Weight, 180.0, None);
+
+        let found = find_duplicate_vital_groups(&db, 5).unwrap();
+        assert_eq!(found.pairs.len(), 1);
+        assert_eq!((found.pairs[0].first.id, found.pairs[0].second.id), (a, b));
+        assert_eq!(found.pairs[0].minutes_apart, 3);
+        assert!(find_duplicate_vital_groups(&db, 2).unwrap().pairs.is_empty());
+
+        let merged = merge_vital_groups(&db, a, b).unwrap();
+        assert_eq!(merged.vitals_moved, 2);
+        assert_eq!(merged.group.vitals.len(), 3);
+        assert!(get_vital_group(&db, b).unwrap().is_none());
+        assert!(merge_vital_groups(&db, a, b).is_err());
+    }
+
+    #[test]
+    fn test_set_vital_timestamp_moves_date_range() {
+        let db = temp_db("vital-timestamp");
+
+        let group = create_vital_group(&db, None, Some("2020-01-01T00:00:00Z"), None, &SystemClock).unwrap().id;
+        let vital = db.with_conn(|conn| Vital::create(conn, &VitalCreate {
+            vital_type: VitalType::HeartRate, timestamp: Some("2020-01-01T00:00:00Z".into()),
+            value1: 60.0, value2: None, unit: None, group_id: Some(group), notes: None, custom_type_id: None,
+        }, &SystemClock)).unwrap();
+        let in_march = || db.with_conn(|conn| Vital::list_by_date_range(conn, "2024-03-05", "2024-03-05", None)).unwrap();
+        assert!(in_march().is_empty());
+
+        let updated = set_vital_timestamp(&db, vital.id, "2024-03-05 07:30").unwrap();
+        assert_eq!(updated.timestamp, "2024-03-05T07:30:00");
+        assert_eq!(updated.group_id, Some(group));
+        assert_eq!(in_march().len(), 1);
+
+        let group_detail = set_vital_group_timestamp(&db, group, "2024-03-05T07:30").unwrap();
+        assert_eq!(group_detail.timestamp, "2024-03-05T07:30:00");
+        assert_eq!(group_detail.vitals.len(), 1);
+
+        assert!(set_vital_timestamp(&db, vital.id, "yesterday-ish").is_err());
+        assert!(set_vital_timestamp(&db, vital.id + 1, "2024-03-05").is_err());
+    }
+
+    #[test]
+    fn test_date_range_tools_reject_bad_dates() {
+        let db = temp_db("vital-bad-dates");
+
+        assert!(matches!(list_vitals_by_date_range(&db, "March 1", "2024-03-31", None), Err(ToolError::Validation(_))));
+        assert!(matches!(list_vitals_by_date_range(&db, "2024-03-01", "2024-3-31x", None), Err(ToolError::Validation(_))));
+        assert!(matches!(vital_histogram(&db, "weight", Some("soon"), None, 1.0), Err(ToolError::Validation(_))));
+        assert!(matches!(vital_correlation(&db, "weight", "hr", "2024-03-01", "later"), Err(ToolError::Validation(_))));
+        assert_eq!(list_vitals_by_date_range(&db, "2024-03-01", "2024-03-31", None).unwrap().total, 0);
+    }
+
+    #[test]
+    fn test_update_vital_checks_values_by_type() {
+        let db = temp_db("update-vital");
+
+        register_vital_type(&db, "mood", "points", None).unwrap();
+        let reading = |vital_type: &str, value1: f64| VitalReading {
+            vital_type: vital_type.to_string(), value1, value2: None, unit: None,
+            timestamp: Some("2024-03-01T08:00:00".into()), group_id: None, notes: None,
+        };
+        let mood = add_vital(&db, reading("mood", 3.0), &SystemClock).unwrap().id;
+        let weight = add_vital(&db, reading("weight", 180.0), &SystemClock).unwrap().id;
+
+        // A custom scale may go to zero or below, but has no second value
+        assert!(update_vital(&db, mood, Some(-2.0), None, None, None).is_ok());
+        assert!(matches!(update_vital(&db, mood, None, Some(1.0), None, None), Err(ToolError::Validation(_))));
+        assert!(matches!(update_vital(&db, mood, Some(f64::NAN), None, None, None), Err(ToolError::Validation(_))));
+
+        assert!(matches!(update_vital(&db, weight, Some(0.0), None, None, None), Err(ToolError::Validation(_))));
+        assert!(update_vital(&db, weight, Some(178.5), None, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_custom_types_in_histogram_calendar_and_gaps() {
+        let db = temp_db("custom-type-tools");
+
+        register_vital_type(&db, "mood", "points", None).unwrap();
+        register_vital_type(&db, "energy", "points", None).unwrap();
+        let reading = |vital_type: &str, value1: f64, timestamp: &str| VitalReading {
+            vital_type: vital_type.to_string(), value1, value2: None, unit: None,
+            timestamp: Some(timestamp.into()), group_id: None, notes: None,
+        };
+        add_vital(&db, reading("mood", 7.0, "2024-03-01T08:00:00"), &SystemClock).unwrap();
+        add_vital(&db, reading("mood", 5.0, "2024-03-03T08:00:00"), &SystemClock).unwrap();
+        add_vital(&db, reading("energy", 2.0, "2024-03-02T08:00:00"), &SystemClock).unwrap();
+
+        let histogram = vital_histogram(&db, "Mood", None, None, 1.0).unwrap();
+        assert_eq!(histogram.vital_type, "mood");
+        assert_eq!(histogram.histograms[0].reading_count, 2);
+
+        let calendar = vitals_calendar(&db, 2024, Some("mood")).unwrap();
+        assert_eq!(calendar.total_readings, 2);
+        assert_eq!(calendar.days.iter().map(|d| d.date.as_str()).collect::<Vec<_>>(), vec!["2024-03-01", "2024-03-03"]);
+
+        let gaps = vital_tracking_gaps(&db, "mood", "2024-03-01", "2024-03-03").unwrap();
+        assert_eq!(gaps.missing_dates, vec!["2024-03-02"]);
+
+        assert!(matches!(vital_histogram(&db, "sleep", None, None, 1.0), Err(ToolError::Validation(_))));
+    }
+
+    #[test]
+    fn test_days_missing_weight() {
+        use crate::models::{Day, MealEntry, MealEntryCreate, MealType, Nutrition};
+
+        let db = temp_db("days-missing-weight");
+
+        db.with_conn(|conn| {
+            // 03-04 only has a planned meal, so it isn't a meal day
+            for (date, planned) in [("2024-03-01", false), ("2024-03-02", false), ("2024-03-03", false), ("2024-03-04", true)] {
+                let day = Day::get_or_create(conn, date)?;
+                MealEntry::create(conn, &MealEntryCreate {
+                    day_id: day.id, meal_type: MealType::Lunch, recipe_id: None, food_item_id: None,
+                    description: Some("Sandwich".to_string()), inline_nutrition: Some(Nutrition { calories: 400.0, ..Nutrition::zero() }),
+                    servings: 1.0, percent_eaten: None, notes: None, eaten_at: None, planned,
+                }, &SystemClock)?;
+            }
+            Ok(())
+        }).unwrap();
+        for (timestamp, value1) in [("2024-02-28T07:00:00", 181.0), ("2024-03-02T07:00:00", 180.0), ("2024-03-06T07:00:00", 179.0)] {
+            add_vital(&db, VitalReading {
+                vital_type: "weight".to_string(), value1, value2: None, unit: Some("lbs".to_string()),
+                timestamp: Some(timestamp.to_string()), group_id: None, notes: None,
+            }, &SystemClock).unwrap();
+        }
+
+        let result = days_missing_weight(&db, "2024-03-01", "2024-03-31").unwrap();
+        assert_eq!((result.meal_days, result.count), (3, 2));
+        let hints: Vec<(&str, Option<&str>, Option<f64>)> = result.days
+            .iter()
+            .map(|d| (
+                d.date.as_str(),
+                d.previous_weight.as_ref().map(|w| w.date.as_str()),
+                d.next_weight.as_ref().map(|w| w.value),
+            ))
+            .collect();
+        assert_eq!(hints, vec![
+            ("2024-03-01", Some("2024-02-28"), Some(180.0)),
+            ("2024-03-03", Some("2024-03-02"), Some(179.0)),
+        ]);
+
+        let narrow = days_missing_weight(&db, "2024-03-02", "2024-03-02").unwrap();
+        assert_eq!((narrow.meal_days, narrow.count), (1, 0));
+
+        assert!(matches!(days_missing_weight(&db, "2024-03-31", "2024-03-01"), Err(ToolError::Validation(_))));
+        assert!(matches!(days_missing_weight(&db, "March 1", "2024-03-31"), Err(ToolError::Validation(_))));
+    }
+
+    #[test]
+    fn test_estimate_a1c_converts_mmol_readings() {
+        let db = temp_db("a1c-units");
+
+        for (day, value1, unit) in [(1, 126.0, "mg/dL"), (2, 7.0, "mmol/L"), (3, 10.0, "mmol/l")] {
+            add_vital(&db, VitalReading {
+                vital_type: "glucose".to_string(), value1, value2: None, unit: Some(unit.to_string()),
+                timestamp: Some(format!("2024-03-{:02}T07:00:00", day)), group_id: None, notes: None,
+            }, &SystemClock).unwrap();
+        }
+
+        // 126, 126 and 180 mg/dL
+        let result = estimate_a1c(&db, "2024-03-01", "2024-03-31").unwrap();
+        assert_eq!(result.reading_count, 3);
+        assert_eq!(result.avg_glucose_mg_dl, Some(144.0));
+        assert_eq!(result.estimated_a1c_percent, Some(round_to(adag_a1c(144.0), 1)));
+    }
+
+    #[test]
+    fn test_list_vitals_stats_splits_exercise_groups() {
+        let db = temp_db("vitals-hr-split");
+
+        let workout = create_vital_group(&db, Some("After WORKOUT"), Some("2024-03-01T18:00:00"), None, &SystemClock).unwrap().id;
+        let run = create_vital_group(&db, Some("Post-run"), Some("2024-03-02T18:00:00"), None, &SystemClock).unwrap().id;
+        for (value1, group_id) in [(140.0, Some(workout)), (70.0, Some(run)), (60.0, None)] {
+            add_vital(&db, VitalReading {
+                vital_type: "heart_rate".to_string(), value1, value2: None, unit: None,
+                timestamp: None, group_id, notes: None,
+            }, &SystemClock).unwrap();
+        }
+
+        let hr = list_vitals_stats(&db, "heart_rate", None, None, 2.0, None, None).unwrap().heart_rate.unwrap();
+        assert_eq!(hr.stats.count, 3);
+        // Only the group mentioning a workout counts as active
+        assert_eq!((hr.active.count, hr.active.average), (1, 140.0));
+        assert_eq!((hr.resting.count, hr.resting.average), (2, 65.0));
+    }
+
+    #[test]
+    fn test_list_vitals_stats_outlier_sd() {
+        let db = temp_db("vitals-outliers");
+
+        // Mean 181, SD ~3.16: 190 is ~2.85 SD out, each 180 ~0.32 SD
+        for (i, value1) in [180.0; 9].into_iter().chain([190.0]).enumerate() {
+            add_vital(&db, VitalReading {
+                vital_type: "weight".to_string(), value1, value2: None, unit: None,
+                timestamp: Some(format!("2024-03-{:02}T07:00:00", i + 1)), group_id: None, notes: None,
+            }, &SystemClock).unwrap();
+        }
+        let outliers = |outlier_sd: f64| -> Vec<f64> {
+            let result = list_vitals_stats(&db, "weight", None, None, outlier_sd, None, None).unwrap();
+            assert_eq!(result.outlier_sd, outlier_sd);
+            result.weight.unwrap().stats.outliers.iter().map(|o| o.value).collect()
+        };
+
+        // 2.0 is the tool's default threshold
+        assert_eq!(outliers(2.0), vec![190.0]);
+        assert!(outliers(3.0).is_empty());
+        assert_eq!(outliers(0.3).len(), 10);
+
+        for outlier_sd in [0.0, -1.0, f64::NAN] {
+            assert!(matches!(
+                list_vitals_stats(&db, "weight", None, None, outlier_sd, None, None),
+                Err(ToolError::Validation(_))
+            ));
         }
     }
 }